@@ -0,0 +1,183 @@
+//! Provider failure-rate and latency alerts.
+//!
+//! Round outcomes already recorded as metrics (`provider_stream_seconds`,
+//! `provider_errors_total`) are also fed into a per-provider sliding window.
+//! When the error rate or average round latency in the window crosses its
+//! threshold, a Slack-compatible `{"text": ...}` payload is posted to
+//! `ALERT_WEBHOOK_URL`. Each provider alerts at most once per cooldown.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::config;
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Thresholds and destination for provider alerts.
+pub struct AlertConfig {
+    pub webhook_url: String,
+    /// Error rate in `[0, 1]` that triggers an alert (`ALERT_ERROR_RATE`, default 0.5).
+    pub error_rate: f64,
+    /// Average round duration that triggers an alert (`ALERT_LATENCY_SECS`, default 60).
+    pub latency_secs: f64,
+    /// Sliding window length (`ALERT_WINDOW_SECS`, default 300).
+    pub window: Duration,
+    /// Minimum rounds in the window before evaluating (`ALERT_MIN_SAMPLES`, default 5).
+    pub min_samples: usize,
+    /// Minimum time between alerts for the same provider (`ALERT_COOLDOWN_SECS`, default 900).
+    pub cooldown: Duration,
+}
+
+impl AlertConfig {
+    /// Alerts are enabled only when `ALERT_WEBHOOK_URL` is set.
+    pub fn from_env() -> Option<Self> {
+        let webhook_url = std::env::var("ALERT_WEBHOOK_URL")
+            .ok()
+            .filter(|v| !v.trim().is_empty())?;
+        Some(Self {
+            webhook_url,
+            error_rate: env_or("ALERT_ERROR_RATE", 0.5f64).clamp(0.0, 1.0),
+            latency_secs: env_or("ALERT_LATENCY_SECS", 60.0),
+            window: Duration::from_secs(env_or("ALERT_WINDOW_SECS", 300)),
+            min_samples: env_or("ALERT_MIN_SAMPLES", 5usize).max(1),
+            cooldown: Duration::from_secs(env_or("ALERT_COOLDOWN_SECS", 900)),
+        })
+    }
+}
+
+#[derive(Default)]
+struct ProviderWindow {
+    /// Round end time and duration in seconds; `None` for a failed round.
+    samples: VecDeque<(Instant, Option<f64>)>,
+    last_alert: Option<Instant>,
+}
+
+/// Sliding-window evaluator that posts to the alert webhook.
+pub struct AlertMonitor {
+    config: AlertConfig,
+    windows: Mutex<HashMap<String, ProviderWindow>>,
+}
+
+impl AlertMonitor {
+    pub fn from_env() -> Option<Self> {
+        let config = AlertConfig::from_env()?;
+        info!(
+            "Provider alerts enabled (error rate >= {:.0}%, latency >= {}s, window {}s)",
+            config.error_rate * 100.0,
+            config.latency_secs,
+            config.window.as_secs()
+        );
+        Some(Self {
+            config,
+            windows: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Record a successful round and its duration.
+    pub fn record_success(&self, provider: &str, seconds: f64) {
+        self.record(provider, Some(seconds));
+    }
+
+    /// Record a failed round.
+    pub fn record_error(&self, provider: &str) {
+        self.record(provider, None);
+    }
+
+    fn record(&self, provider: &str, sample: Option<f64>) {
+        let now = Instant::now();
+        let message = {
+            let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+            let window = windows.entry(provider.to_string()).or_default();
+            window.samples.push_back((now, sample));
+            while let Some((at, _)) = window.samples.front() {
+                if now.duration_since(*at) > self.config.window {
+                    window.samples.pop_front();
+                } else {
+                    break;
+                }
+            }
+            if window
+                .last_alert
+                .is_some_and(|at| now.duration_since(at) < self.config.cooldown)
+            {
+                return;
+            }
+            let Some(message) = self.evaluate(provider, &window.samples) else {
+                return;
+            };
+            window.last_alert = Some(now);
+            message
+        };
+        self.send(message);
+    }
+
+    /// Alert text if the window breaches a threshold.
+    fn evaluate(
+        &self,
+        provider: &str,
+        samples: &VecDeque<(Instant, Option<f64>)>,
+    ) -> Option<String> {
+        let total = samples.len();
+        if total < self.config.min_samples {
+            return None;
+        }
+        let durations: Vec<f64> = samples.iter().filter_map(|(_, s)| *s).collect();
+        let errors = total - durations.len();
+        let error_rate = errors as f64 / total as f64;
+        let window_secs = self.config.window.as_secs();
+        if error_rate >= self.config.error_rate {
+            return Some(format!(
+                ":rotating_light: aidebate: provider `{}` error rate {:.0}% ({}/{} rounds) over the last {}s",
+                provider,
+                error_rate * 100.0,
+                errors,
+                total,
+                window_secs
+            ));
+        }
+        if durations.is_empty() {
+            return None;
+        }
+        let avg = durations.iter().sum::<f64>() / durations.len() as f64;
+        if avg >= self.config.latency_secs {
+            return Some(format!(
+                ":hourglass: aidebate: provider `{}` average round latency {:.1}s over the last {}s ({} rounds)",
+                provider,
+                avg,
+                window_secs,
+                durations.len()
+            ));
+        }
+        None
+    }
+
+    fn send(&self, text: String) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!("Alert dropped (no runtime): {}", text);
+            return;
+        };
+        let url = self.config.webhook_url.clone();
+        warn!("{}", text);
+        runtime.spawn(async move {
+            let result = config::http_client()
+                .post(&url)
+                .timeout(Duration::from_secs(10))
+                .json(&json!({ "text": text }))
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status());
+            if let Err(e) = result {
+                warn!("Failed to deliver alert webhook: {}", e);
+            }
+        });
+    }
+}
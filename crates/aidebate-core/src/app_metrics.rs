@@ -0,0 +1,428 @@
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::alerts::AlertMonitor;
+
+/// Simple timer trait for measuring operation durations
+pub trait Timer: Send {
+    fn stop(self: Box<Self>);
+}
+
+/// Metrics trait for recording application metrics
+#[async_trait]
+pub trait Metrics: Send + Sync {
+    #[allow(dead_code)]
+    async fn incr_counter(&self, name: &str, value: u64);
+    async fn start_timer(&self, name: &str) -> Option<Box<dyn Timer + Send>>;
+
+    /// Increment a labeled counter.
+    fn incr_labeled(&self, _name: &str, _labels: &[(&str, &str)], _value: u64) {}
+
+    /// Record a duration in seconds for a labeled summary.
+    fn observe(&self, _name: &str, _labels: &[(&str, &str)], _seconds: f64) {}
+
+    /// Adjust a gauge by `delta`.
+    fn add_gauge(&self, _name: &str, _delta: i64) {}
+
+    /// Render all metrics in Prometheus text exposition format.
+    fn render(&self) -> String {
+        String::new()
+    }
+
+    /// Per-provider, per-phase latency and error breakdown for `/stats`.
+    fn provider_stats(&self) -> serde_json::Value {
+        serde_json::json!({})
+    }
+
+    /// Current value of a gauge.
+    fn gauge(&self, _name: &str) -> i64 {
+        0
+    }
+}
+
+/// Series key: metric name plus label pairs.
+type SeriesKey = (String, Vec<(String, String)>);
+
+#[derive(Default)]
+struct Registry {
+    counters: Mutex<BTreeMap<SeriesKey, u64>>,
+    gauges: Mutex<BTreeMap<SeriesKey, i64>>,
+    /// Sum of observed seconds and observation count.
+    summaries: Mutex<BTreeMap<SeriesKey, (f64, u64)>>,
+}
+
+/// In-process metrics registry exposed at `/metrics` in Prometheus format.
+pub struct PrometheusMetrics {
+    registry: Arc<Registry>,
+    /// Provider round outcomes are also fed to the alert monitor, if configured.
+    alerts: Option<AlertMonitor>,
+}
+
+pub struct PrometheusTimer {
+    registry: Arc<Registry>,
+    name: String,
+    start: Instant,
+}
+
+impl PrometheusMetrics {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            registry: Arc::new(Registry::default()),
+            alerts: AlertMonitor::from_env(),
+        })
+    }
+}
+
+fn series(name: &str, labels: &[(&str, &str)]) -> SeriesKey {
+    let labels = labels
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    (name.to_string(), labels)
+}
+
+/// Render a label set as `{k="v",...}`, or an empty string.
+fn render_labels(labels: &[(String, String)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let rendered = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", rendered)
+}
+
+fn label<'a>(labels: &'a [(String, String)], key: &str) -> &'a str {
+    labels
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+        .unwrap_or("")
+}
+
+fn provider_label<'a>(labels: &[(&str, &'a str)]) -> &'a str {
+    labels
+        .iter()
+        .find(|(k, _)| *k == "provider")
+        .map(|(_, v)| *v)
+        .unwrap_or("")
+}
+
+impl Registry {
+    fn observe(&self, key: SeriesKey, seconds: f64) {
+        let mut summaries = self.summaries.lock().unwrap();
+        let entry = summaries.entry(key).or_insert((0.0, 0));
+        entry.0 += seconds;
+        entry.1 += 1;
+    }
+}
+
+impl Timer for PrometheusTimer {
+    fn stop(self: Box<Self>) {
+        let seconds = self.start.elapsed().as_secs_f64();
+        self.registry
+            .observe(series(&format!("{}_seconds", self.name), &[]), seconds);
+    }
+}
+
+#[async_trait]
+impl Metrics for PrometheusMetrics {
+    async fn incr_counter(&self, name: &str, value: u64) {
+        self.incr_labeled(name, &[], value);
+    }
+
+    async fn start_timer(&self, name: &str) -> Option<Box<dyn Timer + Send>> {
+        Some(Box::new(PrometheusTimer {
+            registry: self.registry.clone(),
+            name: name.to_string(),
+            start: Instant::now(),
+        }))
+    }
+
+    fn incr_labeled(&self, name: &str, labels: &[(&str, &str)], value: u64) {
+        if let (Some(alerts), "provider_errors_total") = (&self.alerts, name) {
+            alerts.record_error(provider_label(labels));
+        }
+        *self
+            .registry
+            .counters
+            .lock()
+            .unwrap()
+            .entry(series(name, labels))
+            .or_insert(0) += value;
+    }
+
+    fn observe(&self, name: &str, labels: &[(&str, &str)], seconds: f64) {
+        if let (Some(alerts), "provider_stream_seconds") = (&self.alerts, name) {
+            alerts.record_success(provider_label(labels), seconds);
+        }
+        self.registry.observe(series(name, labels), seconds);
+    }
+
+    fn add_gauge(&self, name: &str, delta: i64) {
+        *self
+            .registry
+            .gauges
+            .lock()
+            .unwrap()
+            .entry(series(name, &[]))
+            .or_insert(0) += delta;
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let mut last = String::new();
+        for ((name, labels), value) in self.registry.counters.lock().unwrap().iter() {
+            if *name != last {
+                let _ = writeln!(out, "# TYPE {} counter", name);
+                last = name.clone();
+            }
+            let _ = writeln!(out, "{}{} {}", name, render_labels(labels), value);
+        }
+        for ((name, labels), value) in self.registry.gauges.lock().unwrap().iter() {
+            if *name != last {
+                let _ = writeln!(out, "# TYPE {} gauge", name);
+                last = name.clone();
+            }
+            let _ = writeln!(out, "{}{} {}", name, render_labels(labels), value);
+        }
+        for ((name, labels), (sum, count)) in self.registry.summaries.lock().unwrap().iter() {
+            if *name != last {
+                let _ = writeln!(out, "# TYPE {} summary", name);
+                last = name.clone();
+            }
+            let labels = render_labels(labels);
+            let _ = writeln!(out, "{}_sum{} {}", name, labels, sum);
+            let _ = writeln!(out, "{}_count{} {}", name, labels, count);
+        }
+        out
+    }
+
+    fn gauge(&self, name: &str) -> i64 {
+        self.registry
+            .gauges
+            .lock()
+            .unwrap()
+            .get(&series(name, &[]))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn provider_stats(&self) -> serde_json::Value {
+        let key = |labels: &[(String, String)]| {
+            (
+                label(labels, "provider").to_string(),
+                label(labels, "phase").to_string(),
+            )
+        };
+        let mut phases: BTreeMap<(String, String), serde_json::Value> = BTreeMap::new();
+        for ((name, labels), (sum, count)) in self.registry.summaries.lock().unwrap().iter() {
+            let field = match name.as_str() {
+                "provider_ttft_seconds" => "avg_ttft_ms",
+                "provider_stream_seconds" => "avg_stream_ms",
+                _ => continue,
+            };
+            let phase = phases
+                .entry(key(labels))
+                .or_insert_with(|| serde_json::json!({ "errors": {} }));
+            phase[field] = serde_json::json!((sum / *count as f64 * 1000.0).round());
+            if field == "avg_stream_ms" {
+                phase["rounds"] = serde_json::json!(count);
+            }
+        }
+        for ((name, labels), value) in self.registry.counters.lock().unwrap().iter() {
+            if name != "provider_errors_total" {
+                continue;
+            }
+            let phase = phases
+                .entry(key(labels))
+                .or_insert_with(|| serde_json::json!({ "errors": {} }));
+            phase["errors"][label(labels, "class")] = serde_json::json!(value);
+        }
+
+        let mut stats = serde_json::Map::new();
+        for ((provider, phase), value) in phases {
+            stats
+                .entry(provider)
+                .or_insert_with(|| serde_json::json!({}))[phase.as_str()] = value;
+        }
+        serde_json::Value::Object(stats)
+    }
+}
+
+/// Increments a gauge on creation and decrements it on drop, so a debate
+/// stream that is dropped mid-way (client disconnect) is still accounted for.
+pub struct GaugeGuard {
+    metrics: Arc<dyn Metrics>,
+    name: &'static str,
+}
+
+impl GaugeGuard {
+    pub fn new(metrics: Arc<dyn Metrics>, name: &'static str) -> Self {
+        metrics.add_gauge(name, 1);
+        Self { metrics, name }
+    }
+}
+
+impl Drop for GaugeGuard {
+    fn drop(&mut self) {
+        self.metrics.add_gauge(self.name, -1);
+    }
+}
+
+/// Summarize `provider_stats` into one health entry per provider: rounds,
+/// errors, error rate and a coarse status (`ok`, `degraded`, `failing`).
+pub fn provider_health(stats: &serde_json::Value) -> Vec<serde_json::Value> {
+    let Some(providers) = stats.as_object() else {
+        return vec![];
+    };
+    providers
+        .iter()
+        .map(|(provider, phases)| {
+            let (mut rounds, mut errors) = (0u64, 0u64);
+            for phase in phases.as_object().into_iter().flat_map(|p| p.values()) {
+                rounds += phase["rounds"].as_u64().unwrap_or(0);
+                errors += phase["errors"]
+                    .as_object()
+                    .map(|e| e.values().filter_map(|v| v.as_u64()).sum::<u64>())
+                    .unwrap_or(0);
+            }
+            let error_rate = if rounds + errors == 0 {
+                0.0
+            } else {
+                errors as f64 / (rounds + errors) as f64
+            };
+            let status = match error_rate {
+                r if r < 0.2 => "ok",
+                r if r < 0.5 => "degraded",
+                _ => "failing",
+            };
+            serde_json::json!({
+                "provider": provider,
+                "rounds": rounds,
+                "errors": errors,
+                "error_rate": (error_rate * 1000.0).round() / 1000.0,
+                "status": status,
+            })
+        })
+        .collect()
+}
+
+/// Coarse error class for provider error metrics.
+pub fn error_class(error: &str) -> &'static str {
+    let e = error.to_lowercase();
+    if e.contains("timeout") || e.contains("timed out") {
+        "timeout"
+    } else if e.contains("429") || e.contains("rate limit") || e.contains("rate_limit") {
+        "rate_limit"
+    } else if e.contains("401")
+        || e.contains("403")
+        || e.contains("unauthorized")
+        || e.contains("api key")
+    {
+        "auth"
+    } else if ["500", "502", "503", "504", "overloaded"]
+        .iter()
+        .any(|c| e.contains(c))
+    {
+        "server"
+    } else if e.contains("stream") {
+        "stream"
+    } else {
+        "other"
+    }
+}
+
+/// Per-phase performance recap reported in the `done` event.
+#[derive(Serialize, Clone)]
+pub struct PhaseTiming {
+    pub phase: &'static str,
+    pub side: &'static str,
+    pub model: String,
+    pub wall_ms: u64,
+    pub ttft_ms: Option<u64>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+/// Timing for one provider round: time-to-first-token, total stream duration
+/// and errors, labeled by provider and phase.
+pub struct RoundMetrics {
+    metrics: Arc<dyn Metrics>,
+    provider: String,
+    phase: &'static str,
+    started: Instant,
+    ttft: Option<Duration>,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+}
+
+impl RoundMetrics {
+    pub fn start(metrics: Arc<dyn Metrics>, provider: &str, phase: &'static str) -> Self {
+        Self {
+            metrics,
+            provider: provider.to_string(),
+            phase,
+            started: Instant::now(),
+            ttft: None,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+        }
+    }
+
+    /// Call on every content or thinking chunk; records TTFT on the first.
+    pub fn token(&mut self) {
+        if self.ttft.is_none() {
+            let ttft = self.started.elapsed();
+            self.ttft = Some(ttft);
+            self.metrics.observe(
+                "provider_ttft_seconds",
+                &[("provider", &self.provider), ("phase", self.phase)],
+                ttft.as_secs_f64(),
+            );
+        }
+    }
+
+    /// Accumulate token usage; a tool-calling round reports usage per request.
+    pub fn usage(&mut self, prompt_tokens: u64, completion_tokens: u64) {
+        self.prompt_tokens += prompt_tokens;
+        self.completion_tokens += completion_tokens;
+    }
+
+    pub fn finish(self, side: &'static str, model: &str) -> PhaseTiming {
+        let wall = self.started.elapsed();
+        self.metrics.observe(
+            "provider_stream_seconds",
+            &[("provider", &self.provider), ("phase", self.phase)],
+            wall.as_secs_f64(),
+        );
+        PhaseTiming {
+            phase: self.phase,
+            side,
+            model: model.to_string(),
+            wall_ms: wall.as_millis() as u64,
+            ttft_ms: self.ttft.map(|t| t.as_millis() as u64),
+            prompt_tokens: self.prompt_tokens,
+            completion_tokens: self.completion_tokens,
+        }
+    }
+
+    pub fn error(&self, error: &str) {
+        self.metrics.incr_labeled(
+            "provider_errors_total",
+            &[
+                ("provider", &self.provider),
+                ("phase", self.phase),
+                ("class", error_class(error)),
+            ],
+            1,
+        );
+    }
+}
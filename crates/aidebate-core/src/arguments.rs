@@ -0,0 +1,289 @@
+//! Claim extraction and cross-debate argument analytics.
+//!
+//! With `CLAIM_EXTRACTION=1`, the judge's model lists the main claims of
+//! every Pro and Con round of a finished debate and labels each with one of
+//! [`ARGUMENT_TYPES`]. A round's claims are stored with the model that argued
+//! them and whether its side won the verdict. [`analytics`] aggregates them
+//! over many debates to show which kinds of argument most often appear in
+//! winning cases, per provider.
+
+use std::collections::{BTreeMap, HashSet};
+
+use ai_lib_rust::Message;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::debate::Turn;
+use crate::prompts;
+use crate::storage::Store;
+use crate::types::{ClientBackend, ClientInfo, DebatePhase, Position};
+
+/// Stored `debate_analysis` kind for extracted claims.
+pub const ANALYSIS_KIND: &str = "claims";
+
+/// Kinds of argument a claim can rest on.
+pub const ARGUMENT_TYPES: &[&str] = &[
+    "statistics",
+    "research",
+    "expert_opinion",
+    "precedent",
+    "analogy",
+    "principle",
+    "consequences",
+    "anecdote",
+    "definition",
+];
+
+/// Most claims kept per round.
+const MAX_CLAIMS: usize = 6;
+
+/// Whether finished debates have their claims extracted.
+pub fn enabled() -> bool {
+    std::env::var("CLAIM_EXTRACTION").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claim {
+    /// The claim in one sentence.
+    pub claim: String,
+    /// One of [`ARGUMENT_TYPES`].
+    #[serde(rename = "type")]
+    pub argument_type: String,
+}
+
+/// Claims of one round, as stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundClaims {
+    /// Model that argued the round.
+    pub provider: String,
+    /// Whether the round's side won; `None` when the judge named no winner.
+    pub won: Option<bool>,
+    pub claims: Vec<Claim>,
+}
+
+/// List the main claims of one round.
+pub async fn extract(
+    analyzer: &ClientInfo,
+    topic: &str,
+    side: Position,
+    phase: DebatePhase,
+    content: &str,
+) -> anyhow::Result<Vec<Claim>> {
+    let client = match &analyzer.backend {
+        ClientBackend::Live(client) => client,
+        ClientBackend::Mock => return Ok(Vec::new()),
+    };
+    let messages = vec![
+        Message::system(format!(
+            "You extract the main claims from one debate speech and classify the kind of argument behind each.\n\
+             Argument types: {}.\n\
+             Answer with a JSON array and nothing else. Each item is an object with:\n\
+             - \"claim\": the claim in one short sentence, in the language of the speech\n\
+             - \"type\": the argument type above that best describes how the claim is supported\n\
+             List at most {} claims, most important first.",
+            ARGUMENT_TYPES.join(", "),
+            MAX_CLAIMS
+        )),
+        Message::user(format!(
+            "Debate topic: {}\nSpeaker: {} ({})\n\nSpeech:\n{}",
+            topic,
+            side.label(),
+            phase.as_str(),
+            content
+        )),
+    ];
+    let response = client
+        .chat()
+        .messages(messages)
+        .temperature(0.0)
+        .max_tokens(800)
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("Claim extraction failed for {}: {}", analyzer.name, e))?;
+    Ok(parse_claims(&response.content))
+}
+
+/// Claims from the model's reply with a known argument type.
+fn parse_claims(reply: &str) -> Vec<Claim> {
+    let json = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => return Vec::new(),
+    };
+    let claims: Vec<Claim> = serde_json::from_str(json).unwrap_or_default();
+    claims
+        .into_iter()
+        .map(|c| Claim {
+            claim: c.claim.trim().to_string(),
+            argument_type: c
+                .argument_type
+                .trim()
+                .to_lowercase()
+                .replace([' ', '-'], "_"),
+        })
+        .filter(|c| !c.claim.is_empty() && ARGUMENT_TYPES.contains(&c.argument_type.as_str()))
+        .take(MAX_CLAIMS)
+        .collect()
+}
+
+/// Extract the claims of every Pro and Con round of a finished debate and
+/// store them.
+pub async fn extract_debate(
+    db: &dyn Store,
+    analyzer: &ClientInfo,
+    user_id: &str,
+    session_id: &str,
+    topic: &str,
+    transcript: &[Turn],
+) {
+    let winner = transcript
+        .iter()
+        .rev()
+        .find(|(side, ..)| *side == Position::Judge)
+        .and_then(|(_, _, verdict, _)| prompts::parse_winner(verdict));
+    let rounds: Vec<_> = transcript
+        .iter()
+        .filter(|(side, ..)| *side != Position::Judge)
+        .collect();
+    let extracted = futures::future::join_all(
+        rounds
+            .iter()
+            .map(|(side, phase, content, _)| extract(analyzer, topic, *side, *phase, content)),
+    )
+    .await;
+    for ((side, phase, _, provider), claims) in rounds.into_iter().zip(extracted) {
+        let claims = match claims {
+            Ok(claims) => claims,
+            Err(e) => {
+                warn!(
+                    "Claim extraction of {} {} failed: {}",
+                    side.role_str(),
+                    phase.as_str(),
+                    e
+                );
+                continue;
+            }
+        };
+        let round = RoundClaims {
+            provider: provider.clone(),
+            won: winner.map(|w| w == *side),
+            claims,
+        };
+        if let Err(e) = db
+            .save_analysis(
+                user_id,
+                session_id,
+                side.role_str(),
+                phase.as_str(),
+                ANALYSIS_KIND,
+                &serde_json::json!(round),
+            )
+            .await
+        {
+            warn!("Failed to save extracted claims: {}", e);
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ArgumentTypeStats {
+    #[serde(rename = "type")]
+    pub argument_type: String,
+    /// Claims of this type.
+    pub uses: u64,
+    /// Claims of this type made by the side that won.
+    pub winning_uses: u64,
+    /// `winning_uses / uses` over claims from debates with a winner.
+    pub win_rate: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProviderArguments {
+    pub provider: String,
+    pub debates: usize,
+    pub rounds: u64,
+    /// Argument types, most often used in winning cases first.
+    pub argument_types: Vec<ArgumentTypeStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArgumentAnalytics {
+    pub debates: usize,
+    pub providers: Vec<ProviderArguments>,
+}
+
+/// Aggregate stored claims per provider and argument type, optionally only
+/// from debates tagged `category` and from the sessions in `sessions`.
+pub async fn analytics(
+    db: &dyn Store,
+    category: Option<&str>,
+    sessions: Option<&HashSet<String>>,
+) -> ArgumentAnalytics {
+    let rows = db.fetch_analyses_of_kind(ANALYSIS_KIND, category).await;
+    aggregate(
+        rows.into_iter()
+            .filter(|(session_id, _)| sessions.is_none_or(|s| s.contains(session_id)))
+            .filter_map(|(session_id, result)| {
+                Some((
+                    session_id,
+                    serde_json::from_value::<RoundClaims>(result).ok()?,
+                ))
+            }),
+    )
+}
+
+/// Counts per provider and argument type; `(uses, winning uses, decided uses)`.
+fn aggregate(rounds: impl Iterator<Item = (String, RoundClaims)>) -> ArgumentAnalytics {
+    type Counts = BTreeMap<String, (u64, u64, u64)>;
+    let mut providers: BTreeMap<String, (HashSet<String>, u64, Counts)> = BTreeMap::new();
+    let mut debates = HashSet::new();
+    for (session_id, round) in rounds {
+        let (sessions, rounds, counts) = providers.entry(round.provider).or_default();
+        sessions.insert(session_id.clone());
+        debates.insert(session_id);
+        *rounds += 1;
+        for claim in round.claims {
+            let (uses, winning, decided) = counts.entry(claim.argument_type).or_default();
+            *uses += 1;
+            if let Some(won) = round.won {
+                *decided += 1;
+                if won {
+                    *winning += 1;
+                }
+            }
+        }
+    }
+    let mut providers: Vec<ProviderArguments> = providers
+        .into_iter()
+        .map(|(provider, (sessions, rounds, counts))| {
+            let mut argument_types: Vec<ArgumentTypeStats> = counts
+                .into_iter()
+                .map(
+                    |(argument_type, (uses, winning_uses, decided))| ArgumentTypeStats {
+                        argument_type,
+                        uses,
+                        winning_uses,
+                        win_rate: (decided > 0).then(|| {
+                            (winning_uses as f64 / decided as f64 * 100.0).round() / 100.0
+                        }),
+                    },
+                )
+                .collect();
+            argument_types.sort_by(|a, b| {
+                b.winning_uses
+                    .cmp(&a.winning_uses)
+                    .then(b.uses.cmp(&a.uses))
+            });
+            ProviderArguments {
+                provider,
+                debates: sessions.len(),
+                rounds,
+                argument_types,
+            }
+        })
+        .collect();
+    providers.sort_by_key(|p| std::cmp::Reverse(p.debates));
+    ArgumentAnalytics {
+        debates: debates.len(),
+        providers,
+    }
+}
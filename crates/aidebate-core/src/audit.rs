@@ -0,0 +1,52 @@
+//! Append-only audit trail of debate activity.
+//!
+//! Every debate request is recorded as a sequence of events (`rejected`,
+//! `restricted`, `cached`, `started`, `completed`, `failed`) with the user, client IP, topic and
+//! models involved. Rows are never updated; query them via `GET /admin/audit`.
+
+use std::net::SocketAddr;
+
+use http::HeaderMap;
+use tracing::warn;
+
+use crate::storage::Store;
+
+/// Who started a debate and with what; shared by all of its audit events.
+#[derive(Clone)]
+pub struct AuditContext {
+    pub user_id: String,
+    pub session_id: String,
+    pub ip: String,
+    pub topic: String,
+    /// Models in use, e.g. `pro=deepseek/deepseek-chat,con=...`.
+    pub providers: String,
+}
+
+impl AuditContext {
+    /// Append one event. Failures are logged, never surfaced to the debate.
+    pub async fn record(&self, db: &dyn Store, event: &str, detail: Option<&str>) {
+        if let Err(e) = db.save_audit_event(self, event, detail).await {
+            warn!("Failed to write audit event {}: {}", event, e);
+        }
+    }
+}
+
+/// Client IP for auditing. `X-Forwarded-For` is only honored when
+/// `TRUST_FORWARDED_FOR=1` (i.e. behind a trusted reverse proxy).
+pub fn client_ip(headers: &HeaderMap, addr: &SocketAddr) -> String {
+    let trust = std::env::var("TRUST_FORWARDED_FOR")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    if trust {
+        if let Some(ip) = headers
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        {
+            return ip.to_string();
+        }
+    }
+    addr.ip().to_string()
+}
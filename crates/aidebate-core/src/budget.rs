@@ -0,0 +1,142 @@
+//! Provider spend tracking and budget enforcement.
+//!
+//! Token usage reported by providers is priced with a per-provider table and
+//! accumulated in the `provider_spend` table; responses from providers that
+//! report no usage are priced from a local token count. When `DAILY_BUDGET_USD` and/or
+//! `MONTHLY_BUDGET_USD` is set, new debates are refused once the limit is hit.
+
+use serde::Serialize;
+
+use crate::storage::Store;
+use crate::tokenizer;
+
+/// (provider_id, USD per 1M input tokens, USD per 1M output tokens)
+const PRICE_TABLE: &[(&str, f64, f64)] = &[
+    ("deepseek", 0.27, 1.10),
+    ("zhipu", 0.70, 0.70),
+    ("groq", 0.59, 0.79),
+    ("mistral", 0.20, 0.60),
+    ("openai", 2.50, 10.00),
+    ("anthropic", 3.00, 15.00),
+    ("minimax", 0.20, 1.10),
+    ("mock", 0.00, 0.00),
+];
+
+/// Price used for providers missing from the table.
+const DEFAULT_PRICE: (f64, f64) = (1.00, 2.00);
+
+/// Spend status for the current day and month.
+#[derive(Serialize, Clone)]
+pub struct BudgetStatus {
+    pub daily_limit_usd: Option<f64>,
+    pub daily_spent_usd: f64,
+    pub daily_remaining_usd: Option<f64>,
+    pub monthly_limit_usd: Option<f64>,
+    pub monthly_spent_usd: f64,
+    pub monthly_remaining_usd: Option<f64>,
+}
+
+impl BudgetStatus {
+    pub fn is_exceeded(&self) -> bool {
+        matches!(self.daily_remaining_usd, Some(r) if r <= 0.0)
+            || matches!(self.monthly_remaining_usd, Some(r) if r <= 0.0)
+    }
+}
+
+/// Get (input, output) price per 1M tokens for a provider.
+/// Can be overridden via `<PROVIDER>_PRICE_PER_MTOK=input,output`.
+pub fn price_for_provider(provider: &str) -> (f64, f64) {
+    let env_key = format!("{}_PRICE_PER_MTOK", provider.to_uppercase());
+    if let Ok(val) = std::env::var(&env_key) {
+        let parts: Vec<f64> = val
+            .split(',')
+            .filter_map(|p| p.trim().parse::<f64>().ok())
+            .collect();
+        if parts.len() == 2 {
+            return (parts[0], parts[1]);
+        }
+    }
+    PRICE_TABLE
+        .iter()
+        .find(|(p, _, _)| *p == provider)
+        .map(|(_, input, output)| (*input, *output))
+        .unwrap_or(DEFAULT_PRICE)
+}
+
+/// Extract (prompt, completion) token counts from a provider usage object.
+/// Accepts both OpenAI-style and Anthropic-style field names.
+pub fn usage_tokens(usage: &serde_json::Value) -> (u64, u64) {
+    let field = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|n| usage.get(*n).and_then(|v| v.as_u64()))
+    };
+    let prompt = field(&["prompt_tokens", "input_tokens"]).unwrap_or(0);
+    let completion = field(&["completion_tokens", "output_tokens"])
+        .or_else(|| field(&["total_tokens"]).map(|t| t.saturating_sub(prompt)))
+        .unwrap_or(0);
+    (prompt, completion)
+}
+
+/// Usage object for a response whose provider reported no usage, with the
+/// completion counted locally. Marked `"estimated": true`.
+pub fn estimated_usage(model_id: &str, completion: &str) -> serde_json::Value {
+    serde_json::json!({
+        "completion_tokens": tokenizer::count_tokens(model_id, completion),
+        "estimated": true,
+    })
+}
+
+/// Cost in USD of a usage report for the given model.
+pub fn cost_usd(model_id: &str, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+    let provider = model_id.split('/').next().unwrap_or(model_id);
+    let (input_price, output_price) = price_for_provider(provider);
+    (prompt_tokens as f64 * input_price + completion_tokens as f64 * output_price) / 1_000_000.0
+}
+
+fn budget_from_env(key: &str) -> Option<f64> {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| *v > 0.0)
+}
+
+pub fn daily_budget() -> Option<f64> {
+    budget_from_env("DAILY_BUDGET_USD")
+}
+
+pub fn monthly_budget() -> Option<f64> {
+    budget_from_env("MONTHLY_BUDGET_USD")
+}
+
+/// Price and persist a usage report.
+pub async fn record_usage(
+    db: &dyn Store,
+    model_id: &str,
+    usage: &serde_json::Value,
+) -> anyhow::Result<()> {
+    let (prompt, completion) = usage_tokens(usage);
+    if prompt == 0 && completion == 0 {
+        return Ok(());
+    }
+    let provider = model_id.split('/').next().unwrap_or(model_id);
+    let cost = cost_usd(model_id, prompt, completion);
+    db.save_spend(provider, model_id, prompt, completion, cost)
+        .await
+}
+
+/// Current spend against the configured budgets.
+pub async fn budget_status(db: &dyn Store) -> BudgetStatus {
+    let daily_spent = db.spend_since("start of day").await;
+    let monthly_spent = db.spend_since("start of month").await;
+    let daily_limit = daily_budget();
+    let monthly_limit = monthly_budget();
+    BudgetStatus {
+        daily_limit_usd: daily_limit,
+        daily_spent_usd: daily_spent,
+        daily_remaining_usd: daily_limit.map(|l| l - daily_spent),
+        monthly_limit_usd: monthly_limit,
+        monthly_spent_usd: monthly_spent,
+        monthly_remaining_usd: monthly_limit.map(|l| l - monthly_spent),
+    }
+}
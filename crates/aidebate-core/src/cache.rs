@@ -0,0 +1,153 @@
+//! Opt-in replay cache for repeated topics.
+//!
+//! With `DEBATE_CACHE=1`, a completed debate's transcript is stored under a
+//! key hashed from the normalized topic, the three models, the search
+//! options and the preset, so the topic itself is not stored in the key. A
+//! later request with the same key within `DEBATE_CACHE_TTL_HOURS`
+//! (default 24) is answered by replaying that transcript instantly, with every
+//! event marked `"cached": true`, instead of calling the providers again.
+//! Requests can opt out with `"no_cache": true`. The cache is shared by all
+//! users, so debates over the user's own documents or with a precedent from
+//! their past debates are neither replayed nor stored.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::redaction::redact;
+use crate::storage::Store;
+use crate::types::{DebatePhase, Position};
+
+pub fn cache_enabled() -> bool {
+    std::env::var("DEBATE_CACHE")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+fn cache_ttl_hours() -> i64 {
+    std::env::var("DEBATE_CACHE_TTL_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(24)
+}
+
+/// Cache key: SHA-256 of the topic (case- and whitespace-insensitive),
+/// models, search options, preset, edited prompt templates, language and
+/// weighted rubric, since any of them changes the debate.
+#[allow(clippy::too_many_arguments)]
+pub fn cache_key(
+    topic: &str,
+    models: [&str; 3],
+    search_depth: Option<&str>,
+    search_max_results: Option<usize>,
+    preset: Option<&str>,
+    templates: Option<&str>,
+    language: Option<&str>,
+    rubric: Option<&str>,
+) -> String {
+    let topic = topic
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let mut key = format!(
+        "{}|{}|{}|{}|{}|{}",
+        topic,
+        models[0],
+        models[1],
+        models[2],
+        search_depth.unwrap_or(""),
+        search_max_results
+            .map(|n| n.to_string())
+            .unwrap_or_default()
+    );
+    // Keys of debates without a preset stay as they were before presets
+    if let Some(preset) = preset {
+        key.push('|');
+        key.push_str(preset);
+    }
+    // Likewise for debates on the built-in prompt templates
+    if let Some(templates) = templates {
+        key.push_str("|templates:");
+        key.push_str(templates);
+    }
+    if let Some(language) = language {
+        key.push_str("|language:");
+        key.push_str(&language.trim().to_lowercase());
+    }
+    if let Some(rubric) = rubric {
+        key.push_str("|rubric:");
+        key.push_str(&rubric.to_lowercase());
+    }
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// One turn of a cached transcript.
+#[derive(Serialize, Deserialize)]
+pub struct CachedTurn {
+    pub role: String,
+    pub phase: String,
+    pub model: String,
+    pub content: String,
+}
+
+impl CachedTurn {
+    pub fn position(&self) -> Option<Position> {
+        Position::from_role(&self.role)
+    }
+
+    pub fn debate_phase(&self) -> Option<DebatePhase> {
+        DebatePhase::from_name(&self.phase)
+    }
+}
+
+/// A cached debate and when it was recorded.
+pub struct CachedDebate {
+    pub turns: Vec<CachedTurn>,
+    pub created_at: String,
+}
+
+pub async fn lookup(db: &dyn Store, key: &str) -> Option<CachedDebate> {
+    let modifier = format!("-{} hours", cache_ttl_hours());
+    let (transcript, created_at) = db.fetch_cached_debate(key, &modifier).await?;
+    let turns = serde_json::from_str(&transcript).ok()?;
+    Some(CachedDebate { turns, created_at })
+}
+
+/// Store a completed debate's transcript. Content is redacted like any other
+/// stored text, since it may be replayed to other users.
+pub async fn store(
+    db: &dyn Store,
+    key: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+) -> anyhow::Result<()> {
+    let turns: Vec<CachedTurn> = transcript
+        .iter()
+        .map(|(side, phase, content, model)| CachedTurn {
+            role: side.role_str().to_string(),
+            phase: phase.as_str().to_string(),
+            model: model.clone(),
+            content: redact(content).into_owned(),
+        })
+        .collect();
+    db.save_cached_debate(key, &serde_json::to_string(&turns)?)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(topic: &str) -> String {
+        let models = ["mock/pro", "mock/con", "mock/judge"];
+        cache_key(topic, models, None, None, None, None, None, None)
+    }
+
+    #[test]
+    fn key_hashes_the_normalized_topic() {
+        let hashed = key("Alice Smith should   be elected");
+        assert_eq!(hashed.len(), 64);
+        assert!(!hashed.contains("alice"));
+        assert_eq!(hashed, key("alice smith should be ELECTED"));
+        assert_ne!(hashed, key("Bob Jones should be elected"));
+    }
+}
@@ -0,0 +1,411 @@
+//! Re-judging and judge calibration.
+//!
+//! A finished debate can be judged again by another model: only the
+//! judgement phase runs, against the stored Pro and Con rounds, and the new
+//! verdict is stored as a `rejudge` analysis next to the original one.
+//!
+//! A calibration run has several judge models give a verdict on the same
+//! stored debates and compares them pairwise with Cohen's kappa over the
+//! winner each named (Pro, Con or none), so agreement expected by chance is
+//! discounted. Stored verdicts are reused, and a debate's original verdict
+//! counts for the model that gave it, so repeated runs only judge what is
+//! missing. The Pro share of each judge's decided verdicts shows side bias.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::app_metrics::Metrics;
+use crate::budget;
+use crate::config;
+use crate::debate::{execute_judge_round_stream, DebateStreamChunk, Turn};
+use crate::export;
+use crate::prompts;
+use crate::storage::Store;
+use crate::templates;
+use crate::tools::{self, ToolContext};
+use crate::types::{ClientInfo, DebatePhase, Position};
+
+/// Stored `debate_analysis` kind for verdicts of a repeated judgement.
+pub const VERDICT_KIND: &str = "rejudge";
+
+/// Label of a verdict that names no winner.
+const NO_WINNER: &str = "none";
+
+/// One judge's verdict on a debate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verdict {
+    pub model: String,
+    /// `pro` or `con`; `None` when the verdict names no winner.
+    pub winner: Option<String>,
+    #[serde(default)]
+    pub content: String,
+}
+
+impl Verdict {
+    fn new(model: &str, content: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            winner: prompts::parse_winner(content).map(|w| w.role_str().to_string()),
+            content: content.to_string(),
+        }
+    }
+}
+
+/// The judge turns and the Pro and Con rounds of a stored debate.
+pub fn split_transcript(transcript: Vec<Turn>) -> (Vec<Turn>, Vec<Turn>) {
+    transcript
+        .into_iter()
+        .partition(|(side, ..)| *side == Position::Judge)
+}
+
+/// The latest original verdict among `judged` turns, if any.
+pub fn original_verdict(judged: &[Turn]) -> Option<Verdict> {
+    judged
+        .last()
+        .map(|(_, _, content, model)| Verdict::new(model, content))
+}
+
+/// Have `judge` judge the Pro and Con rounds in `transcript`, without
+/// streaming or storing the verdict.
+pub async fn give_verdict(
+    db: &Arc<dyn Store>,
+    judge: &ClientInfo,
+    topic: &str,
+    transcript: &[Turn],
+    tool_ctx: &ToolContext,
+) -> anyhow::Result<Verdict> {
+    let tool_defs = tools::available_tools(tool_ctx).await;
+    let (mut stream, model_id) =
+        execute_judge_round_stream(judge, topic, transcript, tool_defs, tool_ctx).await?;
+    let mut content = String::new();
+    let mut usage_reported = false;
+    while let Some(chunk) = stream.next().await {
+        match chunk? {
+            DebateStreamChunk::Delta(delta) => content.push_str(&delta),
+            DebateStreamChunk::Usage(usage) => {
+                usage_reported = true;
+                let _ = budget::record_usage(db, &model_id, &usage).await;
+            }
+            _ => {}
+        }
+    }
+    if !usage_reported {
+        let usage = budget::estimated_usage(&model_id, &content);
+        let _ = budget::record_usage(db, &model_id, &usage).await;
+    }
+    Ok(Verdict::new(&model_id, &content))
+}
+
+/// Judge the Pro and Con rounds in `transcript` again with `judge` and store
+/// the verdict.
+pub async fn judge_again(
+    db: &Arc<dyn Store>,
+    judge: &ClientInfo,
+    user_id: &str,
+    session_id: &str,
+    topic: &str,
+    transcript: &[Turn],
+    metrics: Arc<dyn Metrics>,
+) -> anyhow::Result<Verdict> {
+    // The fact-checker role defaults to the judge's model
+    let fact_checker = match config::fact_check_model_id() {
+        Some(model_id) => config::build_client_for_model(&model_id).await?,
+        None => judge.clone(),
+    };
+    let tool_ctx = ToolContext::new(
+        db.clone(),
+        user_id.to_string(),
+        session_id.to_string(),
+        fact_checker,
+        metrics,
+    )
+    .with_templates(templates::load(db).await);
+    let verdict = give_verdict(db, judge, topic, transcript, &tool_ctx).await?;
+    if let Err(e) = db
+        .save_analysis(
+            user_id,
+            session_id,
+            Position::Judge.role_str(),
+            DebatePhase::Judgement.as_str(),
+            VERDICT_KIND,
+            &serde_json::json!(verdict),
+        )
+        .await
+    {
+        warn!(
+            "Failed to save verdict of {} for {}: {}",
+            verdict.model, session_id, e
+        );
+    }
+    Ok(verdict)
+}
+
+#[derive(Debug, Serialize)]
+pub struct JudgeStats {
+    pub model: String,
+    /// Debates this judge gave a verdict on.
+    pub verdicts: usize,
+    pub pro: usize,
+    pub con: usize,
+    pub no_winner: usize,
+    /// Share of decided verdicts won by Pro; 0.5 is no side bias.
+    pub pro_share: Option<f64>,
+    /// Mean kappa against the other judges.
+    pub mean_kappa: Option<f64>,
+    /// Debates this judge failed to judge.
+    pub failed: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalibrationReport {
+    pub debates: usize,
+    pub judges: Vec<JudgeStats>,
+    /// Cohen's kappa between judges `i` and `j`, in the order of `judges`;
+    /// `None` when they share no debates or kappa is undefined.
+    pub kappa: Vec<Vec<Option<f64>>>,
+    /// Share of shared debates on which judges `i` and `j` named the same
+    /// winner.
+    pub agreement: Vec<Vec<Option<f64>>>,
+}
+
+/// Have every judge in `judges` give a verdict on `sessions`
+/// (`(session_id, user_id, topic)`) and compare the verdicts.
+pub async fn calibrate(
+    db: &Arc<dyn Store>,
+    judges: &[ClientInfo],
+    sessions: &[(String, String, String)],
+    metrics: Arc<dyn Metrics>,
+) -> CalibrationReport {
+    let mut known: HashMap<(String, String), Option<String>> = db
+        .fetch_analyses_of_kind(VERDICT_KIND, None)
+        .await
+        .into_iter()
+        .filter_map(|(session_id, result)| {
+            let verdict: Verdict = serde_json::from_value(result).ok()?;
+            Some(((session_id, verdict.model), verdict.winner))
+        })
+        .collect();
+    let mut failed = vec![0; judges.len()];
+    let mut rows: Vec<Vec<Option<Option<String>>>> = Vec::new();
+    for (session_id, user_id, topic) in sessions {
+        let messages = db.fetch_history(user_id, session_id).await;
+        let (judged, transcript) = split_transcript(export::transcript_from_history(&messages));
+        let Some(original) = original_verdict(&judged) else {
+            continue;
+        };
+        known.insert((session_id.clone(), original.model), original.winner);
+        let pending: Vec<(usize, &ClientInfo)> = judges
+            .iter()
+            .enumerate()
+            .filter(|(_, judge)| !known.contains_key(&(session_id.clone(), judge.model_id.clone())))
+            .collect();
+        let verdicts = futures::future::join_all(pending.iter().map(|(_, judge)| {
+            judge_again(
+                db,
+                judge,
+                user_id,
+                session_id,
+                topic,
+                &transcript,
+                metrics.clone(),
+            )
+        }))
+        .await;
+        for ((index, judge), verdict) in pending.into_iter().zip(verdicts) {
+            match verdict {
+                Ok(verdict) => {
+                    known.insert((session_id.clone(), judge.model_id.clone()), verdict.winner);
+                }
+                Err(e) => {
+                    warn!(
+                        "Calibration verdict of {} on {} failed: {}",
+                        judge.model_id, session_id, e
+                    );
+                    failed[index] += 1;
+                }
+            }
+        }
+        rows.push(
+            judges
+                .iter()
+                .map(|judge| {
+                    known
+                        .get(&(session_id.clone(), judge.model_id.clone()))
+                        .cloned()
+                })
+                .collect(),
+        );
+    }
+    report(judges, &rows, &failed)
+}
+
+/// Per-judge counts and pairwise agreement over `rows`, one per debate with
+/// each judge's winner (`None` when the judge gave no verdict).
+fn report(
+    judges: &[ClientInfo],
+    rows: &[Vec<Option<Option<String>>>],
+    failed: &[usize],
+) -> CalibrationReport {
+    let label = |winner: &Option<String>| winner.clone().unwrap_or_else(|| NO_WINNER.to_string());
+    let labels = |i: usize| rows.iter().map(move |row| row[i].as_ref().map(label));
+    let n = judges.len();
+    let mut kappa = vec![vec![None; n]; n];
+    let mut agreement = vec![vec![None; n]; n];
+    for i in 0..n {
+        if labels(i).any(|l| l.is_some()) {
+            kappa[i][i] = Some(1.0);
+            agreement[i][i] = Some(1.0);
+        }
+        for j in i + 1..n {
+            let pairs: Vec<(String, String)> = labels(i)
+                .zip(labels(j))
+                .filter_map(|(a, b)| Some((a?, b?)))
+                .collect();
+            if pairs.is_empty() {
+                continue;
+            }
+            let same = pairs.iter().filter(|(a, b)| a == b).count();
+            let observed = round(same as f64 / pairs.len() as f64);
+            agreement[i][j] = Some(observed);
+            agreement[j][i] = Some(observed);
+            let k = cohens_kappa(&pairs).map(round);
+            kappa[i][j] = k;
+            kappa[j][i] = k;
+        }
+    }
+    let judges = judges
+        .iter()
+        .enumerate()
+        .map(|(i, judge)| {
+            let given: Vec<String> = labels(i).flatten().collect();
+            let count = |l: &str| given.iter().filter(|g| *g == l).count();
+            let (pro, con) = (
+                count(Position::Pro.role_str()),
+                count(Position::Con.role_str()),
+            );
+            let others: Vec<f64> = (0..n)
+                .filter(|j| *j != i)
+                .filter_map(|j| kappa[i][j])
+                .collect();
+            JudgeStats {
+                model: judge.model_id.clone(),
+                verdicts: given.len(),
+                pro,
+                con,
+                no_winner: count(NO_WINNER),
+                pro_share: (pro + con > 0).then(|| round(pro as f64 / (pro + con) as f64)),
+                mean_kappa: (!others.is_empty())
+                    .then(|| round(others.iter().sum::<f64>() / others.len() as f64)),
+                failed: failed[i],
+            }
+        })
+        .collect();
+    CalibrationReport {
+        debates: rows.len(),
+        judges,
+        kappa,
+        agreement,
+    }
+}
+
+/// Cohen's kappa of paired labels; `None` when chance agreement is total.
+fn cohens_kappa(pairs: &[(String, String)]) -> Option<f64> {
+    let total = pairs.len() as f64;
+    let observed = pairs.iter().filter(|(a, b)| a == b).count() as f64 / total;
+    let mut first: HashMap<&str, f64> = HashMap::new();
+    let mut second: HashMap<&str, f64> = HashMap::new();
+    for (a, b) in pairs {
+        *first.entry(a).or_default() += 1.0;
+        *second.entry(b).or_default() += 1.0;
+    }
+    let expected: f64 = first
+        .iter()
+        .map(|(label, n)| n / total * second.get(label).copied().unwrap_or(0.0) / total)
+        .sum();
+    (expected < 1.0).then(|| (observed - expected) / (1.0 - expected))
+}
+
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(labels: &[(&str, &str)]) -> Vec<(String, String)> {
+        labels
+            .iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn kappa_discounts_chance_agreement() {
+        let perfect = pairs(&[("pro", "pro"), ("con", "con"), ("none", "none")]);
+        assert_eq!(cohens_kappa(&perfect), Some(1.0));
+        // Observed 0.75, expected 0.5
+        let partial = pairs(&[
+            ("pro", "pro"),
+            ("pro", "con"),
+            ("con", "con"),
+            ("con", "con"),
+        ]);
+        assert_eq!(cohens_kappa(&partial), Some(0.5));
+        let opposed = pairs(&[("pro", "con"), ("con", "pro")]);
+        assert_eq!(cohens_kappa(&opposed), Some(-1.0));
+    }
+
+    #[test]
+    fn kappa_is_undefined_when_both_judges_always_agree_by_chance() {
+        assert_eq!(
+            cohens_kappa(&pairs(&[("pro", "pro"), ("pro", "pro")])),
+            None
+        );
+    }
+
+    #[test]
+    fn report_compares_judges_on_shared_debates() {
+        let judges: Vec<ClientInfo> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|role| ClientInfo::mock(role))
+            .collect();
+        let winner = |w: &str| Some(Some(w.to_string()));
+        let rows = vec![
+            vec![winner("pro"), winner("pro"), None, None],
+            vec![winner("con"), winner("pro"), Some(None), None],
+            vec![Some(None), Some(None), None, None],
+        ];
+        let report = report(&judges, &rows, &[0, 0, 1, 3]);
+        assert_eq!(report.debates, 3);
+        assert_eq!(report.kappa[0][1], Some(0.5));
+        assert_eq!(report.kappa[1][0], Some(0.5));
+        assert_eq!(report.agreement[0][1], Some(0.67));
+        assert_eq!(report.kappa[0][2], Some(0.0));
+        assert_eq!(report.agreement[0][2], Some(0.0));
+        assert_eq!(report.kappa[2][2], Some(1.0));
+        assert_eq!(report.kappa[3][3], None);
+        assert_eq!(report.kappa[0][3], None);
+
+        let a = &report.judges[0];
+        assert_eq!((a.verdicts, a.pro, a.con, a.no_winner), (3, 1, 1, 1));
+        assert_eq!(a.pro_share, Some(0.5));
+        assert_eq!(a.mean_kappa, Some(0.25));
+        let c = &report.judges[2];
+        assert_eq!((c.verdicts, c.no_winner, c.failed), (1, 1, 1));
+        assert_eq!(c.pro_share, None);
+        let d = &report.judges[3];
+        assert_eq!((d.verdicts, d.mean_kappa, d.failed), (0, None, 3));
+    }
+
+    #[test]
+    fn verdict_reads_the_winner_line() {
+        let verdict = Verdict::new("mock/judge", "Both sides argued well.\n**Winner: Con**");
+        assert_eq!(verdict.winner.as_deref(), Some("con"));
+        assert_eq!(Verdict::new("mock/judge", "A draw.").winner, None);
+    }
+}
@@ -0,0 +1,103 @@
+//! Topic categories.
+//!
+//! When a debate starts, the judge's model files its topic under one to
+//! three of [`CATEGORIES`]; topics that fit none are tagged `other`. Tags are
+//! stored per session in `session_tags` and used to filter session lists,
+//! `/stats` and the podcast feed.
+
+use ai_lib_rust::Message;
+
+use crate::storage::Store;
+use crate::types::{ClientBackend, ClientInfo};
+
+/// Categories a topic can be tagged with, besides [`OTHER`].
+pub const CATEGORIES: &[&str] = &[
+    "tech",
+    "science",
+    "ethics",
+    "policy",
+    "economics",
+    "society",
+    "education",
+    "health",
+    "environment",
+    "sports",
+    "culture",
+    "philosophy",
+];
+
+/// Tag for topics that fit no category.
+pub const OTHER: &str = "other";
+
+/// Most tags kept per topic.
+const MAX_TAGS: usize = 3;
+
+/// The known category named by `name` (case-insensitive), if any.
+pub fn normalize(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_lowercase();
+    CATEGORIES
+        .iter()
+        .chain(std::iter::once(&OTHER))
+        .find(|c| **c == name)
+        .copied()
+}
+
+/// Categories of a debate topic, most relevant first.
+pub async fn classify(client: &ClientInfo, topic: &str) -> anyhow::Result<Vec<&'static str>> {
+    let live = match &client.backend {
+        ClientBackend::Live(live) => live,
+        ClientBackend::Mock => return Ok(vec![OTHER]),
+    };
+    let messages = vec![
+        Message::system(format!(
+            "You file debate topics under categories. Categories: {}.\n\
+             Answer with a JSON array of one to {} category names from that list, most relevant first, \
+             and nothing else. Answer [\"{}\"] when none fits.",
+            CATEGORIES.join(", "),
+            MAX_TAGS,
+            OTHER
+        )),
+        Message::user(topic.to_string()),
+    ];
+    let response = live
+        .chat()
+        .messages(messages)
+        .temperature(0.0)
+        .max_tokens(50)
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("Topic classification failed for {}: {}", client.name, e))?;
+    Ok(parse_categories(&response.content))
+}
+
+/// Known categories from the model's reply, without duplicates; `other` when
+/// none are known.
+fn parse_categories(reply: &str) -> Vec<&'static str> {
+    let names: Vec<String> = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => {
+            serde_json::from_str(&reply[start..=end]).unwrap_or_default()
+        }
+        _ => Vec::new(),
+    };
+    let mut tags: Vec<&'static str> = Vec::new();
+    for tag in names.iter().filter_map(|name| normalize(name)) {
+        if tag != OTHER && !tags.contains(&tag) && tags.len() < MAX_TAGS {
+            tags.push(tag);
+        }
+    }
+    if tags.is_empty() {
+        tags.push(OTHER);
+    }
+    tags
+}
+
+/// Classify a session's topic and store its tags.
+pub async fn tag_session(
+    db: &dyn Store,
+    client: &ClientInfo,
+    session_id: &str,
+    topic: &str,
+) -> anyhow::Result<()> {
+    let tags = classify(client, topic).await?;
+    db.save_session_tags(session_id, &tags).await
+}
@@ -0,0 +1,63 @@
+//! Citation tracking for search-backed rounds.
+//!
+//! Sources from all searches in a round are numbered in order of first
+//! appearance (deduplicated by URL). Debaters are asked to cite them as `[n]`;
+//! a source counts as referenced when its marker or URL appears in the output.
+
+use serde::Serialize;
+
+use crate::search::SearchHit;
+use crate::tools::SearchResult;
+
+/// A source referenced by a debater's argument.
+#[derive(Debug, Clone, Serialize)]
+pub struct Citation {
+    pub index: usize,
+    pub title: String,
+    pub url: String,
+}
+
+/// Number sources across search results in order of first appearance.
+pub fn number_sources(results: &[SearchResult]) -> Vec<SearchHit> {
+    let mut sources: Vec<SearchHit> = Vec::new();
+    for hit in results.iter().flat_map(|r| r.sources.iter()) {
+        if !hit.url.is_empty() && !sources.iter().any(|s| s.url == hit.url) {
+            sources.push(hit.clone());
+        }
+    }
+    sources
+}
+
+/// Format numbered sources for the prompt so the model can cite them.
+pub fn source_list(sources: &[SearchHit]) -> String {
+    sources
+        .iter()
+        .enumerate()
+        .map(|(i, s)| format!("[{}] {} - {}", i + 1, s.title, s.url))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Sources whose `[n]` marker or URL appears in the content.
+pub fn referenced(content: &str, sources: &[SearchHit]) -> Vec<Citation> {
+    sources
+        .iter()
+        .enumerate()
+        .filter(|(i, s)| content.contains(&format!("[{}]", i + 1)) || content.contains(&s.url))
+        .map(|(i, s)| Citation {
+            index: i + 1,
+            title: s.title.clone(),
+            url: s.url.clone(),
+        })
+        .collect()
+}
+
+/// Markdown "Sources" footnote section appended to saved messages.
+pub fn sources_footnote(citations: &[Citation]) -> String {
+    let mut out = String::from("\n\n## Sources\n");
+    for c in citations {
+        let title = if c.title.is_empty() { &c.url } else { &c.title };
+        out.push_str(&format!("[{}] [{}]({})\n", c.index, title, c.url));
+    }
+    out
+}
@@ -0,0 +1,601 @@
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use ai_lib_rust::AiClientBuilder;
+use tracing::{info, warn};
+
+use crate::types::{
+    AvailableModel, AvailableProvider, ClientBackend, ClientInfo, ModelCapabilities, Participant,
+};
+
+// ---------------------------------------------------------------------------
+// Default model assignments
+// ---------------------------------------------------------------------------
+
+const PRO_DEFAULT_MODEL_ID: &str = "deepseek/deepseek-chat";
+const CON_DEFAULT_MODEL_ID: &str = "zhipu/glm-4-plus";
+const JUDGE_DEFAULT_MODEL_ID: &str = "groq/llama-3.3-70b-versatile";
+
+const FALLBACK_MODEL_ID: &str = "mistral/mistral-small-latest";
+
+// ---------------------------------------------------------------------------
+// Runtime token / history configuration (can be overridden via env vars)
+// ---------------------------------------------------------------------------
+
+/// Get max tokens for a role from environment or defaults.
+/// Defaults: PRO=2048, CON=2048, JUDGE=3072
+pub fn max_tokens_for_role(role: &str) -> u32 {
+    let env_key = match role.to_lowercase().as_str() {
+        "pro" => "PRO_MAX_TOKENS",
+        "con" => "CON_MAX_TOKENS",
+        "judge" => "JUDGE_MAX_TOKENS",
+        _ => "DEFAULT_MAX_TOKENS",
+    };
+    std::env::var(env_key)
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or_else(|| match role.to_lowercase().as_str() {
+            "pro" => 2048,
+            "con" => 2048,
+            "judge" => 3072,
+            _ => 2048,
+        })
+}
+
+/// Get reserved tokens for system + reply overhead for a role.
+/// Defaults to 512 tokens; can be overridden per role via env vars.
+pub fn reserved_tokens_for_role(role: &str) -> u32 {
+    let env_key = match role.to_lowercase().as_str() {
+        "pro" => "PRO_RESERVED_TOKENS",
+        "con" => "CON_RESERVED_TOKENS",
+        "judge" => "JUDGE_RESERVED_TOKENS",
+        _ => "TRANSCRIPT_RESERVED_TOKENS",
+    };
+    std::env::var(env_key)
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(512)
+}
+
+/// Check whether the offline mock provider is enabled (`DEBATE_MOCK=1`).
+pub fn mock_enabled() -> bool {
+    std::env::var("DEBATE_MOCK")
+        .ok()
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Whether reasoning models' reasoning is streamed to clients as
+/// `reasoning_delta` events (`STREAM_REASONING`, default on). It is never
+/// saved or shown to the other side either way.
+pub fn stream_reasoning() -> bool {
+    std::env::var("STREAM_REASONING")
+        .map(|v| !(v == "0" || v.eq_ignore_ascii_case("false")))
+        .unwrap_or(true)
+}
+
+/// Delay between mock stream chunks in milliseconds (default 40).
+pub fn mock_delay_ms() -> u64 {
+    std::env::var("DEBATE_MOCK_DELAY_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(40)
+}
+
+/// Load secrets mounted as files (Docker / Kubernetes secrets): for every
+/// `<NAME>_API_KEY_FILE` or `ADMIN_TOKEN_FILE`, read the file into
+/// `<NAME>_API_KEY` / `ADMIN_TOKEN` unless that variable is already set.
+pub fn load_secret_files() {
+    for (key, path) in std::env::vars() {
+        let Some(target) = key.strip_suffix("_FILE") else {
+            continue;
+        };
+        if !(target.ends_with("_API_KEY") || target == "ADMIN_TOKEN") {
+            continue;
+        }
+        if std::env::var(target).is_ok_and(|v| !v.is_empty()) {
+            continue;
+        }
+        match std::fs::read_to_string(&path) {
+            Ok(secret) => {
+                std::env::set_var(target, secret.trim());
+                info!("Loaded {} from {}", target, path);
+            }
+            Err(e) => warn!("Failed to read {} ({}): {}", key, path, e),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Provider registry for auto-detection
+// ---------------------------------------------------------------------------
+
+/// (provider_id, display_name, env_var_name, models[])
+const PROVIDER_REGISTRY: &[(&str, &str, &str, &[(&str, &str)])] = &[
+    (
+        "deepseek",
+        "DeepSeek",
+        "DEEPSEEK_API_KEY",
+        &[
+            ("deepseek/deepseek-chat", "DeepSeek Chat"),
+            ("deepseek/deepseek-reasoner", "DeepSeek Reasoner"),
+        ],
+    ),
+    (
+        "zhipu",
+        "Zhipu (智谱)",
+        "ZHIPU_API_KEY",
+        &[
+            ("zhipu/glm-4-plus", "GLM-4 Plus"),
+            ("zhipu/glm-4-flash", "GLM-4 Flash"),
+        ],
+    ),
+    (
+        "groq",
+        "Groq",
+        "GROQ_API_KEY",
+        &[
+            ("groq/llama-3.3-70b-versatile", "Llama 3.3 70B"),
+            ("groq/llama-3.1-8b-instant", "Llama 3.1 8B Instant"),
+        ],
+    ),
+    (
+        "mistral",
+        "Mistral",
+        "MISTRAL_API_KEY",
+        &[
+            ("mistral/mistral-small-latest", "Mistral Small"),
+            ("mistral/mistral-large-latest", "Mistral Large"),
+        ],
+    ),
+    (
+        "openai",
+        "OpenAI",
+        "OPENAI_API_KEY",
+        &[
+            ("openai/gpt-4o", "GPT-4o"),
+            ("openai/gpt-4o-mini", "GPT-4o Mini"),
+        ],
+    ),
+    (
+        "anthropic",
+        "Anthropic",
+        "ANTHROPIC_API_KEY",
+        &[
+            ("anthropic/claude-3-5-sonnet", "Claude 3.5 Sonnet"),
+            ("anthropic/claude-3-5-haiku", "Claude 3.5 Haiku"),
+        ],
+    ),
+    (
+        "minimax",
+        "MiniMax",
+        "MINIMAX_API_KEY",
+        &[("minimax/abab6.5s-chat", "ABAB 6.5s Chat")],
+    ),
+];
+
+/// (provider_id, brand color, icon URL)
+const PROVIDER_BRANDING: &[(&str, &str, &str)] = &[
+    (
+        "deepseek",
+        "#4d6bfe",
+        "https://www.deepseek.com/favicon.ico",
+    ),
+    ("zhipu", "#3859ff", "https://www.bigmodel.cn/favicon.ico"),
+    ("groq", "#f55036", "https://groq.com/favicon.ico"),
+    ("mistral", "#fa520f", "https://mistral.ai/favicon.ico"),
+    ("openai", "#10a37f", "https://openai.com/favicon.ico"),
+    (
+        "anthropic",
+        "#d97757",
+        "https://www.anthropic.com/favicon.ico",
+    ),
+    ("minimax", "#e73562", "https://www.minimaxi.com/favicon.ico"),
+];
+
+/// Color of providers without branding.
+const DEFAULT_PROVIDER_COLOR: &str = "#6b7280";
+
+/// Models that think before answering.
+const REASONING_MODELS: &[&str] = &["deepseek/deepseek-reasoner"];
+
+/// Models that do not support tool calls.
+const NO_TOOL_MODELS: &[&str] = &["deepseek/deepseek-reasoner"];
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/// Detect available providers by checking environment variables.
+/// Returns a list of providers with their available models and key status.
+pub fn detect_available_providers() -> Vec<AvailableProvider> {
+    PROVIDER_REGISTRY
+        .iter()
+        .map(|(provider, display_name, env_var, models)| {
+            let has_key = std::env::var(env_var).is_ok();
+            AvailableProvider {
+                provider: provider.to_string(),
+                display_name: display_name.to_string(),
+                env_var: env_var.to_string(),
+                has_key,
+                models: models
+                    .iter()
+                    .map(|(id, name)| AvailableModel {
+                        model_id: id.to_string(),
+                        display_name: name.to_string(),
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Display names, branding and capabilities of the model playing `role`.
+pub fn participant(role: &str, model_id: &str) -> Participant {
+    let provider = provider_name_from_model_id(model_id);
+    let registered = PROVIDER_REGISTRY.iter().find(|(id, ..)| *id == provider);
+    let model_name = registered
+        .and_then(|(.., models)| models.iter().find(|(id, _)| *id == model_id))
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| {
+            model_id
+                .split_once('/')
+                .map_or(model_id, |(_, m)| m)
+                .to_string()
+        });
+    let branding = PROVIDER_BRANDING.iter().find(|(id, ..)| *id == provider);
+    let mock = provider == "mock";
+    Participant {
+        role: role.to_string(),
+        model_id: model_id.to_string(),
+        model_name,
+        provider: provider.to_string(),
+        provider_name: registered
+            .map_or(provider, |(_, name, ..)| *name)
+            .to_string(),
+        color: branding
+            .map_or(DEFAULT_PROVIDER_COLOR, |(_, color, _)| *color)
+            .to_string(),
+        logo: branding.map(|(.., logo)| logo.to_string()),
+        capabilities: ModelCapabilities {
+            tools: !mock && !NO_TOOL_MODELS.contains(&model_id),
+            reasoning: REASONING_MODELS.contains(&model_id),
+        },
+    }
+}
+
+/// Get default model IDs for each role.
+pub fn default_models() -> (&'static str, &'static str, &'static str) {
+    (
+        PRO_DEFAULT_MODEL_ID,
+        CON_DEFAULT_MODEL_ID,
+        JUDGE_DEFAULT_MODEL_ID,
+    )
+}
+
+/// Model IDs configured for Pro, Con and Judge (`PRO_MODEL_ID` etc., or the
+/// defaults).
+pub fn role_model_ids() -> (String, String, String) {
+    (
+        model_id_for_role("pro", PRO_DEFAULT_MODEL_ID),
+        model_id_for_role("con", CON_DEFAULT_MODEL_ID),
+        model_id_for_role("judge", JUDGE_DEFAULT_MODEL_ID),
+    )
+}
+
+/// Model every role falls back to when its provider fails.
+pub fn fallback_model_id() -> &'static str {
+    FALLBACK_MODEL_ID
+}
+
+/// Environment variable holding the API key for a `provider/model` id, if the
+/// provider is known.
+pub fn api_key_var(model_id: &str) -> Option<&'static str> {
+    let provider = provider_name_from_model_id(model_id);
+    PROVIDER_REGISTRY
+        .iter()
+        .find(|(id, ..)| *id == provider)
+        .map(|(_, _, env_var, _)| *env_var)
+}
+
+/// Build a client for any model_id (used for dynamic model selection).
+/// Uses Mistral as universal fallback.
+pub async fn build_client_for_model(model_id: &str) -> anyhow::Result<ClientInfo> {
+    if mock_enabled() {
+        return Ok(ClientInfo::mock(provider_name_from_model_id(model_id)));
+    }
+
+    let name = provider_name_from_model_id(model_id);
+    let mut builder = AiClientBuilder::new();
+
+    if use_resilience() {
+        builder = builder.circuit_breaker_default().max_inflight(4);
+    }
+
+    builder = builder.with_fallbacks(vec![FALLBACK_MODEL_ID.to_string()]);
+
+    let client = builder
+        .build(model_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to build client for {}: {}", model_id, e))?;
+
+    info!("✅ Dynamic client ready: {} ({})", model_id, name);
+
+    Ok(ClientInfo {
+        name: name.to_string(),
+        model_id: model_id.to_string(),
+        backend: ClientBackend::Live(Arc::new(client)),
+    })
+}
+
+/// Initialize the AI protocol environment (local dir or GitHub fallback).
+/// Must be called before building any clients.
+pub fn init_protocol_env() {
+    if std::env::var("AI_PROTOCOL_DIR").is_err() && std::env::var("AI_PROTOCOL_PATH").is_err() {
+        let local_paths = ["ai-protocol", "../ai-protocol", "../../ai-protocol"];
+        let mut found_local = false;
+        for path in local_paths {
+            if std::path::Path::new(path).join("v1/providers").exists() {
+                std::env::set_var("AI_PROTOCOL_DIR", path);
+                info!("Using local AI-Protocol: {}", path);
+                found_local = true;
+                break;
+            }
+        }
+        if !found_local {
+            info!("Using remote AI-Protocol from GitHub (may be slow)");
+            std::env::set_var(
+                "AI_PROTOCOL_DIR",
+                "https://raw.githubusercontent.com/hiddenpath/ai-protocol/main",
+            );
+        }
+    }
+}
+
+/// Initialize default clients for the three roles.
+pub async fn init_clients() -> anyhow::Result<(ClientInfo, ClientInfo, ClientInfo)> {
+    load_secret_files();
+
+    if mock_enabled() {
+        info!("DEBATE_MOCK enabled: all roles use the offline mock provider");
+        return Ok((
+            ClientInfo::mock("pro"),
+            ClientInfo::mock("con"),
+            ClientInfo::mock("judge"),
+        ));
+    }
+
+    init_protocol_env();
+    warn_provider_proxy_overrides();
+
+    let (pro_model, con_model, judge_model) = role_model_ids();
+
+    // Log key availability (masked)
+    let check_key = |name: &str, env_var: &str| match std::env::var(env_var) {
+        Ok(val) => {
+            let mask = if val.len() > 4 {
+                format!("{}...", &val[..4])
+            } else {
+                "***".to_string()
+            };
+            info!("Key: {} ({}) SET [{}]", name, env_var, mask);
+        }
+        Err(_) => info!("Key: {} ({}) MISSING", name, env_var),
+    };
+    check_key("DeepSeek", "DEEPSEEK_API_KEY");
+    check_key("Zhipu", "ZHIPU_API_KEY");
+    check_key("Groq", "GROQ_API_KEY");
+    check_key("Mistral", "MISTRAL_API_KEY");
+
+    let pro = build_role_client(&pro_model, "pro").await?;
+    let con = build_role_client(&con_model, "con").await?;
+    let judge = build_role_client(&judge_model, "judge").await?;
+
+    Ok((pro, con, judge))
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+fn provider_name_from_model_id(model_id: &str) -> &str {
+    model_id.split('/').next().unwrap_or(model_id)
+}
+
+/// Model configured for the fact-checker role (`FACT_CHECK_MODEL_ID`), if any.
+pub fn fact_check_model_id() -> Option<String> {
+    std::env::var("FACT_CHECK_MODEL_ID")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+}
+
+/// Judge models compared by judge calibration besides the configured judge
+/// (`CALIBRATION_JUDGE_MODELS`, comma-separated).
+pub fn calibration_judge_model_ids() -> Vec<String> {
+    std::env::var("CALIBRATION_JUDGE_MODELS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect()
+}
+
+/// Judge models that vote on every verdict alongside the judge
+/// (`JUDGE_PANEL`, comma-separated); empty when there is no panel.
+pub fn judge_panel_model_ids() -> Vec<String> {
+    std::env::var("JUDGE_PANEL")
+        .unwrap_or_default()
+        .split(',')
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect()
+}
+
+/// How often partial round content is checkpointed
+/// (`CHECKPOINT_INTERVAL_SECS`, default 5); `None` when set to 0.
+pub fn checkpoint_interval() -> Option<Duration> {
+    let secs = std::env::var("CHECKPOINT_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(5);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// How the Pro and Con opening statements are generated.
+#[derive(Clone, Copy, PartialEq)]
+pub enum OpeningMode {
+    /// Con opens after Pro and sees Pro's statement (default).
+    Sequential,
+    /// Both openings are generated at once and streamed interleaved.
+    Parallel,
+    /// Both openings are generated at once; Con's events are held until
+    /// Pro's opening has finished streaming.
+    Buffered,
+}
+
+/// Opening mode from `CONCURRENT_OPENINGS` (`parallel` or `buffered`).
+pub fn opening_mode() -> OpeningMode {
+    match std::env::var("CONCURRENT_OPENINGS")
+        .unwrap_or_default()
+        .to_lowercase()
+        .as_str()
+    {
+        "parallel" | "1" | "true" => OpeningMode::Parallel,
+        "buffered" => OpeningMode::Buffered,
+        _ => OpeningMode::Sequential,
+    }
+}
+
+fn model_id_for_role(role: &str, default: &str) -> String {
+    let env_key = match role {
+        "pro" => "PRO_MODEL_ID",
+        "con" => "CON_MODEL_ID",
+        "judge" => "JUDGE_MODEL_ID",
+        _ => "MODEL_ID",
+    };
+    std::env::var(env_key).unwrap_or_else(|_| default.to_string())
+}
+
+fn use_resilience() -> bool {
+    std::env::var("AI_DEBATE_RESILIENCE")
+        .ok()
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Get the proxy override for a provider from `<PROVIDER>_PROXY_URL`.
+/// `Some(None)` means the provider should connect directly (`none`/`direct`).
+fn proxy_override_for_provider(provider: &str) -> Option<Option<String>> {
+    let env_key = format!("{}_PROXY_URL", provider.to_uppercase());
+    let val = std::env::var(env_key).ok()?;
+    let val = val.trim();
+    if val.is_empty() || val.eq_ignore_ascii_case("none") || val.eq_ignore_ascii_case("direct") {
+        Some(None)
+    } else {
+        Some(Some(val.to_string()))
+    }
+}
+
+/// Warn about `<PROVIDER>_PROXY_URL` settings for model providers. ai-lib-rust
+/// only reads the process-wide `AI_PROXY_URL` when it creates a transport, so
+/// a per-provider proxy cannot be applied to model clients without rewriting
+/// the environment while other threads run.
+fn warn_provider_proxy_overrides() {
+    for (provider, ..) in PROVIDER_REGISTRY {
+        if proxy_override_for_provider(provider).is_some() {
+            warn!(
+                "{}_PROXY_URL is ignored: model clients all use AI_PROXY_URL",
+                provider.to_uppercase()
+            );
+        }
+    }
+}
+
+/// Public URL of the server (`PUBLIC_BASE_URL`, no trailing slash), used for
+/// links sent outside the web UI.
+pub fn public_base_url() -> Option<String> {
+    std::env::var("PUBLIC_BASE_URL")
+        .ok()
+        .map(|v| v.trim().trim_end_matches('/').to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Directory with a custom web UI (`STATIC_DIR`) served in place of the
+/// embedded pages; `None` when unset or not a directory.
+pub fn static_dir() -> Option<std::path::PathBuf> {
+    let dir = std::env::var("STATIC_DIR")
+        .ok()
+        .filter(|v| !v.trim().is_empty())?;
+    let path = std::path::PathBuf::from(dir.trim());
+    if !path.is_dir() {
+        warn!(
+            "STATIC_DIR {} is not a directory; serving the embedded web UI",
+            path.display()
+        );
+        return None;
+    }
+    Some(path)
+}
+
+/// Shared HTTP client for search backends, embeddings, moderation, alerts and
+/// OIDC discovery, so connections are pooled across calls. Goes through
+/// `AI_PROXY_URL` like the model clients unless `TOOLS_PROXY_URL` overrides it
+/// (`direct` bypasses the proxy). Requests time out after `HTTP_TIMEOUT_SECS`
+/// (default 30) unless the call sets its own timeout.
+pub fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        let timeout = std::env::var("HTTP_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .timeout(Duration::from_secs(timeout));
+        let proxy = proxy_override_for_provider("tools")
+            .unwrap_or_else(|| std::env::var("AI_PROXY_URL").ok().filter(|v| !v.is_empty()));
+        match proxy.map(reqwest::Proxy::all) {
+            Some(Ok(proxy)) => builder = builder.proxy(proxy),
+            Some(Err(e)) => warn!("Ignoring invalid tools proxy: {}", e),
+            None => {}
+        }
+        builder.build().unwrap_or_else(|e| {
+            warn!("Failed to build shared HTTP client, using defaults: {}", e);
+            reqwest::Client::new()
+        })
+    })
+}
+
+fn fallback_for_role(_role: &str) -> Vec<String> {
+    vec![FALLBACK_MODEL_ID.to_string()]
+}
+
+async fn build_role_client(model_id: &str, role: &str) -> anyhow::Result<ClientInfo> {
+    let name = provider_name_from_model_id(model_id);
+    let mut builder = AiClientBuilder::new();
+
+    if use_resilience() {
+        builder = builder.circuit_breaker_default().max_inflight(4);
+    }
+
+    let fallbacks = fallback_for_role(role);
+    if !fallbacks.is_empty() {
+        builder = builder.with_fallbacks(fallbacks.clone());
+    }
+
+    let client = builder
+        .build(model_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to build client for {}: {}", name, e))?;
+
+    info!(
+        "Provider [{}] ready, model: {}, role: {}",
+        name, model_id, role
+    );
+
+    Ok(ClientInfo {
+        name: name.to_string(),
+        model_id: model_id.to_string(),
+        backend: ClientBackend::Live(Arc::new(client)),
+    })
+}
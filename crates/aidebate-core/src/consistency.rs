@@ -0,0 +1,262 @@
+//! Repetition and self-contradiction checks between a side's rounds.
+//!
+//! Each new Pro or Con round is split into paragraphs and embedded together
+//! with the side's earlier rounds. A paragraph nearly identical to an earlier
+//! one (cosine at least `REPETITION_THRESHOLD`, default 0.9) is flagged as a
+//! repetition. Paragraphs that are related but not repeated are the candidates
+//! for a contradiction: the closest pairs go to the judge's model in one call,
+//! which says which of them contradict each other.
+//!
+//! Findings are streamed as warnings and handed to the judge as consistency
+//! notes for scoring.
+
+use ai_lib_rust::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::debate::Turn;
+use crate::embeddings;
+use crate::types::{ClientBackend, ClientInfo, DebatePhase, Position};
+
+/// Stored `debate_analysis` kind for consistency findings.
+pub const ANALYSIS_KIND: &str = "consistency";
+
+const DEFAULT_REPETITION_THRESHOLD: f32 = 0.9;
+
+/// Lowest similarity at which two paragraphs are checked for a contradiction.
+const RELATED_THRESHOLD: f32 = 0.6;
+
+/// Paragraphs shorter than this are too slight to compare.
+const MIN_PARAGRAPH_CHARS: usize = 40;
+
+/// Longest paragraph compared and quoted, in characters.
+const MAX_PARAGRAPH_CHARS: usize = 600;
+
+/// Most repetitions reported per round.
+const MAX_REPETITIONS: usize = 3;
+
+/// Most paragraph pairs sent to the contradiction check per round.
+const MAX_PAIRS: usize = 6;
+
+fn repetition_threshold() -> f32 {
+    std::env::var("REPETITION_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_REPETITION_THRESHOLD)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// `repetition` or `contradiction`.
+    pub kind: String,
+    pub side: String,
+    pub phase: String,
+    /// Phase of the earlier round that is repeated or contradicted.
+    pub earlier_phase: String,
+    /// Paragraph of the new round.
+    pub quote: String,
+    /// Paragraph of the earlier round.
+    pub earlier_quote: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub explanation: String,
+}
+
+/// A paragraph of a round, with the phase it comes from.
+struct Paragraph<'a> {
+    phase: DebatePhase,
+    text: &'a str,
+}
+
+/// Check a new round of `side` against the side's rounds in `earlier`.
+pub async fn check_round(
+    checker: &ClientInfo,
+    topic: &str,
+    side: Position,
+    phase: DebatePhase,
+    content: &str,
+    earlier: &[Turn],
+) -> anyhow::Result<Vec<Finding>> {
+    let previous: Vec<Paragraph> = earlier
+        .iter()
+        .filter(|(s, ..)| *s == side)
+        .flat_map(|(_, p, text, _)| paragraphs(text).map(move |text| Paragraph { phase: *p, text }))
+        .collect();
+    if previous.is_empty() {
+        return Ok(Vec::new());
+    }
+    let current: Vec<&str> = paragraphs(content).collect();
+    if current.is_empty() {
+        return Ok(Vec::new());
+    }
+    let texts: Vec<String> = current
+        .iter()
+        .copied()
+        .chain(previous.iter().map(|p| p.text))
+        .map(str::to_string)
+        .collect();
+    let vectors = embeddings::embed(&texts).await?;
+    let (current_vecs, previous_vecs) = vectors.split_at(current.len().min(vectors.len()));
+
+    let threshold = repetition_threshold();
+    let mut repeated = Vec::new();
+    let mut related = Vec::new();
+    for (text, vector) in current.iter().zip(current_vecs) {
+        let best = previous
+            .iter()
+            .zip(previous_vecs)
+            .map(|(p, v)| (p, embeddings::cosine(vector, v)))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        match best {
+            Some((p, score)) if score >= threshold => repeated.push((*text, p, score)),
+            Some((p, score)) if score >= RELATED_THRESHOLD => related.push((*text, p, score)),
+            _ => {}
+        }
+    }
+    repeated.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+    related.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+    related.truncate(MAX_PAIRS);
+
+    let finding = |kind: &str, text: &str, earlier: &Paragraph| Finding {
+        kind: kind.to_string(),
+        side: side.role_str().to_string(),
+        phase: phase.as_str().to_string(),
+        earlier_phase: earlier.phase.as_str().to_string(),
+        quote: text.to_string(),
+        earlier_quote: earlier.text.to_string(),
+        similarity: None,
+        explanation: String::new(),
+    };
+    let mut findings: Vec<Finding> = repeated
+        .into_iter()
+        .take(MAX_REPETITIONS)
+        .map(|(text, earlier, score)| Finding {
+            similarity: Some((score * 100.0).round() / 100.0),
+            ..finding("repetition", text, earlier)
+        })
+        .collect();
+
+    let pairs: Vec<(&str, &str)> = related.iter().map(|(t, p, _)| (*t, p.text)).collect();
+    for (index, explanation) in contradictions(checker, topic, side, &pairs).await? {
+        let (text, earlier, _) = related[index];
+        findings.push(Finding {
+            explanation,
+            ..finding("contradiction", text, earlier)
+        });
+    }
+    Ok(findings)
+}
+
+/// Indices of the `(new, earlier)` pairs that contradict each other, with the
+/// model's explanation.
+async fn contradictions(
+    checker: &ClientInfo,
+    topic: &str,
+    side: Position,
+    pairs: &[(&str, &str)],
+) -> anyhow::Result<Vec<(usize, String)>> {
+    if pairs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let client = match &checker.backend {
+        ClientBackend::Live(client) => client,
+        ClientBackend::Mock => return Ok(Vec::new()),
+    };
+    let listed = pairs
+        .iter()
+        .enumerate()
+        .map(|(i, (new, earlier))| format!("Pair {}\nEarlier: {}\nLater: {}", i + 1, earlier, new))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let messages = vec![
+        Message::system(
+            "You check a debater for self-contradiction. Each pair holds two passages by the same speaker, \
+             an earlier one and a later one. A pair contradicts when the later passage asserts something \
+             incompatible with the earlier one; a refinement, concession of a minor point or new example \
+             does not count.\n\
+             Answer with a JSON array and nothing else, one object per contradicting pair: \
+             {\"pair\": <number>, \"explanation\": \"<one sentence in the language of the debate>\"}. \
+             Answer [] when none contradict."
+                .to_string(),
+        ),
+        Message::user(format!(
+            "Debate topic: {}\nSpeaker: {}\n\n{}",
+            topic,
+            side.label(),
+            listed
+        )),
+    ];
+    let response = client
+        .chat()
+        .messages(messages)
+        .temperature(0.0)
+        .max_tokens(600)
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("Contradiction check failed for {}: {}", checker.name, e))?;
+    Ok(parse_contradictions(&response.content, pairs.len()))
+}
+
+#[derive(Deserialize)]
+struct Verdict {
+    pair: usize,
+    #[serde(default)]
+    explanation: String,
+}
+
+/// Zero-based pair indices from the model's reply, without duplicates.
+fn parse_contradictions(reply: &str, pairs: usize) -> Vec<(usize, String)> {
+    let json = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => return Vec::new(),
+    };
+    let verdicts: Vec<Verdict> = serde_json::from_str(json).unwrap_or_default();
+    let mut found: Vec<(usize, String)> = Vec::new();
+    for verdict in verdicts {
+        let index = verdict.pair.wrapping_sub(1);
+        if index < pairs && !found.iter().any(|(i, _)| *i == index) {
+            found.push((index, verdict.explanation.trim().to_string()));
+        }
+    }
+    found
+}
+
+/// Paragraphs of a round worth comparing, markdown markers removed.
+fn paragraphs(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(|line| {
+            line.trim()
+                .trim_start_matches(['#', '-', '*', '>', ' '])
+                .trim()
+        })
+        .filter(|line| line.chars().count() >= MIN_PARAGRAPH_CHARS)
+        .map(|line| match line.char_indices().nth(MAX_PARAGRAPH_CHARS) {
+            Some((end, _)) => &line[..end],
+            None => line,
+        })
+}
+
+/// Findings formatted for the judge; `None` when there are none.
+pub fn judge_notes(findings: &[Finding]) -> Option<String> {
+    if findings.is_empty() {
+        return None;
+    }
+    let notes = findings
+        .iter()
+        .map(|f| {
+            let side = Position::from_role(&f.side).map_or(f.side.as_str(), |s| s.label());
+            match f.kind.as_str() {
+                "repetition" => format!(
+                    "- {} {} 重复了 {} 的内容：「{}」",
+                    side, f.phase, f.earlier_phase, f.quote
+                ),
+                _ => format!(
+                    "- {} {} 与其 {} 的说法矛盾：「{}」 vs 「{}」 {}",
+                    side, f.phase, f.earlier_phase, f.quote, f.earlier_quote, f.explanation
+                ),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(notes)
+}
@@ -0,0 +1,957 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use ai_lib_rust::types::tool::ToolDefinition;
+use ai_lib_rust::{AiClient, Message, StreamingEvent};
+use futures::StreamExt;
+use tracing::{info, info_span, Instrument};
+
+use crate::citations;
+use crate::config::max_tokens_for_role;
+use crate::prompt_traces::Trace;
+use crate::prompts::{
+    add_language_instruction, build_dissent_prompt, build_judge_prompt,
+    build_judge_prompt_with_tools, build_outcome_prompt, build_side_prompt,
+    build_side_prompt_with_tools,
+};
+use crate::refusals;
+use crate::rubric::Rubric;
+use crate::sanitize::{self, Sanitizer};
+use crate::tools::{self, SearchResult, ToolCallRecord, ToolContext};
+use crate::types::{ClientBackend, ClientInfo, DebatePhase, Position};
+
+use super::mock;
+
+/// Max model → tool → model iterations per round. The last iteration is sent
+/// without tools so the model has to produce its final answer.
+const MAX_TOOL_ITERATIONS: usize = 3;
+
+/// Output budget of the "what would change the outcome" analysis.
+const OUTCOME_MAX_TOKENS: u32 = 800;
+
+/// Output budget of a panel judge's dissenting opinion.
+const DISSENT_MAX_TOKENS: u32 = 1000;
+
+/// Appended to the prompt once the round or debate tool budget is used up.
+const SEARCH_BUDGET_EXHAUSTED: &str =
+    "（本轮搜索额度已用完，请不要再调用工具，直接基于已有信息完成发言。）";
+
+/// Longest content held back while tools are attached before it is taken to
+/// be the answer itself rather than a preamble to a tool call.
+const MAX_PREAMBLE_CHARS: usize = 200;
+
+/// Tags around the reasoning some models inline at the start of their output.
+const REASONING_OPEN: &str = "<think>";
+const REASONING_CLOSE: &str = "</think>";
+
+/// Boxed stream of chunks produced by one round.
+pub type RoundStream =
+    std::pin::Pin<Box<dyn futures::Stream<Item = anyhow::Result<DebateStreamChunk>> + Send>>;
+
+/// Chunk from debate stream: content delta, thinking, usage metadata, or search activity.
+#[derive(Debug, Clone)]
+pub enum DebateStreamChunk {
+    Delta(String),
+    /// Reasoning of a reasoning model, kept out of the round's content.
+    Thinking(String),
+    Usage(serde_json::Value),
+    /// A tool call is about to run (tool name and its query or claim).
+    ToolCall {
+        name: String,
+        query: String,
+    },
+    /// A web search was performed. Contains query and formatted results.
+    SearchPerformed(SearchResult),
+    /// Provenance of an executed tool call, for persistence.
+    ToolExecuted(ToolCallRecord),
+}
+
+/// A tool call assembled from streamed `ToolCallStarted` / `PartialToolCall` events.
+struct PendingToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Which prompt the tool loop builds on each iteration.
+#[derive(Clone, Copy)]
+enum RoundKind {
+    Side(Position, DebatePhase),
+    Judge,
+}
+
+impl RoundKind {
+    fn role(&self) -> &'static str {
+        match self {
+            RoundKind::Side(side, _) => side.role_str(),
+            RoundKind::Judge => "judge",
+        }
+    }
+
+    fn side_phase(&self) -> (Position, DebatePhase) {
+        match *self {
+            RoundKind::Side(side, phase) => (side, phase),
+            RoundKind::Judge => (Position::Judge, DebatePhase::Judgement),
+        }
+    }
+
+    fn build_messages(
+        &self,
+        topic: &str,
+        transcript: &[(Position, DebatePhase, String, String)],
+        tool_context: Option<&str>,
+        tool_ctx: &ToolContext,
+        model_id: &str,
+    ) -> Vec<Message> {
+        match *self {
+            RoundKind::Side(side, phase) => {
+                let mut messages = build_side_prompt_with_tools(
+                    side,
+                    phase,
+                    topic,
+                    transcript,
+                    tool_context,
+                    tool_ctx.preset,
+                    &tool_ctx.templates.side.body,
+                    model_id,
+                );
+                if tool_ctx.softened {
+                    refusals::soften(&mut messages);
+                }
+                if let Some(language) = &tool_ctx.language {
+                    add_language_instruction(&mut messages, language);
+                }
+                messages
+            }
+            RoundKind::Judge => {
+                let mut messages = build_judge_prompt_with_tools(
+                    topic,
+                    transcript,
+                    tool_context,
+                    tool_ctx.precedent.as_deref(),
+                    tool_ctx.consistency.as_deref(),
+                    tool_ctx.preset,
+                    tool_ctx.judging_rubric(),
+                    &tool_ctx.templates.judge.body,
+                );
+                if let Some(language) = &tool_ctx.language {
+                    add_language_instruction(&mut messages, language);
+                }
+                messages
+            }
+        }
+    }
+}
+
+/// Execute one debate round with streaming.
+///
+/// When `tool_defs` is non-empty the round runs a tool-calling loop:
+/// 1. Stream the model response with tools attached, holding back content deltas
+/// 2. Collect tool calls from the stream; if none, the held content is the
+///    answer and the round is complete, otherwise the content is dropped
+/// 3. Execute the tool calls, yield SearchPerformed events, and stream again
+///    with the accumulated results as context
+/// 4. Once the per-round or per-debate tool budget is spent, stream a final
+///    answer without tools
+pub async fn execute_one_round(
+    client_info: &ClientInfo,
+    side: Position,
+    phase: DebatePhase,
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+    tool_defs: Vec<ToolDefinition>,
+    tool_ctx: &ToolContext,
+) -> anyhow::Result<(RoundStream, String)> {
+    let client = match &client_info.backend {
+        ClientBackend::Live(client) => client.clone(),
+        ClientBackend::Mock => {
+            return Ok((
+                mock::side_round(side, phase, topic),
+                client_info.model_id.clone(),
+            ))
+        }
+    };
+
+    let tool_defs = tools::tools_for_role(side.role_str(), tool_defs);
+    if !tool_defs.is_empty() {
+        let stream = tool_loop_stream(
+            client,
+            client_info,
+            RoundKind::Side(side, phase),
+            topic,
+            transcript,
+            tool_defs,
+            tool_ctx,
+        );
+        return Ok((stream, client_info.model_id.clone()));
+    }
+
+    let mut messages = build_side_prompt(
+        side,
+        phase,
+        topic,
+        transcript,
+        tool_ctx.preset,
+        &tool_ctx.templates.side.body,
+        &client_info.model_id,
+    );
+    if tool_ctx.softened {
+        refusals::soften(&mut messages);
+    }
+    if let Some(language) = &tool_ctx.language {
+        add_language_instruction(&mut messages, language);
+    }
+    let trace = Trace::start(
+        &client_info.name,
+        &client_info.model_id,
+        side,
+        phase,
+        &tool_ctx.session_id,
+        0,
+        &messages,
+    );
+
+    let span = provider_span(client_info);
+    let stream = client
+        .chat()
+        .messages(messages)
+        .temperature(if tool_ctx.seed.is_some() { 0.0 } else { 0.7 })
+        .max_tokens(max_tokens_for_role(side.role_str()))
+        .stream()
+        .execute_stream()
+        .instrument(span.clone())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to start stream for {}: {}", client_info.name, e))?;
+
+    // The span stays open until the stream is fully consumed or dropped
+    let output_stream = stream.map(move |event| {
+        let _span = &span;
+        map_streaming_event(event)
+    });
+    let stream = traced(reasoning_separated(Box::pin(output_stream)), trace);
+    Ok((sanitized(stream), client_info.model_id.clone()))
+}
+
+/// Execute judge round with real streaming. The judge only gets the tools its
+/// allowlist permits (by default `fact_check`, when `JUDGE_FACT_CHECK=1`).
+pub async fn execute_judge_round_stream(
+    client_info: &ClientInfo,
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+    tool_defs: Vec<ToolDefinition>,
+    tool_ctx: &ToolContext,
+) -> anyhow::Result<(RoundStream, String)> {
+    let rubric = tool_ctx.judging_rubric().cloned();
+    let client = match &client_info.backend {
+        ClientBackend::Live(client) => client.clone(),
+        ClientBackend::Mock => {
+            let stream = weighted(mock::judge_round(topic), rubric);
+            return Ok((stream, client_info.model_id.clone()));
+        }
+    };
+
+    let tool_defs = tools::tools_for_role("judge", tool_defs);
+    if !tool_defs.is_empty() {
+        let stream = tool_loop_stream(
+            client,
+            client_info,
+            RoundKind::Judge,
+            topic,
+            transcript,
+            tool_defs,
+            tool_ctx,
+        );
+        return Ok((weighted(stream, rubric), client_info.model_id.clone()));
+    }
+
+    let mut messages = build_judge_prompt(
+        topic,
+        transcript,
+        tool_ctx.precedent.as_deref(),
+        tool_ctx.consistency.as_deref(),
+        tool_ctx.preset,
+        rubric.as_ref(),
+        &tool_ctx.templates.judge.body,
+    );
+    if let Some(language) = &tool_ctx.language {
+        add_language_instruction(&mut messages, language);
+    }
+    let trace = Trace::start(
+        &client_info.name,
+        &client_info.model_id,
+        Position::Judge,
+        DebatePhase::Judgement,
+        &tool_ctx.session_id,
+        0,
+        &messages,
+    );
+
+    let span = provider_span(client_info);
+    let stream = client
+        .chat()
+        .messages(messages)
+        .temperature(if tool_ctx.seed.is_some() { 0.0 } else { 0.3 })
+        .max_tokens(max_tokens_for_role("judge"))
+        .stream()
+        .execute_stream()
+        .instrument(span.clone())
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to start judge stream for {}: {}",
+                client_info.name,
+                e
+            )
+        })?;
+
+    let output_stream = stream.map(move |event| {
+        let _span = &span;
+        map_streaming_event(event)
+    });
+    let stream = sanitized(traced(reasoning_separated(Box::pin(output_stream)), trace));
+    Ok((weighted(stream, rubric), client_info.model_id.clone()))
+}
+
+/// Stream a short analysis of the missing evidence or arguments most likely
+/// to have changed the verdict. `transcript` includes the judge's verdict.
+pub async fn execute_outcome_analysis_stream(
+    client_info: &ClientInfo,
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+) -> anyhow::Result<(RoundStream, String)> {
+    let client = match &client_info.backend {
+        ClientBackend::Live(client) => client.clone(),
+        ClientBackend::Mock => return Ok((mock::outcome_round(), client_info.model_id.clone())),
+    };
+
+    let span = provider_span(client_info);
+    let stream = client
+        .chat()
+        .messages(build_outcome_prompt(topic, transcript))
+        .temperature(0.4)
+        .max_tokens(OUTCOME_MAX_TOKENS)
+        .stream()
+        .execute_stream()
+        .instrument(span.clone())
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to start outcome analysis for {}: {}",
+                client_info.name,
+                e
+            )
+        })?;
+
+    let output_stream = stream.map(move |event| {
+        let _span = &span;
+        map_streaming_event(event)
+    });
+    Ok((
+        sanitized(reasoning_separated(Box::pin(output_stream))),
+        client_info.model_id.clone(),
+    ))
+}
+
+/// Stream a panel judge's dissenting opinion from the verdict `dissent`
+/// gave on the Pro and Con rounds in `transcript`, which a majority for
+/// `majority` outvoted.
+pub async fn execute_dissent_stream(
+    client_info: &ClientInfo,
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+    majority: Position,
+    dissent: &str,
+    language: Option<&str>,
+) -> anyhow::Result<(RoundStream, String)> {
+    let client = match &client_info.backend {
+        ClientBackend::Live(client) => client.clone(),
+        ClientBackend::Mock => return Ok((mock::dissent_round(), client_info.model_id.clone())),
+    };
+
+    let mut messages = build_dissent_prompt(topic, transcript, majority, dissent);
+    if let Some(language) = language {
+        add_language_instruction(&mut messages, language);
+    }
+    let span = provider_span(client_info);
+    let stream = client
+        .chat()
+        .messages(messages)
+        .temperature(0.3)
+        .max_tokens(DISSENT_MAX_TOKENS)
+        .stream()
+        .execute_stream()
+        .instrument(span.clone())
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to start dissenting opinion for {}: {}",
+                client_info.name,
+                e
+            )
+        })?;
+
+    let output_stream = stream.map(move |event| {
+        let _span = &span;
+        map_streaming_event(event)
+    });
+    Ok((
+        sanitized(reasoning_separated(Box::pin(output_stream))),
+        client_info.model_id.clone(),
+    ))
+}
+
+/// Stream a round with tools attached, executing requested tool calls and
+/// re-prompting with their results until the model answers without tools.
+fn tool_loop_stream(
+    client: Arc<AiClient>,
+    client_info: &ClientInfo,
+    kind: RoundKind,
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+    tool_defs: Vec<ToolDefinition>,
+    tool_ctx: &ToolContext,
+) -> RoundStream {
+    let name = client_info.name.clone();
+    let model_id = client_info.model_id.clone();
+    let topic = topic.to_string();
+    let transcript = transcript.to_vec();
+    let tool_ctx = tool_ctx.clone();
+
+    let stream = async_stream::stream! {
+        let mut search_results: Vec<SearchResult> = Vec::new();
+        let mut calls_left = tools::max_tool_calls_per_round();
+
+        for iteration in 0..MAX_TOOL_ITERATIONS {
+            let call_span = info_span!(
+                "provider_call",
+                provider = %name,
+                model = %model_id,
+                iteration,
+            );
+            let budget_exhausted = calls_left == 0 || !tool_ctx.has_search_budget();
+            let mut search_context = format_search_context(&search_results);
+            if budget_exhausted && iteration > 0 {
+                search_context = Some(format!(
+                    "{}\n\n{}",
+                    search_context.unwrap_or_default(),
+                    SEARCH_BUDGET_EXHAUSTED
+                ));
+            }
+            let messages = kind.build_messages(
+                &topic,
+                &transcript,
+                search_context.as_deref(),
+                &tool_ctx,
+                &model_id,
+            );
+            let (side, phase) = kind.side_phase();
+            let trace = Trace::start(
+                &name,
+                &model_id,
+                side,
+                phase,
+                &tool_ctx.session_id,
+                iteration,
+                &messages,
+            );
+
+            let request = client.chat().messages(messages);
+            let mut request = match kind {
+                _ if tool_ctx.seed.is_some() => request.temperature(0.0),
+                RoundKind::Side(..) => request.temperature(0.7),
+                RoundKind::Judge => request.temperature(0.3),
+            }
+            .max_tokens(max_tokens_for_role(kind.role()));
+            let tools_attached = iteration + 1 < MAX_TOOL_ITERATIONS && !budget_exhausted;
+            if tools_attached {
+                request = request.tools(tool_defs.clone());
+            }
+
+            let events = match request.stream().execute_stream().instrument(call_span.clone()).await {
+                Ok(events) => events,
+                Err(e) => {
+                    yield Err(anyhow::anyhow!("Failed to start stream for {}: {}", name, e));
+                    return;
+                }
+            };
+            let mut events = std::pin::pin!(events);
+
+            let mut pending: Vec<PendingToolCall> = Vec::new();
+            let mut output = TraceOutput::default();
+            let mut reasoning = InlineReasoning::default();
+            // Text preceding a tool call ("I'll search for ...") is not part of
+            // the argument, so while tools are attached the start of the content
+            // is held back until it is too long to be such a preamble; from then
+            // on it streams live
+            let mut held = String::new();
+            let mut live = !tools_attached;
+            while let Some(event_res) = events.next().await {
+                match event_res {
+                    Ok(StreamingEvent::ToolCallStarted { tool_call_id, tool_name, .. }) => {
+                        pending.push(PendingToolCall {
+                            id: tool_call_id,
+                            name: tool_name,
+                            arguments: String::new(),
+                        });
+                    }
+                    Ok(StreamingEvent::PartialToolCall { tool_call_id, arguments, .. }) => {
+                        let idx = pending
+                            .iter()
+                            .position(|c| c.id == tool_call_id)
+                            .or_else(|| pending.len().checked_sub(1));
+                        if let Some(idx) = idx {
+                            pending[idx].arguments.push_str(&arguments);
+                        }
+                    }
+                    other => {
+                        for chunk in reasoning.split(map_streaming_event(other)) {
+                            output.observe(&chunk);
+                            match chunk {
+                                Ok(DebateStreamChunk::Delta(delta)) if !live => {
+                                    held.push_str(&delta);
+                                    if pending.is_empty() && held.chars().count() > MAX_PREAMBLE_CHARS {
+                                        live = true;
+                                        yield Ok(DebateStreamChunk::Delta(std::mem::take(&mut held)));
+                                    }
+                                }
+                                chunk => yield chunk,
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(chunk) = reasoning.finish() {
+                let chunk = Ok(chunk);
+                output.observe(&chunk);
+                match chunk {
+                    Ok(DebateStreamChunk::Delta(delta)) if !live => held.push_str(&delta),
+                    chunk => yield chunk,
+                }
+            }
+            if !held.is_empty() {
+                if pending.is_empty() {
+                    yield Ok(DebateStreamChunk::Delta(held));
+                } else {
+                    info!("Dropping {} chars {} sent before its tool calls", held.chars().count(), model_id);
+                }
+            }
+
+            drop(call_span);
+            if let Some(trace) = trace {
+                let tool_calls = pending
+                    .iter()
+                    .map(|c| serde_json::json!({ "name": c.name, "arguments": c.arguments }))
+                    .collect();
+                trace.finish(&output.content, tool_calls, output.usage, output.error);
+            }
+
+            if pending.is_empty() || !tools_attached {
+                return;
+            }
+
+            info!("Model {} requested {} tool call(s)", model_id, pending.len());
+
+            for call in pending {
+                if !tool_defs.iter().any(|d| d.function.name == call.name) {
+                    info!("Rejecting {} call from {}: not allowed for {}", call.name, model_id, kind.role());
+                    continue;
+                }
+                let args: serde_json::Value =
+                    serde_json::from_str(&call.arguments).unwrap_or_else(|_| serde_json::json!({}));
+                // Repeated searches reuse the earlier result and don't spend the debate budget
+                let cached = tool_ctx.cached_result(&call.name, &args);
+                if calls_left == 0 || (cached.is_none() && !tool_ctx.consume_search()) {
+                    info!("Tool budget exhausted for {}, skipping {}", model_id, call.name);
+                    calls_left = 0;
+                    break;
+                }
+                calls_left -= 1;
+                yield Ok(DebateStreamChunk::ToolCall {
+                    name: call.name.clone(),
+                    query: tools::call_summary(&args),
+                });
+                let started = Instant::now();
+                let (result, provider) = match cached {
+                    Some(result) => {
+                        info!("Reusing cached {} result for {}", call.name, model_id);
+                        (Some(result), "cache".to_string())
+                    }
+                    None => {
+                        let span = info_span!(
+                            "tool_call",
+                            tool = %call.name,
+                            query = %tools::call_summary(&args),
+                        );
+                        (
+                            tools::execute_tool_call(&tool_ctx, &call.name, &args)
+                                .instrument(span)
+                                .await,
+                            tools::tool_provider(&call.name),
+                        )
+                    }
+                };
+                if let Some(result) = result {
+                    yield Ok(DebateStreamChunk::SearchPerformed(result.clone()));
+                    yield Ok(DebateStreamChunk::ToolExecuted(ToolCallRecord {
+                        provider,
+                        name: call.name,
+                        args,
+                        result: result.clone(),
+                        latency_ms: started.elapsed().as_millis() as u64,
+                    }));
+                    search_results.push(result);
+                }
+            }
+        }
+    };
+
+    sanitized(Box::pin(stream))
+}
+
+/// What a traced provider call produced, gathered from its chunks.
+#[derive(Default)]
+struct TraceOutput {
+    content: String,
+    usage: Option<serde_json::Value>,
+    error: Option<String>,
+}
+
+impl TraceOutput {
+    fn observe(&mut self, chunk: &anyhow::Result<DebateStreamChunk>) {
+        match chunk {
+            Ok(DebateStreamChunk::Delta(delta)) => self.content.push_str(delta),
+            Ok(DebateStreamChunk::Usage(usage)) => self.usage = Some(usage.clone()),
+            Err(e) => self.error = Some(e.to_string()),
+            _ => {}
+        }
+    }
+}
+
+/// Forward `stream`, sending what it produced to `trace` once it ends.
+fn traced(stream: RoundStream, trace: Option<Trace>) -> RoundStream {
+    let Some(trace) = trace else {
+        return stream;
+    };
+    Box::pin(async_stream::stream! {
+        let mut stream = stream;
+        let mut output = TraceOutput::default();
+        while let Some(chunk) = stream.next().await {
+            output.observe(&chunk);
+            yield chunk;
+        }
+        trace.finish(&output.content, Vec::new(), output.usage, output.error);
+    })
+}
+
+/// Where a round's output is relative to an inline reasoning block.
+#[derive(Default)]
+enum ReasoningState {
+    /// Nothing but whitespace yet; a `<think>` tag may still open the output.
+    #[default]
+    Start,
+    /// Inside the `<think>` block.
+    Inside,
+    /// Right after the block, where whitespace is dropped.
+    Closed,
+    /// Past the reasoning, or there was none.
+    Done,
+}
+
+/// Turns the reasoning some models inline at the start of their output, in a
+/// `<think>` block, into `Thinking` chunks, so it is kept out of the argument
+/// like reasoning the provider streams separately.
+#[derive(Default)]
+struct InlineReasoning {
+    state: ReasoningState,
+    /// Text held back until it is known on which side of a tag it falls.
+    pending: String,
+}
+
+impl InlineReasoning {
+    fn split(
+        &mut self,
+        chunk: anyhow::Result<DebateStreamChunk>,
+    ) -> Vec<anyhow::Result<DebateStreamChunk>> {
+        let delta = match chunk {
+            Ok(DebateStreamChunk::Delta(delta)) if !matches!(self.state, ReasoningState::Done) => {
+                delta
+            }
+            chunk => return vec![chunk],
+        };
+        self.pending.push_str(&delta);
+        let mut chunks = Vec::new();
+        if let ReasoningState::Start = self.state {
+            let start = self.pending.trim_start();
+            if let Some(reasoning) = start.strip_prefix(REASONING_OPEN) {
+                self.pending = reasoning.to_string();
+                self.state = ReasoningState::Inside;
+            } else if !REASONING_OPEN.starts_with(start) {
+                self.state = ReasoningState::Done;
+                chunks.push(Ok(DebateStreamChunk::Delta(std::mem::take(
+                    &mut self.pending,
+                ))));
+            }
+        }
+        if let ReasoningState::Inside = self.state {
+            if let Some(end) = self.pending.find(REASONING_CLOSE) {
+                let rest = self.pending.split_off(end + REASONING_CLOSE.len());
+                self.pending.truncate(end);
+                chunks.push(Ok(DebateStreamChunk::Thinking(std::mem::replace(
+                    &mut self.pending,
+                    rest,
+                ))));
+                self.state = ReasoningState::Closed;
+            } else {
+                // Hold back what may be the start of the closing tag
+                let held = (1..REASONING_CLOSE.len())
+                    .rev()
+                    .find(|&n| self.pending.ends_with(&REASONING_CLOSE[..n]))
+                    .unwrap_or(0);
+                let held = self.pending.split_off(self.pending.len() - held);
+                chunks.push(Ok(DebateStreamChunk::Thinking(std::mem::replace(
+                    &mut self.pending,
+                    held,
+                ))));
+            }
+        }
+        if let ReasoningState::Closed = self.state {
+            let argument = self.pending.trim_start();
+            if !argument.is_empty() {
+                chunks.push(Ok(DebateStreamChunk::Delta(argument.to_string())));
+                self.state = ReasoningState::Done;
+            }
+            self.pending.clear();
+        }
+        chunks
+    }
+
+    /// What is still held back once the output has ended.
+    fn finish(&mut self) -> Option<DebateStreamChunk> {
+        let pending = std::mem::take(&mut self.pending);
+        if pending.is_empty() || matches!(self.state, ReasoningState::Closed) {
+            return None;
+        }
+        match self.state {
+            ReasoningState::Inside => Some(DebateStreamChunk::Thinking(pending)),
+            _ => Some(DebateStreamChunk::Delta(pending)),
+        }
+    }
+}
+
+/// Forward `stream` with a leading `<think>` block in its content turned into
+/// `Thinking` chunks.
+fn reasoning_separated(stream: RoundStream) -> RoundStream {
+    Box::pin(async_stream::stream! {
+        let mut stream = stream;
+        let mut reasoning = InlineReasoning::default();
+        while let Some(chunk) = stream.next().await {
+            for chunk in reasoning.split(chunk) {
+                yield chunk;
+            }
+        }
+        if let Some(chunk) = reasoning.finish() {
+            yield Ok(chunk);
+        }
+    })
+}
+
+/// Forward `stream` with raw HTML stripped from its content and thinking,
+/// when sanitization is enabled.
+fn sanitized(stream: RoundStream) -> RoundStream {
+    if !sanitize::enabled() {
+        return stream;
+    }
+    Box::pin(async_stream::stream! {
+        let mut stream = stream;
+        let (mut content, mut thinking) = (Sanitizer::default(), Sanitizer::default());
+        while let Some(chunk) = stream.next().await {
+            yield match chunk {
+                Ok(DebateStreamChunk::Delta(delta)) => {
+                    Ok(DebateStreamChunk::Delta(content.push(&delta)))
+                }
+                Ok(DebateStreamChunk::Thinking(delta)) => {
+                    Ok(DebateStreamChunk::Thinking(thinking.push(&delta)))
+                }
+                chunk => chunk,
+            };
+        }
+        let rest = thinking.finish();
+        if !rest.is_empty() {
+            yield Ok(DebateStreamChunk::Thinking(rest));
+        }
+        let rest = content.finish();
+        if !rest.is_empty() {
+            yield Ok(DebateStreamChunk::Delta(rest));
+        }
+    })
+}
+
+/// Forward a verdict `stream`, then add the weighted score and winner from
+/// the scores it gave by `rubric`, when it gave them all.
+fn weighted(stream: RoundStream, rubric: Option<Rubric>) -> RoundStream {
+    let Some(rubric) = rubric else {
+        return stream;
+    };
+    Box::pin(async_stream::stream! {
+        let mut stream = stream;
+        let mut verdict = String::new();
+        while let Some(chunk) = stream.next().await {
+            if let Ok(DebateStreamChunk::Delta(delta)) = &chunk {
+                verdict.push_str(delta);
+            }
+            let failed = chunk.is_err();
+            yield chunk;
+            if failed {
+                return;
+            }
+        }
+        if let Some(scores) = rubric.score(&verdict) {
+            yield Ok(DebateStreamChunk::Delta(scores.verdict_lines()));
+        }
+    })
+}
+
+/// Trace span covering one provider streaming call.
+fn provider_span(client_info: &ClientInfo) -> tracing::Span {
+    info_span!(
+        "provider_call",
+        provider = %client_info.name,
+        model = %client_info.model_id,
+    )
+}
+
+/// Format accumulated search results as prompt context, followed by the
+/// numbered source list the model should cite from.
+fn format_search_context(results: &[SearchResult]) -> Option<String> {
+    if results.is_empty() {
+        return None;
+    }
+    let mut context = results
+        .iter()
+        .map(|r| format!("### Search: {}\n{}", r.query, r.results))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let sources = citations::number_sources(results);
+    if !sources.is_empty() {
+        context.push_str("\n\n### 可引用来源（引用时在句末标注编号，如 [1]）\n");
+        context.push_str(&citations::source_list(&sources));
+    }
+    Some(context)
+}
+
+/// Map ai-lib-rust StreamingEvent to DebateStreamChunk.
+fn map_streaming_event<E: std::fmt::Display>(
+    event_res: Result<StreamingEvent, E>,
+) -> anyhow::Result<DebateStreamChunk> {
+    match event_res {
+        Ok(event) => match event {
+            StreamingEvent::PartialContentDelta { content, .. } => {
+                Ok(DebateStreamChunk::Delta(content))
+            }
+            StreamingEvent::ThinkingDelta { thinking, .. } => {
+                Ok(DebateStreamChunk::Thinking(thinking))
+            }
+            StreamingEvent::Metadata { usage, .. } => {
+                if let Some(usage_val) = usage {
+                    Ok(DebateStreamChunk::Usage(usage_val))
+                } else {
+                    Ok(DebateStreamChunk::Delta(String::new()))
+                }
+            }
+            StreamingEvent::StreamError { error, .. } => {
+                let msg: String = error
+                    .get("message")
+                    .or_else(|| error.get("error"))
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+                    .unwrap_or_else(|| error.to_string());
+                Err(anyhow::anyhow!("Stream error: {}", msg))
+            }
+            _ => Ok(DebateStreamChunk::Delta(String::new())),
+        },
+        Err(e) => Err(anyhow::anyhow!("Stream error: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The thinking and the content `pieces` of output are split into.
+    fn separate(pieces: &[&str]) -> (String, String) {
+        let mut reasoning = InlineReasoning::default();
+        let mut chunks: Vec<DebateStreamChunk> = pieces
+            .iter()
+            .flat_map(|piece| reasoning.split(Ok(DebateStreamChunk::Delta(piece.to_string()))))
+            .map(|chunk| chunk.unwrap())
+            .collect();
+        chunks.extend(reasoning.finish());
+        let (mut thinking, mut content) = (String::new(), String::new());
+        for chunk in chunks {
+            match chunk {
+                DebateStreamChunk::Thinking(text) => {
+                    assert!(content.is_empty(), "thinking after content: {:?}", text);
+                    thinking.push_str(&text);
+                }
+                DebateStreamChunk::Delta(text) => content.push_str(&text),
+                chunk => panic!("unexpected chunk {:?}", chunk),
+            }
+        }
+        (thinking, content)
+    }
+
+    fn pair(thinking: &str, content: &str) -> (String, String) {
+        (thinking.to_string(), content.to_string())
+    }
+
+    #[test]
+    fn opening_tag_split_across_deltas() {
+        assert_eq!(
+            separate(&["\n<thi", "nk>plan", " more</think>\n\nArgument"]),
+            pair("plan more", "Argument")
+        );
+        assert_eq!(separate(&["<", "t", "hink>a</think>b"]), pair("a", "b"));
+    }
+
+    #[test]
+    fn closing_tag_split_across_deltas() {
+        assert_eq!(
+            separate(&["<think>a</th", "ink>", "\n", " b"]),
+            pair("a", "b")
+        );
+        // Text that only looks like the start of the closing tag is thinking
+        assert_eq!(
+            separate(&["<think>x </t", "able></think>y"]),
+            pair("x </table>", "y")
+        );
+    }
+
+    #[test]
+    fn output_without_reasoning_is_content() {
+        assert_eq!(separate(&["Hello ", "world"]), pair("", "Hello world"));
+        assert_eq!(separate(&["<th", "e end>"]), pair("", "<the end>"));
+        assert_eq!(
+            separate(&["a <think>b</think>"]),
+            pair("", "a <think>b</think>")
+        );
+        assert_eq!(separate(&["<thi"]), pair("", "<thi"));
+    }
+
+    #[test]
+    fn unterminated_reasoning_is_flushed_as_thinking() {
+        assert_eq!(
+            separate(&["<think>never", " ends</thi"]),
+            pair("never ends</thi", "")
+        );
+        assert_eq!(separate(&["<think>done</think>\n"]), pair("done", ""));
+    }
+
+    #[test]
+    fn other_chunks_pass_through() {
+        let mut reasoning = InlineReasoning::default();
+        let chunks = reasoning.split(Ok(DebateStreamChunk::Usage(serde_json::json!({}))));
+        assert!(matches!(chunks[..], [Ok(DebateStreamChunk::Usage(_))]));
+        assert!(reasoning.split(Err(anyhow::anyhow!("boom")))[0].is_err());
+        assert!(reasoning.finish().is_none());
+    }
+}
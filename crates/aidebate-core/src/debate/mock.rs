@@ -0,0 +1,86 @@
+//! Offline mock provider (`DEBATE_MOCK=1`).
+//!
+//! Streams canned, phase-appropriate Markdown with realistic pacing so the
+//! frontend and storage can be exercised without API keys or cost.
+
+use std::time::Duration;
+
+use crate::config::mock_delay_ms;
+use crate::types::{DebatePhase, Position};
+
+use super::engine::{DebateStreamChunk, RoundStream};
+
+/// Characters per streamed delta.
+const CHUNK_CHARS: usize = 6;
+
+/// Canned content for a debater round.
+fn side_content(side: Position, phase: DebatePhase, topic: &str) -> String {
+    let stance = match side {
+        Position::Pro => "支持",
+        _ => "反对",
+    };
+    let body = match phase {
+        DebatePhase::Opening => format!(
+            "- 我方{stance}“{topic}”。\n- 核心论点一：长期收益大于短期成本。\n- 核心论点二：现有证据与实践经验支持我方判断。"
+        ),
+        DebatePhase::Rebuttal => "- 对方论证依赖个例，缺乏系统性数据。\n- 对方忽略了关键的外部条件与成本。\n- 补充论据：多项调查显示趋势与我方一致。".to_string(),
+        DebatePhase::Defense => "- 对方的质疑混淆了相关与因果。\n- 我方论据在不同场景下依然成立。\n- 风险可以通过制度设计加以控制。".to_string(),
+        DebatePhase::Closing => "- 回顾：我方论点逻辑完整、证据充分。\n- 对方未能有效回应我方核心论点。\n- 综上，我方立场更具说服力。".to_string(),
+        DebatePhase::Judgement => String::new(),
+    };
+    format!(
+        "## Reasoning\n{body}\n\n## Final Position\n本轮{}：我方坚定{stance}该议题。（mock）\n",
+        phase.title()
+    )
+}
+
+/// Canned content for the judge round.
+fn judge_content(topic: &str) -> String {
+    format!(
+        "## Reasoning\n- 议题：{topic}\n- 正方论点结构清晰，证据引用较为充分。\n- 反方反驳有力，但防守阶段未能回应关键质疑。\n\n## Verdict\n正方整体表现更优。\n\nWinner: Pro\nConfidence: 70\n\n## Contested Claims\n- [Pro] 论点有充分证据支撑 — Confidence: 75\n- [Con] 关键质疑已被正面回应 — Confidence: 40\n"
+    )
+}
+
+fn stream_text(text: String) -> RoundStream {
+    let delay = Duration::from_millis(mock_delay_ms());
+    let stream = async_stream::stream! {
+        let chars: Vec<char> = text.chars().collect();
+        for chunk in chars.chunks(CHUNK_CHARS) {
+            tokio::time::sleep(delay).await;
+            yield Ok(DebateStreamChunk::Delta(chunk.iter().collect()));
+        }
+        let completion_tokens = chars.len() as u64;
+        yield Ok(DebateStreamChunk::Usage(serde_json::json!({
+            "prompt_tokens": 0,
+            "completion_tokens": completion_tokens,
+            "total_tokens": completion_tokens,
+        })));
+    };
+    Box::pin(stream)
+}
+
+/// Stream a canned debater round.
+pub fn side_round(side: Position, phase: DebatePhase, topic: &str) -> RoundStream {
+    stream_text(side_content(side, phase, topic))
+}
+
+/// Stream a canned judge verdict.
+pub fn judge_round(topic: &str) -> RoundStream {
+    stream_text(judge_content(topic))
+}
+
+/// Stream a canned "what would change the outcome" analysis.
+pub fn outcome_round() -> RoundStream {
+    stream_text(
+        "## What Would Change the Outcome\n- 反方若在防守阶段给出系统性数据回应正方的关键质疑，裁决很可能改变。\n- 反方可补充外部条件变化的具体案例，削弱正方“长期收益”论点。\n- 正方仍需补强成本估算的来源，否则优势并不稳固。（mock）\n"
+            .to_string(),
+    )
+}
+
+/// Stream a canned dissenting opinion.
+pub fn dissent_round() -> RoundStream {
+    stream_text(
+        "## Dissenting Opinion\n- 多数意见认定正方获胜，本席不能同意。\n- 正方的长期收益论点缺乏成本估算的来源，多数意见对此未加审视。\n- 反方对外部条件的质疑在防守阶段虽未被完整回应，但其核心论证并未被驳倒。（mock）\n"
+            .to_string(),
+    )
+}
@@ -0,0 +1,9 @@
+mod engine;
+mod mock;
+mod runner;
+
+pub use engine::{
+    execute_dissent_stream, execute_judge_round_stream, execute_one_round,
+    execute_outcome_analysis_stream, DebateStreamChunk, RoundStream,
+};
+pub use runner::{continue_debate, rounds_before, run_debate, Roles, RunEvent, Turn};
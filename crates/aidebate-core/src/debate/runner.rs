@@ -0,0 +1,166 @@
+//! Headless runner: a whole debate, round by round, as one event stream.
+//!
+//! Used by the CLI, the batch runner and chat integrations, which need the
+//! debate without the SSE plumbing of the HTTP handler. Rounds run one after
+//! another: Pro then Con for each phase, then the judge. A debate can also be
+//! continued from its first rounds, to re-run the rest after an edit or with
+//! other models.
+
+use async_stream::try_stream;
+use futures::{Stream, StreamExt};
+
+use super::engine::{execute_judge_round_stream, execute_one_round, DebateStreamChunk};
+use crate::config;
+use crate::tools::{self, ToolContext};
+use crate::types::{ClientInfo, DebatePhase, Position};
+
+/// One finished turn: side, phase, content and the model that produced it.
+pub type Turn = (Position, DebatePhase, String, String);
+
+const PHASES: [DebatePhase; 5] = [
+    DebatePhase::Opening,
+    DebatePhase::Rebuttal,
+    DebatePhase::Defense,
+    DebatePhase::Closing,
+    DebatePhase::Judgement,
+];
+
+/// Clients for the roles of one debate.
+pub struct Roles {
+    pub pro: ClientInfo,
+    pub con: ClientInfo,
+    pub judge: ClientInfo,
+    pub fact_checker: ClientInfo,
+}
+
+impl Roles {
+    /// The default clients, with any per-role model overrides applied.
+    pub async fn resolve(
+        defaults: &(ClientInfo, ClientInfo, ClientInfo),
+        pro: &Option<String>,
+        con: &Option<String>,
+        judge: &Option<String>,
+    ) -> anyhow::Result<Self> {
+        let pro = override_client(&defaults.0, pro).await?;
+        let con = override_client(&defaults.1, con).await?;
+        let judge = override_client(&defaults.2, judge).await?;
+        // The fact-checker role defaults to the judge's model
+        let fact_checker = match config::fact_check_model_id() {
+            Some(model_id) => config::build_client_for_model(&model_id).await?,
+            None => judge.clone(),
+        };
+        Ok(Self {
+            pro,
+            con,
+            judge,
+            fact_checker,
+        })
+    }
+}
+
+async fn override_client(
+    default: &ClientInfo,
+    model: &Option<String>,
+) -> anyhow::Result<ClientInfo> {
+    match model.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        Some(model_id) => config::build_client_for_model(model_id).await,
+        None => Ok(default.clone()),
+    }
+}
+
+/// Progress of a running debate.
+pub enum RunEvent {
+    RoundStarted {
+        side: Position,
+        phase: DebatePhase,
+        model: String,
+    },
+    Delta(String),
+    ToolCall {
+        name: String,
+        query: String,
+    },
+    /// A round finished; the turn has been added to the transcript.
+    RoundFinished(Turn),
+}
+
+/// Run a full debate on `topic`. The stream ends after the judge's round, or
+/// with the first error.
+pub fn run_debate<'a>(
+    topic: &'a str,
+    roles: &'a Roles,
+    tool_ctx: &'a ToolContext,
+) -> impl Stream<Item = anyhow::Result<RunEvent>> + Send + 'a {
+    continue_debate(topic, roles, tool_ctx, Vec::new())
+}
+
+/// Continue a debate whose first rounds are in `transcript`: only the rounds
+/// not in it run, each seeing the whole transcript so far.
+pub fn continue_debate<'a>(
+    topic: &'a str,
+    roles: &'a Roles,
+    tool_ctx: &'a ToolContext,
+    transcript: Vec<Turn>,
+) -> impl Stream<Item = anyhow::Result<RunEvent>> + Send + 'a {
+    try_stream! {
+        let mut transcript = transcript;
+        for phase in PHASES {
+            let sides = match phase {
+                DebatePhase::Judgement => vec![(Position::Judge, &roles.judge)],
+                _ => vec![(Position::Pro, &roles.pro), (Position::Con, &roles.con)],
+            };
+            for (side, client) in sides {
+                if transcript.iter().any(|(s, p, ..)| *s == side && *p == phase) {
+                    continue;
+                }
+                yield RunEvent::RoundStarted { side, phase, model: client.model_id.clone() };
+                let tool_defs = tools::available_tools(tool_ctx).await;
+                let (mut stream, model_id) = match side {
+                    Position::Judge => {
+                        execute_judge_round_stream(client, topic, &transcript, tool_defs, tool_ctx)
+                            .await?
+                    }
+                    _ => {
+                        execute_one_round(client, side, phase, topic, &transcript, tool_defs, tool_ctx)
+                            .await?
+                    }
+                };
+                let mut content = String::new();
+                while let Some(chunk) = stream.next().await {
+                    match chunk? {
+                        DebateStreamChunk::Delta(delta) => {
+                            content.push_str(&delta);
+                            yield RunEvent::Delta(delta);
+                        }
+                        DebateStreamChunk::ToolCall { name, query } => {
+                            yield RunEvent::ToolCall { name, query };
+                        }
+                        _ => {}
+                    }
+                }
+                let turn = (side, phase, content, model_id);
+                transcript.push(turn.clone());
+                yield RunEvent::RoundFinished(turn);
+            }
+        }
+    }
+}
+
+/// The debater turns of `transcript` that run before the round of `side` in
+/// `phase`, in running order.
+pub fn rounds_before(transcript: &[Turn], side: Position, phase: DebatePhase) -> Vec<Turn> {
+    let order = |side: Position, phase: DebatePhase| {
+        let phase = PHASES
+            .iter()
+            .position(|p| *p == phase)
+            .unwrap_or(PHASES.len());
+        phase * 2 + usize::from(side == Position::Con)
+    };
+    let mut rounds: Vec<Turn> = transcript
+        .iter()
+        .filter(|(s, p, ..)| *s != Position::Judge && order(*s, *p) < order(side, phase))
+        .cloned()
+        .collect();
+    rounds.sort_by_key(|(s, p, ..)| order(*s, *p));
+    rounds
+}
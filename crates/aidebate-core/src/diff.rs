@@ -0,0 +1,191 @@
+//! Comparison of two debate transcripts.
+//!
+//! Two debates on the same topic (a rerun, or a rematch with other models)
+//! are aligned round by round: each round of one is paired with the round of
+//! the other with the same side and phase, and the pair gets a line diff of
+//! their content. The verdicts are compared by winner and confidence.
+
+use serde::Serialize;
+
+use crate::debate::Turn;
+use crate::prompts::{parse_confidence, parse_winner};
+use crate::types::{DebatePhase, Position};
+
+/// Pairs of lines compared at most per round; longer rounds are diffed as a
+/// whole removal and addition.
+const MAX_DIFF_CELLS: usize = 250_000;
+
+#[derive(Debug, Serialize)]
+pub struct TranscriptDiff {
+    pub rounds: Vec<RoundDiff>,
+    pub verdict: VerdictDiff,
+}
+
+/// One side's round in a phase, in either debate.
+#[derive(Debug, Serialize)]
+pub struct RoundDiff {
+    pub side: &'static str,
+    pub phase: &'static str,
+    /// Model of the round in each debate; `None` where it has no such round.
+    pub model_a: Option<String>,
+    pub model_b: Option<String>,
+    /// Share of lines the two rounds have in common, from 0 to 1.
+    pub similarity: f64,
+    pub changes: Vec<Change>,
+}
+
+/// A run of lines that is in both rounds, or only in one.
+#[derive(Debug, Serialize)]
+pub struct Change {
+    /// `equal`, `removed` (only in `a`) or `added` (only in `b`).
+    pub op: &'static str,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerdictDiff {
+    /// `pro` or `con`, from each verdict's `Winner:` line.
+    pub winner_a: Option<&'static str>,
+    pub winner_b: Option<&'static str>,
+    pub winner_changed: bool,
+    pub confidence_a: Option<u8>,
+    pub confidence_b: Option<u8>,
+}
+
+/// Compare debate `a` with debate `b`, in the round order of `a` followed by
+/// the rounds only `b` has.
+pub fn diff_transcripts(a: &[Turn], b: &[Turn]) -> TranscriptDiff {
+    let mut paired = vec![false; b.len()];
+    let mut rounds = Vec::new();
+    for (i, (side, phase, content, model)) in a.iter().enumerate() {
+        // The n-th round of a side in a phase pairs with the n-th in `b`
+        let nth = a[..i]
+            .iter()
+            .filter(|(s, p, ..)| s == side && p == phase)
+            .count();
+        let other = b
+            .iter()
+            .enumerate()
+            .filter(|(_, (s, p, ..))| s == side && p == phase)
+            .nth(nth);
+        let (other_content, other_model) = match other {
+            Some((j, (_, _, content, model))) => {
+                paired[j] = true;
+                (content.as_str(), Some(model.clone()))
+            }
+            None => ("", None),
+        };
+        rounds.push(round_diff(
+            *side,
+            *phase,
+            (content, Some(model.clone())),
+            (other_content, other_model),
+        ));
+    }
+    for ((side, phase, content, model), _) in b.iter().zip(paired).filter(|(_, p)| !p) {
+        rounds.push(round_diff(
+            *side,
+            *phase,
+            ("", None),
+            (content, Some(model.clone())),
+        ));
+    }
+
+    let verdict = |transcript: &[Turn]| {
+        transcript
+            .iter()
+            .rev()
+            .find(|(side, ..)| *side == Position::Judge)
+            .map(|(_, _, content, _)| content.clone())
+            .unwrap_or_default()
+    };
+    let (verdict_a, verdict_b) = (verdict(a), verdict(b));
+    let winner_a = parse_winner(&verdict_a).map(|side| side.role_str());
+    let winner_b = parse_winner(&verdict_b).map(|side| side.role_str());
+    TranscriptDiff {
+        rounds,
+        verdict: VerdictDiff {
+            winner_a,
+            winner_b,
+            winner_changed: winner_a != winner_b,
+            confidence_a: parse_confidence(&verdict_a).overall,
+            confidence_b: parse_confidence(&verdict_b).overall,
+        },
+    }
+}
+
+fn round_diff(
+    side: Position,
+    phase: DebatePhase,
+    (a, model_a): (&str, Option<String>),
+    (b, model_b): (&str, Option<String>),
+) -> RoundDiff {
+    let (a, b): (Vec<&str>, Vec<&str>) = (a.lines().collect(), b.lines().collect());
+    let ops = diff_lines(&a, &b);
+    let common = ops.iter().filter(|(op, _)| *op == "equal").count();
+    let similarity = match a.len() + b.len() {
+        0 => 1.0,
+        total => ((2 * common) as f64 / total as f64 * 100.0).round() / 100.0,
+    };
+    RoundDiff {
+        side: side.role_str(),
+        phase: phase.as_str(),
+        model_a,
+        model_b,
+        similarity,
+        changes: merge(ops),
+    }
+}
+
+/// Line diff of `a` into `b` by longest common subsequence.
+fn diff_lines<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(&'static str, &'a str)> {
+    let mut ops = Vec::new();
+    if a.len() * b.len() > MAX_DIFF_CELLS {
+        ops.extend(a.iter().map(|line| ("removed", *line)));
+        ops.extend(b.iter().map(|line| ("added", *line)));
+    } else {
+        // common[i][j]: length of the longest common subsequence of a[i..], b[j..]
+        let mut common = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                common[i][j] = if a[i] == b[j] {
+                    common[i + 1][j + 1] + 1
+                } else {
+                    common[i + 1][j].max(common[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                ops.push(("equal", a[i]));
+                (i, j) = (i + 1, j + 1);
+            } else if j == b.len() || (i < a.len() && common[i + 1][j] >= common[i][j + 1]) {
+                ops.push(("removed", a[i]));
+                i += 1;
+            } else {
+                ops.push(("added", b[j]));
+                j += 1;
+            }
+        }
+    }
+    ops
+}
+
+/// Runs of lines of the same kind, each as one change.
+fn merge(ops: Vec<(&'static str, &str)>) -> Vec<Change> {
+    let mut changes: Vec<Change> = Vec::new();
+    for (op, line) in ops {
+        match changes.last_mut() {
+            Some(last) if last.op == op => {
+                last.text.push('\n');
+                last.text.push_str(line);
+            }
+            _ => changes.push(Change {
+                op,
+                text: line.to_string(),
+            }),
+        }
+    }
+    changes
+}
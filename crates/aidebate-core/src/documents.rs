@@ -0,0 +1,183 @@
+//! User-supplied evidence corpus per session (RAG).
+//!
+//! Uploaded documents (PDF, plain text, Markdown) are split into overlapping
+//! chunks, embedded, and stored. The `search_documents` tool retrieves the
+//! most similar chunks so both sides can argue from the supplied material.
+
+use crate::embeddings;
+use crate::storage::Store;
+
+/// Target chunk size in characters.
+const CHUNK_CHARS: usize = 800;
+
+/// Characters carried over from the previous chunk.
+const CHUNK_OVERLAP: usize = 100;
+
+/// Max upload size in bytes (can be overridden via `DOCUMENT_MAX_BYTES`).
+pub fn max_document_bytes() -> usize {
+    std::env::var("DOCUMENT_MAX_BYTES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(10 * 1024 * 1024)
+}
+
+/// A retrieved chunk with its similarity score.
+#[derive(Debug, Clone)]
+pub struct DocumentHit {
+    pub filename: String,
+    pub chunk_index: i64,
+    pub content: String,
+    pub score: f32,
+}
+
+/// Extract text from an uploaded file based on its name or content type.
+pub async fn extract_text(
+    filename: &str,
+    content_type: &str,
+    bytes: Vec<u8>,
+) -> anyhow::Result<String> {
+    let is_pdf = content_type.contains("pdf") || filename.to_lowercase().ends_with(".pdf");
+    if is_pdf {
+        return extract_pdf(bytes).await;
+    }
+    String::from_utf8(bytes).map_err(|_| anyhow::anyhow!("Document is not valid UTF-8 text"))
+}
+
+#[cfg(feature = "pdf")]
+async fn extract_pdf(bytes: Vec<u8>) -> anyhow::Result<String> {
+    tokio::task::spawn_blocking(move || pdf_extract::extract_text_from_mem(&bytes))
+        .await
+        .map_err(|e| anyhow::anyhow!("PDF extraction task failed: {}", e))?
+        .map_err(|e| anyhow::anyhow!("PDF extraction failed: {}", e))
+}
+
+#[cfg(not(feature = "pdf"))]
+async fn extract_pdf(_bytes: Vec<u8>) -> anyhow::Result<String> {
+    anyhow::bail!("PDF uploads are not supported by this build")
+}
+
+/// Split text into overlapping chunks, preferring paragraph boundaries.
+pub fn chunk_text(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for para in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        if !current.is_empty() && current.chars().count() + para.chars().count() > CHUNK_CHARS {
+            let overlap: String = {
+                let chars: Vec<char> = current.chars().collect();
+                chars[chars.len().saturating_sub(CHUNK_OVERLAP)..]
+                    .iter()
+                    .collect()
+            };
+            chunks.push(std::mem::take(&mut current).trim().to_string());
+            current = overlap;
+            current.push_str("\n\n");
+        }
+        // Paragraphs longer than a chunk are split on char boundaries
+        let chars: Vec<char> = para.chars().collect();
+        if chars.len() > CHUNK_CHARS {
+            for piece in chars.chunks(CHUNK_CHARS - CHUNK_OVERLAP) {
+                if !current.trim().is_empty() {
+                    chunks.push(std::mem::take(&mut current).trim().to_string());
+                }
+                current = piece.iter().collect();
+                current.push_str("\n\n");
+            }
+        } else {
+            current.push_str(para);
+            current.push_str("\n\n");
+        }
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current.trim().to_string());
+    }
+    chunks
+}
+
+/// Store a document: chunk, embed, and persist. Returns (document_id, chunk_count).
+pub async fn ingest_document(
+    db: &dyn Store,
+    user_id: &str,
+    session_id: &str,
+    filename: &str,
+    content_type: &str,
+    text: &str,
+) -> anyhow::Result<(i64, usize)> {
+    let chunks = chunk_text(text);
+    if chunks.is_empty() {
+        return Err(anyhow::anyhow!("Document contains no text"));
+    }
+    let vectors = embeddings::embed(&chunks).await?;
+    let model = embeddings::model_name();
+
+    let document_id = db
+        .save_document(
+            user_id,
+            session_id,
+            filename,
+            content_type,
+            text.chars().count() as i64,
+        )
+        .await?;
+    for (i, (chunk, vector)) in chunks.iter().zip(vectors.iter()).enumerate() {
+        db.save_document_chunk(document_id, session_id, i as i64, chunk, vector, &model)
+            .await?;
+    }
+    Ok((document_id, chunks.len()))
+}
+
+/// Retrieve the chunks most similar to the query among the documents the
+/// user uploaded to a session.
+pub async fn search_documents(
+    db: &dyn Store,
+    user_id: &str,
+    session_id: &str,
+    query: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<DocumentHit>> {
+    let model = embeddings::model_name();
+    let query_vec = embeddings::embed(&[query.to_string()])
+        .await?
+        .pop()
+        .unwrap_or_default();
+
+    let mut hits: Vec<DocumentHit> = db
+        .fetch_document_chunks(user_id, session_id, &model)
+        .await
+        .into_iter()
+        .map(|c| {
+            let vector: Vec<f32> = serde_json::from_str(&c.embedding).unwrap_or_default();
+            DocumentHit {
+                score: embeddings::cosine(&query_vec, &vector),
+                filename: c.filename,
+                chunk_index: c.chunk_index,
+                content: c.content,
+            }
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// Format retrieved chunks for model consumption.
+pub fn format_hits(hits: &[DocumentHit]) -> String {
+    if hits.is_empty() {
+        return "No relevant passages found in the supplied documents.".to_string();
+    }
+    hits.iter()
+        .map(|h| {
+            format!(
+                "Document: {} (chunk {})\n{}\n",
+                h.filename,
+                h.chunk_index + 1,
+                h.content
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
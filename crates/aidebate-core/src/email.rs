@@ -0,0 +1,177 @@
+//! Email delivery of finished transcripts over SMTP.
+//!
+//! Enabled when `SMTP_HOST` and `SMTP_FROM` are set. `SMTP_PORT` defaults to
+//! 587 with `SMTP_TLS=starttls`; use `SMTP_TLS=tls` for implicit TLS (usually
+//! port 465) or `none` for a local relay. `SMTP_USERNAME` / `SMTP_PASSWORD`
+//! are sent when set.
+
+use std::sync::OnceLock;
+
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use tracing::{info, warn};
+
+use crate::debate::Turn;
+use crate::prompts;
+use crate::types::Position;
+
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+static MAILER: OnceLock<Option<Mailer>> = OnceLock::new();
+
+/// The configured mailer; `None` when SMTP is not configured.
+pub fn mailer() -> Option<&'static Mailer> {
+    MAILER
+        .get_or_init(|| match Mailer::from_env() {
+            Ok(mailer) => mailer,
+            Err(e) => {
+                warn!("Email delivery disabled: {}", e);
+                None
+            }
+        })
+        .as_ref()
+}
+
+/// Whether `address` is a syntactically valid email address.
+pub fn is_valid_address(address: &str) -> bool {
+    address.trim().parse::<Address>().is_ok()
+}
+
+impl Mailer {
+    fn from_env() -> anyhow::Result<Option<Self>> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let (Some(host), Some(from)) = (var("SMTP_HOST"), var("SMTP_FROM")) else {
+            return Ok(None);
+        };
+        let from: Mailbox = from
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid SMTP_FROM: {}", e))?;
+        let tls = var("SMTP_TLS").unwrap_or_else(|| "starttls".to_string());
+        let mut builder = match tls.to_ascii_lowercase().as_str() {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&host)?,
+            "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host),
+            _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)?,
+        };
+        if let Some(port) = var("SMTP_PORT").and_then(|v| v.parse().ok()) {
+            builder = builder.port(port);
+        }
+        if let (Some(user), Some(password)) = (var("SMTP_USERNAME"), var("SMTP_PASSWORD")) {
+            builder = builder.credentials(Credentials::new(user, password));
+        }
+        info!("Email delivery enabled via {}", host);
+        Ok(Some(Self {
+            transport: builder.build(),
+            from,
+        }))
+    }
+
+    /// Send the transcript of a finished debate to `to`.
+    pub async fn send_transcript(
+        &self,
+        to: &str,
+        topic: &str,
+        transcript: &[Turn],
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = to
+            .trim()
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid recipient: {}", e))?;
+        let subject: String = format!("Debate transcript: {}", topic)
+            .chars()
+            .take(150)
+            .collect();
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject)
+            .multipart(MultiPart::alternative_plain_html(
+                render_text(topic, transcript),
+                render_html(topic, transcript),
+            ))?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+fn verdict_line(transcript: &[Turn]) -> Option<String> {
+    let (_, _, verdict, _) = transcript
+        .last()
+        .filter(|(side, ..)| *side == Position::Judge)?;
+    let line = match prompts::parse_winner(verdict) {
+        Some(winner) => format!("Winner: {}", winner.label()),
+        None => "No clear winner".to_string(),
+    };
+    Some(line)
+}
+
+fn render_text(topic: &str, transcript: &[Turn]) -> String {
+    let mut text = format!("{}\n\n", topic);
+    if let Some(line) = verdict_line(transcript) {
+        text.push_str(&format!("{}\n\n", line));
+    }
+    for (side, phase, content, model) in transcript {
+        text.push_str(&format!(
+            "== {} · {} ({}) ==\n\n{}\n\n",
+            side.label(),
+            phase.title(),
+            model,
+            content.trim()
+        ));
+    }
+    text
+}
+
+fn render_html(topic: &str, transcript: &[Turn]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html><html><body style=\"font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;max-width:720px;margin:0 auto;padding:16px;color:#1f2328\">\
+         <h1 style=\"font-size:22px\">{}</h1>",
+        escape(topic)
+    );
+    if let Some(line) = verdict_line(transcript) {
+        html.push_str(&format!(
+            "<p style=\"font-size:16px;font-weight:600;padding:8px 12px;background:#fff8c5;border-radius:6px\">{}</p>",
+            escape(&line)
+        ));
+    }
+    for (side, phase, content, model) in transcript {
+        let color = match side {
+            Position::Pro => "#1a7f37",
+            Position::Con => "#cf222e",
+            Position::Judge => "#8250df",
+        };
+        html.push_str(&format!(
+            "<h2 style=\"font-size:17px;color:{}\">{} · {} <span style=\"font-weight:400;color:#656d76;font-size:13px\">{}</span></h2>\
+             <div style=\"white-space:pre-wrap;line-height:1.5\">{}</div>",
+            color,
+            side.label(),
+            phase.title(),
+            escape(model),
+            escape(content.trim())
+        ));
+    }
+    html.push_str("</body></html>");
+    html
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,157 @@
+//! Text embeddings for similarity search.
+//!
+//! Uses an OpenAI-compatible embeddings endpoint when `EMBEDDING_API_KEY` is
+//! set. Otherwise falls back to a local feature-hashing embedding (word and
+//! CJK character bigrams), which needs no network and is good enough for
+//! keyword-level retrieval. Each vector is stored with the model name so
+//! vectors from different models are never compared.
+
+use serde_json::json;
+
+use crate::config;
+
+/// Dimension of the local hashing embedding.
+const LOCAL_DIM: usize = 256;
+
+/// Model name recorded for locally computed vectors.
+pub const LOCAL_MODEL: &str = "local-hash-256";
+
+const DEFAULT_EMBEDDING_URL: &str = "https://api.openai.com/v1/embeddings";
+const DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+
+/// Name of the embedding model currently in use.
+pub fn model_name() -> String {
+    if std::env::var("EMBEDDING_API_KEY").is_ok() {
+        std::env::var("EMBEDDING_MODEL").unwrap_or_else(|_| DEFAULT_EMBEDDING_MODEL.to_string())
+    } else {
+        LOCAL_MODEL.to_string()
+    }
+}
+
+/// Embed a batch of texts with the configured model.
+pub async fn embed(texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+    if texts.is_empty() {
+        return Ok(vec![]);
+    }
+    match std::env::var("EMBEDDING_API_KEY") {
+        Ok(api_key) => embed_remote(&api_key, texts).await,
+        Err(_) => Ok(texts.iter().map(|t| embed_local(t)).collect()),
+    }
+}
+
+async fn embed_remote(api_key: &str, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+    let url =
+        std::env::var("EMBEDDING_API_URL").unwrap_or_else(|_| DEFAULT_EMBEDDING_URL.to_string());
+    let resp = config::http_client()
+        .post(url)
+        .bearer_auth(api_key)
+        .json(&json!({ "model": model_name(), "input": texts }))
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Embedding request failed: {}", e))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| anyhow::anyhow!("Embedding response parse failed: {}", e))?;
+
+    let data = resp["data"]
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("Embedding response missing data: {}", resp))?;
+    let vectors: Vec<Vec<f32>> = data
+        .iter()
+        .map(|d| {
+            d["embedding"]
+                .as_array()
+                .map(|v| {
+                    v.iter()
+                        .filter_map(|x| x.as_f64())
+                        .map(|x| x as f32)
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+        .collect();
+    if vectors.len() != texts.len() {
+        return Err(anyhow::anyhow!(
+            "Embedding count mismatch: expected {}, got {}",
+            texts.len(),
+            vectors.len()
+        ));
+    }
+    Ok(vectors)
+}
+
+/// FNV-1a hash, stable across Rust versions (unlike `DefaultHasher`).
+fn fnv1a(s: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in s.as_bytes() {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0xF900..=0xFAFF)
+}
+
+/// Local feature-hashing embedding, L2-normalized.
+pub fn embed_local(text: &str) -> Vec<f32> {
+    let mut vec = vec![0f32; LOCAL_DIM];
+    let mut add = |feature: &str| {
+        let h = fnv1a(feature);
+        let idx = (h % LOCAL_DIM as u64) as usize;
+        let sign = if (h >> 63) == 0 { 1.0 } else { -1.0 };
+        vec[idx] += sign;
+    };
+
+    let mut word = String::new();
+    let mut prev_cjk: Option<char> = None;
+    for c in text.chars() {
+        if is_cjk(c) {
+            if !word.is_empty() {
+                add(&word);
+                word.clear();
+            }
+            add(&c.to_string());
+            if let Some(p) = prev_cjk {
+                add(&format!("{}{}", p, c));
+            }
+            prev_cjk = Some(c);
+        } else if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+            prev_cjk = None;
+        } else {
+            if !word.is_empty() {
+                add(&word);
+                word.clear();
+            }
+            prev_cjk = None;
+        }
+    }
+    if !word.is_empty() {
+        add(&word);
+    }
+
+    let norm = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in vec.iter_mut() {
+            *x /= norm;
+        }
+    }
+    vec
+}
+
+/// Cosine similarity; 0.0 for mismatched or empty vectors.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let na = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let nb = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if na == 0.0 || nb == 0.0 {
+        0.0
+    } else {
+        dot / (na * nb)
+    }
+}
@@ -0,0 +1,233 @@
+//! Transcript export: JSON, Markdown, and a standalone HTML page.
+//!
+//! The HTML export is a single self-contained file (inline CSS, no scripts or
+//! external assets) that can be published to any static host: the rounds are
+//! rendered from Markdown into collapsible sections and the judge's verdict
+//! is highlighted. Raw HTML in model output is escaped, never passed through.
+
+#[cfg(feature = "markdown")]
+use pulldown_cmark::{html, Event, Options, Parser};
+use serde_json::json;
+
+use crate::debate::Turn;
+use crate::prompts;
+use crate::types::{DebatePhase, HistoryMessage, Position};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    /// Parse a `format` parameter (`json`, `markdown` / `md`, `html`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(ExportFormat::Json),
+            "markdown" | "md" => Some(ExportFormat::Markdown),
+            "html" => Some(ExportFormat::Html),
+            _ => None,
+        }
+    }
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ExportFormat::Html => "text/html; charset=utf-8",
+        }
+    }
+    /// Render `transcript` in this format.
+    pub fn render(&self, topic: &str, transcript: &[Turn]) -> String {
+        match self {
+            ExportFormat::Json => {
+                serde_json::to_string_pretty(&to_json(topic, transcript)).unwrap_or_default()
+            }
+            ExportFormat::Markdown => to_markdown(topic, transcript),
+            ExportFormat::Html => to_html(topic, transcript),
+        }
+    }
+    /// Render a stored session, headed by the title its owner gave it. JSON
+    /// keeps the topic and adds the title and notes.
+    pub fn render_session(
+        &self,
+        topic: &str,
+        title: Option<&str>,
+        notes: Option<&str>,
+        transcript: &[Turn],
+    ) -> String {
+        match self {
+            ExportFormat::Json => {
+                let mut value = to_json(topic, transcript);
+                value["title"] = json!(title);
+                value["notes"] = json!(notes);
+                serde_json::to_string_pretty(&value).unwrap_or_default()
+            }
+            _ => self.render(title.unwrap_or(topic), transcript),
+        }
+    }
+}
+
+/// Rebuild a transcript from stored history, skipping rows it cannot parse.
+pub fn transcript_from_history(messages: &[HistoryMessage]) -> Vec<Turn> {
+    messages
+        .iter()
+        .filter_map(|m| {
+            Some((
+                Position::from_role(&m.role)?,
+                DebatePhase::from_name(&m.phase)?,
+                m.content.clone(),
+                m.provider.clone().unwrap_or_default(),
+            ))
+        })
+        .collect()
+}
+
+pub fn to_json(topic: &str, transcript: &[Turn]) -> serde_json::Value {
+    json!({
+        "topic": topic,
+        "turns": transcript
+            .iter()
+            .map(|(side, phase, content, model)| json!({
+                "role": side.role_str(),
+                "phase": phase.as_str(),
+                "model": model,
+                "content": content,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+pub fn to_markdown(topic: &str, transcript: &[Turn]) -> String {
+    let mut md = format!("# {}\n\n", topic);
+    for (side, phase, content, model) in transcript {
+        md.push_str(&format!(
+            "## {} · {} ({})\n\n{}\n\n",
+            side.label(),
+            phase.title(),
+            model,
+            content.trim()
+        ));
+    }
+    md
+}
+
+const STYLE: &str = r#"
+:root { --pro: #1a7f37; --con: #cf222e; --judge: #8250df; --muted: #656d76; --border: #d0d7de; }
+* { box-sizing: border-box; }
+body { margin: 0; background: #f6f8fa; color: #1f2328; font: 16px/1.6 -apple-system, "Segoe UI", Helvetica, Arial, "PingFang SC", "Microsoft YaHei", sans-serif; }
+main { max-width: 820px; margin: 0 auto; padding: 32px 20px 48px; }
+header h1 { font-size: 28px; line-height: 1.3; margin: 4px 0 8px; }
+.eyebrow { margin: 0; color: var(--muted); font-size: 13px; letter-spacing: .08em; text-transform: uppercase; }
+.models { margin: 0 0 20px; color: var(--muted); font-size: 14px; }
+.banner { display: inline-block; margin: 0 0 24px; padding: 6px 14px; border-radius: 999px; font-weight: 600; color: #fff; background: var(--muted); text-decoration: none; }
+.banner.pro { background: var(--pro); } .banner.con { background: var(--con); }
+details { background: #fff; border: 1px solid var(--border); border-left: 4px solid var(--muted); border-radius: 8px; margin: 0 0 12px; }
+details.pro { border-left-color: var(--pro); } details.con { border-left-color: var(--con); }
+details.judge { border: 2px solid var(--judge); background: #fbf8ff; box-shadow: 0 2px 10px rgba(130, 80, 223, .15); }
+summary { cursor: pointer; padding: 12px 16px; font-weight: 600; }
+summary .model { font-weight: 400; color: var(--muted); font-size: 13px; margin-left: 6px; }
+.pro summary .side { color: var(--pro); } .con summary .side { color: var(--con); } .judge summary .side { color: var(--judge); }
+.content { padding: 0 20px 12px; overflow-wrap: anywhere; }
+.content table { border-collapse: collapse; margin: 12px 0; }
+.content th, .content td { border: 1px solid var(--border); padding: 4px 10px; }
+.content pre { background: #f6f8fa; padding: 12px; border-radius: 6px; overflow-x: auto; }
+.content blockquote { margin: 0; padding-left: 12px; border-left: 3px solid var(--border); color: var(--muted); }
+footer { margin-top: 32px; color: var(--muted); font-size: 13px; text-align: center; }
+@media print { details { break-inside: avoid; } }
+"#;
+
+/// A standalone HTML page for `transcript`.
+pub fn to_html(topic: &str, transcript: &[Turn]) -> String {
+    let verdict = transcript
+        .iter()
+        .rev()
+        .find(|(side, ..)| *side == Position::Judge);
+    let model_of = |position: Position| {
+        transcript
+            .iter()
+            .find(|(side, ..)| *side == position)
+            .map(|(_, _, _, model)| escape(model))
+    };
+    let models: Vec<String> = [Position::Pro, Position::Con, Position::Judge]
+        .into_iter()
+        .filter_map(|side| Some(format!("{}: {}", side.label(), model_of(side)?)))
+        .collect();
+
+    let mut page = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{style}</style>\n</head>\n<body>\n<main>\n\
+         <header>\n<p class=\"eyebrow\">AI Debate</p>\n<h1>{title}</h1>\n\
+         <p class=\"models\">{models}</p>\n</header>\n",
+        title = escape(topic),
+        style = STYLE,
+        models = models.join(" · "),
+    );
+    if let Some((_, _, content, _)) = verdict {
+        let (class, text) = match prompts::parse_winner(content) {
+            Some(winner) => (winner.role_str(), format!("Winner: {}", winner.label())),
+            None => ("none", "No clear winner".to_string()),
+        };
+        page.push_str(&format!(
+            "<a class=\"banner {}\" href=\"#verdict\">{}</a>\n",
+            class, text
+        ));
+    }
+    for (side, phase, content, model) in transcript {
+        let is_verdict = *side == Position::Judge;
+        page.push_str(&format!(
+            "<details class=\"{class}\"{id} open>\n<summary><span class=\"side\">{side}</span> · {phase}\
+             <span class=\"model\">{model}</span></summary>\n<div class=\"content\">\n{body}</div>\n</details>\n",
+            class = side.role_str(),
+            id = if is_verdict { " id=\"verdict\"" } else { "" },
+            side = side.label(),
+            phase = phase.title(),
+            model = escape(model),
+            body = render_markdown(content),
+        ));
+    }
+    page.push_str("<footer>Exported from aidebate</footer>\n</main>\n</body>\n</html>\n");
+    page
+}
+
+/// Markdown to HTML, with any raw HTML in the source shown as text.
+#[cfg(feature = "markdown")]
+fn render_markdown(markdown: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        event => event,
+    });
+    let mut out = String::new();
+    html::push_html(&mut out, events);
+    out
+}
+
+/// Without the `markdown` feature, rounds are shown as preformatted text.
+#[cfg(not(feature = "markdown"))]
+fn render_markdown(markdown: &str) -> String {
+    format!("<pre>{}</pre>\n", escape(markdown))
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
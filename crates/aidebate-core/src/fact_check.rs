@@ -0,0 +1,127 @@
+//! Automated claim fact-checking.
+//!
+//! The `fact_check` tool searches for evidence about a specific claim and asks
+//! the fact-checker model (`FACT_CHECK_MODEL_ID`, defaulting to the judge's
+//! model) for a supported / contradicted / unclear judgment with sources.
+
+use ai_lib_rust::Message;
+use tracing::info;
+
+use crate::search::SearchOptions;
+use crate::tools::{self, SearchResult};
+use crate::types::{ClientBackend, ClientInfo};
+
+/// Outcome of a fact check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FactVerdict {
+    Supported,
+    Contradicted,
+    Unclear,
+}
+
+impl FactVerdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FactVerdict::Supported => "supported",
+            FactVerdict::Contradicted => "contradicted",
+            FactVerdict::Unclear => "unclear",
+        }
+    }
+
+    /// Parse the `Verdict:` line of the checker's reply.
+    fn parse(reply: &str) -> Self {
+        let line = reply
+            .lines()
+            .find(|l| l.trim_start().to_lowercase().starts_with("verdict"))
+            .unwrap_or(reply)
+            .to_lowercase();
+        if line.contains("contradicted") {
+            FactVerdict::Contradicted
+        } else if line.contains("supported") {
+            FactVerdict::Supported
+        } else {
+            FactVerdict::Unclear
+        }
+    }
+}
+
+/// Whether the judge may call `fact_check` (`JUDGE_FACT_CHECK=1`, requires web search).
+pub fn judge_fact_check_enabled() -> bool {
+    tools::is_search_enabled()
+        && std::env::var("JUDGE_FACT_CHECK")
+            .ok()
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+}
+
+/// Search for evidence about a claim and judge it with the fact-checker model.
+pub async fn check_claim(checker: &ClientInfo, claim: &str, opts: &SearchOptions) -> SearchResult {
+    let evidence = match tools::execute_web_search(claim, opts).await {
+        Ok(result) => result,
+        Err(e) => SearchResult {
+            query: claim.to_string(),
+            results: format!("Search failed: {}", e),
+            sources: vec![],
+        },
+    };
+
+    let (verdict, reason) = match judge_claim(checker, claim, &evidence.results).await {
+        Ok(reply) => {
+            let reason = reply
+                .lines()
+                .find(|l| l.trim_start().to_lowercase().starts_with("reason"))
+                .map(|l| {
+                    l.split_once(':')
+                        .map(|(_, r)| r)
+                        .unwrap_or(l)
+                        .trim()
+                        .to_string()
+                })
+                .unwrap_or_else(|| reply.trim().to_string());
+            (FactVerdict::parse(&reply), reason)
+        }
+        Err(e) => {
+            info!("Fact check failed for '{}': {}", claim, e);
+            (
+                FactVerdict::Unclear,
+                format!("Fact check unavailable: {}", e),
+            )
+        }
+    };
+
+    SearchResult {
+        query: format!("fact_check: {}", claim),
+        results: format!(
+            "Claim: {}\nVerdict: {}\nReason: {}\n\nEvidence:\n{}",
+            claim,
+            verdict.as_str().to_uppercase(),
+            reason,
+            evidence.results
+        ),
+        sources: evidence.sources,
+    }
+}
+
+async fn judge_claim(checker: &ClientInfo, claim: &str, evidence: &str) -> anyhow::Result<String> {
+    let client = match &checker.backend {
+        ClientBackend::Live(client) => client,
+        ClientBackend::Mock => {
+            return Ok("Verdict: unclear\nReason: mock provider does not verify claims".to_string())
+        }
+    };
+    let messages = vec![
+        Message::system(
+            "You are a careful fact-checker. Judge the claim strictly against the evidence provided.\nAnswer in exactly two lines:\nVerdict: supported | contradicted | unclear\nReason: <one or two sentences citing the evidence>".to_string(),
+        ),
+        Message::user(format!("Claim: {}\n\nEvidence:\n{}", claim, evidence)),
+    ];
+    let response = client
+        .chat()
+        .messages(messages)
+        .temperature(0.0)
+        .max_tokens(300)
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("Fact-check call failed for {}: {}", checker.name, e))?;
+    Ok(response.content)
+}
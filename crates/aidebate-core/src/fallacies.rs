@@ -0,0 +1,107 @@
+//! Logical fallacy detection for debate training.
+//!
+//! After a debate, each Pro and Con round is reviewed by the analysis model
+//! (the judge's model) for common fallacies. Every finding names the fallacy,
+//! quotes the offending span word for word, and explains the flaw; findings
+//! whose quote does not appear in the round are dropped, so a quote can always
+//! be located in the transcript.
+
+use ai_lib_rust::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ClientBackend, ClientInfo, DebatePhase, Position};
+
+/// Fallacies the analysis looks for.
+pub const FALLACY_KINDS: &[&str] = &[
+    "strawman",
+    "ad_hominem",
+    "false_dilemma",
+    "slippery_slope",
+    "appeal_to_authority",
+    "hasty_generalization",
+    "circular_reasoning",
+    "red_herring",
+];
+
+/// Most findings kept per round.
+const MAX_FINDINGS: usize = 5;
+
+/// Stored `debate_analysis` kind for fallacy findings.
+pub const ANALYSIS_KIND: &str = "fallacies";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fallacy {
+    /// One of [`FALLACY_KINDS`].
+    pub kind: String,
+    /// The offending span, verbatim from the round.
+    pub quote: String,
+    #[serde(default)]
+    pub explanation: String,
+}
+
+/// Review one round for fallacies.
+pub async fn detect(
+    analyzer: &ClientInfo,
+    topic: &str,
+    side: Position,
+    phase: DebatePhase,
+    content: &str,
+) -> anyhow::Result<Vec<Fallacy>> {
+    let client = match &analyzer.backend {
+        ClientBackend::Live(client) => client,
+        ClientBackend::Mock => return Ok(Vec::new()),
+    };
+    let messages = vec![
+        Message::system(format!(
+            "You are a debate coach reviewing one speech for logical fallacies.\n\
+             Only flag clear cases of these fallacies: {}.\n\
+             Answer with a JSON array and nothing else. Each item is an object with:\n\
+             - \"kind\": one of the fallacy names above\n\
+             - \"quote\": the offending sentence or phrase, copied exactly from the speech in its original language\n\
+             - \"explanation\": one sentence, in the language of the speech, on why it is fallacious\n\
+             Answer [] when there are none. Flag at most {} items.",
+            FALLACY_KINDS.join(", "),
+            MAX_FINDINGS
+        )),
+        Message::user(format!(
+            "Debate topic: {}\nSpeaker: {} ({})\n\nSpeech:\n{}",
+            topic,
+            side.label(),
+            phase.as_str(),
+            content
+        )),
+    ];
+    let response = client
+        .chat()
+        .messages(messages)
+        .temperature(0.0)
+        .max_tokens(1000)
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("Fallacy analysis failed for {}: {}", analyzer.name, e))?;
+    Ok(parse_findings(&response.content, content))
+}
+
+/// Findings from the model's reply that name a known fallacy and quote
+/// `content` verbatim.
+fn parse_findings(reply: &str, content: &str) -> Vec<Fallacy> {
+    let json = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => return Vec::new(),
+    };
+    let findings: Vec<Fallacy> = serde_json::from_str(json).unwrap_or_default();
+    findings
+        .into_iter()
+        .map(|f| Fallacy {
+            kind: f.kind.trim().to_lowercase().replace([' ', '-'], "_"),
+            quote: f.quote.trim().to_string(),
+            explanation: f.explanation.trim().to_string(),
+        })
+        .filter(|f| {
+            FALLACY_KINDS.contains(&f.kind.as_str())
+                && !f.quote.is_empty()
+                && content.contains(&f.quote)
+        })
+        .take(MAX_FINDINGS)
+        .collect()
+}
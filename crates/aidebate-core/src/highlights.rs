@@ -0,0 +1,151 @@
+//! Highlight quotes for share cards and social posts.
+//!
+//! The analysis model picks the most impactful passages of each side from a
+//! finished transcript. Quotes must appear word for word in the round they
+//! are attributed to; a quote found in another round of the same side is
+//! re-attributed, and anything else is dropped.
+
+use ai_lib_rust::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::debate::Turn;
+use crate::types::{ClientBackend, ClientInfo, Position};
+
+/// Stored `debate_analysis` kind for highlights.
+pub const ANALYSIS_KIND: &str = "highlights";
+
+/// Quotes requested per side; at least `MIN_PER_SIDE` are asked for.
+const MIN_PER_SIDE: usize = 3;
+const MAX_PER_SIDE: usize = 5;
+
+/// Longest quote kept, in characters.
+const MAX_QUOTE_CHARS: usize = 280;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Highlight {
+    /// `pro` or `con`.
+    pub side: String,
+    /// Phase of the round the quote is from (e.g. `rebuttal`).
+    pub phase: String,
+    pub quote: String,
+    /// Why the passage lands, in a few words.
+    #[serde(default)]
+    pub why: String,
+}
+
+/// Pick the most impactful quotes of each side.
+pub async fn extract(
+    client: &ClientInfo,
+    topic: &str,
+    transcript: &[Turn],
+) -> anyhow::Result<Vec<Highlight>> {
+    let live = match &client.backend {
+        ClientBackend::Live(live) => live,
+        ClientBackend::Mock => return Ok(first_sentences(transcript)),
+    };
+    let rounds = transcript
+        .iter()
+        .filter(|(side, ..)| *side != Position::Judge)
+        .map(|(side, phase, content, _)| {
+            format!(
+                "### side={} phase={}\n{}",
+                side.role_str(),
+                phase.as_str(),
+                content
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let messages = vec![
+        Message::system(format!(
+            "You pick highlight quotes from a finished debate for share cards.\n\
+             Choose the {min} to {max} most impactful passages for each side (pro and con): \
+             memorable, self-contained lines that carry a key argument or rebuttal.\n\
+             Answer with a JSON array and nothing else. Each item is an object with:\n\
+             - \"side\": \"pro\" or \"con\"\n\
+             - \"phase\": the phase of the round the quote is from\n\
+             - \"quote\": one or two sentences copied exactly from that round, at most {chars} characters\n\
+             - \"why\": a few words, in the language of the debate, on why it stands out",
+            min = MIN_PER_SIDE,
+            max = MAX_PER_SIDE,
+            chars = MAX_QUOTE_CHARS
+        )),
+        Message::user(format!("Topic: {}\n\n{}", topic, rounds)),
+    ];
+    let response = live
+        .chat()
+        .messages(messages)
+        .temperature(0.2)
+        .max_tokens(1500)
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("Highlight extraction failed for {}: {}", client.name, e))?;
+    Ok(parse_highlights(&response.content, transcript))
+}
+
+/// Highlights from the model's reply whose quotes are found in the
+/// transcript, at most `MAX_PER_SIDE` per side.
+fn parse_highlights(reply: &str, transcript: &[Turn]) -> Vec<Highlight> {
+    let json = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => return Vec::new(),
+    };
+    let candidates: Vec<Highlight> = serde_json::from_str(json).unwrap_or_default();
+    let mut kept: Vec<Highlight> = Vec::new();
+    for candidate in candidates {
+        let Some(side) = Position::from_role(candidate.side.trim()) else {
+            continue;
+        };
+        let quote = candidate.quote.trim();
+        if side == Position::Judge || quote.is_empty() || quote.chars().count() > MAX_QUOTE_CHARS {
+            continue;
+        }
+        let rounds = || {
+            transcript
+                .iter()
+                .filter(move |(s, _, content, _)| *s == side && content.contains(quote))
+        };
+        let Some((_, phase, ..)) = rounds()
+            .find(|(_, phase, ..)| phase.as_str() == candidate.phase.trim())
+            .or_else(|| rounds().next())
+        else {
+            continue;
+        };
+        let per_side = kept.iter().filter(|h| h.side == side.role_str()).count();
+        if per_side >= MAX_PER_SIDE || kept.iter().any(|h| h.quote == quote) {
+            continue;
+        }
+        kept.push(Highlight {
+            side: side.role_str().to_string(),
+            phase: phase.as_str().to_string(),
+            quote: quote.to_string(),
+            why: candidate.why.trim().to_string(),
+        });
+    }
+    kept
+}
+
+/// The opening sentence of each debater round, for the mock provider.
+fn first_sentences(transcript: &[Turn]) -> Vec<Highlight> {
+    transcript
+        .iter()
+        .filter(|(side, ..)| *side != Position::Judge)
+        .filter_map(|(side, phase, content, _)| {
+            let line = content
+                .lines()
+                .map(str::trim)
+                .find(|l| !l.is_empty() && !l.starts_with('#'))?;
+            let end = line
+                .find(['。', '.', '!', '?', '！', '？'])
+                .map_or(line.len(), |i| {
+                    i + line[i..].chars().next().map_or(1, char::len_utf8)
+                });
+            Some(Highlight {
+                side: side.role_str().to_string(),
+                phase: phase.as_str().to_string(),
+                quote: line[..end].chars().take(MAX_QUOTE_CHARS).collect(),
+                why: String::new(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,83 @@
+//! Follow-up questions to the judge after a verdict.
+//!
+//! The user asks why the judge decided as it did ("why did Con's statistics
+//! fail to convince you?"). The judge's model answers in the judge's voice,
+//! with the stored transcript, the verdict and the earlier questions on the
+//! same debate as context. Exchanges are stored in `judge_questions`.
+
+use ai_lib_rust::Message;
+
+use crate::debate::Turn;
+use crate::storage::JudgeExchange;
+use crate::types::{ClientBackend, ClientInfo, Position};
+
+/// Longest question accepted, in bytes.
+pub const MAX_QUESTION_BYTES: usize = 2000;
+
+/// Earlier exchanges sent along as conversation context.
+const MAX_EARLIER_EXCHANGES: usize = 5;
+
+/// Answer a question about the verdict of a finished debate. `transcript`
+/// includes the verdict.
+pub async fn answer(
+    judge: &ClientInfo,
+    topic: &str,
+    transcript: &[Turn],
+    earlier: &[JudgeExchange],
+    question: &str,
+) -> anyhow::Result<String> {
+    let live = match &judge.backend {
+        ClientBackend::Live(live) => live,
+        ClientBackend::Mock => {
+            return Ok(format!(
+                "（mock）关于“{}”：我的裁决依据双方在各阶段的实际表现，关键在于哪一方更充分地回应了对方的核心质疑。",
+                question
+            ))
+        }
+    };
+    let rounds = transcript
+        .iter()
+        .map(|(side, phase, content, _)| {
+            let speaker = match side {
+                Position::Judge => "Your verdict".to_string(),
+                _ => format!("{} · {}", side.label(), phase.title()),
+            };
+            format!("## {}\n{}", speaker, content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let skip = earlier.len().saturating_sub(MAX_EARLIER_EXCHANGES);
+    let earlier = earlier[skip..]
+        .iter()
+        .map(|e| format!("Q: {}\nA: {}", e.question, e.answer))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let mut messages = vec![Message::system(format!(
+        "You judged the debate below and gave the verdict at its end. The user has follow-up questions about it.\n\
+         Answer as the judge, in the language of the question: explain your reasoning with reference to what each side \
+         actually said, quote the transcript where it helps, and admit it plainly if a point could reasonably have gone \
+         the other way. Do not invent arguments that were not made. Keep the answer under 250 words.\n\n\
+         Topic: {}\n\n{}",
+        topic, rounds
+    ))];
+    if !earlier.is_empty() {
+        messages.push(Message::user(format!(
+            "Questions already asked about this verdict, with your answers:\n\n{}",
+            earlier
+        )));
+    }
+    messages.push(Message::user(question.to_string()));
+    let response = live
+        .chat()
+        .messages(messages)
+        .temperature(0.3)
+        .max_tokens(700)
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("Judge answer failed for {}: {}", judge.name, e))?;
+    let answer = response.content.trim().to_string();
+    if answer.is_empty() {
+        anyhow::bail!("Judge gave an empty answer");
+    }
+    Ok(answer)
+}
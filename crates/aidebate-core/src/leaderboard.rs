@@ -0,0 +1,140 @@
+//! Model and provider standings from stored verdicts.
+//!
+//! Every judged debate is a match between the Pro and the Con model; a
+//! verdict without a winner is a draw. Standings count wins, losses and
+//! draws per model and per provider (the model id up to its `/`), and rate
+//! each with Elo, replaying the debates oldest first. Debates a model or
+//! provider had against itself, and forks and debates continued from an
+//! edited round, are left out.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::prompts;
+use crate::storage::Store;
+use crate::types::Position;
+
+/// Rating every model and provider starts from.
+const INITIAL_ELO: f64 = 1500.0;
+
+/// How far one debate moves a rating.
+const ELO_K: f64 = 32.0;
+
+/// Which debates count.
+#[derive(Debug, Default)]
+pub struct Filter<'a> {
+    /// Only debates whose topic is tagged with this category.
+    pub category: Option<&'a str>,
+    /// Only debates started on or after this day (`YYYY-MM-DD`).
+    pub from: Option<&'a str>,
+    /// Only debates started on or before this day (`YYYY-MM-DD`).
+    pub to: Option<&'a str>,
+    /// Only debates judged by this model.
+    pub judge_model: Option<&'a str>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Standing {
+    /// Model id, or provider name.
+    pub name: String,
+    pub debates: u64,
+    pub wins: u64,
+    pub losses: u64,
+    /// Debates the judge named no winner in.
+    pub draws: u64,
+    /// Share of decided debates won.
+    pub win_rate: Option<f64>,
+    pub elo: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Leaderboard {
+    /// Judged debates that matched the filter.
+    pub debates: u64,
+    /// Highest rated first.
+    pub providers: Vec<Standing>,
+    /// Highest rated first.
+    pub models: Vec<Standing>,
+}
+
+/// Standings over the judged debates matching `filter`.
+pub async fn standings(db: &dyn Store, filter: &Filter<'_>) -> Leaderboard {
+    let results = db
+        .fetch_debate_results(filter.category, filter.from, filter.to)
+        .await
+        .into_iter()
+        .filter(|(.., judge, _)| {
+            filter
+                .judge_model
+                .is_none_or(|model| judge.as_str() == model)
+        })
+        .map(|(pro, con, _, verdict)| (pro, con, prompts::parse_winner(&verdict)))
+        .collect::<Vec<_>>();
+    let provider = |model: &str| model.split('/').next().unwrap_or(model).to_string();
+    Leaderboard {
+        debates: results.len() as u64,
+        providers: rank(
+            results
+                .iter()
+                .map(|(pro, con, winner)| (provider(pro), provider(con), *winner)),
+        ),
+        models: rank(results.into_iter()),
+    }
+}
+
+/// Standings from `(pro, con, winner)` matches in the order they were played.
+fn rank(matches: impl Iterator<Item = (String, String, Option<Position>)>) -> Vec<Standing> {
+    let mut standings: BTreeMap<String, Standing> = BTreeMap::new();
+    for (pro, con, winner) in matches {
+        if pro == con {
+            continue;
+        }
+        let pro_won = match winner {
+            Some(Position::Pro) => Some(true),
+            Some(Position::Con) => Some(false),
+            _ => None,
+        };
+        let mut entry = |name: &str| {
+            standings.remove(name).unwrap_or_else(|| Standing {
+                name: name.to_string(),
+                elo: INITIAL_ELO,
+                ..Default::default()
+            })
+        };
+        let (mut pro_standing, mut con_standing) = (entry(&pro), entry(&con));
+        let expected = 1.0 / (1.0 + 10f64.powf((con_standing.elo - pro_standing.elo) / 400.0));
+        let score = pro_won.map_or(0.5, |won| if won { 1.0 } else { 0.0 });
+        pro_standing.elo += ELO_K * (score - expected);
+        con_standing.elo -= ELO_K * (score - expected);
+        for (standing, won) in [
+            (&mut pro_standing, pro_won),
+            (&mut con_standing, pro_won.map(|won| !won)),
+        ] {
+            standing.debates += 1;
+            match won {
+                Some(true) => standing.wins += 1,
+                Some(false) => standing.losses += 1,
+                None => standing.draws += 1,
+            }
+        }
+        standings.insert(pro, pro_standing);
+        standings.insert(con, con_standing);
+    }
+    let round = |v: f64, places: i32| {
+        let scale = 10f64.powi(places);
+        (v * scale).round() / scale
+    };
+    let mut standings: Vec<Standing> = standings
+        .into_values()
+        .map(|mut standing| {
+            let decided = standing.wins + standing.losses;
+            standing.win_rate =
+                (decided > 0).then(|| round(standing.wins as f64 / decided as f64, 2));
+            standing.elo = round(standing.elo, 1);
+            standing
+        })
+        .collect();
+    standings.sort_by(|a, b| b.elo.total_cmp(&a.elo).then(b.debates.cmp(&a.debates)));
+    standings
+}
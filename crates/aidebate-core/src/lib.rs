@@ -0,0 +1,98 @@
+//! Debate orchestration without the HTTP server.
+//!
+//! `aidebate-core` holds everything needed to run a debate: provider client
+//! setup ([`config::init_clients`]), round execution ([`debate`]), prompt
+//! construction ([`prompts`]), tool calling and web search ([`tools`],
+//! [`search`]), and persistence behind [`storage::Store`], with an SQLite
+//! implementation. The `aidebate` server binary adds the axum routes,
+//! authentication, rate limiting and quotas on top.
+//!
+//! ```no_run
+//! use aidebate_core::app_metrics::PrometheusMetrics;
+//! use aidebate_core::debate::{execute_one_round, DebateStreamChunk};
+//! use aidebate_core::tools::ToolContext;
+//! use aidebate_core::types::{DebatePhase, Position};
+//! use aidebate_core::{config, storage};
+//! use futures::StreamExt;
+//! use std::sync::Arc;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let db = storage::init_db("sqlite://debate.db").await?;
+//! let store = Arc::new(storage::SqliteStore::new(db));
+//! let (pro, _con, judge) = config::init_clients().await?;
+//! let tool_ctx = ToolContext::new(
+//!     store,
+//!     "embedder".to_string(),
+//!     "session-1".to_string(),
+//!     judge,
+//!     PrometheusMetrics::new(),
+//! );
+//! let (mut stream, _model) = execute_one_round(
+//!     &pro,
+//!     Position::Pro,
+//!     DebatePhase::Opening,
+//!     "Remote work is better than office work",
+//!     &[],
+//!     vec![],
+//!     &tool_ctx,
+//! )
+//! .await?;
+//! while let Some(chunk) = stream.next().await {
+//!     if let DebateStreamChunk::Delta(text) = chunk? {
+//!         print!("{}", text);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod alerts;
+pub mod app_metrics;
+pub mod arguments;
+pub mod audit;
+pub mod budget;
+pub mod cache;
+pub mod calibration;
+pub mod categories;
+pub mod citations;
+pub mod config;
+pub mod consistency;
+pub mod debate;
+pub mod diff;
+pub mod documents;
+#[cfg(feature = "email")]
+pub mod email;
+pub mod embeddings;
+pub mod export;
+pub mod fact_check;
+pub mod fallacies;
+pub mod highlights;
+pub mod judge_qa;
+pub mod leaderboard;
+#[cfg(feature = "notion")]
+pub mod notion;
+pub mod panel;
+#[cfg(feature = "podcast")]
+pub mod podcast;
+pub mod presets;
+pub mod prompt_traces;
+pub mod prompts;
+pub mod quiz;
+pub mod redaction;
+pub mod refusals;
+pub mod rubric;
+pub mod sanitize;
+pub mod search;
+pub mod sections;
+pub mod similar;
+pub mod storage;
+pub mod subtitles;
+pub mod suggestions;
+pub mod summaries;
+pub mod templates;
+pub mod tokenizer;
+pub mod tone;
+pub mod tools;
+#[cfg(feature = "podcast")]
+pub mod tts;
+pub mod types;
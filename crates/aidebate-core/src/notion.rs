@@ -0,0 +1,170 @@
+//! Notion export of finished transcripts.
+//!
+//! Enabled when `NOTION_TOKEN` (an internal integration token) and
+//! `NOTION_DATABASE_ID` are set; the database must be shared with the
+//! integration. Every finished debate becomes a page in the database with the
+//! topic as its title (`NOTION_TITLE_PROPERTY`, default `Name`), a `Providers`
+//! text property, a `Winner` select property, and the rounds as its content.
+
+use std::sync::OnceLock;
+
+use serde_json::json;
+use tracing::info;
+
+use crate::config;
+use crate::debate::Turn;
+use crate::prompts;
+use crate::types::Position;
+
+const API_BASE: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+
+/// Notion caps a text object at 2000 characters and a request at 100 blocks.
+const MAX_TEXT_CHARS: usize = 2000;
+const MAX_BLOCKS_PER_REQUEST: usize = 100;
+
+pub struct Notion {
+    token: String,
+    database_id: String,
+    title_property: String,
+}
+
+static NOTION: OnceLock<Option<Notion>> = OnceLock::new();
+
+/// The configured Notion export; `None` when it is not configured.
+pub fn notion() -> Option<&'static Notion> {
+    NOTION.get_or_init(Notion::from_env).as_ref()
+}
+
+impl Notion {
+    fn from_env() -> Option<Self> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let token = var("NOTION_TOKEN")?;
+        let database_id = var("NOTION_DATABASE_ID")?;
+        info!("Notion export enabled for database {}", database_id);
+        Some(Self {
+            token,
+            database_id,
+            title_property: var("NOTION_TITLE_PROPERTY").unwrap_or_else(|| "Name".to_string()),
+        })
+    }
+
+    /// Create a page for a finished debate. Returns the page URL.
+    pub async fn export_transcript(
+        &self,
+        topic: &str,
+        transcript: &[Turn],
+    ) -> anyhow::Result<String> {
+        let providers = [Position::Pro, Position::Con, Position::Judge]
+            .into_iter()
+            .filter_map(|position| {
+                let (_, _, _, model) = transcript.iter().find(|(side, ..)| *side == position)?;
+                Some(format!("{}: {}", position.label(), model))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let winner = transcript
+            .iter()
+            .rev()
+            .find(|(side, ..)| *side == Position::Judge)
+            .and_then(|(_, _, verdict, _)| prompts::parse_winner(verdict))
+            .map_or("No clear winner", |side| side.label());
+
+        let mut blocks = content_blocks(transcript);
+        let rest = blocks.split_off(blocks.len().min(MAX_BLOCKS_PER_REQUEST));
+        let page = self
+            .request(
+                reqwest::Method::POST,
+                "pages",
+                json!({
+                    "parent": { "database_id": self.database_id },
+                    "properties": {
+                        self.title_property.as_str(): { "title": rich_text(topic) },
+                        "Providers": { "rich_text": rich_text(&providers) },
+                        "Winner": { "select": { "name": winner } },
+                    },
+                    "children": blocks,
+                }),
+            )
+            .await?;
+        let page_id = page["id"].as_str().unwrap_or_default();
+        for batch in rest.chunks(MAX_BLOCKS_PER_REQUEST) {
+            self.request(
+                reqwest::Method::PATCH,
+                &format!("blocks/{}/children", page_id),
+                json!({ "children": batch }),
+            )
+            .await?;
+        }
+        Ok(page["url"].as_str().unwrap_or_default().to_string())
+    }
+
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        let resp = config::http_client()
+            .request(method, format!("{}/{}", API_BASE, path))
+            .bearer_auth(&self.token)
+            .header("Notion-Version", NOTION_VERSION)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Notion request failed: {}", e))?;
+        let status = resp.status();
+        let body: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("Notion response parse failed: {}", e))?;
+        if !status.is_success() {
+            anyhow::bail!(
+                "Notion request failed ({}): {}",
+                status,
+                body["message"].as_str().unwrap_or("unknown error")
+            );
+        }
+        Ok(body)
+    }
+}
+
+/// A heading per round followed by its paragraphs.
+fn content_blocks(transcript: &[Turn]) -> Vec<serde_json::Value> {
+    let mut blocks = Vec::new();
+    for (side, phase, content, model) in transcript {
+        blocks.push(json!({
+            "object": "block",
+            "type": "heading_2",
+            "heading_2": {
+                "rich_text": rich_text(&format!("{} · {} ({})", side.label(), phase.title(), model)),
+            },
+        }));
+        for paragraph in content
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+        {
+            blocks.push(json!({
+                "object": "block",
+                "type": "paragraph",
+                "paragraph": { "rich_text": rich_text(paragraph) },
+            }));
+        }
+    }
+    blocks
+}
+
+/// Plain rich text, split into text objects Notion accepts.
+fn rich_text(text: &str) -> serde_json::Value {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(MAX_TEXT_CHARS)
+        .map(|chunk| json!({ "type": "text", "text": { "content": chunk.iter().collect::<String>() } }))
+        .collect()
+}
@@ -0,0 +1,111 @@
+//! Judge panels and dissenting opinions.
+//!
+//! With `JUDGE_PANEL` set, the models it lists also judge every debate once
+//! the judge's verdict has streamed, and each verdict counts as one vote.
+//! The judge's verdict stays the debate's verdict. When the vote is split
+//! and a majority names a winner, the first judge it outvoted writes a
+//! dissenting opinion, as in appellate courts; the opinion is stored as a
+//! `dissent` analysis and streamed as its own `dissent` phase.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tracing::warn;
+
+use crate::calibration::{self, Verdict};
+use crate::debate::Turn;
+use crate::storage::Store;
+use crate::tools::ToolContext;
+use crate::types::{ClientInfo, Position};
+
+/// Phase name and stored analysis kind of a dissenting opinion.
+pub const DISSENT_PHASE: &str = "dissent";
+
+/// The votes on the Pro and Con rounds in `transcript`: `verdict`, the
+/// judge's own, then those of `panel` in order. Panelists that fail to judge
+/// are left out.
+pub async fn vote(
+    db: &Arc<dyn Store>,
+    panel: &[ClientInfo],
+    topic: &str,
+    transcript: &[Turn],
+    verdict: Verdict,
+    tool_ctx: &ToolContext,
+) -> Vec<Verdict> {
+    let verdicts = futures::future::join_all(
+        panel
+            .iter()
+            .map(|judge| calibration::give_verdict(db, judge, topic, transcript, tool_ctx)),
+    )
+    .await;
+    let mut votes = vec![verdict];
+    for (judge, verdict) in panel.iter().zip(verdicts) {
+        match verdict {
+            Ok(verdict) => votes.push(verdict),
+            Err(e) => warn!("Panel judge {} failed: {}", judge.model_id, e),
+        }
+    }
+    votes
+}
+
+/// The side a majority of `votes` named and the first vote against it;
+/// `None` when the vote is unanimous or no side has a majority.
+pub fn dissent(votes: &[Verdict]) -> Option<(Position, &Verdict)> {
+    let mut counts: HashMap<Option<&str>, usize> = HashMap::new();
+    for vote in votes {
+        *counts.entry(vote.winner.as_deref()).or_default() += 1;
+    }
+    let (winner, count) = counts.into_iter().max_by_key(|(_, count)| *count)?;
+    if count * 2 <= votes.len() {
+        return None;
+    }
+    let majority = Position::from_role(winner?)?;
+    let dissent = votes
+        .iter()
+        .find(|vote| vote.winner.as_deref() != Some(majority.role_str()))?;
+    Some((majority, dissent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn votes(winners: &[Option<&str>]) -> Vec<Verdict> {
+        winners
+            .iter()
+            .enumerate()
+            .map(|(i, winner)| Verdict {
+                model: format!("judge-{}", i),
+                winner: winner.map(str::to_string),
+                content: String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_outvoted_judge_dissents() {
+        let split = votes(&[Some("pro"), Some("con"), Some("con")]);
+        let (majority, dissent_vote) = dissent(&split).unwrap();
+        assert_eq!(majority, Position::Con);
+        assert_eq!(dissent_vote.model, "judge-0");
+
+        let split = votes(&[Some("con"), None, Some("con")]);
+        let (majority, dissent_vote) = dissent(&split).unwrap();
+        assert_eq!(majority, Position::Con);
+        assert_eq!(dissent_vote.model, "judge-1");
+    }
+
+    #[test]
+    fn unanimous_or_hung_panel_has_no_dissent() {
+        assert!(dissent(&votes(&[Some("pro"), Some("pro"), Some("pro")])).is_none());
+        assert!(dissent(&votes(&[Some("pro"), Some("con")])).is_none());
+        assert!(dissent(&votes(&[Some("pro"), Some("con"), None])).is_none());
+        assert!(dissent(&votes(&[Some("pro"), Some("con"), Some("con"), None])).is_none());
+        assert!(dissent(&votes(&[Some("pro")])).is_none());
+    }
+
+    #[test]
+    fn majority_naming_no_winner_has_no_dissent() {
+        assert!(dissent(&votes(&[None, None, Some("con")])).is_none());
+    }
+}
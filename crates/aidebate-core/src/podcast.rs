@@ -0,0 +1,175 @@
+//! Podcast feed of audio debates.
+//!
+//! Debates published as episodes are rendered to speech (see `tts.rs`) and
+//! the MP3 is saved under `PODCAST_DIR` (default `podcast`). The server lists
+//! the episodes as an RSS feed with iTunes tags, so a regularly run debate
+//! can be followed in any podcast app. The channel title is `PODCAST_TITLE`.
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::debate::Turn;
+use crate::prompt_traces::civil_date;
+use crate::prompts;
+use crate::storage::{PodcastEpisode, Store};
+use crate::tts;
+use crate::types::Position;
+
+/// Directory the episode audio files are written to.
+pub fn podcast_dir() -> PathBuf {
+    std::env::var("PODCAST_DIR")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map_or_else(|| PathBuf::from("podcast"), PathBuf::from)
+}
+
+fn podcast_title() -> String {
+    std::env::var("PODCAST_TITLE")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "AI Debate".to_string())
+}
+
+/// Render a finished debate to audio and add it to the feed. Returns the
+/// episode id.
+pub async fn publish_episode(
+    db: &dyn Store,
+    user_id: &str,
+    session_id: &str,
+    topic: &str,
+    transcript: &[Turn],
+) -> anyhow::Result<i64> {
+    let audio = tts::render_transcript(topic, transcript).await?;
+    let duration_secs = tts::mp3_duration_secs(&audio).round() as i64;
+    let winner = transcript
+        .iter()
+        .rev()
+        .find(|(side, ..)| *side == Position::Judge)
+        .and_then(|(_, _, verdict, _)| prompts::parse_winner(verdict))
+        .map(|side| side.label());
+
+    let dir = podcast_dir();
+    tokio::fs::create_dir_all(&dir).await?;
+    let stem: String = session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let file = format!("{}-{}.mp3", stem, millis);
+    tokio::fs::write(dir.join(&file), &audio).await?;
+
+    db.save_podcast_episode(&PodcastEpisode {
+        id: 0,
+        user_id: user_id.to_string(),
+        session_id: session_id.to_string(),
+        topic: topic.to_string(),
+        winner: winner.map(str::to_string),
+        duration_secs,
+        size_bytes: audio.len() as i64,
+        file,
+        published_unix: 0,
+        summary: None,
+        tags: None,
+    })
+    .await
+}
+
+/// RSS 2.0 feed of `episodes` (newest first). Enclosure URLs point at
+/// `{base_url}/podcast/episodes/{id}.mp3`.
+pub fn render_feed(base_url: &str, episodes: &[PodcastEpisode]) -> String {
+    let title = escape(&podcast_title());
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\" xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\">\n\
+         <channel>\n\
+         <title>{title}</title>\n\
+         <link>{base}</link>\n\
+         <description>Debates between AI models, argued and judged automatically.</description>\n\
+         <itunes:author>{title}</itunes:author>\n\
+         <itunes:category text=\"Technology\"/>\n\
+         <itunes:explicit>false</itunes:explicit>\n",
+        title = title,
+        base = escape(base_url),
+    );
+    for episode in episodes {
+        let outcome = match &episode.winner {
+            Some(winner) => format!("Winner: {}", winner),
+            None => "No clear winner".to_string(),
+        };
+        let description = match &episode.summary {
+            Some(summary) => format!("{}. {}", outcome, summary),
+            None => outcome,
+        };
+        xml.push_str(&format!(
+            "<item>\n\
+             <title>{topic}</title>\n\
+             <description>{description}</description>\n\
+             <enclosure url=\"{base}/podcast/episodes/{id}.mp3\" length=\"{size}\" type=\"audio/mpeg\"/>\n\
+             <guid isPermaLink=\"false\">aidebate-episode-{id}</guid>\n\
+             <pubDate>{date}</pubDate>\n\
+             <itunes:duration>{duration}</itunes:duration>\n\
+             <itunes:summary>{description}</itunes:summary>\n\
+             {categories}</item>\n",
+            topic = escape(&episode.topic),
+            description = escape(&description),
+            base = escape(base_url),
+            id = episode.id,
+            size = episode.size_bytes,
+            date = rfc2822(episode.published_unix),
+            duration = episode.duration_secs,
+            categories = episode
+                .tags
+                .iter()
+                .flat_map(|tags| tags.split(','))
+                .map(|tag| format!("<category>{}</category>\n", escape(tag)))
+                .collect::<String>(),
+        ));
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Format a Unix timestamp as an RFC 2822 date in UTC, as RSS expects.
+fn rfc2822(unix: i64) -> String {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let days = unix.div_euclid(86_400);
+    let secs = unix.rem_euclid(86_400);
+    let (year, month, day) = civil_date(days);
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} +0000",
+        DAYS[days.rem_euclid(7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
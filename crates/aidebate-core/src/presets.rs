@@ -0,0 +1,85 @@
+//! Curated debate presets.
+//!
+//! A preset bundles a debate format and speaking style for the debaters, a
+//! rubric for the judge and a few example topics, so a debate on an ethics
+//! dilemma is argued and judged differently from one on a product decision.
+//! Presets are listed by `GET /presets` and chosen with `"preset": "<id>"` in
+//! the debate request; without one the debate runs with the default prompts.
+
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct Preset {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// How the debaters structure their rounds.
+    pub format: &'static str,
+    /// Tone and kind of evidence expected from the debaters.
+    pub style: &'static str,
+    /// Criteria the judge weighs, in order of importance.
+    pub rubric: &'static [&'static str],
+    pub example_topics: &'static [&'static str],
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        id: "ethics_dilemma",
+        name: "Ethics dilemma",
+        description: "Moral questions without a factual answer, argued from principles and consequences.",
+        format: "每轮先点明所依据的伦理原则（如功利主义、义务论、美德伦理），再用具体情境检验该原则，并正面回应对方原则下最强的反例。",
+        style: "审慎、讲理，承认道德代价；以思想实验和真实案例为主，不必堆砌统计数据。",
+        rubric: &[
+            "原则是否清晰且前后一致",
+            "是否正面处理了最难的反例与道德代价",
+            "论证能否推广到相似情境而不导出荒谬结论",
+            "对对方价值立场的理解与回应是否公允",
+        ],
+        example_topics: &[
+            "自动驾驶汽车在不可避免的事故中应优先保护车内乘客",
+            "为挽救更多生命，强制器官捐献在道德上可以接受",
+            "向陌生人说善意的谎言是不道德的",
+        ],
+    },
+    Preset {
+        id: "product_decision",
+        name: "Product decision",
+        description: "A concrete business or product choice, argued on costs, risks and user impact.",
+        format: "每轮围绕用户价值、成本与收益、风险与可逆性展开，给出可执行的建议，并说明成功与失败的衡量指标。",
+        style: "务实、数据导向；引用市场数据、用户研究或可比案例，明确区分事实与假设。",
+        rubric: &[
+            "对用户价值与业务影响的论证是否有数据支撑",
+            "成本、风险与可逆性是否被充分评估",
+            "建议是否具体、可执行且可衡量",
+            "是否回应了对方提出的最大风险",
+        ],
+        example_topics: &[
+            "初创公司应该在产品早期就推出免费增值模式",
+            "移动应用应当放弃自研推荐算法，改用第三方服务",
+            "SaaS 产品应从按席位收费改为按用量收费",
+        ],
+    },
+    Preset {
+        id: "historical_counterfactual",
+        name: "Historical counterfactual",
+        description: "What-if questions about history, argued from sources and plausible causal chains.",
+        format: "每轮先交代真实的历史背景，再给出反事实假设下的因果链条，逐环说明其可信度，并指出对方链条中最薄弱的一环。",
+        style: "严谨、史料为本；引用史料与史学研究，区分史实、主流解释与推测。",
+        rubric: &[
+            "史实是否准确，史料引用是否可靠",
+            "反事实因果链是否合理，每一环是否有依据",
+            "是否考虑了其他历史力量的制约",
+            "对对方因果链薄弱环节的揭示是否有力",
+        ],
+        example_topics: &[
+            "如果郑和船队持续远航，中国会率先开启全球航海时代",
+            "如果没有印刷术的普及，宗教改革不会成功",
+            "如果罗马帝国没有分裂，欧洲将更早进入工业时代",
+        ],
+    },
+];
+
+/// The preset with this id.
+pub fn find(id: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|preset| preset.id == id)
+}
@@ -0,0 +1,204 @@
+//! Prompt/response traces for LLM observability services.
+//!
+//! Enabled when `LANGSMITH_API_KEY` is set. Every provider call made by a
+//! round (one per tool-loop iteration) is posted as an `llm` run to the
+//! LangSmith API (`LANGSMITH_ENDPOINT`, default the hosted service; any
+//! compatible endpoint works) under the project `LANGSMITH_PROJECT` (default
+//! `aidebate`). A run holds the exact prompt messages, the response text and
+//! any tool calls it requested, usage, and the session, side, phase, provider
+//! and model as metadata, so prompt changes can be compared outside the app.
+//!
+//! Configured redaction rules (see [`crate::redaction`]) apply to traces as
+//! well. Delivery happens in the background; failures are logged and never
+//! affect the debate.
+
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ai_lib_rust::Message;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::config;
+use crate::redaction::redact;
+use crate::types::{DebatePhase, Position};
+
+const DEFAULT_ENDPOINT: &str = "https://api.smith.langchain.com";
+const DEFAULT_PROJECT: &str = "aidebate";
+
+pub struct Tracer {
+    api_key: String,
+    endpoint: String,
+    project: String,
+}
+
+static TRACER: OnceLock<Option<Tracer>> = OnceLock::new();
+
+/// The configured trace exporter; `None` when it is not configured.
+pub fn tracer() -> Option<&'static Tracer> {
+    TRACER.get_or_init(Tracer::from_env).as_ref()
+}
+
+impl Tracer {
+    fn from_env() -> Option<Self> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let api_key = var("LANGSMITH_API_KEY")?;
+        let project = var("LANGSMITH_PROJECT").unwrap_or_else(|| DEFAULT_PROJECT.to_string());
+        info!("Prompt traces enabled for project {}", project);
+        Some(Self {
+            api_key,
+            endpoint: var("LANGSMITH_ENDPOINT")
+                .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            project,
+        })
+    }
+
+    async fn post_run(&self, run: serde_json::Value) -> anyhow::Result<()> {
+        let resp = config::http_client()
+            .post(format!("{}/runs", self.endpoint))
+            .header("x-api-key", &self.api_key)
+            .timeout(Duration::from_secs(30))
+            .json(&run)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Trace request failed: {}", e.without_url()))?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!(
+                "Trace request failed ({}): {}",
+                status,
+                body.chars().take(300).collect::<String>()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// One provider call being traced.
+pub struct Trace {
+    tracer: &'static Tracer,
+    name: String,
+    messages: serde_json::Value,
+    metadata: serde_json::Value,
+    started: SystemTime,
+}
+
+impl Trace {
+    /// Start tracing a call that sends `messages`; `None` when traces are off.
+    pub fn start(
+        provider: &str,
+        model: &str,
+        side: Position,
+        phase: DebatePhase,
+        session_id: &str,
+        iteration: usize,
+        messages: &[Message],
+    ) -> Option<Self> {
+        let tracer = tracer()?;
+        Some(Self {
+            tracer,
+            name: format!("{}.{}", side.role_str(), phase.as_str()),
+            messages: serde_json::to_value(messages).unwrap_or_default(),
+            metadata: json!({
+                "session_id": session_id,
+                "side": side.role_str(),
+                "phase": phase.as_str(),
+                "provider": provider,
+                "model": model,
+                "iteration": iteration,
+            }),
+            started: SystemTime::now(),
+        })
+    }
+
+    /// Send the run with the call's response, in the background.
+    pub fn finish(
+        self,
+        content: &str,
+        tool_calls: Vec<serde_json::Value>,
+        usage: Option<serde_json::Value>,
+        error: Option<String>,
+    ) {
+        let tags = [
+            self.metadata["side"].clone(),
+            self.metadata["phase"].clone(),
+            self.metadata["provider"].clone(),
+        ];
+        let run = json!({
+            "name": self.name,
+            "run_type": "llm",
+            "session_name": self.tracer.project,
+            "start_time": iso8601(self.started),
+            "end_time": iso8601(SystemTime::now()),
+            "inputs": { "messages": self.messages },
+            "outputs": {
+                "content": content,
+                "tool_calls": tool_calls,
+                "usage": usage,
+            },
+            "error": error,
+            "tags": tags,
+            "extra": { "metadata": self.metadata },
+        });
+        let run = redact_strings(run);
+        let tracer = self.tracer;
+        tokio::spawn(async move {
+            if let Err(e) = tracer.post_run(run).await {
+                warn!("Failed to export prompt trace: {}", e);
+            }
+        });
+    }
+}
+
+/// Apply configured redaction to every string in `value`.
+fn redact_strings(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(s) => serde_json::Value::String(redact(&s).into_owned()),
+        serde_json::Value::Array(items) => items.into_iter().map(redact_strings).collect(),
+        serde_json::Value::Object(fields) => fields
+            .into_iter()
+            .map(|(key, value)| (key, redact_strings(value)))
+            .collect(),
+        other => other,
+    }
+}
+
+/// `2024-05-01T12:34:56.789Z`
+fn iso8601(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs() as i64;
+    let (year, month, day) = civil_date(secs.div_euclid(86_400));
+    let secs_of_day = secs.rem_euclid(86_400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// (year, month, day) of a count of days since 1970-01-01 (Howard Hinnant's
+/// algorithm).
+pub(crate) fn civil_date(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
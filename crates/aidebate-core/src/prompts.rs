@@ -0,0 +1,573 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+
+use ai_lib_rust::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{max_tokens_for_role, reserved_tokens_for_role};
+use crate::presets::Preset;
+use crate::rubric::Rubric;
+use crate::templates;
+use crate::tokenizer::{counter_for_model, TokenCounter};
+use crate::types::{DebatePhase, Position};
+
+/// A transcript entry as it appears in a prompt. Content is borrowed from the
+/// transcript unless it had to be truncated.
+struct HistoryEntry<'a> {
+    position: Position,
+    phase: DebatePhase,
+    content: Cow<'a, str>,
+    provider: &'a str,
+}
+
+impl<'a> HistoryEntry<'a> {
+    fn borrowed(entry: &'a (Position, DebatePhase, String, String)) -> Self {
+        let (position, phase, content, provider) = entry;
+        Self {
+            position: *position,
+            phase: *phase,
+            content: Cow::Borrowed(content),
+            provider,
+        }
+    }
+}
+
+/// Render entries as `[label - phase - provider]` blocks.
+fn format_history<'a>(entries: impl IntoIterator<Item = HistoryEntry<'a>>) -> String {
+    let mut history = String::new();
+    for entry in entries {
+        let _ = write!(
+            history,
+            "[{} - {} - {}]\n{}\n\n",
+            entry.position.label(),
+            entry.phase.title(),
+            entry.provider,
+            entry.content
+        );
+    }
+    history
+}
+
+/// Compress transcript to fit token budget for a role.
+/// Keeps recent entries first; if even the most recent entry is too large,
+/// it is truncated to fit. Only a truncated entry is copied.
+fn compress_transcript_for_role<'a>(
+    transcript: &'a [(Position, DebatePhase, String, String)],
+    role: &str,
+    counter: &dyn TokenCounter,
+) -> Vec<HistoryEntry<'a>> {
+    if transcript.is_empty() {
+        return vec![];
+    }
+
+    let reserved = reserved_tokens_for_role(role);
+    let max_tokens = max_tokens_for_role(role);
+    let allowed_history_tokens = max_tokens.saturating_sub(reserved);
+
+    // Build recent-first, sum tokens until budget exceeded
+    let mut out = Vec::new();
+    let mut total = 0u32;
+    for entry in transcript.iter().rev() {
+        let content = &entry.2;
+        let est = counter.count(content).max(1);
+        if total + est > allowed_history_tokens {
+            if out.is_empty() {
+                // Scale the token budget to characters at this entry's density
+                let chars = content.chars().count();
+                let allowed_chars =
+                    (chars as u64 * allowed_history_tokens as u64 / est as u64) as usize;
+                let truncated = format!(
+                    "{}\n\n[...已截断]",
+                    truncate_at_sentence(content, allowed_chars.max(80))
+                );
+                out.push(HistoryEntry {
+                    content: Cow::Owned(truncated),
+                    ..HistoryEntry::borrowed(entry)
+                });
+            }
+            break;
+        }
+        out.push(HistoryEntry::borrowed(entry));
+        total += est;
+    }
+
+    out.reverse();
+    out
+}
+
+/// Sentence terminators, Chinese and Western.
+const SENTENCE_ENDS: &[char] = &['。', '！', '？', '；', '.', '!', '?', ';', '\n'];
+
+/// Longest prefix of `text` with at most `max_chars` characters, cut after the
+/// last sentence end when that keeps at least half of the allowance.
+fn truncate_at_sentence(text: &str, max_chars: usize) -> &str {
+    let cut = match text.char_indices().nth(max_chars) {
+        Some((byte_idx, _)) => byte_idx,
+        None => return text,
+    };
+    let prefix = &text[..cut];
+    let sentence_end = prefix
+        .char_indices()
+        .filter(|(_, c)| SENTENCE_ENDS.contains(c))
+        .map(|(i, c)| i + c.len_utf8())
+        .last();
+    match sentence_end {
+        Some(end) if prefix[..end].chars().count() * 2 >= max_chars => &prefix[..end],
+        _ => prefix,
+    }
+}
+
+/// Build system prompt from the side `template` with optional tool calling
+/// instructions and the format and style of an optional preset.
+pub fn build_side_prompt(
+    side: Position,
+    phase: DebatePhase,
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+    preset: Option<&Preset>,
+    template: &str,
+    model_id: &str,
+) -> Vec<Message> {
+    let compressed =
+        compress_transcript_for_role(transcript, side.role_str(), counter_for_model(model_id));
+    build_side_prompt_inner(
+        side, phase, topic, compressed, false, None, preset, template,
+    )
+}
+
+/// Build system prompt with tool calling enabled and optional search context.
+#[allow(clippy::too_many_arguments)]
+pub fn build_side_prompt_with_tools(
+    side: Position,
+    phase: DebatePhase,
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+    search_context: Option<&str>,
+    preset: Option<&Preset>,
+    template: &str,
+    model_id: &str,
+) -> Vec<Message> {
+    let compressed =
+        compress_transcript_for_role(transcript, side.role_str(), counter_for_model(model_id));
+    build_side_prompt_inner(
+        side,
+        phase,
+        topic,
+        compressed,
+        true,
+        search_context,
+        preset,
+        rubric,
+        template,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_side_prompt_inner(
+    side: Position,
+    phase: DebatePhase,
+    topic: &str,
+    transcript: Vec<HistoryEntry<'_>>,
+    tools_enabled: bool,
+    search_context: Option<&str>,
+    preset: Option<&Preset>,
+    template: &str,
+) -> Vec<Message> {
+    let stance = match side {
+        Position::Pro => "你是正方，支持该议题。",
+        Position::Con => "你是反方，反对该议题。",
+        Position::Judge => "",
+    };
+    let history = format_history(transcript);
+
+    let phase_goal = match phase {
+        DebatePhase::Opening => "开篇陈词：阐述立场与核心论点。",
+        DebatePhase::Rebuttal => "反驳：针对对方论点逐条反驳，并补充论据。",
+        DebatePhase::Defense => "防守：回应对方反驳，巩固自身论据。",
+        DebatePhase::Closing => "总结陈词：总结关键论点，强调结论。",
+        DebatePhase::Judgement => "",
+    };
+
+    let tool_instruction = if tools_enabled {
+        "\n- 当需要事实、数据、统计或最新信息来支持论点时，请调用 web_search 工具搜索证据。\n- 搜索结果要自然融入你的论点，不要提及工具调用过程。\n- 涉及时事时可调用 news_search 工具（可指定日期范围），引用新闻时注明发布日期。\n- 引用搜索来源时，在句末标注来源编号，如 [1]。\n- 若可使用 search_documents 工具，优先从本场辩论提供的文档中检索依据。\n- 对对方提出的可疑数据或事实，可调用 fact_check 工具核查具体论断。\n"
+    } else {
+        ""
+    };
+    let preset_instruction = preset
+        .map(|p| format!("\n- 辩论形式：{}\n- 风格：{}", p.format, p.style))
+        .unwrap_or_default();
+
+    let system = templates::render(
+        template,
+        &[
+            ("stance", stance),
+            ("topic", topic),
+            ("phase_goal", phase_goal),
+            ("preset_instruction", &preset_instruction),
+            ("tool_instruction", tool_instruction),
+        ],
+    );
+
+    let mut messages = vec![Message::system(system)];
+    if !history.is_empty() {
+        messages.push(Message::user(format!("已进行的辩论记录：\n{}", history)));
+    }
+
+    // Inject search results as reference context if available
+    if let Some(ctx) = search_context {
+        messages.push(Message::user(format!(
+            "以下是搜索到的参考资料，请将相关内容自然地融入你的论点：\n\n{}",
+            ctx
+        )));
+    }
+
+    messages.push(Message::user(format!(
+        "请完成本轮 `{}` 发言。",
+        phase.title()
+    )));
+    messages
+}
+
+/// Build judge prompt from the judge `template`, with an optional earlier
+/// verdict on a similar topic, optional consistency findings and the rubric
+/// of an optional preset, or a weighted rubric in its place.
+pub fn build_judge_prompt(
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+    precedent: Option<&str>,
+    consistency: Option<&str>,
+    preset: Option<&Preset>,
+    rubric: Option<&Rubric>,
+    template: &str,
+) -> Vec<Message> {
+    build_judge_prompt_inner(
+        topic,
+        transcript,
+        false,
+        None,
+        precedent,
+        consistency,
+        preset,
+        rubric,
+        template,
+    )
+}
+
+/// Build judge prompt with the fact_check tool enabled and optional fact-check results.
+#[allow(clippy::too_many_arguments)]
+pub fn build_judge_prompt_with_tools(
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+    tool_context: Option<&str>,
+    precedent: Option<&str>,
+    consistency: Option<&str>,
+    preset: Option<&Preset>,
+    rubric: Option<&Rubric>,
+    template: &str,
+) -> Vec<Message> {
+    build_judge_prompt_inner(
+        topic,
+        transcript,
+        true,
+        tool_context,
+        precedent,
+        consistency,
+        preset,
+        rubric,
+        template,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_judge_prompt_inner(
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+    tools_enabled: bool,
+    tool_context: Option<&str>,
+    precedent: Option<&str>,
+    consistency: Option<&str>,
+    preset: Option<&Preset>,
+    weighted: Option<&Rubric>,
+    template: &str,
+) -> Vec<Message> {
+    let history = format_history(transcript.iter().map(HistoryEntry::borrowed));
+    let tool_instruction = if tools_enabled {
+        "- 对双方存在争议的关键事实主张，可调用 fact_check 工具核查，并在推理中说明核查结果。\n"
+    } else {
+        ""
+    };
+    let preset_rubric = || {
+        preset.map(|p| {
+            let criteria: Vec<String> = p
+                .rubric
+                .iter()
+                .enumerate()
+                .map(|(i, criterion)| format!("{}. {}", i + 1, criterion))
+                .collect();
+            format!(
+                "- 按以下评分标准（按重要性排序）评判，并在推理中逐项说明：{}\n",
+                criteria.join("；")
+            )
+        })
+    };
+    let rubric = weighted
+        .map(Rubric::instruction)
+        .or_else(preset_rubric)
+        .unwrap_or_default();
+    let system = templates::render(
+        template,
+        &[
+            ("topic", topic),
+            ("rubric", &rubric),
+            ("tool_instruction", tool_instruction),
+        ],
+    );
+    let mut messages = vec![Message::system(system)];
+    if let Some(precedent) = precedent {
+        messages.push(Message::user(format!(
+            "相似辩题的既往裁决（仅供参考，本场须依据双方实际表现独立裁决）：\n\n{}",
+            precedent
+        )));
+    }
+    messages.push(Message::user(format!("完整辩论记录：\n{}", history)));
+    if let Some(ctx) = tool_context {
+        messages.push(Message::user(format!("事实核查结果：\n\n{}", ctx)));
+    }
+    if let Some(notes) = consistency {
+        messages.push(Message::user(format!(
+            "一致性检查（自动检测，请核对原文后酌情扣分：简单重复前轮内容的论证价值较低，自相矛盾削弱该方可信度）：\n\n{}",
+            notes
+        )));
+    }
+    messages
+}
+
+/// Ask for the round in `language`. The section headings and the lines the
+/// verdict parsers look for must stay as they are.
+pub fn add_language_instruction(messages: &mut Vec<Message>, language: &str) {
+    messages.push(Message::user(format!(
+        "请使用{}撰写本轮内容。`## Reasoning` 等 Markdown 标题以及 `Winner:`、`Confidence:` 等标记保持原样，不要翻译。",
+        language.trim()
+    )));
+}
+
+/// Build the prompt for the "what would change the outcome" analysis; the
+/// transcript includes the verdict.
+pub fn build_outcome_prompt(
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+) -> Vec<Message> {
+    let history = format_history(transcript.iter().map(HistoryEntry::borrowed));
+    let system = format!(
+        "你是辩论教练，裁判已对下面的辩论做出裁决。请分析哪些缺失的证据或论点最有可能改变裁决结果，供辩手训练参考。\n议题：{topic}\n要求：\n- 用 Markdown 输出，以 `## What Would Change the Outcome` 开头，不超过 300 字。\n- 针对落败方列出 2-4 条最可能扭转裁决的缺失证据或论点，并说明各自为何会影响裁判的判断。\n- 再用一条说明胜方最需要补强之处。\n- 只讨论辩论中没有出现或没有充分展开的内容，不要复述辩论记录。\n"
+    );
+    vec![
+        Message::system(system),
+        Message::user(format!("完整辩论记录（含裁决）：\n{}", history)),
+    ]
+}
+
+/// Build the prompt for a panel judge's dissenting opinion. `verdict` is the
+/// judge's own verdict on the Pro and Con rounds in `transcript`, outvoted by
+/// a panel majority for `majority`.
+pub fn build_dissent_prompt(
+    topic: &str,
+    transcript: &[(Position, DebatePhase, String, String)],
+    majority: Position,
+    verdict: &str,
+) -> Vec<Message> {
+    let history = format_history(transcript.iter().map(HistoryEntry::borrowed));
+    let majority = match majority {
+        Position::Pro => "正方",
+        _ => "反方",
+    };
+    let system = format!(
+        "你是评审团中的一名裁判。评审团多数认定{majority}获胜，你的裁决与多数意见不同。请仿照上诉法院的少数意见，撰写你的反对意见。\n议题：{topic}\n要求：\n- 用 Markdown 输出，以 `## Dissenting Opinion` 开头，不超过 400 字。\n- 先用一句话概括多数意见，再说明你不同意的理由。\n- 指出多数意见忽略或高估的具体论点与证据，并引用辩论记录。\n- 语气克制，就事论事，不要重复完整的裁决。\n"
+    );
+    vec![
+        Message::system(system),
+        Message::user(format!(
+            "完整辩论记录：\n{}\n\n你的裁决：\n{}",
+            history, verdict
+        )),
+    ]
+}
+
+/// The side named on the last `Winner:` line of the judgement; `None` when
+/// there is no such line or it names neither side, as `Winner: Tie` does.
+/// Markdown emphasis and a full-width colon are tolerated.
+pub fn parse_winner(judgement: &str) -> Option<Position> {
+    let side = judgement.lines().rev().find_map(|line| {
+        let line = line.replace(['*', '_', '`'], "").to_lowercase();
+        let (_, rest) = line.split_once("winner")?;
+        Some(
+            rest.trim_start()
+                .strip_prefix([':', '：'])?
+                .trim()
+                .to_string(),
+        )
+    })?;
+    if side.starts_with("pro") {
+        Some(Position::Pro)
+    } else if side.starts_with("con") {
+        Some(Position::Con)
+    } else {
+        None
+    }
+}
+
+/// Stored `debate_analysis` kind for the confidence stated in a verdict.
+pub const CONFIDENCE_ANALYSIS_KIND: &str = "confidence";
+
+/// Confidence the judge states in a verdict, each from 0 to 100.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VerdictConfidence {
+    /// Confidence in the verdict as a whole, from the `Confidence:` line.
+    pub overall: Option<u8>,
+    /// Key contested claims, from the `## Contested Claims` list.
+    pub claims: Vec<ClaimConfidence>,
+}
+
+impl VerdictConfidence {
+    pub fn is_empty(&self) -> bool {
+        self.overall.is_none() && self.claims.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClaimConfidence {
+    /// `pro` or `con`, when the judge named the side making the claim.
+    pub side: Option<String>,
+    pub claim: String,
+    /// How likely the judge thinks the claim holds.
+    pub confidence: u8,
+}
+
+/// The overall `Confidence: 80` line and the `- [Pro] claim — Confidence: 60`
+/// items of a verdict. Values above 100 are ignored.
+pub fn parse_confidence(judgement: &str) -> VerdictConfidence {
+    let mut confidence = VerdictConfidence::default();
+    for line in judgement.lines() {
+        let line = line.trim();
+        let item = line
+            .strip_prefix(['-', '*', '+'])
+            .filter(|rest| rest.starts_with(char::is_whitespace));
+        if let Some(item) = item {
+            if let Some(claim) = parse_claim_confidence(item) {
+                confidence.claims.push(claim);
+            }
+        } else if let Some(value) = confidence_value(&line.replace(['*', '_', '`'], "")) {
+            confidence.overall = Some(value);
+        }
+    }
+    confidence
+}
+
+/// A `[Pro] claim — Confidence: 60` list item.
+fn parse_claim_confidence(item: &str) -> Option<ClaimConfidence> {
+    let item = item.replace(['*', '`'], "");
+    let start = item.to_ascii_lowercase().rfind("confidence")?;
+    let confidence = confidence_value(&item[start..])?;
+    let mut claim = item[..start]
+        .trim()
+        .trim_end_matches(['—', '–', '-', '|', ':', '(', '（', ',', '，']);
+    let mut side = None;
+    if let Some(rest) = claim.trim_start().strip_prefix(['[', '【']) {
+        if let Some((tag, rest)) = rest.split_once([']', '】']) {
+            side = Position::from_role(&tag.trim().to_lowercase())
+                .filter(|p| *p != Position::Judge)
+                .map(|p| p.role_str().to_string());
+            claim = rest;
+        }
+    }
+    let claim = claim.trim();
+    (!claim.is_empty()).then(|| ClaimConfidence {
+        side,
+        claim: claim.to_string(),
+        confidence,
+    })
+}
+
+/// The number in a line starting `Confidence:` (case-insensitive), if 0-100.
+fn confidence_value(line: &str) -> Option<u8> {
+    let (label, rest) = line.trim().split_at_checked("confidence".len())?;
+    if !label.eq_ignore_ascii_case("confidence") {
+        return None;
+    }
+    let rest = rest.trim_start().strip_prefix([':', '：'])?.trim_start();
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse::<u8>().ok().filter(|v| *v <= 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::CharRatio;
+    use proptest::prelude::*;
+
+    const COUNTER: CharRatio = CharRatio {
+        cjk: 0.6,
+        other: 0.3,
+    };
+
+    proptest! {
+        #[test]
+        fn truncation_is_a_char_boundary_prefix(text in "\\PC*", max_chars in 0usize..200) {
+            let out = truncate_at_sentence(&text, max_chars);
+            prop_assert!(text.starts_with(out));
+            prop_assert!(out.chars().count() <= max_chars);
+        }
+
+        #[test]
+        fn short_text_is_untouched(text in "[一-龥a-z。，！ ]{0,50}") {
+            prop_assert_eq!(truncate_at_sentence(&text, 50), text.as_str());
+        }
+
+        #[test]
+        fn oversized_multibyte_entry_is_truncated(text in "[一-龥😀é。！？ ]{6000,9000}") {
+            let transcript = vec![(Position::Pro, DebatePhase::Opening, text.clone(), "mock".to_string())];
+            let out = compress_transcript_for_role(&transcript, "con", &COUNTER);
+            prop_assert_eq!(out.len(), 1);
+            prop_assert!(out[0].content.ends_with("[...已截断]"));
+        }
+    }
+
+    #[test]
+    fn truncation_prefers_sentence_end() {
+        let text = "第一句话说完了。第二句话还没有说完就被截断";
+        assert_eq!(truncate_at_sentence(text, 12), "第一句话说完了。");
+    }
+
+    #[test]
+    fn winner_line_is_parsed() {
+        assert_eq!(
+            parse_winner("## Verdict\n正方更优。\n\nWinner: Pro\n"),
+            Some(Position::Pro)
+        );
+        assert_eq!(parse_winner("**Winner：Con**"), Some(Position::Con));
+        assert_eq!(parse_winner("## Verdict\n平局"), None);
+        // A later tie line overrules the judge's own
+        assert_eq!(parse_winner("Winner: Pro\n\nWinner: Tie\n"), None);
+    }
+
+    #[test]
+    fn confidence_is_parsed() {
+        let verdict = "## Verdict\nWinner: Pro\n**Confidence: 72**\n\n## Contested Claims\n\
+                       - [Pro] 远程办公提升效率 — Confidence: 65\n\
+                       - [Con] 协作成本被低估 — Confidence：40\n\
+                       - 没有数值的主张\n";
+        let confidence = parse_confidence(verdict);
+        assert_eq!(confidence.overall, Some(72));
+        assert_eq!(confidence.claims.len(), 2);
+        assert_eq!(confidence.claims[0].side.as_deref(), Some("pro"));
+        assert_eq!(confidence.claims[0].claim, "远程办公提升效率");
+        assert_eq!(confidence.claims[1].confidence, 40);
+        assert!(parse_confidence("Winner: Con\nConfidence: 180").is_empty());
+    }
+
+    #[test]
+    fn truncation_falls_back_to_char_boundary() {
+        let text = "短。这是一个非常非常长的句子没有任何标点";
+        assert_eq!(truncate_at_sentence(text, 10), "短。这是一个非常非常");
+    }
+}
@@ -0,0 +1,238 @@
+//! Classroom quizzes from finished debates.
+//!
+//! The judge's model writes multiple-choice comprehension questions on what
+//! each side argued and open critical-thinking questions on how well they
+//! argued it, each with an answer key. Quizzes are rendered as JSON or as
+//! printable Markdown with the answer key on its own page.
+
+use ai_lib_rust::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::debate::Turn;
+use crate::prompts;
+use crate::types::{ClientBackend, ClientInfo, DebatePhase, Position};
+
+/// Stored `debate_analysis` kind for quizzes.
+pub const ANALYSIS_KIND: &str = "quiz";
+
+const COMPREHENSION: &str = "comprehension";
+const CRITICAL_THINKING: &str = "critical_thinking";
+
+/// Questions requested of each kind.
+const COMPREHENSION_QUESTIONS: usize = 5;
+const CRITICAL_THINKING_QUESTIONS: usize = 3;
+
+/// Most options kept per multiple-choice question.
+const MAX_CHOICES: usize = 6;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Question {
+    /// `comprehension` (multiple choice) or `critical_thinking` (open).
+    pub kind: String,
+    pub question: String,
+    /// Answer options of a multiple-choice question.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<String>,
+    /// The correct choice, or a model answer for open questions.
+    pub answer: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub explanation: String,
+}
+
+/// Write a quiz on a finished debate.
+pub async fn generate(
+    client: &ClientInfo,
+    topic: &str,
+    transcript: &[Turn],
+) -> anyhow::Result<Vec<Question>> {
+    let live = match &client.backend {
+        ClientBackend::Live(live) => live,
+        ClientBackend::Mock => return Ok(mock_questions(topic, transcript)),
+    };
+    let rounds = transcript
+        .iter()
+        .map(|(side, phase, content, _)| {
+            format!("## {} · {}\n{}", side.label(), phase.title(), content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let messages = vec![
+        Message::system(format!(
+            "You write classroom quizzes about debates, in the language of the debate.\n\
+             Write {comprehension} comprehension questions that check what each side argued and what the judge decided, \
+             and {critical} critical-thinking questions that ask students to evaluate the strength of the arguments, \
+             spot weaknesses or propose counterarguments.\n\
+             Answer with a JSON array and nothing else. Each item is an object with:\n\
+             - \"kind\": \"{c}\" or \"{t}\"\n\
+             - \"question\": the question\n\
+             - \"choices\": for {c} questions, four answer options; omit for {t} questions\n\
+             - \"answer\": for {c} questions, the correct option copied exactly; for {t} questions, a model answer in two or three sentences\n\
+             - \"explanation\": one sentence pointing to where the transcript supports the answer",
+            comprehension = COMPREHENSION_QUESTIONS,
+            critical = CRITICAL_THINKING_QUESTIONS,
+            c = COMPREHENSION,
+            t = CRITICAL_THINKING,
+        )),
+        Message::user(format!("Topic: {}\n\n{}", topic, rounds)),
+    ];
+    let response = live
+        .chat()
+        .messages(messages)
+        .temperature(0.4)
+        .max_tokens(2500)
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("Quiz generation failed for {}: {}", client.name, e))?;
+    let questions = parse_questions(&response.content);
+    if questions.is_empty() {
+        anyhow::bail!("Quiz reply had no usable questions");
+    }
+    Ok(questions)
+}
+
+/// Well-formed questions from the model's reply. A multiple-choice answer
+/// given as a letter (`B`) is replaced by the option it names; one matching no
+/// option drops the question.
+fn parse_questions(reply: &str) -> Vec<Question> {
+    let json = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => return Vec::new(),
+    };
+    let questions: Vec<Question> = serde_json::from_str(json).unwrap_or_default();
+    questions
+        .into_iter()
+        .filter_map(|q| {
+            let kind = q.kind.trim().to_lowercase().replace([' ', '-'], "_");
+            let question = q.question.trim().to_string();
+            let mut answer = q.answer.trim().to_string();
+            let choices: Vec<String> = q
+                .choices
+                .iter()
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty())
+                .take(MAX_CHOICES)
+                .collect();
+            if question.is_empty() || answer.is_empty() {
+                return None;
+            }
+            match kind.as_str() {
+                COMPREHENSION => {
+                    if choices.len() < 2 {
+                        return None;
+                    }
+                    if !choices.contains(&answer) {
+                        let letter = answer.trim_end_matches(['.', ')']).to_ascii_uppercase();
+                        let index = match letter.as_bytes() {
+                            [b] if b.is_ascii_uppercase() => (b - b'A') as usize,
+                            _ => return None,
+                        };
+                        answer = choices.get(index)?.clone();
+                    }
+                }
+                CRITICAL_THINKING => {}
+                _ => return None,
+            }
+            let choices = if kind == CRITICAL_THINKING {
+                Vec::new()
+            } else {
+                choices
+            };
+            Some(Question {
+                kind,
+                question,
+                choices,
+                answer,
+                explanation: q.explanation.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// A fixed quiz for the mock provider.
+fn mock_questions(topic: &str, transcript: &[Turn]) -> Vec<Question> {
+    let winner = transcript
+        .iter()
+        .rev()
+        .find(|(side, ..)| *side == Position::Judge)
+        .and_then(|(_, _, verdict, _)| prompts::parse_winner(verdict));
+    let mut questions = vec![Question {
+        kind: COMPREHENSION.to_string(),
+        question: format!(
+            "Which side did the judge find more convincing on \"{}\"?",
+            topic
+        ),
+        choices: vec![
+            Position::Pro.label().to_string(),
+            Position::Con.label().to_string(),
+            "Neither".to_string(),
+        ],
+        answer: winner.map_or("Neither", |side| side.label()).to_string(),
+        explanation: String::new(),
+    }];
+    if transcript
+        .iter()
+        .any(|(_, phase, ..)| matches!(phase, DebatePhase::Rebuttal))
+    {
+        questions.push(Question {
+            kind: CRITICAL_THINKING.to_string(),
+            question: "Which rebuttal answered the opposing opening most directly, and why?"
+                .to_string(),
+            choices: Vec::new(),
+            answer: "Open answer.".to_string(),
+            explanation: String::new(),
+        });
+    }
+    questions
+}
+
+/// Printable Markdown: questions first, answer key on a separate page.
+pub fn to_markdown(topic: &str, questions: &[Question]) -> String {
+    let mut md = format!("# Quiz: {}\n\nName: ____________________\n", topic);
+    for (kind, heading) in [
+        (COMPREHENSION, "Comprehension"),
+        (CRITICAL_THINKING, "Critical thinking"),
+    ] {
+        let numbered: Vec<(usize, &Question)> = numbered(questions)
+            .filter(|(_, q)| q.kind == kind)
+            .collect();
+        if numbered.is_empty() {
+            continue;
+        }
+        md.push_str(&format!("\n## {}\n", heading));
+        for (n, q) in numbered {
+            md.push_str(&format!("\n{}. {}\n", n, q.question));
+            if q.choices.is_empty() {
+                md.push_str("\n   ______________________________________\n\n   ______________________________________\n");
+            }
+            for (i, choice) in q.choices.iter().enumerate() {
+                md.push_str(&format!("   - {}. {}\n", choice_letter(i), choice));
+            }
+        }
+    }
+    md.push_str("\n<div style=\"page-break-before: always\"></div>\n\n## Answer key\n");
+    for (n, q) in numbered(questions) {
+        let answer = match q.choices.iter().position(|c| *c == q.answer) {
+            Some(i) => format!("{}. {}", choice_letter(i), q.answer),
+            None => q.answer.clone(),
+        };
+        md.push_str(&format!("\n{}. {}\n", n, answer));
+        if !q.explanation.is_empty() {
+            md.push_str(&format!("   *{}*\n", q.explanation));
+        }
+    }
+    md
+}
+
+/// Questions numbered as printed: comprehension first, then critical thinking.
+fn numbered(questions: &[Question]) -> impl Iterator<Item = (usize, &Question)> {
+    questions
+        .iter()
+        .filter(|q| q.kind == COMPREHENSION)
+        .chain(questions.iter().filter(|q| q.kind == CRITICAL_THINKING))
+        .enumerate()
+        .map(|(i, q)| (i + 1, q))
+}
+
+fn choice_letter(index: usize) -> char {
+    (b'A' + index as u8) as char
+}
@@ -0,0 +1,153 @@
+//! PII redaction for persisted text.
+//!
+//! When enabled, transcripts, tool call records and audit topics are redacted
+//! inside the storage layer, so unredacted text is never written to the
+//! database. The live SSE stream is not affected.
+//!
+//! - `REDACT_PII=1` masks e-mail addresses and phone numbers.
+//! - `REDACT_PATTERNS` adds comma-separated regular expressions (e.g. account
+//!   or ID number formats), each replaced with `[REDACTED]`.
+
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use tracing::{info, warn};
+
+const EMAIL: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}";
+
+/// International (`+44 20 7946 0958`), parenthesized area code, US-style
+/// `555-123-4567`, and mainland China mobile numbers. Plain digit runs such as
+/// years or ranges (`2020-2024`) are left alone.
+const PHONE: &str = r"\+\d{1,3}[\s.-]?\d[\d\s.-]{6,}\d|\(\d{2,4}\)\s?\d{3,4}[\s.-]?\d{4}|\b\d{3}[.-]\d{3}[.-]\d{4}\b|\b1[3-9]\d{9}\b";
+
+pub struct Redactor {
+    rules: Vec<(Regex, &'static str)>,
+}
+
+impl Redactor {
+    fn from_env() -> Option<Self> {
+        let pii = std::env::var("REDACT_PII")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        Self::new(pii, &std::env::var("REDACT_PATTERNS").unwrap_or_default())
+    }
+
+    /// Rules for `pii` plus the comma-separated `patterns`; `None` when there
+    /// are none.
+    fn new(pii: bool, patterns: &str) -> Option<Self> {
+        let mut rules = Vec::new();
+        if pii {
+            rules.push((
+                Regex::new(EMAIL).expect("valid email regex"),
+                "[REDACTED_EMAIL]",
+            ));
+            rules.push((
+                Regex::new(PHONE).expect("valid phone regex"),
+                "[REDACTED_PHONE]",
+            ));
+        }
+        for pattern in patterns.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match Regex::new(pattern) {
+                Ok(re) => rules.push((re, "[REDACTED]")),
+                Err(e) => warn!(
+                    "Ignoring invalid REDACT_PATTERNS entry '{}': {}",
+                    pattern, e
+                ),
+            }
+        }
+        if rules.is_empty() {
+            return None;
+        }
+        info!(
+            "Redaction enabled for stored transcripts ({} rules)",
+            rules.len()
+        );
+        Some(Self { rules })
+    }
+
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut out = Cow::Borrowed(text);
+        for (re, replacement) in &self.rules {
+            let replaced = match re.replace_all(&out, *replacement) {
+                Cow::Owned(replaced) => Some(replaced),
+                Cow::Borrowed(_) => None,
+            };
+            if let Some(replaced) = replaced {
+                out = Cow::Owned(replaced);
+            }
+        }
+        out
+    }
+}
+
+fn redactor() -> Option<&'static Redactor> {
+    static REDACTOR: OnceLock<Option<Redactor>> = OnceLock::new();
+    REDACTOR.get_or_init(Redactor::from_env).as_ref()
+}
+
+/// Redact `text` if redaction is configured; otherwise return it unchanged.
+pub fn redact(text: &str) -> Cow<'_, str> {
+    match redactor() {
+        Some(r) => r.redact(text),
+        None => Cow::Borrowed(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pii() -> Redactor {
+        Redactor::new(true, "").unwrap()
+    }
+
+    #[test]
+    fn nothing_configured_means_no_redactor() {
+        assert!(Redactor::new(false, "").is_none());
+        assert!(Redactor::new(false, " , ").is_none());
+    }
+
+    #[test]
+    fn emails_are_masked() {
+        assert_eq!(
+            pii().redact("Write to jane.doe+debate@example.co.uk today."),
+            "Write to [REDACTED_EMAIL] today."
+        );
+    }
+
+    #[test]
+    fn phone_formats_are_masked() {
+        let redactor = pii();
+        for phone in [
+            "+44 20 7946 0958",
+            "(212) 555-1234",
+            "555-123-4567",
+            "555.123.4567",
+            "13812345678",
+        ] {
+            assert_eq!(
+                redactor.redact(&format!("Call {} now", phone)),
+                "Call [REDACTED_PHONE] now",
+                "{}",
+                phone
+            );
+        }
+    }
+
+    #[test]
+    fn years_and_ranges_are_kept() {
+        let text = "GDP grew 3.2% from 2020-2024, and 1,000,000 jobs were added in 2023.";
+        assert!(matches!(pii().redact(text), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn custom_patterns_are_masked_and_invalid_ones_skipped() {
+        let redactor = Redactor::new(false, r"ACCT-\d{6}, (unclosed").unwrap();
+        assert_eq!(redactor.rules.len(), 1);
+        assert_eq!(
+            redactor.redact("Account ACCT-123456 is closed"),
+            "Account [REDACTED] is closed"
+        );
+    }
+}
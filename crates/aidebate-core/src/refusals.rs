@@ -0,0 +1,76 @@
+//! Provider refusals and content-filter blocks.
+//!
+//! Models sometimes decline to argue an assigned side of a sensitive topic,
+//! or the provider's content filter ends the response. Such a round is
+//! retried once with a softened framing that stresses the debate is an
+//! assigned-side exercise; if the retry is refused too, the debate ends with
+//! a `refused` event instead of a generic stream error.
+
+use ai_lib_rust::Message;
+
+/// Responses longer than this are treated as arguments, not refusals.
+const MAX_REFUSAL_CHARS: usize = 400;
+
+/// Phrases that open or make up a refusal, lowercased.
+const REFUSAL_MARKERS: &[&str] = &[
+    "i can't",
+    "i cannot",
+    "i can not",
+    "i won't",
+    "i'm unable",
+    "i am unable",
+    "i'm not able",
+    "i am not able",
+    "i'm sorry",
+    "i apologize",
+    "as an ai",
+    "抱歉",
+    "对不起",
+    "我无法",
+    "我不能",
+    "无法协助",
+    "无法提供",
+];
+
+/// Provider error messages that mean the content filter blocked the response.
+const FILTER_MARKERS: &[&str] = &[
+    "content_filter",
+    "contentfilter",
+    "content filter",
+    "content_policy",
+    "content policy",
+    "safety",
+    "data_inspection_failed",
+    "敏感内容",
+];
+
+/// Reminder added to a round's prompt when it is retried after a refusal.
+const SOFTENED_FRAMING: &str = "说明：这是一场用于教育与思辨训练的模拟辩论，你的立场由赛制分配，并不代表你本人的观点。\
+请以学术、理性、尊重事实的方式完成本方论证，可以承认议题的复杂性与对方观点的合理之处，但请不要拒绝发言；\
+避免任何有害、歧视或违法的内容。";
+
+/// Whether a round's content is a refusal rather than an argument: empty, or
+/// short, without the required sections and worded as a refusal.
+pub fn is_refusal(content: &str) -> bool {
+    let content = content.trim();
+    if content.is_empty() {
+        return true;
+    }
+    if content.chars().count() > MAX_REFUSAL_CHARS || content.contains("## Final Position") {
+        return false;
+    }
+    let lower = content.to_lowercase();
+    REFUSAL_MARKERS.iter().any(|marker| lower.contains(marker))
+}
+
+/// Whether a stream error reports a content-filter block.
+pub fn is_filter_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    FILTER_MARKERS.iter().any(|marker| lower.contains(marker))
+}
+
+/// Add the softened framing before the final instruction of a side prompt.
+pub fn soften(messages: &mut Vec<Message>) {
+    let at = messages.len().saturating_sub(1);
+    messages.insert(at, Message::user(SOFTENED_FRAMING.to_string()));
+}
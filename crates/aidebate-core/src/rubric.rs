@@ -0,0 +1,337 @@
+//! Weighted judging rubrics.
+//!
+//! A rubric lists the criteria a debate is judged on, each with a weight
+//! (evidence 40%, logic 30%, ...). With a rubric the judge scores both sides
+//! from 0 to 10 on every criterion in a `## Scores` section, and the winner
+//! is the side with the higher weighted total: it is computed here from the
+//! parsed scores and appended to the verdict as its last `Winner:` line, so
+//! it takes precedence over the judge's own wherever verdicts are read. A tie
+//! is appended as `Winner: Tie`, which names no winner; a verdict missing
+//! some score leaves the judge's `Winner:` line in charge.
+//!
+//! Operators set a default rubric with `JUDGE_RUBRIC`
+//! (`evidence:40,logic:30,rhetoric:20,responsiveness:10`), used by debates
+//! without a preset; a debate request can bring its own as `rubric`.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::types::Position;
+
+/// Stored `debate_analysis` kind for the weighted scores of a verdict.
+pub const SCORES_ANALYSIS_KIND: &str = "scores";
+
+const MAX_CRITERIA: usize = 8;
+const MAX_NAME_CHARS: usize = 40;
+const MAX_SCORE: f64 = 10.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Criterion {
+    pub name: String,
+    /// Relative weight; weights are scaled to sum to 1.
+    pub weight: f64,
+}
+
+/// Criteria with weights summing to 1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Rubric {
+    criteria: Vec<Criterion>,
+}
+
+/// A side's scores on one criterion.
+#[derive(Debug, Clone, Serialize)]
+pub struct CriterionScores {
+    pub name: String,
+    pub weight: f64,
+    pub pro: f64,
+    pub con: f64,
+}
+
+/// The scores of a verdict with their weighted totals, out of 10.
+#[derive(Debug, Clone, Serialize)]
+pub struct WeightedScores {
+    pub criteria: Vec<CriterionScores>,
+    pub pro: f64,
+    pub con: f64,
+    /// `pro` or `con`; none on a tie.
+    pub winner: Option<String>,
+}
+
+impl Rubric {
+    /// 1-8 uniquely named criteria with positive weights, in any unit.
+    pub fn new(criteria: Vec<Criterion>) -> Result<Self, String> {
+        if criteria.is_empty() || criteria.len() > MAX_CRITERIA {
+            return Err(format!("a rubric has 1-{} criteria", MAX_CRITERIA));
+        }
+        let valid_char = |c: char| c.is_alphanumeric() || " _-".contains(c);
+        for (i, criterion) in criteria.iter().enumerate() {
+            let name = criterion.name.trim();
+            if name.is_empty()
+                || name.chars().count() > MAX_NAME_CHARS
+                || !name.chars().all(valid_char)
+            {
+                return Err(format!(
+                    "invalid criterion {:?}: expected 1-{} letters, digits, spaces, _ or -",
+                    criterion.name, MAX_NAME_CHARS
+                ));
+            }
+            if !(criterion.weight.is_finite() && criterion.weight > 0.0) {
+                return Err(format!("criterion {:?} needs a positive weight", name));
+            }
+            if criteria[..i]
+                .iter()
+                .any(|c| c.name.trim().eq_ignore_ascii_case(name))
+            {
+                return Err(format!("criterion {:?} is listed twice", name));
+            }
+        }
+        let total: f64 = criteria.iter().map(|c| c.weight).sum();
+        let criteria = criteria
+            .into_iter()
+            .map(|c| Criterion {
+                name: c.name.trim().to_string(),
+                weight: c.weight / total,
+            })
+            .collect();
+        Ok(Self { criteria })
+    }
+
+    /// Parse `name:weight` pairs separated by commas.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let criteria = spec
+            .split(',')
+            .filter(|item| !item.trim().is_empty())
+            .map(|item| {
+                let (name, weight) = item
+                    .split_once(':')
+                    .ok_or_else(|| format!("expected name:weight, got {:?}", item.trim()))?;
+                let weight = weight
+                    .trim()
+                    .trim_end_matches('%')
+                    .parse()
+                    .map_err(|_| format!("invalid weight in {:?}", item.trim()))?;
+                Ok(Criterion {
+                    name: name.to_string(),
+                    weight,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Self::new(criteria)
+    }
+
+    /// The criteria as in `JUDGE_RUBRIC`, with percentages.
+    pub fn tag(&self) -> String {
+        self.criteria
+            .iter()
+            .map(|c| format!("{}:{}", c.name, percent(c.weight)))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// The `{rubric}` instruction of the judge template.
+    pub fn instruction(&self) -> String {
+        let criteria: Vec<String> = self
+            .criteria
+            .iter()
+            .map(|c| format!("{}（{}%）", c.name, percent(c.weight)))
+            .collect();
+        format!(
+            "- 按以下加权评分标准评判：{}。在 `## Verdict` 之前用 `## Scores` 逐项为双方打 0-10 分，每行格式为 `- <标准>: Pro <分数>, Con <分数>`，标准名称保持原样；胜方由系统按加权总分计算，你的 `Winner:` 应与评分一致。\n",
+            criteria.join("；")
+        )
+    }
+
+    /// Scores of a verdict and their weighted totals; `None` unless every
+    /// criterion is scored for both sides. A criterion scored twice keeps its
+    /// last scores.
+    pub fn score(&self, verdict: &str) -> Option<WeightedScores> {
+        let mut scores: Vec<Option<(f64, f64)>> = vec![None; self.criteria.len()];
+        for line in verdict.lines() {
+            let line = line
+                .replace(['*', '`'], "")
+                .trim()
+                .trim_start_matches(['-', '+'])
+                .trim()
+                .to_lowercase();
+            let Some((name, rest)) = line.split_once([':', '：']) else {
+                continue;
+            };
+            let Some(i) = self
+                .criteria
+                .iter()
+                .position(|c| c.name.to_lowercase() == name.trim())
+            else {
+                continue;
+            };
+            if let (Some(pro), Some(con)) = (score_after(rest, "pro"), score_after(rest, "con")) {
+                scores[i] = Some((pro, con));
+            }
+        }
+        let criteria: Vec<CriterionScores> = self
+            .criteria
+            .iter()
+            .zip(scores)
+            .map(|(c, scores)| {
+                let (pro, con) = scores?;
+                Some(CriterionScores {
+                    name: c.name.clone(),
+                    weight: c.weight,
+                    pro,
+                    con,
+                })
+            })
+            .collect::<Option<_>>()?;
+        let total = |side: fn(&CriterionScores) -> f64| {
+            let total: f64 = criteria.iter().map(|c| c.weight * side(c)).sum();
+            (total * 100.0).round() / 100.0
+        };
+        let (pro, con) = (total(|c| c.pro), total(|c| c.con));
+        let winner = if pro > con {
+            Some(Position::Pro)
+        } else if con > pro {
+            Some(Position::Con)
+        } else {
+            None
+        };
+        Some(WeightedScores {
+            criteria,
+            pro,
+            con,
+            winner: winner.map(|side| side.role_str().to_string()),
+        })
+    }
+}
+
+impl WeightedScores {
+    /// Lines appended to the verdict: the totals and the `Winner:` line.
+    pub fn verdict_lines(&self) -> String {
+        let mut lines = format!(
+            "\n\nWeighted score: Pro {:.2} · Con {:.2}\n",
+            self.pro, self.con
+        );
+        match self.winner.as_deref() {
+            Some("pro") => lines.push_str("Winner: Pro\n"),
+            Some("con") => lines.push_str("Winner: Con\n"),
+            _ => lines.push_str("Winner: Tie\n"),
+        }
+        lines
+    }
+}
+
+/// The operator's default rubric (`JUDGE_RUBRIC`); ignored with a warning
+/// when invalid.
+pub fn configured() -> Option<&'static Rubric> {
+    static RUBRIC: OnceLock<Option<Rubric>> = OnceLock::new();
+    RUBRIC
+        .get_or_init(|| {
+            let spec = std::env::var("JUDGE_RUBRIC").ok()?;
+            match Rubric::parse(&spec) {
+                Ok(rubric) => {
+                    info!("Judging rubric: {}", rubric.tag());
+                    Some(rubric)
+                }
+                Err(e) => {
+                    warn!("Ignoring JUDGE_RUBRIC: {}", e);
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+fn percent(weight: f64) -> String {
+    let percent = (weight * 1000.0).round() / 10.0;
+    format!("{}", percent)
+}
+
+/// The 0-10 score following `label` in `text`, as in `pro 8` or `con: 6.5`.
+fn score_after(text: &str, label: &str) -> Option<f64> {
+    let rest = &text[text.find(label)? + label.len()..];
+    let rest = rest.trim_start_matches(|c: char| c.is_whitespace() || ":：=".contains(c));
+    let number: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|score| (0.0..=MAX_SCORE).contains(score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompts;
+
+    fn rubric() -> Rubric {
+        Rubric::parse("evidence:60, logic:40").unwrap()
+    }
+
+    #[test]
+    fn parse_scales_weights() {
+        let rubric = Rubric::parse("Evidence:40%,logic:30,rhetoric:20,responsiveness:10,").unwrap();
+        assert_eq!(
+            rubric.tag(),
+            "Evidence:40,logic:30,rhetoric:20,responsiveness:10"
+        );
+        assert_eq!(Rubric::parse("a:1,b:2").unwrap().tag(), "a:33.3,b:66.7");
+    }
+
+    #[test]
+    fn parse_rejects_invalid_rubrics() {
+        for spec in [
+            "",
+            "evidence",
+            "evidence:x",
+            "evidence:0",
+            "evidence:-5",
+            "evidence:40,Evidence:60",
+            "<b>:10",
+            "a:1,b:1,c:1,d:1,e:1,f:1,g:1,h:1,i:1",
+        ] {
+            assert!(Rubric::parse(spec).is_err(), "{:?}", spec);
+        }
+    }
+
+    #[test]
+    fn score_reads_every_criterion() {
+        let verdict = "## Scores\n- **Evidence**: Pro 8, Con 6\n- logic：pro 5.5, con: 9\n\n## Verdict\nWinner: Con\n";
+        let scores = rubric().score(verdict).unwrap();
+        assert_eq!(scores.criteria.len(), 2);
+        assert_eq!((scores.criteria[1].pro, scores.criteria[1].con), (5.5, 9.0));
+        assert_eq!((scores.pro, scores.con), (7.0, 7.2));
+        assert_eq!(scores.winner.as_deref(), Some("con"));
+        assert!(rubric().score("- evidence: Pro 8, Con 6\n").is_none());
+        assert!(rubric()
+            .score("- evidence: Pro 8, Con 6\n- logic: Pro 11, Con 6\n")
+            .is_none());
+    }
+
+    #[test]
+    fn weighted_winner_overrules_the_judge() {
+        // Con wins more criteria, but Pro wins the heavier one by more
+        let rubric = Rubric::parse("evidence:70,logic:20,rhetoric:10").unwrap();
+        let verdict = "- evidence: Pro 9, Con 5\n- logic: Pro 6, Con 7\n- rhetoric: Pro 6, Con 8\nWinner: Con\n";
+        let scores = rubric.score(verdict).unwrap();
+        assert_eq!(scores.winner.as_deref(), Some("pro"));
+        let judged = format!("{}{}", verdict, scores.verdict_lines());
+        assert_eq!(prompts::parse_winner(&judged), Some(Position::Pro));
+    }
+
+    #[test]
+    fn weighted_tie_names_no_winner() {
+        let verdict = "- evidence: Pro 8, Con 6\n- logic: Pro 4, Con 7\nWinner: Pro\n";
+        let scores = rubric().score(verdict).unwrap();
+        assert_eq!((scores.pro, scores.con), (6.4, 6.4));
+        assert_eq!(scores.winner, None);
+        let lines = scores.verdict_lines();
+        assert!(lines.ends_with("Winner: Tie\n"), "{:?}", lines);
+        assert_eq!(
+            prompts::parse_winner(&format!("{}{}", verdict, lines)),
+            None
+        );
+    }
+}
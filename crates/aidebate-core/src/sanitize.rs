@@ -0,0 +1,216 @@
+//! Raw HTML removal from model output.
+//!
+//! Model output is Markdown, which renderers usually pass raw HTML through.
+//! With `SANITIZE_MARKDOWN=1` the debate engine strips HTML tags, comments
+//! and the content of `<script>` and `<style>` elements from every round as
+//! it streams, so both the live stream and the stored transcript are free of
+//! it and embedders can render the Markdown as HTML safely. Fenced code
+//! blocks are left alone, and a `<` that does not start a tag is kept as
+//! text. Inline code spans are not: a stream cannot tell whether a backtick
+//! will be closed, and an unclosed one leaves the HTML after it raw.
+
+use std::sync::OnceLock;
+
+use tracing::info;
+
+/// Longest tag kept pending before the `<` is given up on as text.
+const MAX_TAG_CHARS: usize = 512;
+
+/// Elements whose content is dropped together with their tags.
+const DROPPED_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Whether model output is sanitized (`SANITIZE_MARKDOWN`, off by default).
+pub fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        let enabled = std::env::var("SANITIZE_MARKDOWN")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if enabled {
+            info!("Raw HTML is stripped from model output");
+        }
+        enabled
+    })
+}
+
+/// Strips HTML from text that arrives in pieces. A tag split across pieces is
+/// held back until it is complete.
+#[derive(Default)]
+pub struct Sanitizer {
+    /// A tag being read, starting with its `<`.
+    tag: String,
+    /// Quote character open inside the tag being read.
+    quote: Option<char>,
+    /// Element whose content is being dropped, until its closing tag.
+    dropping: Option<&'static str>,
+    /// Length of the backtick run being read.
+    ticks: usize,
+    /// Whether that run opened its line.
+    ticks_open_line: bool,
+    /// Whether anything but spaces has been read on the current line.
+    mid_line: bool,
+    /// Length of the backtick fence that opened the current code block.
+    fence: Option<usize>,
+}
+
+impl Sanitizer {
+    /// Sanitize the next piece of text.
+    pub fn push(&mut self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            if !self.tag.is_empty() {
+                self.push_tag_char(c, &mut out);
+                continue;
+            }
+            if c == '`' {
+                if self.ticks == 0 {
+                    self.ticks_open_line = !self.mid_line;
+                }
+                self.ticks += 1;
+            } else {
+                self.end_tick_run();
+                if c == '<' && self.fence.is_none() {
+                    self.tag.push(c);
+                    continue;
+                }
+            }
+            self.mid_line = c != '\n' && (self.mid_line || c != ' ');
+            if self.dropping.is_none() {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    /// What is still held back once the text has ended.
+    pub fn finish(&mut self) -> String {
+        let mut out = String::new();
+        self.give_up_tag(&mut out);
+        out
+    }
+
+    fn end_tick_run(&mut self) {
+        if self.ticks >= 3 && self.ticks_open_line {
+            self.fence = match self.fence {
+                None => Some(self.ticks),
+                Some(opened) if self.ticks >= opened => None,
+                open => open,
+            };
+        }
+        self.ticks = 0;
+    }
+
+    fn push_tag_char(&mut self, c: char, out: &mut String) {
+        // Only `<` followed by a letter, `/`, `!` or `?` can start a tag
+        if self.tag.len() == 1 && !(c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?')) {
+            self.tag.clear();
+            if self.dropping.is_none() {
+                out.push('<');
+            }
+            let rest = self.push(c.encode_utf8(&mut [0; 4]));
+            out.push_str(&rest);
+            return;
+        }
+        self.tag.push(c);
+        match (self.quote, c) {
+            (Some(quote), c) if c == quote => self.quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => self.quote = Some(c),
+            (None, '>') => {
+                let tag = std::mem::take(&mut self.tag);
+                self.end_tag(&tag);
+                return;
+            }
+            (None, _) => {}
+        }
+        if self.tag.chars().count() > MAX_TAG_CHARS {
+            self.give_up_tag(out);
+        }
+    }
+
+    /// A complete tag was dropped; track the elements whose content goes too.
+    fn end_tag(&mut self, tag: &str) {
+        let name: String = tag
+            .trim_start_matches('<')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '/')
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match self.dropping {
+            Some(element) if name.strip_prefix('/') == Some(element) => self.dropping = None,
+            Some(_) => {}
+            None => {
+                self.dropping = DROPPED_ELEMENTS
+                    .iter()
+                    .find(|element| **element == name && !tag.ends_with("/>"))
+                    .copied();
+            }
+        }
+    }
+
+    /// Emit a pending tag that never closed as text, its `<` escaped so it
+    /// cannot become a tag with later text.
+    fn give_up_tag(&mut self, out: &mut String) {
+        let tag = std::mem::take(&mut self.tag);
+        self.quote = None;
+        if tag.is_empty() || self.dropping.is_some() {
+            return;
+        }
+        out.push_str("&lt;");
+        out.push_str(&self.push(&tag[1..]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sanitize(pieces: &[&str]) -> String {
+        let mut sanitizer = Sanitizer::default();
+        let mut out: String = pieces.iter().map(|piece| sanitizer.push(piece)).collect();
+        out.push_str(&sanitizer.finish());
+        out
+    }
+
+    #[test]
+    fn strips_tags_split_across_pieces() {
+        assert_eq!(sanitize(&["Hello <b", "r/>world"]), "Hello world");
+        assert_eq!(sanitize(&["<", "a href=\"x>y\">link</a", ">"]), "link");
+        assert_eq!(sanitize(&["a<!-- note -", "->b"]), "ab");
+    }
+
+    #[test]
+    fn drops_script_content_across_pieces() {
+        assert_eq!(sanitize(&["a<script>alert(", "1)</scr", "ipt>b"]), "ab");
+        assert_eq!(sanitize(&["<STYLE>p { color: red }</style>", "c"]), "c");
+        assert_eq!(sanitize(&["<script/>d"]), "d");
+    }
+
+    #[test]
+    fn leaves_fenced_code_alone() {
+        let text = "```html\n<b>x</b>\n```\n<i>y</i>";
+        assert_eq!(sanitize(&[text]), "```html\n<b>x</b>\n```\ny");
+        assert_eq!(
+            sanitize(&["``", "`\n<b>", "x</b>\n``", "`\n<i>y</i>"]),
+            "```\n<b>x</b>\n```\ny"
+        );
+        // Inline backticks do not open a block
+        assert_eq!(sanitize(&["a ```<b>x</b>"]), "a ```x");
+    }
+
+    #[test]
+    fn keeps_a_literal_less_than() {
+        assert_eq!(
+            sanitize(&["a < b and 1<", "2, c <= d"]),
+            "a < b and 1<2, c <= d"
+        );
+    }
+
+    #[test]
+    fn escapes_a_tag_that_never_closes() {
+        assert_eq!(sanitize(&["x <b", " y"]), "x &lt;b y");
+        let long = format!("<a{}", "b".repeat(MAX_TAG_CHARS));
+        let out = sanitize(&[&long, "<i>c</i>"]);
+        assert_eq!(out, format!("&lt;{}c", &long[1..]));
+    }
+}
@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+
+use crate::config;
+
+use super::{SearchHit, SearchOptions, SearchProvider, SearchResponse};
+
+/// Bing Web Search API v7.
+pub struct BingSearch {
+    api_key: String,
+}
+
+impl BingSearch {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for BingSearch {
+    fn name(&self) -> &'static str {
+        "bing"
+    }
+
+    async fn search(&self, query: &str, opts: &SearchOptions) -> anyhow::Result<SearchResponse> {
+        let count = opts.max_results.to_string();
+        let resp = config::http_client()
+            .get("https://api.bing.microsoft.com/v7.0/search")
+            .header("Ocp-Apim-Subscription-Key", &self.api_key)
+            .query(&[("q", query), ("count", count.as_str())])
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Search request failed: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| anyhow::anyhow!("Search response parse failed: {}", e))?;
+
+        let hits = resp["webPages"]["value"]
+            .as_array()
+            .map(|results| {
+                results
+                    .iter()
+                    .take(opts.max_results)
+                    .map(|r| SearchHit {
+                        title: r["name"].as_str().unwrap_or("").to_string(),
+                        url: r["url"].as_str().unwrap_or("").to_string(),
+                        snippet: r["snippet"].as_str().unwrap_or("").to_string(),
+                        published: None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(SearchResponse { answer: None, hits })
+    }
+}
@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+
+use crate::config;
+
+use super::{DateRange, SearchHit, SearchOptions, SearchProvider, SearchResponse};
+
+/// Brave Search API (https://brave.com/search/api/).
+pub struct BraveSearch {
+    api_key: String,
+}
+
+impl BraveSearch {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for BraveSearch {
+    fn name(&self) -> &'static str {
+        "brave"
+    }
+
+    async fn search(&self, query: &str, opts: &SearchOptions) -> anyhow::Result<SearchResponse> {
+        let count = opts.max_results.to_string();
+        let resp = config::http_client()
+            .get("https://api.search.brave.com/res/v1/web/search")
+            .header("X-Subscription-Token", &self.api_key)
+            .header("Accept", "application/json")
+            .query(&[("q", query), ("count", count.as_str())])
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Search request failed: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| anyhow::anyhow!("Search response parse failed: {}", e))?;
+
+        let hits = resp["web"]["results"]
+            .as_array()
+            .map(|results| {
+                results
+                    .iter()
+                    .take(opts.max_results)
+                    .map(|r| SearchHit {
+                        title: r["title"].as_str().unwrap_or("").to_string(),
+                        url: r["url"].as_str().unwrap_or("").to_string(),
+                        snippet: r["description"].as_str().unwrap_or("").to_string(),
+                        published: None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(SearchResponse { answer: None, hits })
+    }
+
+    fn supports_news(&self) -> bool {
+        true
+    }
+
+    async fn search_news(
+        &self,
+        query: &str,
+        opts: &SearchOptions,
+        range: &DateRange,
+    ) -> anyhow::Result<SearchResponse> {
+        let count = opts.max_results.to_string();
+        let freshness = brave_freshness(range);
+        let mut params = vec![("q", query), ("count", count.as_str())];
+        if let Some(freshness) = &freshness {
+            params.push(("freshness", freshness.as_str()));
+        }
+
+        let resp = config::http_client()
+            .get("https://api.search.brave.com/res/v1/news/search")
+            .header("X-Subscription-Token", &self.api_key)
+            .header("Accept", "application/json")
+            .query(&params)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("News search request failed: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| anyhow::anyhow!("News search response parse failed: {}", e))?;
+
+        let hits = resp["results"]
+            .as_array()
+            .map(|results| {
+                results
+                    .iter()
+                    .take(opts.max_results)
+                    .map(|r| SearchHit {
+                        title: r["title"].as_str().unwrap_or("").to_string(),
+                        url: r["url"].as_str().unwrap_or("").to_string(),
+                        snippet: r["description"].as_str().unwrap_or("").to_string(),
+                        published: r["page_age"]
+                            .as_str()
+                            .or_else(|| r["age"].as_str())
+                            .map(String::from),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(SearchResponse { answer: None, hits })
+    }
+}
+
+/// Map a date range to Brave's `freshness` parameter.
+fn brave_freshness(range: &DateRange) -> Option<String> {
+    if range.start_date.is_some() || range.end_date.is_some() {
+        return Some(format!(
+            "{}to{}",
+            range.start_date.as_deref().unwrap_or("1970-01-01"),
+            range.end_date.as_deref().unwrap_or("2100-01-01")
+        ));
+    }
+    range.days.map(|days| {
+        match days {
+            1 => "pd",
+            2..=7 => "pw",
+            8..=31 => "pm",
+            _ => "py",
+        }
+        .to_string()
+    })
+}
@@ -0,0 +1,155 @@
+//! Operator-configured domain filtering for search evidence.
+//!
+//! `SEARCH_BLOCKED_DOMAINS` drops hits from low-credibility sites;
+//! `SEARCH_PREFERRED_DOMAINS` moves hits from trusted sites to the front.
+//! Both are comma-separated and also match subdomains (`who.int` matches
+//! `www.who.int`).
+
+use tracing::info;
+
+use super::SearchHit;
+
+/// Blocked and preferred domains read from the environment.
+#[derive(Debug, Clone, Default)]
+pub struct DomainPolicy {
+    pub blocked: Vec<String>,
+    pub preferred: Vec<String>,
+}
+
+impl DomainPolicy {
+    pub fn from_env() -> Self {
+        Self {
+            blocked: domain_list("SEARCH_BLOCKED_DOMAINS"),
+            preferred: domain_list("SEARCH_PREFERRED_DOMAINS"),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocked.is_empty() && self.preferred.is_empty()
+    }
+
+    /// Drop blocked hits and put preferred ones first, keeping relative order.
+    pub fn apply(&self, hits: Vec<SearchHit>) -> Vec<SearchHit> {
+        if self.is_empty() {
+            return hits;
+        }
+        let (kept, filtered): (Vec<_>, Vec<_>) = hits
+            .into_iter()
+            .partition(|hit| !matches_any(&hit.url, &self.blocked));
+        for hit in &filtered {
+            info!("Filtered search result from blocked domain: {}", hit.url);
+        }
+        let (mut preferred, rest): (Vec<_>, Vec<_>) = kept
+            .into_iter()
+            .partition(|hit| matches_any(&hit.url, &self.preferred));
+        preferred.extend(rest);
+        preferred
+    }
+}
+
+fn domain_list(key: &str) -> Vec<String> {
+    parse_domains(&std::env::var(key).unwrap_or_default())
+}
+
+fn parse_domains(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|d| d.trim().trim_start_matches("*.").to_lowercase())
+        .filter(|d| !d.is_empty())
+        .collect()
+}
+
+/// Host part of a URL, lowercased, without scheme, port or path.
+fn host(url: &str) -> String {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let authority = authority.rsplit('@').next().unwrap_or(authority);
+    authority.split(':').next().unwrap_or("").to_lowercase()
+}
+
+fn matches_any(url: &str, domains: &[String]) -> bool {
+    let host = host(url);
+    domains
+        .iter()
+        .any(|d| host == *d || host.ends_with(&format!(".{}", d)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(url: &str) -> SearchHit {
+        SearchHit {
+            title: String::new(),
+            url: url.to_string(),
+            snippet: String::new(),
+            published: None,
+        }
+    }
+
+    fn urls(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.url.as_str()).collect()
+    }
+
+    #[test]
+    fn domain_lists_are_normalized() {
+        assert_eq!(
+            parse_domains(" WHO.int, *.example.com ,,"),
+            vec!["who.int", "example.com"]
+        );
+        assert!(parse_domains("").is_empty());
+    }
+
+    #[test]
+    fn host_ignores_scheme_credentials_port_and_path() {
+        assert_eq!(
+            host("https://user:pw@News.Example.com:8443/a?b#c"),
+            "news.example.com"
+        );
+        assert_eq!(host("example.org/path"), "example.org");
+    }
+
+    #[test]
+    fn subdomains_match_but_lookalikes_do_not() {
+        let domains = parse_domains("who.int");
+        assert!(matches_any("https://who.int/", &domains));
+        assert!(matches_any("https://www.who.int/news", &domains));
+        assert!(!matches_any("https://notwho.int/", &domains));
+        assert!(!matches_any("https://who.int.evil.com/", &domains));
+    }
+
+    #[test]
+    fn blocked_hits_are_dropped_and_preferred_moved_first() {
+        let policy = DomainPolicy {
+            blocked: parse_domains("spam.com"),
+            preferred: parse_domains("who.int,nature.com"),
+        };
+        let hits = vec![
+            hit("https://blog.example.com/1"),
+            hit("https://www.spam.com/2"),
+            hit("https://www.nature.com/3"),
+            hit("https://example.org/4"),
+            hit("https://who.int/5"),
+        ];
+        assert_eq!(
+            urls(&policy.apply(hits)),
+            vec![
+                "https://www.nature.com/3",
+                "https://who.int/5",
+                "https://blog.example.com/1",
+                "https://example.org/4",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_policy_keeps_hits_unchanged() {
+        let policy = DomainPolicy::default();
+        assert!(policy.is_empty());
+        let hits = vec![hit("https://b.com"), hit("https://a.com")];
+        assert_eq!(
+            urls(&policy.apply(hits)),
+            vec!["https://b.com", "https://a.com"]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+
+use crate::config;
+
+use super::{SearchHit, SearchOptions, SearchProvider, SearchResponse};
+
+/// DuckDuckGo Instant Answer API (no API key required, limited coverage).
+pub struct DuckDuckGoSearch;
+
+#[async_trait]
+impl SearchProvider for DuckDuckGoSearch {
+    fn name(&self) -> &'static str {
+        "duckduckgo"
+    }
+
+    async fn search(&self, query: &str, opts: &SearchOptions) -> anyhow::Result<SearchResponse> {
+        let resp = config::http_client()
+            .get("https://api.duckduckgo.com/")
+            .query(&[
+                ("q", query),
+                ("format", "json"),
+                ("no_html", "1"),
+                ("skip_disambig", "1"),
+            ])
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Search request failed: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| anyhow::anyhow!("Search response parse failed: {}", e))?;
+
+        let answer = resp["AbstractText"]
+            .as_str()
+            .filter(|a| !a.is_empty())
+            .map(String::from);
+
+        let mut hits = Vec::new();
+        if let (Some(text), Some(url)) =
+            (resp["AbstractText"].as_str(), resp["AbstractURL"].as_str())
+        {
+            if !text.is_empty() && !url.is_empty() {
+                hits.push(SearchHit {
+                    title: resp["Heading"].as_str().unwrap_or("").to_string(),
+                    url: url.to_string(),
+                    snippet: text.to_string(),
+                    published: None,
+                });
+            }
+        }
+        if let Some(topics) = resp["RelatedTopics"].as_array() {
+            for t in topics {
+                if hits.len() >= opts.max_results {
+                    break;
+                }
+                // Topic groups nest their entries under "Topics"; only flat entries are used
+                let (Some(text), Some(url)) = (t["Text"].as_str(), t["FirstURL"].as_str()) else {
+                    continue;
+                };
+                hits.push(SearchHit {
+                    title: text.split(" - ").next().unwrap_or(text).to_string(),
+                    url: url.to_string(),
+                    snippet: text.to_string(),
+                    published: None,
+                });
+            }
+        }
+
+        Ok(SearchResponse { answer, hits })
+    }
+}
@@ -0,0 +1,232 @@
+//! Pluggable web search backends.
+//!
+//! The backend is selected via `SEARCH_PROVIDER` (`tavily`, `brave`, `bing`,
+//! `serpapi`, `duckduckgo`). When unset, Tavily is used if `TAVILY_API_KEY`
+//! is present; otherwise web search is disabled.
+
+mod bing;
+mod brave;
+mod domains;
+mod duckduckgo;
+mod serpapi;
+mod tavily;
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+pub use bing::BingSearch;
+pub use brave::BraveSearch;
+pub use domains::DomainPolicy;
+pub use duckduckgo::DuckDuckGoSearch;
+pub use serpapi::SerpApiSearch;
+pub use tavily::TavilySearch;
+
+/// Number of results requested from the backend.
+const DEFAULT_MAX_RESULTS: usize = 3;
+
+/// Upper bound for `SEARCH_MAX_RESULTS` and per-request overrides.
+const MAX_RESULTS_LIMIT: usize = 10;
+
+/// Max characters of each result snippet passed to the model.
+const SNIPPET_CHARS: usize = 300;
+
+/// One search hit returned by a backend.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+    /// Publication date as reported by the backend (news results).
+    pub published: Option<String>,
+}
+
+/// Date filter for news searches. Dates are `YYYY-MM-DD`.
+#[derive(Debug, Clone, Default)]
+pub struct DateRange {
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    /// Look back this many days from today (used when no explicit dates are given).
+    pub days: Option<u32>,
+}
+
+impl DateRange {
+    /// Parse `start_date`, `end_date`, and `days` from tool call arguments.
+    pub fn from_args(args: &serde_json::Value) -> Self {
+        let date = |key: &str| {
+            args.get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|v| is_iso_date(v))
+                .map(String::from)
+        };
+        Self {
+            start_date: date("start_date"),
+            end_date: date("end_date"),
+            days: args
+                .get("days")
+                .and_then(|v| v.as_u64())
+                .map(|d| d.clamp(1, 365) as u32),
+        }
+    }
+
+    /// Human-readable label, e.g. `2025-01-01..2025-02-01` or `last 7 days`.
+    pub fn label(&self) -> String {
+        match (&self.start_date, &self.end_date, self.days) {
+            (None, None, Some(days)) => format!("last {} days", days),
+            (None, None, None) => "any time".to_string(),
+            (start, end, _) => format!(
+                "{}..{}",
+                start.as_deref().unwrap_or(""),
+                end.as_deref().unwrap_or("")
+            ),
+        }
+    }
+}
+
+fn is_iso_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 10
+        && b[4] == b'-'
+        && b[7] == b'-'
+        && b.iter()
+            .enumerate()
+            .all(|(i, c)| i == 4 || i == 7 || c.is_ascii_digit())
+}
+
+/// Per-search settings: result count and (Tavily) search depth.
+///
+/// Defaults come from `SEARCH_MAX_RESULTS` (1-10, default 3) and `SEARCH_DEPTH`
+/// (`basic` or `advanced`, default `basic`); a debate request may override both.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub max_results: usize,
+    pub depth: &'static str,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+            depth: "basic",
+        }
+    }
+}
+
+impl SearchOptions {
+    pub fn from_env() -> Self {
+        Self::default().with_overrides(
+            std::env::var("SEARCH_DEPTH").ok().as_deref(),
+            std::env::var("SEARCH_MAX_RESULTS")
+                .ok()
+                .and_then(|v| v.trim().parse().ok()),
+        )
+    }
+
+    /// Apply overrides; unknown depths and out-of-range counts are ignored.
+    pub fn with_overrides(mut self, depth: Option<&str>, max_results: Option<usize>) -> Self {
+        match depth.map(|d| d.trim().to_lowercase()).as_deref() {
+            Some("basic") => self.depth = "basic",
+            Some("advanced") => self.depth = "advanced",
+            _ => {}
+        }
+        if let Some(n) = max_results.filter(|n| (1..=MAX_RESULTS_LIMIT).contains(n)) {
+            self.max_results = n;
+        }
+        self
+    }
+}
+
+/// Normalized response from a search backend.
+#[derive(Debug, Clone, Default)]
+pub struct SearchResponse {
+    /// Direct answer, if the backend provides one.
+    pub answer: Option<String>,
+    pub hits: Vec<SearchHit>,
+}
+
+/// A web search backend.
+#[async_trait]
+pub trait SearchProvider: Send + Sync {
+    /// Backend identifier used in logs and `SEARCH_PROVIDER`.
+    fn name(&self) -> &'static str;
+    async fn search(&self, query: &str, opts: &SearchOptions) -> anyhow::Result<SearchResponse>;
+
+    /// Whether the backend implements `search_news`.
+    fn supports_news(&self) -> bool {
+        false
+    }
+
+    /// Search recent news coverage within a date range.
+    async fn search_news(
+        &self,
+        _query: &str,
+        _opts: &SearchOptions,
+        _range: &DateRange,
+    ) -> anyhow::Result<SearchResponse> {
+        Err(anyhow::anyhow!(
+            "News search is not supported by {}",
+            self.name()
+        ))
+    }
+}
+
+fn env_key(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+/// Build the configured search backend, or `None` if search is disabled.
+pub fn provider_from_env() -> Option<Box<dyn SearchProvider>> {
+    let selected = std::env::var("SEARCH_PROVIDER")
+        .ok()
+        .map(|v| v.trim().to_lowercase())
+        .filter(|v| !v.is_empty());
+
+    match selected.as_deref() {
+        None | Some("tavily") => env_key("TAVILY_API_KEY")
+            .map(|key| Box::new(TavilySearch::new(key)) as Box<dyn SearchProvider>),
+        Some("brave") => env_key("BRAVE_SEARCH_API_KEY")
+            .map(|key| Box::new(BraveSearch::new(key)) as Box<dyn SearchProvider>),
+        Some("bing") => env_key("BING_SEARCH_API_KEY")
+            .map(|key| Box::new(BingSearch::new(key)) as Box<dyn SearchProvider>),
+        Some("serpapi") => env_key("SERPAPI_API_KEY")
+            .map(|key| Box::new(SerpApiSearch::new(key)) as Box<dyn SearchProvider>),
+        Some("duckduckgo") | Some("ddg") => Some(Box::new(DuckDuckGoSearch)),
+        Some(_) => None,
+    }
+}
+
+/// Name of the configured backend, if any.
+pub fn provider_name() -> Option<&'static str> {
+    provider_from_env().map(|p| p.name())
+}
+
+/// Format a search response for model consumption.
+pub fn format_response(resp: &SearchResponse) -> String {
+    let mut formatted = Vec::new();
+
+    if let Some(answer) = &resp.answer {
+        if !answer.is_empty() {
+            formatted.push(format!("Direct Answer: {}\n", answer));
+        }
+    }
+
+    for hit in &resp.hits {
+        let snippet: String = hit.snippet.chars().take(SNIPPET_CHARS).collect();
+        match &hit.published {
+            Some(date) => formatted.push(format!(
+                "Source: {}\nPublished: {}\n{}\nURL: {}\n",
+                hit.title, date, snippet, hit.url
+            )),
+            None => formatted.push(format!(
+                "Source: {}\n{}\nURL: {}\n",
+                hit.title, snippet, hit.url
+            )),
+        }
+    }
+
+    if formatted.is_empty() {
+        "No relevant results found.".to_string()
+    } else {
+        formatted.join("\n")
+    }
+}
@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+
+use crate::config;
+
+use super::{SearchHit, SearchOptions, SearchProvider, SearchResponse};
+
+/// SerpAPI Google results (https://serpapi.com).
+pub struct SerpApiSearch {
+    api_key: String,
+}
+
+impl SerpApiSearch {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for SerpApiSearch {
+    fn name(&self) -> &'static str {
+        "serpapi"
+    }
+
+    async fn search(&self, query: &str, opts: &SearchOptions) -> anyhow::Result<SearchResponse> {
+        let count = opts.max_results.to_string();
+        let resp = config::http_client()
+            .get("https://serpapi.com/search.json")
+            .query(&[
+                ("engine", "google"),
+                ("q", query),
+                ("num", count.as_str()),
+                ("api_key", self.api_key.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Search request failed: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| anyhow::anyhow!("Search response parse failed: {}", e))?;
+
+        let answer = resp["answer_box"]["answer"]
+            .as_str()
+            .or_else(|| resp["answer_box"]["snippet"].as_str())
+            .map(String::from);
+        let hits = resp["organic_results"]
+            .as_array()
+            .map(|results| {
+                results
+                    .iter()
+                    .take(opts.max_results)
+                    .map(|r| SearchHit {
+                        title: r["title"].as_str().unwrap_or("").to_string(),
+                        url: r["link"].as_str().unwrap_or("").to_string(),
+                        snippet: r["snippet"].as_str().unwrap_or("").to_string(),
+                        published: None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(SearchResponse { answer, hits })
+    }
+}
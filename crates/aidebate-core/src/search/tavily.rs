@@ -0,0 +1,104 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::config;
+
+use super::{DateRange, SearchHit, SearchOptions, SearchProvider, SearchResponse};
+
+/// Tavily search API (https://tavily.com).
+pub struct TavilySearch {
+    api_key: String,
+}
+
+impl TavilySearch {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for TavilySearch {
+    fn name(&self) -> &'static str {
+        "tavily"
+    }
+
+    async fn search(&self, query: &str, opts: &SearchOptions) -> anyhow::Result<SearchResponse> {
+        let resp = config::http_client()
+            .post("https://api.tavily.com/search")
+            .json(&json!({
+                "api_key": self.api_key,
+                "query": query,
+                "search_depth": opts.depth,
+                "include_answer": true,
+                "max_results": opts.max_results
+            }))
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Search request failed: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| anyhow::anyhow!("Search response parse failed: {}", e))?;
+
+        Ok(parse_response(&resp))
+    }
+
+    fn supports_news(&self) -> bool {
+        true
+    }
+
+    async fn search_news(
+        &self,
+        query: &str,
+        opts: &SearchOptions,
+        range: &DateRange,
+    ) -> anyhow::Result<SearchResponse> {
+        let mut body = json!({
+            "api_key": self.api_key,
+            "query": query,
+            "topic": "news",
+            "search_depth": opts.depth,
+            "include_answer": true,
+            "max_results": opts.max_results
+        });
+        if let Some(days) = range.days {
+            body["days"] = json!(days);
+        }
+        if let Some(start) = &range.start_date {
+            body["start_date"] = json!(start);
+        }
+        if let Some(end) = &range.end_date {
+            body["end_date"] = json!(end);
+        }
+
+        let resp = config::http_client()
+            .post("https://api.tavily.com/search")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("News search request failed: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| anyhow::anyhow!("News search response parse failed: {}", e))?;
+
+        Ok(parse_response(&resp))
+    }
+}
+
+fn parse_response(resp: &serde_json::Value) -> SearchResponse {
+    let answer = resp["answer"].as_str().map(String::from);
+    let hits = resp["results"]
+        .as_array()
+        .map(|results| {
+            results
+                .iter()
+                .map(|r| SearchHit {
+                    title: r["title"].as_str().unwrap_or("").to_string(),
+                    url: r["url"].as_str().unwrap_or("").to_string(),
+                    snippet: r["content"].as_str().unwrap_or("").to_string(),
+                    published: r["published_date"].as_str().map(String::from),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    SearchResponse { answer, hits }
+}
@@ -0,0 +1,221 @@
+//! Live section detection in debater output.
+//!
+//! Debaters answer with a `## Reasoning` section followed by a
+//! `## Final Position` section. [`SectionSplitter`] finds these headings as a
+//! round streams, so the server can mark where each section starts with a
+//! `section` event and UIs can render them into separate panes as they
+//! arrive. A line that may still turn out to be one of the headings is held
+//! back until it is complete, so the event always comes before the heading's
+//! text.
+
+/// Longest line held back while it may be a section heading.
+const MAX_HEADING_CHARS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Reasoning,
+    FinalPosition,
+}
+
+impl Section {
+    const ALL: [Section; 2] = [Section::Reasoning, Section::FinalPosition];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Section::Reasoning => "reasoning",
+            Section::FinalPosition => "final_position",
+        }
+    }
+
+    /// Heading title, lowercased.
+    fn title(&self) -> &'static str {
+        match self {
+            Section::Reasoning => "reasoning",
+            Section::FinalPosition => "final position",
+        }
+    }
+}
+
+/// Streamed text, split where a section starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Piece {
+    Text(String),
+    /// A section starts with the text that follows.
+    Section(Section),
+}
+
+/// Finds section headings in text that arrives in pieces. The text pieces it
+/// returns add up to the text pushed.
+#[derive(Default)]
+pub struct SectionSplitter {
+    /// Start of the current line, held back while it may be a heading.
+    line: String,
+    /// Whether anything has been read on the current line and let through.
+    mid_line: bool,
+}
+
+impl SectionSplitter {
+    /// Split the next piece of text.
+    pub fn push(&mut self, text: &str) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        let mut out = String::new();
+        for c in text.chars() {
+            if !self.line.is_empty() {
+                self.line.push(c);
+                if c == '\n' {
+                    self.end_line(&mut pieces, &mut out);
+                } else if !may_be_heading(&self.line) {
+                    out.push_str(&std::mem::take(&mut self.line));
+                    self.mid_line = true;
+                }
+            } else if c == '#' && !self.mid_line {
+                self.line.push(c);
+            } else {
+                out.push(c);
+                self.mid_line = c != '\n';
+            }
+        }
+        if !out.is_empty() {
+            pieces.push(Piece::Text(out));
+        }
+        pieces
+    }
+
+    /// What is still held back once the text has ended.
+    pub fn finish(&mut self) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        let mut out = String::new();
+        self.end_line(&mut pieces, &mut out);
+        if !out.is_empty() {
+            pieces.push(Piece::Text(out));
+        }
+        pieces
+    }
+
+    fn end_line(&mut self, pieces: &mut Vec<Piece>, out: &mut String) {
+        let line = std::mem::take(&mut self.line);
+        self.mid_line = false;
+        if let Some(section) = heading(&line) {
+            if !out.is_empty() {
+                pieces.push(Piece::Text(std::mem::take(out)));
+            }
+            pieces.push(Piece::Section(section));
+        }
+        out.push_str(&line);
+    }
+}
+
+/// Title of a Markdown heading line, trimmed and lowercased.
+fn heading_title(line: &str) -> Option<String> {
+    let title = line.trim_start_matches('#').strip_prefix(' ')?;
+    Some(title.trim().to_lowercase())
+}
+
+/// Whether a line read so far may still become a section heading.
+fn may_be_heading(line: &str) -> bool {
+    if line.trim_start_matches('#').is_empty() {
+        return true;
+    }
+    match heading_title(line) {
+        Some(title) if line.chars().count() <= MAX_HEADING_CHARS => Section::ALL
+            .iter()
+            .any(|s| s.title().starts_with(&title) || title.strip_prefix(s.title()) == Some(":")),
+        _ => false,
+    }
+}
+
+/// The section a complete line is the heading of.
+fn heading(line: &str) -> Option<Section> {
+    let title = heading_title(line)?;
+    let title = title.trim_end_matches(':');
+    Section::ALL.into_iter().find(|s| s.title() == title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Piece {
+        Piece::Text(s.to_string())
+    }
+
+    /// The pieces `deltas` are split into, adjacent text merged.
+    fn split(deltas: &[&str]) -> Vec<Piece> {
+        let mut splitter = SectionSplitter::default();
+        let mut pieces: Vec<Piece> = Vec::new();
+        let mut all: Vec<Piece> = deltas
+            .iter()
+            .flat_map(|delta| splitter.push(delta))
+            .collect();
+        all.extend(splitter.finish());
+        for piece in all {
+            match (pieces.last_mut(), piece) {
+                (Some(Piece::Text(last)), Piece::Text(next)) => last.push_str(&next),
+                (_, piece) => pieces.push(piece),
+            }
+        }
+        let joined: String = pieces
+            .iter()
+            .filter_map(|piece| match piece {
+                Piece::Text(text) => Some(text.as_str()),
+                Piece::Section(_) => None,
+            })
+            .collect();
+        assert_eq!(joined, deltas.concat());
+        pieces
+    }
+
+    #[test]
+    fn headings_split_across_deltas() {
+        assert_eq!(
+            split(&[
+                "Intro\n## Rea",
+                "soning\nSome text\n##",
+                " Final Position:\nI support"
+            ]),
+            vec![
+                text("Intro\n"),
+                Piece::Section(Section::Reasoning),
+                text("## Reasoning\nSome text\n"),
+                Piece::Section(Section::FinalPosition),
+                text("## Final Position:\nI support"),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_without_headings_passes_through() {
+        assert_eq!(
+            split(&[
+                "Plain text, ",
+                "# not a section\n",
+                "and #hashtag ## Reasoning\n"
+            ]),
+            vec![text(
+                "Plain text, # not a section\nand #hashtag ## Reasoning\n"
+            )]
+        );
+        assert_eq!(
+            split(&["## Reasons to doubt\n"]),
+            vec![text("## Reasons to doubt\n")]
+        );
+    }
+
+    #[test]
+    fn held_line_is_flushed_at_the_end() {
+        let mut splitter = SectionSplitter::default();
+        assert_eq!(
+            splitter.push("Text\n## Final Position"),
+            vec![text("Text\n")]
+        );
+        assert_eq!(
+            splitter.finish(),
+            vec![
+                Piece::Section(Section::FinalPosition),
+                text("## Final Position")
+            ]
+        );
+        assert_eq!(split(&["Text\n## Reas"]), vec![text("Text\n## Reas")]);
+        assert!(SectionSplitter::default().finish().is_empty());
+    }
+}
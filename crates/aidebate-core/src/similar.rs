@@ -0,0 +1,177 @@
+//! Search over finished debates by motion.
+//!
+//! Every finished debate is embedded (its topic together with the judge's
+//! verdict, which sums up both sides) with the configured embedding model and
+//! stored in `debate_embeddings`. A new topic is matched against the stored
+//! vectors of the same model by cosine similarity.
+//!
+//! With `JUDGE_PRECEDENT=1`, the verdict of the most similar earlier debate of
+//! the same user is shown to the judge as a non-binding precedent.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+use tracing::warn;
+
+use crate::debate::Turn;
+use crate::embeddings;
+use crate::prompts;
+use crate::storage::Store;
+use crate::types::Position;
+
+/// Longest verdict text embedded and kept for precedents.
+const MAX_VERDICT_CHARS: usize = 4000;
+
+/// Lowest similarity at which an earlier verdict is used as a precedent.
+const DEFAULT_PRECEDENT_MIN_SCORE: f32 = 0.6;
+
+/// Whether the judge is shown the most similar earlier verdict.
+pub fn precedent_enabled() -> bool {
+    std::env::var("JUDGE_PRECEDENT").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+fn precedent_min_score() -> f32 {
+    std::env::var("JUDGE_PRECEDENT_MIN_SCORE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_PRECEDENT_MIN_SCORE)
+}
+
+#[derive(Serialize)]
+pub struct SimilarDebate {
+    pub session_id: String,
+    pub topic: String,
+    /// `pro` / `con`, or `None` when the judge named no winner.
+    pub winner: Option<String>,
+    pub score: f32,
+    pub created_at: String,
+    #[serde(skip)]
+    pub verdict: String,
+}
+
+/// Embed and store a finished debate so later topics can find it.
+pub async fn index_debate(
+    db: &dyn Store,
+    user_id: &str,
+    session_id: &str,
+    topic: &str,
+    transcript: &[Turn],
+) -> anyhow::Result<()> {
+    let verdict = transcript
+        .iter()
+        .rev()
+        .find(|(side, ..)| *side == Position::Judge)
+        .map(|(_, _, content, _)| content.as_str())
+        .unwrap_or_default();
+    let verdict: String = verdict.chars().take(MAX_VERDICT_CHARS).collect();
+    let vector = embeddings::embed(&[format!("{}\n\n{}", topic, verdict)])
+        .await?
+        .pop()
+        .unwrap_or_default();
+    db.save_debate_embedding(
+        user_id,
+        session_id,
+        topic,
+        prompts::parse_winner(&verdict).map(|side| side.role_str()),
+        &verdict,
+        &vector,
+        &embeddings::model_name(),
+    )
+    .await
+}
+
+/// Earlier debates of `user_id` closest to `topic`, most similar first.
+pub async fn find_similar(
+    db: &dyn Store,
+    user_id: &str,
+    topic: &str,
+    exclude_session: Option<&str>,
+    limit: usize,
+) -> anyhow::Result<Vec<SimilarDebate>> {
+    let mut matches: Vec<SimilarDebate> = scored(db, Some(user_id), topic)
+        .await?
+        .into_iter()
+        .filter(|d| Some(d.session_id.as_str()) != exclude_session)
+        .collect();
+    matches.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    matches.truncate(limit);
+    Ok(matches)
+}
+
+/// Sessions of any user whose debate scores at least `min_score` against
+/// `topic`.
+pub async fn sessions_near(
+    db: &dyn Store,
+    topic: &str,
+    min_score: f32,
+) -> anyhow::Result<HashSet<String>> {
+    Ok(scored(db, None, topic)
+        .await?
+        .into_iter()
+        .filter(|d| d.score >= min_score)
+        .map(|d| d.session_id)
+        .collect())
+}
+
+/// Indexed debates (of `user_id`, or of everyone), scored against `topic`.
+async fn scored(
+    db: &dyn Store,
+    user_id: Option<&str>,
+    topic: &str,
+) -> anyhow::Result<Vec<SimilarDebate>> {
+    let model = embeddings::model_name();
+    let query_vec = embeddings::embed(&[topic.to_string()])
+        .await?
+        .pop()
+        .unwrap_or_default();
+
+    Ok(db
+        .fetch_debate_embeddings(user_id, &model)
+        .await
+        .into_iter()
+        .map(|d| {
+            let vector: Vec<f32> = serde_json::from_str(&d.embedding).unwrap_or_default();
+            SimilarDebate {
+                score: embeddings::cosine(&query_vec, &vector),
+                session_id: d.session_id,
+                topic: d.topic,
+                winner: d.winner,
+                created_at: d.created_at,
+                verdict: d.verdict,
+            }
+        })
+        .collect())
+}
+
+/// The most similar earlier verdict, formatted for the judge, when precedents
+/// are enabled and one is close enough.
+pub async fn precedent(
+    db: &dyn Store,
+    user_id: &str,
+    topic: &str,
+    session_id: &str,
+) -> Option<String> {
+    if !precedent_enabled() {
+        return None;
+    }
+    let best = match find_similar(db, user_id, topic, Some(session_id), 1).await {
+        Ok(mut matches) => matches.pop()?,
+        Err(e) => {
+            warn!("Precedent lookup failed: {}", e);
+            return None;
+        }
+    };
+    if best.score < precedent_min_score() || best.verdict.trim().is_empty() {
+        return None;
+    }
+    Some(format!(
+        "议题：{}\n相似度：{:.2}\n\n{}",
+        best.topic,
+        best.score,
+        best.verdict.trim()
+    ))
+}
@@ -0,0 +1,2478 @@
+use async_trait::async_trait;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+use std::str::FromStr;
+
+use crate::audit::AuditContext;
+use crate::redaction::redact;
+use crate::templates::{PromptTemplate, PromptVariant};
+use crate::tools::ToolCallRecord;
+use crate::types::{DebatePhase, HistoryMessage, Position, StoredToolCall, UserPreferences};
+
+/// Tables created by [`init_db`].
+pub const TABLES: &[&str] = &[
+    "debate_messages",
+    "provider_spend",
+    "session_documents",
+    "document_chunks",
+    "tool_calls",
+    "audit_log",
+    "debate_timings",
+    "round_checkpoints",
+    "debate_cache",
+    "podcast_episodes",
+    "webhook_subscriptions",
+    "debate_embeddings",
+    "debate_analysis",
+    "sessions",
+    "session_tags",
+    "judge_questions",
+    "prompt_templates",
+    "session_templates",
+    "prompt_variants",
+    "debate_inputs",
+    "user_preferences",
+    "topic_collections",
+    "collection_items",
+    "gallery_votes",
+];
+
+pub async fn init_db(db_url: &str) -> anyhow::Result<SqlitePool> {
+    // Ensure database file is created
+    use sqlx::sqlite::SqliteConnectOptions;
+    let opts = SqliteConnectOptions::from_str(db_url)?.create_if_missing(true);
+    let db = SqlitePoolOptions::new().connect_with(opts).await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS debate_messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            phase TEXT NOT NULL,
+            provider TEXT,
+            content TEXT NOT NULL,
+            human_edited INTEGER NOT NULL DEFAULT 0,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+    add_column(
+        &db,
+        "debate_messages",
+        "human_edited",
+        "INTEGER NOT NULL DEFAULT 0",
+    )
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS provider_spend (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            provider TEXT NOT NULL,
+            model_id TEXT NOT NULL,
+            prompt_tokens INTEGER NOT NULL,
+            completion_tokens INTEGER NOT NULL,
+            cost_usd REAL NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS session_documents (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            filename TEXT NOT NULL,
+            content_type TEXT NOT NULL,
+            chars INTEGER NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS document_chunks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            document_id INTEGER NOT NULL REFERENCES session_documents(id),
+            session_id TEXT NOT NULL,
+            chunk_index INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            embedding TEXT NOT NULL,
+            embedding_model TEXT NOT NULL
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS tool_calls (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            message_id INTEGER NOT NULL REFERENCES debate_messages(id),
+            session_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            args TEXT NOT NULL,
+            query TEXT NOT NULL,
+            result TEXT NOT NULL,
+            sources TEXT NOT NULL,
+            latency_ms INTEGER NOT NULL,
+            provider TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            event TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            ip TEXT NOT NULL,
+            topic TEXT NOT NULL,
+            providers TEXT NOT NULL,
+            detail TEXT,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_audit_log_user ON audit_log (user_id, created_at)")
+        .execute(&db)
+        .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS debate_timings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            timings TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    // Partial content of rounds still being generated; a row is removed once
+    // the round is saved to debate_messages
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS round_checkpoints (
+            session_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            phase TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            provider TEXT,
+            content TEXT NOT NULL,
+            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (session_id, role, phase)
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS debate_cache (
+            cache_key TEXT PRIMARY KEY,
+            transcript TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+    // Keys once held the topic in plain text; drop entries from before they
+    // were hashed
+    sqlx::query("DELETE FROM debate_cache WHERE length(cache_key) != 64")
+        .execute(&db)
+        .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS podcast_episodes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            topic TEXT NOT NULL,
+            winner TEXT,
+            duration_secs INTEGER NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            file TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    // `events` is a comma-separated list; empty means every event
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            url TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            events TEXT NOT NULL,
+            user_id TEXT,
+            topic_contains TEXT,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    // One row per finished debate: its topic and verdict, embedded for
+    // similar-debate search
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS debate_embeddings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            topic TEXT NOT NULL,
+            winner TEXT,
+            verdict TEXT NOT NULL,
+            embedding TEXT NOT NULL,
+            embedding_model TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_debate_embeddings_user ON debate_embeddings (user_id, embedding_model)")
+        .execute(&db)
+        .await?;
+
+    // Post-debate analysis of a round, or of the whole debate (`role` and
+    // `phase` are `all`); `result` is JSON whose shape depends on `kind`
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS debate_analysis (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            phase TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            result TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    // One row per debate session; `summary` and `blurb` are filled in once
+    // the debate completes, `prompt_variant` while a prompt A/B test runs,
+    // `gallery` once the owner nominates it for the public gallery
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            session_id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            topic TEXT NOT NULL,
+            summary TEXT,
+            blurb TEXT,
+            prompt_variant TEXT,
+            forked_from TEXT,
+            title TEXT,
+            notes TEXT,
+            visibility TEXT,
+            collection_item INTEGER,
+            gallery TEXT,
+            gallery_updated_at TIMESTAMP,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+    add_column(&db, "sessions", "prompt_variant", "TEXT").await?;
+    add_column(&db, "sessions", "forked_from", "TEXT").await?;
+    add_column(&db, "sessions", "title", "TEXT").await?;
+    add_column(&db, "sessions", "notes", "TEXT").await?;
+    add_column(&db, "sessions", "visibility", "TEXT").await?;
+    add_column(&db, "sessions", "collection_item", "INTEGER").await?;
+    add_column(&db, "sessions", "gallery", "TEXT").await?;
+    add_column(&db, "sessions", "gallery_updated_at", "TIMESTAMP").await?;
+
+    // Topic categories of a session (see `categories`)
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS session_tags (
+            session_id TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (session_id, tag)
+        )",
+    )
+    .execute(&db)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags(tag)")
+        .execute(&db)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_sessions_user ON sessions (user_id, created_at)")
+        .execute(&db)
+        .await?;
+
+    // Follow-up questions to the judge about a verdict, with its answers
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS judge_questions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            question TEXT NOT NULL,
+            answer TEXT NOT NULL,
+            model TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_judge_questions_session ON judge_questions (session_id, id)",
+    )
+    .execute(&db)
+    .await?;
+
+    // Versions of the prompt templates edited through the admin API; the
+    // latest version of each is in use (see `templates`)
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS prompt_templates (
+            name TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            body TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (name, version)
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    // Template versions a session ran with; version 0 is the built-in default
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS session_templates (
+            session_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            PRIMARY KEY (session_id, name)
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    // Prompt variants debates are randomly assigned to; `templates` is a JSON
+    // object of template versions by template name
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS prompt_variants (
+            name TEXT PRIMARY KEY,
+            templates TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    // Defaults each user saved for their debates
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS user_preferences (
+            user_id TEXT PRIMARY KEY,
+            pro_model TEXT,
+            con_model TEXT,
+            judge_model TEXT,
+            preset TEXT,
+            language TEXT,
+            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    // Everything a debate depended on besides the model outputs, as JSON, so
+    // it can be run again with the same inputs
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS debate_inputs (
+            session_id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            inputs TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    // Ordered topic collections (curricula) of a user; debates are linked to
+    // an item through `sessions.collection_item`
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS topic_collections (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(&db)
+    .await?;
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS collection_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            collection_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            topic TEXT NOT NULL
+        )",
+    )
+    .execute(&db)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_collection_items_collection ON collection_items (collection_id, position)",
+    )
+    .execute(&db)
+    .await?;
+
+    // Audience votes on the debates of the public gallery, one per user
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS gallery_votes (
+            session_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            side TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (session_id, user_id)
+        )",
+    )
+    .execute(&db)
+    .await?;
+
+    Ok(db)
+}
+
+/// Add a column that tables created by earlier versions lack.
+async fn add_column(
+    db: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> anyhow::Result<()> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2")
+            .bind(table)
+            .bind(column)
+            .fetch_one(db)
+            .await?;
+    if !exists {
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))
+        .execute(db)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Tables from [`TABLES`] that do not exist yet in `db`.
+pub async fn missing_tables(db: &SqlitePool) -> anyhow::Result<Vec<&'static str>> {
+    let existing: Vec<String> =
+        sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table'")
+            .fetch_all(db)
+            .await?;
+    Ok(TABLES
+        .iter()
+        .copied()
+        .filter(|table| !existing.iter().any(|name| name == table))
+        .collect())
+}
+
+/// Partial content of a round that never finished.
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct Checkpoint {
+    pub role: String,
+    pub phase: String,
+    pub provider: Option<String>,
+    pub content: String,
+    pub updated_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct ToolCallRow {
+    message_id: i64,
+    name: String,
+    args: String,
+    query: String,
+    result: String,
+    sources: String,
+    latency_ms: i64,
+    provider: String,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct ProviderSpend {
+    pub provider: String,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub cost_usd: f64,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct DocumentInfo {
+    pub id: i64,
+    pub filename: String,
+    pub content_type: String,
+    pub chars: i64,
+    pub chunks: i64,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct StoredChunk {
+    pub filename: String,
+    pub chunk_index: i64,
+    pub content: String,
+    pub embedding: String,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub event: String,
+    pub user_id: String,
+    pub session_id: String,
+    pub ip: String,
+    pub topic: String,
+    pub providers: String,
+    pub detail: Option<String>,
+    pub created_at: String,
+}
+
+/// An audio debate published to the podcast feed.
+#[derive(sqlx::FromRow)]
+pub struct PodcastEpisode {
+    pub id: i64,
+    pub user_id: String,
+    pub session_id: String,
+    pub topic: String,
+    /// `Pro` / `Con`, or `None` when the judge named no winner.
+    pub winner: Option<String>,
+    pub duration_secs: i64,
+    pub size_bytes: i64,
+    /// Audio file name, relative to the podcast directory.
+    pub file: String,
+    pub published_unix: i64,
+    /// The session's stored summary, if it has one.
+    pub summary: Option<String>,
+    /// The session's topic categories, comma-separated.
+    pub tags: Option<String>,
+}
+
+const PODCAST_EPISODE_COLUMNS: &str = "e.id, e.user_id, e.session_id, COALESCE(s.title, e.topic) AS topic, e.winner, e.duration_secs, e.size_bytes, e.file, CAST(strftime('%s', e.created_at) AS INTEGER) AS published_unix, s.summary, (SELECT GROUP_CONCAT(tag) FROM session_tags t WHERE t.session_id = e.session_id) AS tags";
+
+/// Episodes of sessions whose owner made them private or shared are left out
+/// of the feed; those never given a visibility stay in it.
+const LISTED_IN_FEED: &str = "(s.visibility IS NULL OR s.visibility = 'public')";
+
+/// An outbound webhook subscription. `user_id` and `topic_contains` narrow
+/// which debates it receives events for.
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct WebhookSubscription {
+    pub id: i64,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Comma-separated event names; empty for all events.
+    pub events: String,
+    pub user_id: Option<String>,
+    pub topic_contains: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct StoredDebateEmbedding {
+    pub session_id: String,
+    pub topic: String,
+    pub winner: Option<String>,
+    pub verdict: String,
+    pub embedding: String,
+    pub created_at: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct StoredAnalysis {
+    pub role: String,
+    pub phase: String,
+    pub kind: String,
+    pub result: serde_json::Value,
+    pub created_at: String,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct ProviderTone {
+    pub provider: String,
+    pub rounds: i64,
+    pub aggressiveness: f64,
+    pub hedging: f64,
+    pub confidence: f64,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct CategoryCount {
+    pub category: String,
+    pub debates: i64,
+}
+
+#[derive(serde::Serialize)]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub topic: String,
+    pub summary: Option<String>,
+    pub blurb: Option<String>,
+    /// Topic categories.
+    pub tags: Vec<String>,
+    /// Session this one was forked from by editing one of its rounds.
+    pub forked_from: Option<String>,
+    /// Title the owner gave the session.
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    /// `private`, `shared` or `public`; `None` until the owner sets it.
+    pub visibility: Option<String>,
+    pub created_at: String,
+}
+
+type SessionRow = (
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    String,
+);
+
+/// Who can see a session besides its owner.
+pub const VISIBILITIES: &[&str] = &["private", "shared", "public"];
+
+/// The metadata a session's owner can edit.
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub struct SessionMetadata {
+    pub session_id: String,
+    #[serde(skip)]
+    pub user_id: String,
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub visibility: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize, sqlx::FromRow)]
+pub struct JudgeExchange {
+    pub id: i64,
+    pub question: String,
+    pub answer: String,
+    pub model: String,
+    pub created_at: String,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct TopicCollection {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    /// Number of topics in the collection.
+    pub items: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Clone, serde::Serialize, sqlx::FromRow)]
+pub struct CollectionItem {
+    pub id: i64,
+    #[serde(skip_serializing)]
+    pub collection_id: i64,
+    /// 1-based place of the topic in the collection.
+    pub position: i64,
+    pub topic: String,
+}
+
+/// A debate run against a collection item.
+#[derive(sqlx::FromRow)]
+pub struct CollectionDebate {
+    pub item_id: i64,
+    pub session_id: String,
+    pub created_at: String,
+    /// The judge's last message, if the debate was judged.
+    pub verdict: Option<String>,
+}
+
+/// Curation status of a session nominated for the public gallery.
+pub const GALLERY_STATUSES: &[&str] = &["nominated", "approved", "rejected"];
+
+/// A nominated debate, as shown in the gallery.
+#[derive(sqlx::FromRow)]
+pub struct GalleryEntry {
+    pub session_id: String,
+    /// The owner's title, or else the topic.
+    pub topic: String,
+    pub summary: Option<String>,
+    pub blurb: Option<String>,
+    /// Comma-separated topic categories.
+    pub tags: Option<String>,
+    /// The judge's last message.
+    pub verdict: Option<String>,
+    pub status: String,
+    pub pro_votes: i64,
+    pub con_votes: i64,
+    /// When the status last changed.
+    pub updated_at: String,
+}
+
+const GALLERY_COLUMNS: &str = "s.session_id, COALESCE(s.title, s.topic) AS topic, s.summary, s.blurb, (SELECT GROUP_CONCAT(tag) FROM session_tags t WHERE t.session_id = s.session_id) AS tags, (SELECT content FROM debate_messages m WHERE m.session_id = s.session_id AND m.role = 'judge' ORDER BY m.id DESC LIMIT 1) AS verdict, s.gallery AS status, (SELECT COUNT(*) FROM gallery_votes v WHERE v.session_id = s.session_id AND v.side = 'pro') AS pro_votes, (SELECT COUNT(*) FROM gallery_votes v WHERE v.session_id = s.session_id AND v.side = 'con') AS con_votes, CAST(s.gallery_updated_at AS TEXT) AS updated_at";
+
+/// Sessions their owner made private or shared again drop out of the
+/// gallery.
+const LISTED_IN_GALLERY: &str = "s.gallery IS NOT NULL AND s.visibility = 'public'";
+
+/// Everything the engine and server persist. [`SqliteStore`] is the bundled
+/// implementation; an embedding app can pass its own to `DebateRouter::new`.
+#[async_trait]
+pub trait Store: Send + Sync {
+    async fn save_message(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        role: Position,
+        phase: DebatePhase,
+        provider: Option<&str>,
+        content: &str,
+    ) -> anyhow::Result<i64>;
+
+    /// Mark a stored round as written or changed by a person.
+    async fn mark_message_edited(&self, message_id: i64) -> anyhow::Result<()>;
+
+    /// Save the partial content of a round in progress, replacing any earlier
+    /// checkpoint for the same round.
+    #[allow(clippy::too_many_arguments)]
+    async fn save_checkpoint(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        role: Position,
+        phase: DebatePhase,
+        provider: Option<&str>,
+        content: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Drop the checkpoint of a round that has been saved in full.
+    async fn clear_checkpoint(
+        &self,
+        session_id: &str,
+        role: Position,
+        phase: DebatePhase,
+    ) -> anyhow::Result<()>;
+
+    /// Partial rounds left behind by debates that did not finish.
+    async fn fetch_checkpoints(&self, user_id: &str, session_id: &str) -> Vec<Checkpoint>;
+
+    /// Cached transcript JSON and its creation time, if stored after
+    /// `datetime('now', modifier)`.
+    async fn fetch_cached_debate(
+        &self,
+        cache_key: &str,
+        modifier: &str,
+    ) -> Option<(String, String)>;
+
+    /// Store (or refresh) a cached debate transcript.
+    async fn save_cached_debate(&self, cache_key: &str, transcript: &str) -> anyhow::Result<()>;
+
+    /// Store the per-phase timing recap sent with a debate's `done` event.
+    async fn save_timings(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        timings: &serde_json::Value,
+    ) -> anyhow::Result<()>;
+
+    /// The most recent timing recap stored for a session.
+    async fn fetch_timings(&self, user_id: &str, session_id: &str) -> Option<serde_json::Value>;
+
+    /// Record the inputs of a debate, with its topic and precedent redacted.
+    async fn save_debate_inputs(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        inputs: &serde_json::Value,
+    ) -> anyhow::Result<()>;
+
+    /// The inputs recorded when a session's debate started.
+    async fn fetch_debate_inputs(
+        &self,
+        user_id: &str,
+        session_id: &str,
+    ) -> Option<serde_json::Value>;
+
+    async fn save_preferences(
+        &self,
+        user_id: &str,
+        preferences: &UserPreferences,
+    ) -> anyhow::Result<()>;
+
+    async fn fetch_preferences(&self, user_id: &str) -> Option<UserPreferences>;
+
+    /// Delete a user's preferences; false if there were none.
+    async fn delete_preferences(&self, user_id: &str) -> anyhow::Result<bool>;
+
+    async fn save_tool_call(
+        &self,
+        message_id: i64,
+        session_id: &str,
+        record: &ToolCallRecord,
+    ) -> anyhow::Result<()>;
+
+    async fn fetch_history(&self, user_id: &str, session_id: &str) -> Vec<HistoryMessage>;
+
+    async fn save_spend(
+        &self,
+        provider: &str,
+        model_id: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        cost_usd: f64,
+    ) -> anyhow::Result<()>;
+
+    /// Total spend since the start of the period given as an SQLite date modifier
+    /// (e.g. `"start of day"`, `"start of month"`).
+    async fn spend_since(&self, modifier: &str) -> f64;
+
+    /// Prompt and completion tokens recorded since the start of the period.
+    async fn tokens_since(&self, modifier: &str) -> (i64, i64);
+
+    /// Per-provider spend since the start of the period.
+    async fn spend_by_provider(&self, modifier: &str) -> Vec<ProviderSpend>;
+
+    async fn save_document(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        filename: &str,
+        content_type: &str,
+        chars: i64,
+    ) -> anyhow::Result<i64>;
+
+    async fn save_document_chunk(
+        &self,
+        document_id: i64,
+        session_id: &str,
+        chunk_index: i64,
+        content: &str,
+        embedding: &[f32],
+        embedding_model: &str,
+    ) -> anyhow::Result<()>;
+
+    async fn fetch_documents(&self, user_id: &str, session_id: &str) -> Vec<DocumentInfo>;
+
+    async fn session_has_documents(&self, user_id: &str, session_id: &str) -> bool;
+
+    async fn fetch_document_chunks(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        embedding_model: &str,
+    ) -> Vec<StoredChunk>;
+
+    async fn save_audit_event(
+        &self,
+        ctx: &AuditContext,
+        event: &str,
+        detail: Option<&str>,
+    ) -> anyhow::Result<()>;
+
+    /// Whether any message or document in the session belongs to a different user.
+    async fn session_owned_by_other(&self, session_id: &str, user_id: &str)
+        -> anyhow::Result<bool>;
+
+    /// Number of debates a user has started, live or replayed from the cache,
+    /// since the start of the period.
+    async fn debates_started_since(&self, user_id: &str, modifier: &str) -> i64;
+
+    /// Most recent audit events, newest first, optionally for one user and
+    /// before a given id (for paging).
+    async fn fetch_audit_events(
+        &self,
+        user_id: Option<&str>,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Vec<AuditEntry>;
+
+    /// Add an episode (its `id` and `published_unix` are ignored). Returns the
+    /// new episode id.
+    async fn save_podcast_episode(&self, episode: &PodcastEpisode) -> anyhow::Result<i64>;
+
+    /// The topic a session was debated on, from its audit trail.
+    async fn fetch_session_topic(&self, session_id: &str) -> Option<String>;
+
+    /// Most recent podcast episodes, newest first, optionally only those in
+    /// `category`.
+    async fn fetch_podcast_episodes(
+        &self,
+        category: Option<&str>,
+        limit: i64,
+    ) -> Vec<PodcastEpisode>;
+
+    async fn fetch_podcast_episode(&self, id: i64) -> Option<PodcastEpisode>;
+
+    /// Add a subscription (its `id` is ignored). Returns the new id.
+    async fn save_webhook_subscription(
+        &self,
+        subscription: &WebhookSubscription,
+    ) -> anyhow::Result<i64>;
+
+    async fn fetch_webhook_subscriptions(&self) -> Vec<WebhookSubscription>;
+
+    /// Remove a subscription. Returns whether it existed.
+    async fn delete_webhook_subscription(&self, id: i64) -> anyhow::Result<bool>;
+
+    /// Store a debate's embedding, replacing any earlier one for the session.
+    #[allow(clippy::too_many_arguments)]
+    async fn save_debate_embedding(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        topic: &str,
+        winner: Option<&str>,
+        verdict: &str,
+        embedding: &[f32],
+        embedding_model: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Indexed debates embedded with `embedding_model`, of one user or of all
+    /// users when `user_id` is `None`.
+    async fn fetch_debate_embeddings(
+        &self,
+        user_id: Option<&str>,
+        embedding_model: &str,
+    ) -> Vec<StoredDebateEmbedding>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn save_analysis(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        role: &str,
+        phase: &str,
+        kind: &str,
+        result: &serde_json::Value,
+    ) -> anyhow::Result<()>;
+
+    /// Every stored analysis of one kind, optionally only of sessions tagged
+    /// `category`.
+    async fn fetch_analyses_of_kind(
+        &self,
+        kind: &str,
+        category: Option<&str>,
+    ) -> Vec<(String, serde_json::Value)>;
+
+    /// Remove a session's analyses of one kind, before they are redone.
+    async fn delete_analysis(&self, session_id: &str, kind: &str) -> anyhow::Result<()>;
+
+    /// Analyses stored for a session, in the order they were made.
+    async fn fetch_analysis(&self, user_id: &str, session_id: &str) -> Vec<StoredAnalysis>;
+
+    /// Average tone scores (`tone` analyses) of the rounds written by each model,
+    /// optionally only in debates tagged `category`.
+    async fn tone_by_provider(&self, category: Option<&str>) -> Vec<ProviderTone>;
+
+    /// Record a debate session when it starts (the topic is updated if the
+    /// session is run again).
+    async fn save_session(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        topic: &str,
+    ) -> anyhow::Result<()>;
+
+    async fn save_session_summary(
+        &self,
+        session_id: &str,
+        summary: &str,
+        blurb: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Replace the topic categories of a session.
+    async fn save_session_tags(&self, session_id: &str, tags: &[&str]) -> anyhow::Result<()>;
+
+    /// Number of sessions tagged with each category, most common first.
+    async fn sessions_by_category(&self) -> Vec<CategoryCount>;
+
+    /// A user's sessions, newest first, optionally only those in `category`.
+    async fn fetch_sessions(
+        &self,
+        user_id: &str,
+        category: Option<&str>,
+        limit: i64,
+    ) -> Vec<SessionInfo>;
+
+    async fn fetch_session_metadata(&self, session_id: &str) -> Option<SessionMetadata>;
+
+    async fn save_session_metadata(&self, metadata: &SessionMetadata) -> anyhow::Result<()>;
+
+    /// Sessions with a verdict, newest first, optionally only those in
+    /// `category`; `(session_id, user_id, topic)`.
+    async fn fetch_judged_sessions(
+        &self,
+        category: Option<&str>,
+        limit: i64,
+    ) -> Vec<(String, String, String)>;
+
+    /// `(pro_model, con_model, judge_model, verdict)` of every judged debate,
+    /// oldest first, optionally only those in `category` and started between the
+    /// days `from` and `to` (`YYYY-MM-DD`, inclusive). The models are those of
+    /// each role's last round and the verdict is the judge's last message.
+    /// Debates continued from an edited round are left out.
+    async fn fetch_debate_results(
+        &self,
+        category: Option<&str>,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Vec<(String, String, String, String)>;
+
+    async fn save_judge_question(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        question: &str,
+        answer: &str,
+        model: &str,
+    ) -> anyhow::Result<i64>;
+
+    /// Questions asked to the judge about a session, oldest first.
+    async fn fetch_judge_questions(&self, user_id: &str, session_id: &str) -> Vec<JudgeExchange>;
+
+    /// Store a new version of a prompt template. Returns the version number.
+    async fn save_prompt_template(&self, name: &str, body: &str) -> anyhow::Result<i64>;
+
+    /// Every stored version of a prompt template, newest first.
+    async fn fetch_prompt_template_versions(&self, name: &str) -> Vec<PromptTemplate>;
+
+    async fn fetch_latest_prompt_template(&self, name: &str) -> Option<PromptTemplate>;
+
+    async fn fetch_prompt_template(&self, name: &str, version: i64) -> Option<PromptTemplate>;
+
+    /// Record the template versions a session runs with, replacing any earlier
+    /// record if the session is run again.
+    async fn save_session_templates(
+        &self,
+        session_id: &str,
+        versions: &[(&str, i64)],
+    ) -> anyhow::Result<()>;
+
+    /// `(name, version)` of the templates a session ran with.
+    async fn fetch_session_templates(&self, session_id: &str) -> Vec<(String, i64)>;
+
+    /// Replace the prompt variants; an empty list ends the A/B test.
+    async fn save_prompt_variants(&self, variants: &[PromptVariant]) -> anyhow::Result<()>;
+
+    async fn fetch_prompt_variants(&self) -> Vec<PromptVariant>;
+
+    /// One of the prompt variants, picked at random.
+    async fn fetch_random_prompt_variant(&self) -> Option<PromptVariant>;
+
+    async fn save_session_fork(&self, session_id: &str, forked_from: &str) -> anyhow::Result<()>;
+
+    async fn save_session_variant(
+        &self,
+        session_id: &str,
+        variant: Option<&str>,
+    ) -> anyhow::Result<()>;
+
+    /// `(variant, verdict, confidence)` of every session assigned to a prompt
+    /// variant, optionally only those in `category`. The verdict is the judge's
+    /// last message, if any; the confidence is the stored `confidence_kind`
+    /// analysis as JSON.
+    async fn fetch_variant_verdicts(
+        &self,
+        confidence_kind: &str,
+        category: Option<&str>,
+    ) -> Vec<(String, Option<String>, Option<String>)>;
+
+    /// Create a collection with its topics in order. Returns the new id.
+    async fn save_collection(
+        &self,
+        user_id: &str,
+        name: &str,
+        description: Option<&str>,
+        topics: &[String],
+    ) -> anyhow::Result<i64>;
+
+    /// Replace the name, description and topics of a collection. Items whose
+    /// topic is still listed keep their id, and with it their debates; debates of
+    /// removed items are unlinked.
+    async fn update_collection(
+        &self,
+        id: i64,
+        name: &str,
+        description: Option<&str>,
+        topics: &[String],
+    ) -> anyhow::Result<()>;
+
+    /// A user's collections, newest first.
+    async fn fetch_collections(&self, user_id: &str) -> Vec<TopicCollection>;
+
+    /// One of a user's collections.
+    async fn fetch_collection(&self, user_id: &str, id: i64) -> Option<TopicCollection>;
+
+    /// The topics of a collection, in order.
+    async fn fetch_collection_items(&self, collection_id: i64) -> Vec<CollectionItem>;
+
+    /// An item of one of a user's collections.
+    async fn fetch_collection_item(&self, user_id: &str, item_id: i64) -> Option<CollectionItem>;
+
+    /// Remove one of a user's collections with its items; their debates are
+    /// kept. Returns whether it existed.
+    async fn delete_collection(&self, user_id: &str, id: i64) -> anyhow::Result<bool>;
+
+    async fn save_session_collection_item(
+        &self,
+        session_id: &str,
+        item_id: i64,
+    ) -> anyhow::Result<()>;
+
+    /// The debates run against the items of a collection, oldest first.
+    async fn fetch_collection_debates(&self, collection_id: i64) -> Vec<CollectionDebate>;
+
+    async fn fetch_gallery_status(&self, session_id: &str) -> Option<String>;
+
+    /// Set the curation status of a session; `None` takes it out of the gallery.
+    async fn save_gallery_status(
+        &self,
+        session_id: &str,
+        status: Option<&str>,
+    ) -> anyhow::Result<()>;
+
+    /// Gallery debates with the given status, most recently curated first,
+    /// optionally only those in `category`.
+    async fn fetch_gallery(
+        &self,
+        status: &str,
+        category: Option<&str>,
+        limit: i64,
+    ) -> Vec<GalleryEntry>;
+
+    /// A nominated session, whatever its status, unless its owner made it
+    /// private or shared.
+    async fn fetch_gallery_entry(&self, session_id: &str) -> Option<GalleryEntry>;
+
+    /// Record a user's vote on a gallery debate, replacing any earlier one.
+    async fn save_gallery_vote(
+        &self,
+        session_id: &str,
+        user_id: &str,
+        side: &str,
+    ) -> anyhow::Result<()>;
+}
+
+/// [`Store`] over an SQLite pool opened with [`init_db`].
+#[derive(Clone)]
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Attach stored tool calls to their history messages.
+    async fn attach_tool_calls(&self, session_id: &str, messages: &mut [HistoryMessage]) {
+        let rows = sqlx::query_as::<_, ToolCallRow>(
+            "SELECT message_id, name, args, query, result, sources, latency_ms, provider FROM tool_calls WHERE session_id = ?1 ORDER BY id",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+        for row in rows {
+            if let Some(message) = messages.iter_mut().find(|m| m.id == row.message_id) {
+                message.tool_calls.push(StoredToolCall {
+                    name: row.name,
+                    args: serde_json::from_str(&row.args).unwrap_or(serde_json::Value::Null),
+                    query: row.query,
+                    result: row.result,
+                    sources: serde_json::from_str(&row.sources)
+                        .unwrap_or_else(|_| serde_json::json!([])),
+                    latency_ms: row.latency_ms,
+                    provider: row.provider,
+                });
+            }
+        }
+    }
+
+    async fn save_collection_items(
+        &self,
+        collection_id: i64,
+        topics: &[String],
+    ) -> anyhow::Result<()> {
+        let mut existing = self.fetch_collection_items(collection_id).await;
+        for (i, topic) in topics.iter().enumerate() {
+            let position = i as i64 + 1;
+            // Stored topics are redacted, so match on the redacted form
+            let topic = redact(topic);
+            match existing.iter().position(|item| item.topic == topic) {
+                Some(found) => {
+                    let item = existing.remove(found);
+                    sqlx::query("UPDATE collection_items SET position = ?2 WHERE id = ?1")
+                        .bind(item.id)
+                        .bind(position)
+                        .execute(&self.pool)
+                        .await?;
+                }
+                None => {
+                    sqlx::query(
+                        "INSERT INTO collection_items (collection_id, position, topic) VALUES (?1, ?2, ?3)",
+                    )
+                    .bind(collection_id)
+                    .bind(position)
+                    .bind(topic)
+                    .execute(&self.pool)
+                    .await?;
+                }
+            }
+        }
+        for item in existing {
+            self.delete_collection_item(item.id).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete_collection_item(&self, item_id: i64) -> anyhow::Result<()> {
+        sqlx::query("UPDATE sessions SET collection_item = NULL WHERE collection_item = ?1")
+            .bind(item_id)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM collection_items WHERE id = ?1")
+            .bind(item_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn save_message(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        role: Position,
+        phase: DebatePhase,
+        provider: Option<&str>,
+        content: &str,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO debate_messages (user_id, session_id, role, phase, provider, content) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .bind(role.role_str())
+        .bind(phase.as_str())
+        .bind(provider)
+        .bind(redact(content))
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn mark_message_edited(&self, message_id: i64) -> anyhow::Result<()> {
+        sqlx::query("UPDATE debate_messages SET human_edited = 1 WHERE id = ?1")
+            .bind(message_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn save_checkpoint(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        role: Position,
+        phase: DebatePhase,
+        provider: Option<&str>,
+        content: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO round_checkpoints (session_id, role, phase, user_id, provider, content) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (session_id, role, phase) DO UPDATE SET provider = excluded.provider, content = excluded.content, updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(session_id)
+        .bind(role.role_str())
+        .bind(phase.as_str())
+        .bind(user_id)
+        .bind(provider)
+        .bind(redact(content))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn clear_checkpoint(
+        &self,
+        session_id: &str,
+        role: Position,
+        phase: DebatePhase,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "DELETE FROM round_checkpoints WHERE session_id = ?1 AND role = ?2 AND phase = ?3",
+        )
+        .bind(session_id)
+        .bind(role.role_str())
+        .bind(phase.as_str())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_checkpoints(&self, user_id: &str, session_id: &str) -> Vec<Checkpoint> {
+        sqlx::query_as::<_, Checkpoint>(
+            "SELECT role, phase, provider, content, CAST(updated_at AS TEXT) AS updated_at FROM round_checkpoints WHERE user_id = ?1 AND session_id = ?2 ORDER BY updated_at",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn fetch_cached_debate(
+        &self,
+        cache_key: &str,
+        modifier: &str,
+    ) -> Option<(String, String)> {
+        sqlx::query_as::<_, (String, String)>(
+            "SELECT transcript, CAST(created_at AS TEXT) FROM debate_cache WHERE cache_key = ?1 AND created_at >= datetime('now', ?2)",
+        )
+        .bind(cache_key)
+        .bind(modifier)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn save_cached_debate(&self, cache_key: &str, transcript: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO debate_cache (cache_key, transcript) VALUES (?1, ?2)
+             ON CONFLICT (cache_key) DO UPDATE SET transcript = excluded.transcript, created_at = CURRENT_TIMESTAMP",
+        )
+        .bind(cache_key)
+        .bind(transcript)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn save_timings(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        timings: &serde_json::Value,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO debate_timings (user_id, session_id, timings) VALUES (?1, ?2, ?3)",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .bind(timings.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_timings(&self, user_id: &str, session_id: &str) -> Option<serde_json::Value> {
+        let (timings,) = sqlx::query_as::<_, (String,)>(
+            "SELECT timings FROM debate_timings WHERE user_id = ?1 AND session_id = ?2 ORDER BY id DESC LIMIT 1",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()??;
+        serde_json::from_str(&timings).ok()
+    }
+
+    async fn save_debate_inputs(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        inputs: &serde_json::Value,
+    ) -> anyhow::Result<()> {
+        // The topic and precedent are free text, redacted like the transcript
+        let mut inputs = inputs.clone();
+        for field in ["topic", "precedent"] {
+            if let Some(text) = inputs[field].as_str().map(|text| redact(text).into_owned()) {
+                inputs[field] = serde_json::Value::String(text);
+            }
+        }
+        sqlx::query(
+            "INSERT INTO debate_inputs (session_id, user_id, inputs) VALUES (?1, ?2, ?3)
+             ON CONFLICT(session_id) DO UPDATE SET user_id = excluded.user_id, inputs = excluded.inputs",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(inputs.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_debate_inputs(
+        &self,
+        user_id: &str,
+        session_id: &str,
+    ) -> Option<serde_json::Value> {
+        let (inputs,) = sqlx::query_as::<_, (String,)>(
+            "SELECT inputs FROM debate_inputs WHERE user_id = ?1 AND session_id = ?2",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()??;
+        serde_json::from_str(&inputs).ok()
+    }
+
+    async fn save_preferences(
+        &self,
+        user_id: &str,
+        preferences: &UserPreferences,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO user_preferences (user_id, pro_model, con_model, judge_model, preset, language) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(user_id) DO UPDATE SET pro_model = excluded.pro_model, con_model = excluded.con_model, judge_model = excluded.judge_model, preset = excluded.preset, language = excluded.language, updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(user_id)
+        .bind(&preferences.pro_model)
+        .bind(&preferences.con_model)
+        .bind(&preferences.judge_model)
+        .bind(&preferences.preset)
+        .bind(&preferences.language)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_preferences(&self, user_id: &str) -> Option<UserPreferences> {
+        sqlx::query_as::<_, UserPreferences>(
+            "SELECT pro_model, con_model, judge_model, preset, language FROM user_preferences WHERE user_id = ?1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn delete_preferences(&self, user_id: &str) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM user_preferences WHERE user_id = ?1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn save_tool_call(
+        &self,
+        message_id: i64,
+        session_id: &str,
+        record: &ToolCallRecord,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO tool_calls (message_id, session_id, name, args, query, result, sources, latency_ms, provider) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )
+        .bind(message_id)
+        .bind(session_id)
+        .bind(&record.name)
+        .bind(redact(&record.args.to_string()))
+        .bind(redact(&record.result.query))
+        .bind(redact(&record.result.results))
+        .bind(serde_json::to_string(&record.result.sources)?)
+        .bind(record.latency_ms as i64)
+        .bind(&record.provider)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_history(&self, user_id: &str, session_id: &str) -> Vec<HistoryMessage> {
+        let mut rows = sqlx::query_as::<_, HistoryMessage>(
+            "SELECT id, role, phase, provider, content, human_edited FROM debate_messages WHERE user_id = ?1 AND session_id = ?2 ORDER BY id DESC LIMIT 50",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default();
+        rows.reverse();
+        self.attach_tool_calls(session_id, &mut rows).await;
+        rows
+    }
+
+    async fn save_spend(
+        &self,
+        provider: &str,
+        model_id: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        cost_usd: f64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO provider_spend (provider, model_id, prompt_tokens, completion_tokens, cost_usd) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(provider)
+        .bind(model_id)
+        .bind(prompt_tokens as i64)
+        .bind(completion_tokens as i64)
+        .bind(cost_usd)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn spend_since(&self, modifier: &str) -> f64 {
+        sqlx::query_scalar::<_, f64>(
+            "SELECT COALESCE(SUM(cost_usd), 0.0) FROM provider_spend WHERE created_at >= datetime('now', ?1)",
+        )
+        .bind(modifier)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap_or(0.0)
+    }
+
+    async fn tokens_since(&self, modifier: &str) -> (i64, i64) {
+        sqlx::query_as::<_, (i64, i64)>(
+            "SELECT COALESCE(SUM(prompt_tokens), 0), COALESCE(SUM(completion_tokens), 0) FROM provider_spend WHERE created_at >= datetime('now', ?1)",
+        )
+        .bind(modifier)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap_or((0, 0))
+    }
+
+    async fn spend_by_provider(&self, modifier: &str) -> Vec<ProviderSpend> {
+        sqlx::query_as::<_, ProviderSpend>(
+            "SELECT provider, SUM(prompt_tokens) AS prompt_tokens, SUM(completion_tokens) AS completion_tokens, SUM(cost_usd) AS cost_usd FROM provider_spend WHERE created_at >= datetime('now', ?1) GROUP BY provider ORDER BY cost_usd DESC",
+        )
+        .bind(modifier)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn save_document(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        filename: &str,
+        content_type: &str,
+        chars: i64,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO session_documents (user_id, session_id, filename, content_type, chars) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .bind(filename)
+        .bind(content_type)
+        .bind(chars)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn save_document_chunk(
+        &self,
+        document_id: i64,
+        session_id: &str,
+        chunk_index: i64,
+        content: &str,
+        embedding: &[f32],
+        embedding_model: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO document_chunks (document_id, session_id, chunk_index, content, embedding, embedding_model) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .bind(document_id)
+        .bind(session_id)
+        .bind(chunk_index)
+        .bind(content)
+        .bind(serde_json::to_string(embedding)?)
+        .bind(embedding_model)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_documents(&self, user_id: &str, session_id: &str) -> Vec<DocumentInfo> {
+        sqlx::query_as::<_, DocumentInfo>(
+            "SELECT d.id, d.filename, d.content_type, d.chars, (SELECT COUNT(*) FROM document_chunks c WHERE c.document_id = d.id) AS chunks FROM session_documents d WHERE d.user_id = ?1 AND d.session_id = ?2 ORDER BY d.id",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn session_has_documents(&self, user_id: &str, session_id: &str) -> bool {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM session_documents WHERE user_id = ?1 AND session_id = ?2",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .fetch_one(&self.pool)
+        .await
+        .map(|n| n > 0)
+        .unwrap_or(false)
+    }
+
+    async fn fetch_document_chunks(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        embedding_model: &str,
+    ) -> Vec<StoredChunk> {
+        sqlx::query_as::<_, StoredChunk>(
+            "SELECT d.filename, c.chunk_index, c.content, c.embedding FROM document_chunks c JOIN session_documents d ON d.id = c.document_id WHERE d.user_id = ?1 AND c.session_id = ?2 AND c.embedding_model = ?3",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .bind(embedding_model)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn save_audit_event(
+        &self,
+        ctx: &AuditContext,
+        event: &str,
+        detail: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO audit_log (event, user_id, session_id, ip, topic, providers, detail) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )
+        .bind(event)
+        .bind(&ctx.user_id)
+        .bind(&ctx.session_id)
+        .bind(&ctx.ip)
+        .bind(redact(&ctx.topic))
+        .bind(&ctx.providers)
+        .bind(detail.map(redact))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn session_owned_by_other(
+        &self,
+        session_id: &str,
+        user_id: &str,
+    ) -> anyhow::Result<bool> {
+        let owned = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM sessions WHERE session_id = ?1 AND user_id != ?2)
+                OR EXISTS (SELECT 1 FROM debate_messages WHERE session_id = ?1 AND user_id != ?2)
+                OR EXISTS (SELECT 1 FROM session_documents WHERE session_id = ?1 AND user_id != ?2)",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(owned)
+    }
+
+    async fn debates_started_since(&self, user_id: &str, modifier: &str) -> i64 {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM audit_log WHERE event IN ('started', 'cached') AND user_id = ?1 AND created_at >= datetime('now', ?2)",
+        )
+        .bind(user_id)
+        .bind(modifier)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap_or(0)
+    }
+
+    async fn fetch_audit_events(
+        &self,
+        user_id: Option<&str>,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Vec<AuditEntry> {
+        sqlx::query_as::<_, AuditEntry>(
+            "SELECT id, event, user_id, session_id, ip, topic, providers, detail, CAST(created_at AS TEXT) AS created_at FROM audit_log WHERE (?1 IS NULL OR user_id = ?1) AND (?2 IS NULL OR id < ?2) ORDER BY id DESC LIMIT ?3",
+        )
+        .bind(user_id)
+        .bind(before_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn save_podcast_episode(&self, episode: &PodcastEpisode) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO podcast_episodes (user_id, session_id, topic, winner, duration_secs, size_bytes, file) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )
+        .bind(&episode.user_id)
+        .bind(&episode.session_id)
+        .bind(redact(&episode.topic))
+        .bind(&episode.winner)
+        .bind(episode.duration_secs)
+        .bind(episode.size_bytes)
+        .bind(&episode.file)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn fetch_session_topic(&self, session_id: &str) -> Option<String> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT topic FROM audit_log WHERE session_id = ?1 AND event IN ('started', 'cached') ORDER BY id DESC LIMIT 1",
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn fetch_podcast_episodes(
+        &self,
+        category: Option<&str>,
+        limit: i64,
+    ) -> Vec<PodcastEpisode> {
+        sqlx::query_as::<_, PodcastEpisode>(&format!(
+            "SELECT {} FROM podcast_episodes e LEFT JOIN sessions s ON s.session_id = e.session_id WHERE {} AND (?2 IS NULL OR e.session_id IN (SELECT session_id FROM session_tags WHERE tag = ?2)) ORDER BY e.id DESC LIMIT ?1",
+            PODCAST_EPISODE_COLUMNS, LISTED_IN_FEED
+        ))
+        .bind(limit)
+        .bind(category)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn fetch_podcast_episode(&self, id: i64) -> Option<PodcastEpisode> {
+        sqlx::query_as::<_, PodcastEpisode>(&format!(
+            "SELECT {} FROM podcast_episodes e LEFT JOIN sessions s ON s.session_id = e.session_id WHERE e.id = ?1 AND {}",
+            PODCAST_EPISODE_COLUMNS, LISTED_IN_FEED
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn save_webhook_subscription(
+        &self,
+        subscription: &WebhookSubscription,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO webhook_subscriptions (url, secret, events, user_id, topic_contains) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(&subscription.url)
+        .bind(&subscription.secret)
+        .bind(&subscription.events)
+        .bind(&subscription.user_id)
+        .bind(&subscription.topic_contains)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn fetch_webhook_subscriptions(&self) -> Vec<WebhookSubscription> {
+        sqlx::query_as::<_, WebhookSubscription>(
+            "SELECT id, url, secret, events, user_id, topic_contains FROM webhook_subscriptions ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn delete_webhook_subscription(&self, id: i64) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn save_debate_embedding(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        topic: &str,
+        winner: Option<&str>,
+        verdict: &str,
+        embedding: &[f32],
+        embedding_model: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM debate_embeddings WHERE session_id = ?1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            "INSERT INTO debate_embeddings (user_id, session_id, topic, winner, verdict, embedding, embedding_model) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .bind(redact(topic))
+        .bind(winner)
+        .bind(redact(verdict))
+        .bind(serde_json::to_string(embedding)?)
+        .bind(embedding_model)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_debate_embeddings(
+        &self,
+        user_id: Option<&str>,
+        embedding_model: &str,
+    ) -> Vec<StoredDebateEmbedding> {
+        sqlx::query_as::<_, StoredDebateEmbedding>(
+            "SELECT session_id, topic, winner, verdict, embedding, CAST(created_at AS TEXT) AS created_at FROM debate_embeddings WHERE (?1 IS NULL OR user_id = ?1) AND embedding_model = ?2",
+        )
+        .bind(user_id)
+        .bind(embedding_model)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn save_analysis(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        role: &str,
+        phase: &str,
+        kind: &str,
+        result: &serde_json::Value,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO debate_analysis (user_id, session_id, role, phase, kind, result) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .bind(role)
+        .bind(phase)
+        .bind(kind)
+        .bind(redact(&result.to_string()))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_analyses_of_kind(
+        &self,
+        kind: &str,
+        category: Option<&str>,
+    ) -> Vec<(String, serde_json::Value)> {
+        sqlx::query_as::<_, (String, String)>(
+            "SELECT session_id, result FROM debate_analysis WHERE kind = ?1 AND (?2 IS NULL OR session_id IN (SELECT session_id FROM session_tags WHERE tag = ?2)) ORDER BY id",
+        )
+        .bind(kind)
+        .bind(category)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(session_id, result)| (session_id, serde_json::from_str(&result).unwrap_or_default()))
+        .collect()
+    }
+
+    async fn delete_analysis(&self, session_id: &str, kind: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM debate_analysis WHERE session_id = ?1 AND kind = ?2")
+            .bind(session_id)
+            .bind(kind)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn fetch_analysis(&self, user_id: &str, session_id: &str) -> Vec<StoredAnalysis> {
+        sqlx::query_as::<_, (String, String, String, String, String)>(
+            "SELECT role, phase, kind, result, CAST(created_at AS TEXT) FROM debate_analysis WHERE user_id = ?1 AND session_id = ?2 ORDER BY id",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(role, phase, kind, result, created_at)| StoredAnalysis {
+            role,
+            phase,
+            kind,
+            result: serde_json::from_str(&result).unwrap_or_default(),
+            created_at,
+        })
+        .collect()
+    }
+
+    async fn tone_by_provider(&self, category: Option<&str>) -> Vec<ProviderTone> {
+        sqlx::query_as::<_, ProviderTone>(
+            "SELECT COALESCE(json_extract(result, '$.provider'), '') AS provider, COUNT(*) AS rounds, AVG(json_extract(result, '$.aggressiveness')) AS aggressiveness, AVG(json_extract(result, '$.hedging')) AS hedging, AVG(json_extract(result, '$.confidence')) AS confidence FROM debate_analysis WHERE kind = 'tone' AND (?1 IS NULL OR session_id IN (SELECT session_id FROM session_tags WHERE tag = ?1)) GROUP BY 1 ORDER BY rounds DESC",
+        )
+        .bind(category)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn save_session(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        topic: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO sessions (session_id, user_id, topic) VALUES (?1, ?2, ?3)
+             ON CONFLICT(session_id) DO UPDATE SET topic = excluded.topic, updated_at = CURRENT_TIMESTAMP
+             WHERE sessions.user_id = excluded.user_id",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(redact(topic))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn save_session_summary(
+        &self,
+        session_id: &str,
+        summary: &str,
+        blurb: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "UPDATE sessions SET summary = ?2, blurb = ?3, updated_at = CURRENT_TIMESTAMP WHERE session_id = ?1",
+        )
+        .bind(session_id)
+        .bind(redact(summary))
+        .bind(redact(blurb))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn save_session_tags(&self, session_id: &str, tags: &[&str]) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM session_tags WHERE session_id = ?1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        for tag in tags {
+            sqlx::query("INSERT OR IGNORE INTO session_tags (session_id, tag) VALUES (?1, ?2)")
+                .bind(session_id)
+                .bind(tag)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn sessions_by_category(&self) -> Vec<CategoryCount> {
+        sqlx::query_as::<_, CategoryCount>(
+            "SELECT tag AS category, COUNT(*) AS debates FROM session_tags GROUP BY tag ORDER BY debates DESC, tag",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn fetch_sessions(
+        &self,
+        user_id: &str,
+        category: Option<&str>,
+        limit: i64,
+    ) -> Vec<SessionInfo> {
+        sqlx::query_as::<_, SessionRow>(
+            "SELECT session_id, topic, summary, blurb, (SELECT GROUP_CONCAT(tag) FROM session_tags t WHERE t.session_id = s.session_id), forked_from, title, notes, visibility, CAST(created_at AS TEXT) FROM sessions s WHERE user_id = ?1 AND (?3 IS NULL OR session_id IN (SELECT session_id FROM session_tags WHERE tag = ?3)) ORDER BY created_at DESC, rowid DESC LIMIT ?2",
+        )
+        .bind(user_id)
+        .bind(limit)
+        .bind(category)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(
+            |(session_id, topic, summary, blurb, tags, forked_from, title, notes, visibility, created_at)| {
+                SessionInfo {
+                    session_id,
+                    topic,
+                    summary,
+                    blurb,
+                    tags: tags
+                        .map(|tags| tags.split(',').map(str::to_string).collect())
+                        .unwrap_or_default(),
+                    forked_from,
+                    title,
+                    notes,
+                    visibility,
+                    created_at,
+                }
+            },
+        )
+        .collect()
+    }
+
+    async fn fetch_session_metadata(&self, session_id: &str) -> Option<SessionMetadata> {
+        sqlx::query_as::<_, SessionMetadata>(
+            "SELECT session_id, user_id, title, notes, visibility FROM sessions WHERE session_id = ?1",
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn save_session_metadata(&self, metadata: &SessionMetadata) -> anyhow::Result<()> {
+        sqlx::query(
+            "UPDATE sessions SET title = ?2, notes = ?3, visibility = ?4, updated_at = CURRENT_TIMESTAMP WHERE session_id = ?1",
+        )
+        .bind(&metadata.session_id)
+        .bind(metadata.title.as_deref().map(redact))
+        .bind(metadata.notes.as_deref().map(redact))
+        .bind(&metadata.visibility)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_judged_sessions(
+        &self,
+        category: Option<&str>,
+        limit: i64,
+    ) -> Vec<(String, String, String)> {
+        sqlx::query_as::<_, (String, String, String)>(
+            "SELECT session_id, user_id, topic FROM sessions s WHERE EXISTS (SELECT 1 FROM debate_messages m WHERE m.session_id = s.session_id AND m.role = 'judge') AND (?2 IS NULL OR session_id IN (SELECT session_id FROM session_tags WHERE tag = ?2)) ORDER BY created_at DESC, rowid DESC LIMIT ?1",
+        )
+        .bind(limit)
+        .bind(category)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn fetch_debate_results(
+        &self,
+        category: Option<&str>,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Vec<(String, String, String, String)> {
+        sqlx::query_as::<_, (Option<String>, Option<String>, Option<String>, String)>(
+            "SELECT (SELECT provider FROM debate_messages m WHERE m.session_id = s.session_id AND m.role = 'pro' ORDER BY m.id DESC LIMIT 1), (SELECT provider FROM debate_messages m WHERE m.session_id = s.session_id AND m.role = 'con' ORDER BY m.id DESC LIMIT 1), j.provider, j.content FROM sessions s JOIN debate_messages j ON j.id = (SELECT MAX(id) FROM debate_messages m WHERE m.session_id = s.session_id AND m.role = 'judge') WHERE s.forked_from IS NULL AND (?1 IS NULL OR s.session_id IN (SELECT session_id FROM session_tags WHERE tag = ?1)) AND (?2 IS NULL OR date(s.created_at) >= ?2) AND (?3 IS NULL OR date(s.created_at) <= ?3) ORDER BY s.created_at, s.rowid",
+        )
+        .bind(category)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(pro, con, judge, verdict)| Some((pro?, con?, judge?, verdict)))
+        .collect()
+    }
+
+    async fn save_judge_question(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        question: &str,
+        answer: &str,
+        model: &str,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO judge_questions (user_id, session_id, question, answer, model) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .bind(redact(question))
+        .bind(redact(answer))
+        .bind(model)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn fetch_judge_questions(&self, user_id: &str, session_id: &str) -> Vec<JudgeExchange> {
+        sqlx::query_as::<_, JudgeExchange>(
+            "SELECT id, question, answer, model, CAST(created_at AS TEXT) AS created_at FROM judge_questions WHERE user_id = ?1 AND session_id = ?2 ORDER BY id",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn save_prompt_template(&self, name: &str, body: &str) -> anyhow::Result<i64> {
+        let version: i64 = sqlx::query_scalar(
+            "INSERT INTO prompt_templates (name, version, body)
+             SELECT ?1, COALESCE(MAX(version), 0) + 1, ?2 FROM prompt_templates WHERE name = ?1
+             RETURNING version",
+        )
+        .bind(name)
+        .bind(body)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(version)
+    }
+
+    async fn fetch_prompt_template_versions(&self, name: &str) -> Vec<PromptTemplate> {
+        sqlx::query_as::<_, PromptTemplate>(
+            "SELECT name, version, body, CAST(created_at AS TEXT) AS created_at FROM prompt_templates WHERE name = ?1 ORDER BY version DESC",
+        )
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn fetch_latest_prompt_template(&self, name: &str) -> Option<PromptTemplate> {
+        sqlx::query_as::<_, PromptTemplate>(
+            "SELECT name, version, body, CAST(created_at AS TEXT) AS created_at FROM prompt_templates WHERE name = ?1 ORDER BY version DESC LIMIT 1",
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn fetch_prompt_template(&self, name: &str, version: i64) -> Option<PromptTemplate> {
+        sqlx::query_as::<_, PromptTemplate>(
+            "SELECT name, version, body, CAST(created_at AS TEXT) AS created_at FROM prompt_templates WHERE name = ?1 AND version = ?2",
+        )
+        .bind(name)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn save_session_templates(
+        &self,
+        session_id: &str,
+        versions: &[(&str, i64)],
+    ) -> anyhow::Result<()> {
+        for (name, version) in versions {
+            sqlx::query(
+                "INSERT INTO session_templates (session_id, name, version) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(session_id, name) DO UPDATE SET version = excluded.version",
+            )
+            .bind(session_id)
+            .bind(name)
+            .bind(version)
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn fetch_session_templates(&self, session_id: &str) -> Vec<(String, i64)> {
+        sqlx::query_as::<_, (String, i64)>(
+            "SELECT name, version FROM session_templates WHERE session_id = ?1 ORDER BY name",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn save_prompt_variants(&self, variants: &[PromptVariant]) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM prompt_variants")
+            .execute(&self.pool)
+            .await?;
+        for variant in variants {
+            sqlx::query("INSERT INTO prompt_variants (name, templates) VALUES (?1, ?2)")
+                .bind(&variant.name)
+                .bind(serde_json::to_string(&variant.templates)?)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn fetch_prompt_variants(&self) -> Vec<PromptVariant> {
+        sqlx::query_as::<_, (String, String)>(
+            "SELECT name, templates FROM prompt_variants ORDER BY name",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, templates)| PromptVariant {
+            name,
+            templates: serde_json::from_str(&templates).unwrap_or_default(),
+        })
+        .collect()
+    }
+
+    async fn fetch_random_prompt_variant(&self) -> Option<PromptVariant> {
+        sqlx::query_as::<_, (String, String)>(
+            "SELECT name, templates FROM prompt_variants ORDER BY RANDOM() LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+        .map(|(name, templates)| PromptVariant {
+            name,
+            templates: serde_json::from_str(&templates).unwrap_or_default(),
+        })
+    }
+
+    async fn save_session_fork(&self, session_id: &str, forked_from: &str) -> anyhow::Result<()> {
+        sqlx::query("UPDATE sessions SET forked_from = ?2 WHERE session_id = ?1")
+            .bind(session_id)
+            .bind(forked_from)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn save_session_variant(
+        &self,
+        session_id: &str,
+        variant: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query("UPDATE sessions SET prompt_variant = ?2 WHERE session_id = ?1")
+            .bind(session_id)
+            .bind(variant)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn fetch_variant_verdicts(
+        &self,
+        confidence_kind: &str,
+        category: Option<&str>,
+    ) -> Vec<(String, Option<String>, Option<String>)> {
+        sqlx::query_as::<_, (String, Option<String>, Option<String>)>(
+            "SELECT s.prompt_variant, (SELECT content FROM debate_messages m WHERE m.session_id = s.session_id AND m.role = 'judge' ORDER BY m.id DESC LIMIT 1), (SELECT result FROM debate_analysis a WHERE a.session_id = s.session_id AND a.kind = ?1 ORDER BY a.id DESC LIMIT 1) FROM sessions s WHERE s.prompt_variant IS NOT NULL AND (?2 IS NULL OR s.session_id IN (SELECT session_id FROM session_tags WHERE tag = ?2)) ORDER BY s.prompt_variant",
+        )
+        .bind(confidence_kind)
+        .bind(category)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn save_collection(
+        &self,
+        user_id: &str,
+        name: &str,
+        description: Option<&str>,
+        topics: &[String],
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO topic_collections (user_id, name, description) VALUES (?1, ?2, ?3)",
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(description)
+        .execute(&self.pool)
+        .await?;
+        let id = result.last_insert_rowid();
+        self.save_collection_items(id, topics).await?;
+        Ok(id)
+    }
+
+    async fn update_collection(
+        &self,
+        id: i64,
+        name: &str,
+        description: Option<&str>,
+        topics: &[String],
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "UPDATE topic_collections SET name = ?2, description = ?3, updated_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        )
+        .bind(id)
+        .bind(name)
+        .bind(description)
+        .execute(&self.pool)
+        .await?;
+        self.save_collection_items(id, topics).await
+    }
+
+    async fn fetch_collections(&self, user_id: &str) -> Vec<TopicCollection> {
+        sqlx::query_as::<_, TopicCollection>(
+            "SELECT id, name, description, (SELECT COUNT(*) FROM collection_items i WHERE i.collection_id = c.id) AS items, created_at, updated_at FROM topic_collections c WHERE user_id = ?1 ORDER BY created_at DESC, id DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn fetch_collection(&self, user_id: &str, id: i64) -> Option<TopicCollection> {
+        sqlx::query_as::<_, TopicCollection>(
+            "SELECT id, name, description, (SELECT COUNT(*) FROM collection_items i WHERE i.collection_id = c.id) AS items, created_at, updated_at FROM topic_collections c WHERE id = ?1 AND user_id = ?2",
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn fetch_collection_items(&self, collection_id: i64) -> Vec<CollectionItem> {
+        sqlx::query_as::<_, CollectionItem>(
+            "SELECT id, collection_id, position, topic FROM collection_items WHERE collection_id = ?1 ORDER BY position",
+        )
+        .bind(collection_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn fetch_collection_item(&self, user_id: &str, item_id: i64) -> Option<CollectionItem> {
+        sqlx::query_as::<_, CollectionItem>(
+            "SELECT i.id, i.collection_id, i.position, i.topic FROM collection_items i JOIN topic_collections c ON c.id = i.collection_id WHERE i.id = ?1 AND c.user_id = ?2",
+        )
+        .bind(item_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn delete_collection(&self, user_id: &str, id: i64) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM topic_collections WHERE id = ?1 AND user_id = ?2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query(
+            "UPDATE sessions SET collection_item = NULL WHERE collection_item IN (SELECT id FROM collection_items WHERE collection_id = ?1)",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("DELETE FROM collection_items WHERE collection_id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(true)
+    }
+
+    async fn save_session_collection_item(
+        &self,
+        session_id: &str,
+        item_id: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query("UPDATE sessions SET collection_item = ?2 WHERE session_id = ?1")
+            .bind(session_id)
+            .bind(item_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn fetch_collection_debates(&self, collection_id: i64) -> Vec<CollectionDebate> {
+        sqlx::query_as::<_, CollectionDebate>(
+            "SELECT s.collection_item AS item_id, s.session_id, s.created_at, (SELECT content FROM debate_messages m WHERE m.session_id = s.session_id AND m.role = 'judge' ORDER BY m.id DESC LIMIT 1) AS verdict FROM sessions s JOIN collection_items i ON i.id = s.collection_item WHERE i.collection_id = ?1 ORDER BY s.created_at, s.rowid",
+        )
+        .bind(collection_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn fetch_gallery_status(&self, session_id: &str) -> Option<String> {
+        sqlx::query_scalar::<_, Option<String>>(
+            "SELECT gallery FROM sessions WHERE session_id = ?1",
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+        .flatten()
+    }
+
+    async fn save_gallery_status(
+        &self,
+        session_id: &str,
+        status: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "UPDATE sessions SET gallery = ?2, gallery_updated_at = CURRENT_TIMESTAMP WHERE session_id = ?1",
+        )
+        .bind(session_id)
+        .bind(status)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_gallery(
+        &self,
+        status: &str,
+        category: Option<&str>,
+        limit: i64,
+    ) -> Vec<GalleryEntry> {
+        sqlx::query_as::<_, GalleryEntry>(&format!(
+            "SELECT {} FROM sessions s WHERE {} AND s.gallery = ?1 AND (?2 IS NULL OR s.session_id IN (SELECT session_id FROM session_tags WHERE tag = ?2)) ORDER BY s.gallery_updated_at DESC, s.rowid DESC LIMIT ?3",
+            GALLERY_COLUMNS, LISTED_IN_GALLERY
+        ))
+        .bind(status)
+        .bind(category)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn fetch_gallery_entry(&self, session_id: &str) -> Option<GalleryEntry> {
+        sqlx::query_as::<_, GalleryEntry>(&format!(
+            "SELECT {} FROM sessions s WHERE s.session_id = ?1 AND {}",
+            GALLERY_COLUMNS, LISTED_IN_GALLERY
+        ))
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn save_gallery_vote(
+        &self,
+        session_id: &str,
+        user_id: &str,
+        side: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO gallery_votes (session_id, user_id, side) VALUES (?1, ?2, ?3)
+             ON CONFLICT(session_id, user_id) DO UPDATE SET side = excluded.side, created_at = CURRENT_TIMESTAMP",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(side)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+//! Subtitle (SRT / WebVTT) export of a debate session.
+//!
+//! Rounds are laid out one after another. Each round lasts as long as it took
+//! to stream, taken from the stored timing recap (`debate_timings`); rounds
+//! without a recap are estimated from their token count at a typical
+//! streaming pace. A round's text is split into short captions that share its
+//! time in proportion to their length, each attributed to its speaker.
+
+use regex::Regex;
+
+use crate::tokenizer;
+use crate::types::{HistoryMessage, Position};
+
+/// Streaming pace assumed for rounds without recorded timings.
+const ESTIMATED_TOKENS_PER_SEC: u64 = 30;
+
+/// Longest caption, in columns (CJK characters take two); captions wider
+/// than a line are split over two lines.
+const MAX_CUE_WIDTH: usize = 84;
+const MAX_LINE_WIDTH: usize = 42;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
+impl SubtitleFormat {
+    /// Parse a `format` parameter (`srt` or `vtt`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "srt" => Some(SubtitleFormat::Srt),
+            "vtt" | "webvtt" => Some(SubtitleFormat::Vtt),
+            _ => None,
+        }
+    }
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Vtt => "vtt",
+        }
+    }
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "application/x-subrip; charset=utf-8",
+            SubtitleFormat::Vtt => "text/vtt; charset=utf-8",
+        }
+    }
+}
+
+/// One caption: `[start_ms, end_ms)` spoken by `speaker`.
+pub struct Cue {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub speaker: &'static str,
+    pub text: String,
+}
+
+/// Captions for `messages`, timed from `timings` (a stored `done` recap)
+/// where it covers a round.
+pub fn cues(messages: &[HistoryMessage], timings: Option<&serde_json::Value>) -> Vec<Cue> {
+    let phases = timings
+        .and_then(|t| t["phases"].as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let mut cues = Vec::new();
+    let mut clock = 0;
+    for message in messages {
+        let Some(side) = Position::from_role(&message.role) else {
+            continue;
+        };
+        let recorded = phases
+            .iter()
+            .find(|p| p["side"] == message.role.as_str() && p["phase"] == message.phase.as_str());
+        let duration_ms = match recorded.and_then(|p| p["wall_ms"].as_u64()) {
+            Some(wall_ms) => wall_ms,
+            None => {
+                let model = message.provider.as_deref().unwrap_or_default();
+                let tokens = u64::from(tokenizer::count_tokens(model, &message.content));
+                tokens * 1000 / ESTIMATED_TOKENS_PER_SEC
+            }
+        };
+
+        let captions = captions(&speakable(&message.content));
+        let total_chars: usize = captions.iter().map(|c| c.chars().count()).sum();
+        let mut elapsed_chars = 0;
+        for text in captions {
+            let start_ms = clock + duration_ms * elapsed_chars as u64 / total_chars.max(1) as u64;
+            elapsed_chars += text.chars().count();
+            let end_ms = clock + duration_ms * elapsed_chars as u64 / total_chars.max(1) as u64;
+            cues.push(Cue {
+                start_ms,
+                end_ms: end_ms.max(start_ms + 1),
+                speaker: side.label(),
+                text,
+            });
+        }
+        clock += duration_ms;
+    }
+    cues
+}
+
+/// Render `cues` as an SRT or WebVTT document.
+pub fn render(format: SubtitleFormat, cues: &[Cue]) -> String {
+    let mut out = String::new();
+    if format == SubtitleFormat::Vtt {
+        out.push_str("WEBVTT\n\n");
+    }
+    for (index, cue) in cues.iter().enumerate() {
+        let text = wrap(&cue.text);
+        match format {
+            SubtitleFormat::Srt => out.push_str(&format!(
+                "{}\n{} --> {}\n{}: {}\n\n",
+                index + 1,
+                timestamp(cue.start_ms, ','),
+                timestamp(cue.end_ms, ','),
+                cue.speaker,
+                text
+            )),
+            SubtitleFormat::Vtt => out.push_str(&format!(
+                "{} --> {}\n<v {}>{}\n\n",
+                timestamp(cue.start_ms, '.'),
+                timestamp(cue.end_ms, '.'),
+                cue.speaker,
+                text.replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;")
+            )),
+        }
+    }
+    out
+}
+
+fn timestamp(ms: u64, separator: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+/// Display columns taken by `c`: two for CJK and other wide characters.
+fn width(c: char) -> usize {
+    if ('\u{1100}'..='\u{115f}').contains(&c) || ('\u{2e80}'..='\u{ffdc}').contains(&c) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Whether a caption may end after `c`: whitespace or sentence and clause
+/// punctuation, including CJK punctuation for text without spaces.
+fn is_break(c: char) -> bool {
+    c.is_whitespace()
+        || matches!(
+            c,
+            '.' | ',' | ';' | ':' | '!' | '?' | '。' | '，' | '；' | '：' | '！' | '？' | '、'
+        )
+}
+
+/// Number of leading characters of `chars` that fit in `max_width` columns.
+fn fitting(chars: &[char], max_width: usize) -> usize {
+    let mut total = 0;
+    chars
+        .iter()
+        .take_while(|&&c| {
+            total += width(c);
+            total <= max_width
+        })
+        .count()
+}
+
+/// Split text into captions of at most `MAX_CUE_WIDTH` columns, ending at a
+/// sentence end where possible and otherwise at the last break.
+fn captions(text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    for paragraph in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let mut rest: Vec<char> = paragraph.chars().collect();
+        loop {
+            let fit = fitting(&rest, MAX_CUE_WIDTH);
+            if fit == rest.len() {
+                break;
+            }
+            let window = &rest[..fit];
+            let sentence_end = window
+                .iter()
+                .rposition(|c| matches!(c, '.' | '!' | '?' | '。' | '！' | '？'))
+                .filter(|&i| i >= fit / 2);
+            let cut = sentence_end
+                .or_else(|| window.iter().rposition(|&c| is_break(c)).filter(|&i| i > 0))
+                .map_or(fit, |i| i + 1);
+            out.push(rest[..cut].iter().collect::<String>().trim().to_string());
+            rest.drain(..cut);
+        }
+        out.push(rest.iter().collect::<String>().trim().to_string());
+    }
+    out.retain(|caption| !caption.is_empty());
+    out
+}
+
+/// Break a caption over two lines when it is wider than `MAX_LINE_WIDTH`.
+fn wrap(caption: &str) -> String {
+    let chars: Vec<char> = caption.chars().collect();
+    let total: usize = chars.iter().map(|&c| width(c)).sum();
+    if total <= MAX_LINE_WIDTH {
+        return caption.to_string();
+    }
+    let middle = fitting(&chars, total.div_ceil(2));
+    let split = (0..middle)
+        .flat_map(|offset| [middle - offset, middle + offset])
+        .find(|&i| i < chars.len() && chars[i].is_whitespace())
+        .unwrap_or(middle);
+    let first: String = chars[..split].iter().collect();
+    let second: String = chars[split..].iter().collect();
+    format!("{}\n{}", first.trim_end(), second.trim_start())
+}
+
+/// Strip Markdown that would otherwise be shown in captions or read aloud.
+pub(crate) fn speakable(markdown: &str) -> String {
+    let links = Regex::new(r"\[([^\]]+)\]\([^)]+\)").unwrap();
+    let text = links.replace_all(markdown, "$1");
+    text.lines()
+        .map(|line| line.trim_start_matches(['#', '>', '-', ' ']))
+        .map(|line| line.replace(['*', '`', '_'], ""))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
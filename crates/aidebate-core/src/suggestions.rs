@@ -0,0 +1,67 @@
+//! Debate topic suggestions.
+//!
+//! Scheduled debates without configured topics ask the judge's model for a
+//! fresh, debatable motion, avoiding the topics debated recently.
+
+use ai_lib_rust::Message;
+
+use crate::types::{ClientBackend, ClientInfo};
+
+/// Topics the mock provider suggests in turn.
+const MOCK_TOPICS: &[&str] = &[
+    "人工智能应当被允许独立作出医疗诊断",
+    "城市应当全面禁止燃油车进入市中心",
+    "大学应当取消标准化入学考试",
+];
+
+/// Longest suggestion accepted, in characters.
+const MAX_TOPIC_CHARS: usize = 200;
+
+/// A new debate topic unlike any in `recent`.
+pub async fn suggest_topic(client: &ClientInfo, recent: &[String]) -> anyhow::Result<String> {
+    let live = match &client.backend {
+        ClientBackend::Live(live) => live,
+        ClientBackend::Mock => {
+            let topic = MOCK_TOPICS
+                .iter()
+                .find(|topic| !recent.iter().any(|r| r == *topic))
+                .unwrap_or(&MOCK_TOPICS[0]);
+            return Ok(topic.to_string());
+        }
+    };
+    let mut messages = vec![Message::system(
+        "You choose motions for a daily debate between two AI debaters. Suggest one motion that is \
+         timely, genuinely two-sided and arguable from evidence, phrased as a single declarative \
+         sentence in Chinese. Answer with the motion only, without quotes or explanation."
+            .to_string(),
+    )];
+    if !recent.is_empty() {
+        messages.push(Message::user(format!(
+            "Recent motions, do not repeat or paraphrase them:\n{}",
+            recent
+                .iter()
+                .map(|topic| format!("- {}", topic))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )));
+    }
+    messages.push(Message::user("Suggest today's motion.".to_string()));
+    let response = live
+        .chat()
+        .messages(messages)
+        .temperature(0.9)
+        .max_tokens(100)
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("Topic suggestion failed for {}: {}", client.name, e))?;
+    let topic = response
+        .content
+        .lines()
+        .map(|line| line.trim().trim_matches(['"', '“', '”', '「', '」']).trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    if topic.is_empty() || topic.chars().count() > MAX_TOPIC_CHARS {
+        anyhow::bail!("Unusable topic suggestion: {:?}", response.content);
+    }
+    Ok(topic.to_string())
+}
@@ -0,0 +1,113 @@
+//! Stored session summaries.
+//!
+//! When a debate completes, the judge's model writes a short summary of the
+//! debate and a one-line verdict blurb, saved on the session row so session
+//! lists and the podcast feed can show a preview without loading the
+//! transcript.
+
+use ai_lib_rust::Message;
+
+use crate::debate::Turn;
+use crate::prompts;
+use crate::storage::Store;
+use crate::types::{ClientBackend, ClientInfo, Position};
+
+/// Longest round excerpt sent to the summarizer.
+const MAX_ROUND_CHARS: usize = 1500;
+
+/// Longest stored blurb, in characters.
+const MAX_BLURB_CHARS: usize = 160;
+
+pub struct Summary {
+    pub summary: String,
+    pub blurb: String,
+}
+
+/// Summarize a finished debate with `client`.
+pub async fn summarize(
+    client: &ClientInfo,
+    topic: &str,
+    transcript: &[Turn],
+) -> anyhow::Result<Summary> {
+    let winner = transcript
+        .iter()
+        .rev()
+        .find(|(side, ..)| *side == Position::Judge)
+        .and_then(|(_, _, verdict, _)| prompts::parse_winner(verdict));
+    let live = match &client.backend {
+        ClientBackend::Live(live) => live,
+        ClientBackend::Mock => {
+            let blurb = match winner {
+                Some(side) => format!("{} wins the debate on {}", side.label(), topic),
+                None => format!("No clear winner on {}", topic),
+            };
+            return Ok(Summary {
+                summary: format!("Mock debate on {}.", topic),
+                blurb,
+            });
+        }
+    };
+    let rounds = transcript
+        .iter()
+        .map(|(side, phase, content, _)| {
+            let excerpt: String = content.chars().take(MAX_ROUND_CHARS).collect();
+            format!("## {} · {}\n{}", side.label(), phase.title(), excerpt)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let messages = vec![
+        Message::system(
+            "You summarize finished debates for a list of past sessions. Write in the language of the debate.\nAnswer in exactly two parts:\nSummary: <two or three sentences on each side's main arguments and why the judge decided as it did>\nBlurb: <one short line stating the outcome, at most 120 characters>".to_string(),
+        ),
+        Message::user(format!("Topic: {}\n\n{}", topic, rounds)),
+    ];
+    let response = live
+        .chat()
+        .messages(messages)
+        .temperature(0.3)
+        .max_tokens(400)
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("Summary call failed for {}: {}", client.name, e))?;
+    parse_summary(&response.content)
+        .ok_or_else(|| anyhow::anyhow!("Summary reply had no Summary/Blurb lines"))
+}
+
+/// Split a `Summary: ... Blurb: ...` reply.
+fn parse_summary(reply: &str) -> Option<Summary> {
+    let field = |name: &str| {
+        let start = reply.find(name)? + name.len();
+        let rest = reply[start..].trim_start_matches([':', '：', ' ', '*']);
+        let end = ["Summary", "Blurb"]
+            .iter()
+            .filter_map(|next| rest.find(next))
+            .min()
+            .unwrap_or(rest.len());
+        Some(rest[..end].trim().trim_end_matches('*').trim().to_string())
+    };
+    let summary = field("Summary")?;
+    let blurb: String = field("Blurb")?
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .take(MAX_BLURB_CHARS)
+        .collect();
+    if summary.is_empty() || blurb.is_empty() {
+        return None;
+    }
+    Some(Summary { summary, blurb })
+}
+
+/// Summarize a finished debate and store the result on its session row.
+pub async fn summarize_session(
+    db: &dyn Store,
+    client: &ClientInfo,
+    session_id: &str,
+    topic: &str,
+    transcript: &[Turn],
+) -> anyhow::Result<()> {
+    let summary = summarize(client, topic, transcript).await?;
+    db.save_session_summary(session_id, &summary.summary, &summary.blurb)
+        .await
+}
@@ -0,0 +1,345 @@
+//! Prompt templates for the debaters and the judge.
+//!
+//! The system prompts are templates with `{variable}` placeholders. Admins
+//! store new versions through `/admin/prompt-templates`; a debate uses the
+//! latest version of each template when it starts (version 0 is the built-in
+//! default) and records the versions on its session, so it can be reproduced
+//! later even after the templates have changed.
+//!
+//! To compare template versions, admins define two or more prompt variants,
+//! each naming some template versions. Every new debate is then assigned to
+//! a variant at random, the variant is recorded on its session row, and
+//! `/stats` reports the verdicts per variant.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::prompts::{self, VerdictConfidence};
+use crate::storage::Store;
+use crate::types::Position;
+
+/// System prompt of the Pro and Con debaters.
+pub const SIDE: &str = "side";
+/// System prompt of the judge.
+pub const JUDGE: &str = "judge";
+
+pub const NAMES: &[&str] = &[SIDE, JUDGE];
+
+const SIDE_VARIABLES: &[&str] = &[
+    "stance",
+    "topic",
+    "phase_goal",
+    "preset_instruction",
+    "tool_instruction",
+];
+
+const JUDGE_VARIABLES: &[&str] = &["topic", "rubric", "tool_instruction"];
+
+const DEFAULT_SIDE: &str = "{stance}\n议题：{topic}\n当前阶段：{phase_goal}\n要求：\n- 用 Markdown 输出。\n- 必须包含 `## Reasoning`（推理过程，精简列点）和 `## Final Position`（本轮结论）。\n- 语言简洁有力，避免重复。\n- 字数建议 120-220 中文字。{preset_instruction}{tool_instruction}\n";
+
+const DEFAULT_JUDGE: &str = "你是中立裁判，请根据完整辩论记录做出裁决。\n议题：{topic}\n要求：\n- 用 Markdown 输出。\n- 必须包含 `## Reasoning`（裁判推理过程，条理清晰）和 `## Verdict`（结论）。\n- 在结论中用 `Winner: Pro` 或 `Winner: Con` 指明胜方，并在下一行用 `Confidence: <0-100>` 给出你对该裁决的整体把握。\n- 最后用 `## Contested Claims` 列出 2-5 个双方争议最大的关键主张，每行格式为 `- [Pro] 主张 — Confidence: <0-100>`（方括号内为提出该主张的一方，数值为你认为该主张成立的把握）。\n- 评估证据时考虑其时效性：较新的、注明日期的资料优先于过时或未注明日期的资料。\n{rubric}{tool_instruction}- 简洁客观，避免复读。\n";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PromptTemplate {
+    pub name: String,
+    /// 0 for the built-in default.
+    pub version: i64,
+    pub body: String,
+    /// When the version was stored; empty for the built-in default.
+    pub created_at: String,
+}
+
+/// The templates a debate runs with.
+#[derive(Debug, Clone)]
+pub struct PromptTemplates {
+    pub side: PromptTemplate,
+    pub judge: PromptTemplate,
+    /// Prompt variant the debate was assigned to, during an A/B test.
+    pub variant: Option<String>,
+}
+
+impl Default for PromptTemplates {
+    fn default() -> Self {
+        Self {
+            side: builtin(SIDE).expect("built-in side template"),
+            judge: builtin(JUDGE).expect("built-in judge template"),
+            variant: None,
+        }
+    }
+}
+
+/// A set of template versions compared in an A/B test.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptVariant {
+    pub name: String,
+    /// Versions by template name; templates left out use their latest
+    /// version.
+    #[serde(default)]
+    pub templates: BTreeMap<String, i64>,
+}
+
+/// Verdicts of the debates assigned to one prompt variant.
+#[derive(Debug, Default, Serialize)]
+pub struct VariantStats {
+    pub variant: String,
+    pub debates: u64,
+    /// Debates with a verdict.
+    pub judged: u64,
+    pub pro_wins: u64,
+    pub con_wins: u64,
+    /// Verdicts naming no winner.
+    pub no_winner: u64,
+    /// Share of decided verdicts won by Pro.
+    pub pro_win_rate: Option<f64>,
+    /// Mean overall confidence the judge stated in its verdicts, 0-100.
+    pub mean_confidence: Option<f64>,
+}
+
+impl PromptTemplates {
+    /// `(name, version)` of each template.
+    pub fn versions(&self) -> [(&str, i64); 2] {
+        [(SIDE, self.side.version), (JUDGE, self.judge.version)]
+    }
+
+    /// Versions for the replay cache key; `None` while every template is the
+    /// built-in default.
+    pub fn cache_tag(&self) -> Option<String> {
+        let versions = self.versions();
+        versions.iter().any(|(_, version)| *version > 0).then(|| {
+            versions
+                .iter()
+                .map(|(name, version)| format!("{}={}", name, version))
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+    }
+}
+
+/// Variables a template may use.
+pub fn variables(name: &str) -> Option<&'static [&'static str]> {
+    match name {
+        SIDE => Some(SIDE_VARIABLES),
+        JUDGE => Some(JUDGE_VARIABLES),
+        _ => None,
+    }
+}
+
+/// The built-in default of a template.
+pub fn builtin(name: &str) -> Option<PromptTemplate> {
+    let body = match name {
+        SIDE => DEFAULT_SIDE,
+        JUDGE => DEFAULT_JUDGE,
+        _ => return None,
+    };
+    Some(PromptTemplate {
+        name: name.to_string(),
+        version: 0,
+        body: body.to_string(),
+        created_at: String::new(),
+    })
+}
+
+/// The latest stored version of each template, or its built-in default.
+pub async fn load(db: &dyn Store) -> PromptTemplates {
+    let latest = |name: &'static str| async move {
+        db.fetch_latest_prompt_template(name)
+            .await
+            .or_else(|| builtin(name))
+            .expect("known template name")
+    };
+    PromptTemplates {
+        side: latest(SIDE).await,
+        judge: latest(JUDGE).await,
+        variant: None,
+    }
+}
+
+/// Templates for a new debate: while prompt variants are defined, those of a
+/// variant picked at random, otherwise the latest.
+pub async fn assign(db: &dyn Store) -> PromptTemplates {
+    let mut templates = load(db).await;
+    let Some(variant) = db.fetch_random_prompt_variant().await else {
+        return templates;
+    };
+    for (name, version) in &variant.templates {
+        let template = match version {
+            0 => builtin(name),
+            _ => db.fetch_prompt_template(name, *version).await,
+        };
+        match (name.as_str(), template) {
+            (SIDE, Some(template)) => templates.side = template,
+            (JUDGE, Some(template)) => templates.judge = template,
+            _ => {}
+        }
+    }
+    templates.variant = Some(variant.name);
+    templates
+}
+
+/// The templates a session ran with; templates it did not record use their
+/// built-in default.
+pub async fn for_session(db: &dyn Store, session_id: &str) -> PromptTemplates {
+    let mut templates = PromptTemplates::default();
+    for (name, version) in db.fetch_session_templates(session_id).await {
+        let Some(template) = db.fetch_prompt_template(&name, version).await else {
+            continue;
+        };
+        match name.as_str() {
+            SIDE => templates.side = template,
+            JUDGE => templates.judge = template,
+            _ => {}
+        }
+    }
+    templates
+}
+
+/// Check a new version of a template: it must use `{topic}` and no unknown
+/// variables.
+pub fn validate(name: &str, body: &str) -> Result<(), String> {
+    let Some(known) = variables(name) else {
+        return Err(format!(
+            "unknown template {:?}; expected one of {}",
+            name,
+            NAMES.join(", ")
+        ));
+    };
+    if body.trim().is_empty() {
+        return Err("body must not be empty".to_string());
+    }
+    let used = placeholders(body);
+    if let Some(unknown) = used.iter().find(|v| !known.contains(v)) {
+        return Err(format!(
+            "unknown variable {{{}}}; available: {}",
+            unknown,
+            known.join(", ")
+        ));
+    }
+    if !used.contains(&"topic") {
+        return Err("body must use {topic}".to_string());
+    }
+    Ok(())
+}
+
+/// Check the variants of a new A/B test: none at all (ending the test), or
+/// at least two with distinct names, each naming known template versions.
+pub async fn validate_variants(db: &dyn Store, variants: &[PromptVariant]) -> Result<(), String> {
+    if variants.len() == 1 {
+        return Err("an A/B test needs at least two variants".to_string());
+    }
+    let mut names = HashSet::new();
+    for variant in variants {
+        let valid_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+        if variant.name.is_empty()
+            || variant.name.len() > 64
+            || !variant.name.chars().all(valid_char)
+        {
+            return Err(format!(
+                "invalid variant name {:?}: expected 1-64 characters of A-Z a-z 0-9 _ -",
+                variant.name
+            ));
+        }
+        if !names.insert(variant.name.as_str()) {
+            return Err(format!("duplicate variant {:?}", variant.name));
+        }
+        for (name, version) in &variant.templates {
+            if variables(name).is_none() {
+                return Err(format!(
+                    "unknown template {:?}; expected one of {}",
+                    name,
+                    NAMES.join(", ")
+                ));
+            }
+            if *version != 0 && db.fetch_prompt_template(name, *version).await.is_none() {
+                return Err(format!("{} template has no version {}", name, version));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Verdicts per prompt variant over the debates assigned to one, optionally
+/// only those in `category`.
+pub async fn variant_stats(db: &dyn Store, category: Option<&str>) -> Vec<VariantStats> {
+    let rows = db
+        .fetch_variant_verdicts(prompts::CONFIDENCE_ANALYSIS_KIND, category)
+        .await;
+    let mut variants: BTreeMap<String, (VariantStats, u64, u64)> = BTreeMap::new();
+    for (variant, verdict, confidence) in rows {
+        let (stats, confidence_sum, confidence_count) =
+            variants.entry(variant.clone()).or_insert_with(|| {
+                (
+                    VariantStats {
+                        variant,
+                        ..Default::default()
+                    },
+                    0,
+                    0,
+                )
+            });
+        stats.debates += 1;
+        let Some(verdict) = verdict else {
+            continue;
+        };
+        stats.judged += 1;
+        match prompts::parse_winner(&verdict) {
+            Some(Position::Pro) => stats.pro_wins += 1,
+            Some(Position::Con) => stats.con_wins += 1,
+            _ => stats.no_winner += 1,
+        }
+        let overall = confidence
+            .and_then(|c| serde_json::from_str::<VerdictConfidence>(&c).ok())
+            .and_then(|c| c.overall);
+        if let Some(overall) = overall {
+            *confidence_sum += u64::from(overall);
+            *confidence_count += 1;
+        }
+    }
+    let round = |v: f64| (v * 100.0).round() / 100.0;
+    variants
+        .into_values()
+        .map(|(mut stats, confidence_sum, confidence_count)| {
+            let decided = stats.pro_wins + stats.con_wins;
+            stats.pro_win_rate =
+                (decided > 0).then(|| round(stats.pro_wins as f64 / decided as f64));
+            stats.mean_confidence = (confidence_count > 0)
+                .then(|| round(confidence_sum as f64 / confidence_count as f64));
+            stats
+        })
+        .collect()
+}
+
+/// Replace each `{variable}` with its value. Braces around anything else are
+/// kept, and values are not expanded again.
+pub fn render(body: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let var = after
+            .find('}')
+            .and_then(|end| vars.iter().find(|(name, _)| *name == &after[..end]));
+        match var {
+            Some((name, value)) => {
+                out.push_str(value);
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Names in `{name}` placeholders, where a name is lowercase letters and `_`.
+fn placeholders(body: &str) -> Vec<&str> {
+    body.split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+        .filter(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c == '_'))
+        .collect()
+}
@@ -0,0 +1,113 @@
+//! Token counting for prompt budgeting and usage estimates.
+//!
+//! OpenAI models are counted with their real BPE vocabularies (tiktoken).
+//! Providers with Chinese-optimized vocabularies (DeepSeek, Zhipu, Qwen,
+//! Moonshot, MiniMax) use per-character ratios from their published
+//! guidance; other providers are approximated with `cl100k_base`. Without the
+//! `tiktoken` feature, BPE vocabularies are estimated at about four
+//! characters per token.
+
+#[cfg(feature = "tiktoken")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "tiktoken")]
+use tiktoken_rs::CoreBPE;
+
+pub trait TokenCounter: Send + Sync {
+    fn count(&self, text: &str) -> u32;
+}
+
+/// Exact counts from a tiktoken vocabulary.
+#[cfg(feature = "tiktoken")]
+pub struct Tiktoken(CoreBPE);
+
+#[cfg(feature = "tiktoken")]
+impl TokenCounter for Tiktoken {
+    fn count(&self, text: &str) -> u32 {
+        self.0.encode_ordinary(text).len() as u32
+    }
+}
+
+/// Estimate from per-character ratios, with CJK characters weighted
+/// separately from everything else.
+pub struct CharRatio {
+    pub cjk: f32,
+    pub other: f32,
+}
+
+impl TokenCounter for CharRatio {
+    fn count(&self, text: &str) -> u32 {
+        let tokens: f32 = text
+            .chars()
+            .map(|c| if is_cjk(c) { self.cjk } else { self.other })
+            .sum();
+        tokens.ceil() as u32
+    }
+}
+
+/// DeepSeek's guidance: ~0.6 tokens per Chinese character, ~0.3 per English
+/// character. Close enough for the other Chinese-first vocabularies too.
+static CHINESE_VOCAB: CharRatio = CharRatio {
+    cjk: 0.6,
+    other: 0.3,
+};
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3000}'..='\u{303F}'   // CJK punctuation
+        | '\u{3040}'..='\u{30FF}' // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}' // Extension A
+        | '\u{4E00}'..='\u{9FFF}' // Unified ideographs
+        | '\u{AC00}'..='\u{D7AF}' // Hangul
+        | '\u{F900}'..='\u{FAFF}' // Compatibility ideographs
+        | '\u{FF00}'..='\u{FFEF}' // Full-width forms
+        | '\u{20000}'..='\u{2FFFF}')
+}
+
+#[cfg(feature = "tiktoken")]
+fn cl100k() -> &'static Tiktoken {
+    static BPE: OnceLock<Tiktoken> = OnceLock::new();
+    BPE.get_or_init(|| Tiktoken(tiktoken_rs::cl100k_base().expect("cl100k_base vocabulary")))
+}
+
+#[cfg(feature = "tiktoken")]
+fn o200k() -> &'static Tiktoken {
+    static BPE: OnceLock<Tiktoken> = OnceLock::new();
+    BPE.get_or_init(|| Tiktoken(tiktoken_rs::o200k_base().expect("o200k_base vocabulary")))
+}
+
+#[cfg(not(feature = "tiktoken"))]
+static BPE_ESTIMATE: CharRatio = CharRatio {
+    cjk: 1.0,
+    other: 0.25,
+};
+
+#[cfg(not(feature = "tiktoken"))]
+fn cl100k() -> &'static CharRatio {
+    &BPE_ESTIMATE
+}
+
+#[cfg(not(feature = "tiktoken"))]
+fn o200k() -> &'static CharRatio {
+    &BPE_ESTIMATE
+}
+
+/// Token counter for a `provider/model` id.
+pub fn counter_for_model(model_id: &str) -> &'static dyn TokenCounter {
+    let (provider, model) = model_id.split_once('/').unwrap_or((model_id, ""));
+    match provider {
+        "deepseek" | "zhipu" | "qwen" | "moonshot" | "minimax" => &CHINESE_VOCAB,
+        "openai"
+            if model.starts_with("gpt-3.5") || model.starts_with("gpt-4-") || model == "gpt-4" =>
+        {
+            cl100k()
+        }
+        "openai" => o200k(),
+        _ => cl100k(),
+    }
+}
+
+/// Count tokens in `text` as seen by `model_id` (at least 1).
+pub fn count_tokens(model_id: &str, text: &str) -> u32 {
+    counter_for_model(model_id).count(text).max(1)
+}
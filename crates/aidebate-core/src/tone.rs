@@ -0,0 +1,151 @@
+//! Tone metrics per debate round.
+//!
+//! With `TONE_ANALYSIS=1`, every Pro and Con round of a finished debate is
+//! scored by the judge's model for aggressiveness, hedging and confidence,
+//! each from 0 to 1, in one short call per round. Scores are stored together
+//! with the model that wrote the round, so `/stats` can average them per
+//! model and rhetorical styles can be compared across providers.
+
+use ai_lib_rust::Message;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::debate::Turn;
+use crate::storage::Store;
+use crate::types::{ClientBackend, ClientInfo, DebatePhase, Position};
+
+/// Stored `debate_analysis` kind for tone metrics.
+pub const ANALYSIS_KIND: &str = "tone";
+
+/// Longest round excerpt sent for scoring.
+const MAX_ROUND_CHARS: usize = 4000;
+
+/// Whether finished debates are scored for tone.
+pub fn enabled() -> bool {
+    std::env::var("TONE_ANALYSIS").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tone {
+    /// Hostility towards the opponent, 0 (courteous) to 1 (combative).
+    pub aggressiveness: f64,
+    /// Qualified, tentative wording, 0 (none) to 1 (pervasive).
+    pub hedging: f64,
+    /// Assertiveness of the claims, 0 (unsure) to 1 (certain).
+    pub confidence: f64,
+    /// Model that wrote the round.
+    #[serde(default)]
+    pub provider: String,
+}
+
+/// Score one round.
+pub async fn analyze(
+    analyzer: &ClientInfo,
+    side: Position,
+    phase: DebatePhase,
+    content: &str,
+) -> anyhow::Result<Tone> {
+    let client = match &analyzer.backend {
+        ClientBackend::Live(client) => client,
+        ClientBackend::Mock => {
+            return Ok(Tone {
+                aggressiveness: 0.5,
+                hedging: 0.5,
+                confidence: 0.5,
+                provider: String::new(),
+            })
+        }
+    };
+    let excerpt: String = content.chars().take(MAX_ROUND_CHARS).collect();
+    let messages = vec![
+        Message::system(
+            "You rate the tone of one debate speech. Answer with a JSON object and nothing else: \
+             {\"aggressiveness\": 0.0-1.0, \"hedging\": 0.0-1.0, \"confidence\": 0.0-1.0}.\n\
+             - aggressiveness: hostility towards the opponent, from courteous (0) to combative (1)\n\
+             - hedging: qualified, tentative wording, from none (0) to pervasive (1)\n\
+             - confidence: assertiveness of the claims, from unsure (0) to certain (1)"
+                .to_string(),
+        ),
+        Message::user(format!(
+            "Speaker: {} ({})\n\n{}",
+            side.label(),
+            phase.as_str(),
+            excerpt
+        )),
+    ];
+    let response = client
+        .chat()
+        .messages(messages)
+        .temperature(0.0)
+        .max_tokens(60)
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("Tone analysis failed for {}: {}", analyzer.name, e))?;
+    parse_tone(&response.content).ok_or_else(|| anyhow::anyhow!("Tone reply had no valid scores"))
+}
+
+/// Scores from the model's reply, clamped to 0..=1.
+fn parse_tone(reply: &str) -> Option<Tone> {
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => return None,
+    };
+    let tone: Tone = serde_json::from_str(json).ok()?;
+    let score = |v: f64| v.is_finite().then(|| v.clamp(0.0, 1.0));
+    Some(Tone {
+        aggressiveness: score(tone.aggressiveness)?,
+        hedging: score(tone.hedging)?,
+        confidence: score(tone.confidence)?,
+        provider: String::new(),
+    })
+}
+
+/// Score every Pro and Con round of a finished debate and store the results.
+pub async fn analyze_debate(
+    db: &dyn Store,
+    analyzer: &ClientInfo,
+    user_id: &str,
+    session_id: &str,
+    transcript: &[Turn],
+) {
+    let rounds: Vec<_> = transcript
+        .iter()
+        .filter(|(side, ..)| *side != Position::Judge)
+        .collect();
+    let scores = futures::future::join_all(
+        rounds
+            .iter()
+            .map(|(side, phase, content, _)| analyze(analyzer, *side, *phase, content)),
+    )
+    .await;
+    for ((side, phase, _, provider), score) in rounds.into_iter().zip(scores) {
+        let tone = match score {
+            Ok(tone) => Tone {
+                provider: provider.clone(),
+                ..tone
+            },
+            Err(e) => {
+                warn!(
+                    "Tone analysis of {} {} failed: {}",
+                    side.role_str(),
+                    phase.as_str(),
+                    e
+                );
+                continue;
+            }
+        };
+        if let Err(e) = db
+            .save_analysis(
+                user_id,
+                session_id,
+                side.role_str(),
+                phase.as_str(),
+                ANALYSIS_KIND,
+                &serde_json::json!(tone),
+            )
+            .await
+        {
+            warn!("Failed to save tone analysis: {}", e);
+        }
+    }
+}
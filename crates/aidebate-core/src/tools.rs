@@ -0,0 +1,640 @@
+//! Web search tool for evidence-backed debates.
+//!
+//! Searches go through the backend selected in `search` (Tavily by default).
+//! When no backend is configured, debates proceed without tool calling (no behavior change).
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ai_lib_rust::types::tool::{FunctionDefinition, ToolDefinition};
+use anyhow::Result;
+use serde_json::json;
+use tracing::info;
+
+use crate::app_metrics::Metrics;
+use crate::documents;
+use crate::fact_check;
+use crate::presets::Preset;
+use crate::rubric::{self, Rubric};
+use crate::search::{self, DateRange, DomainPolicy, SearchHit, SearchOptions};
+use crate::storage::Store;
+use crate::templates::PromptTemplates;
+use crate::types::ClientInfo;
+
+/// Passages returned per `search_documents` call.
+const DOCUMENT_HITS: usize = 4;
+
+/// Prefix of degraded tool results; these are never cached.
+const UNAVAILABLE: &str = "Search unavailable";
+
+/// Check if the web search tool is available (a search backend is configured).
+pub fn is_search_enabled() -> bool {
+    search::provider_from_env().is_some()
+}
+
+/// Check if the news search tool is available (the search backend supports news).
+pub fn is_news_search_enabled() -> bool {
+    search::provider_from_env()
+        .map(|p| p.supports_news())
+        .unwrap_or(false)
+}
+
+/// Max tool calls a debater may make in one round (`MAX_TOOL_CALLS_PER_ROUND`, default 2).
+pub fn max_tool_calls_per_round() -> usize {
+    std::env::var("MAX_TOOL_CALLS_PER_ROUND")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(2)
+}
+
+/// Max tool calls across a whole debate (`MAX_SEARCHES_PER_DEBATE`, default 12).
+pub fn max_searches_per_debate() -> usize {
+    std::env::var("MAX_SEARCHES_PER_DEBATE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(12)
+}
+
+/// Max time a single tool call may take (`TOOL_TIMEOUT_SECS`, default 20).
+pub fn tool_timeout() -> Duration {
+    let secs = std::env::var("TOOL_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(20);
+    Duration::from_secs(secs)
+}
+
+/// Cache key for a tool call: tool name plus arguments with case and
+/// whitespace normalized (object keys are already sorted by serde_json).
+fn cache_key(name: &str, args: &serde_json::Value) -> String {
+    let args = args.to_string().to_lowercase();
+    format!(
+        "{}:{}",
+        name,
+        args.split_whitespace().collect::<Vec<_>>().join(" ")
+    )
+}
+
+/// Per-debate context available to tool executions.
+#[derive(Clone)]
+pub struct ToolContext {
+    pub db: Arc<dyn Store>,
+    pub user_id: String,
+    pub session_id: String,
+    /// Model used by the `fact_check` tool.
+    pub fact_checker: ClientInfo,
+    /// Tool calls left for the whole debate, shared by every round.
+    pub searches_remaining: Arc<AtomicUsize>,
+    /// Result count and depth for web/news searches in this debate.
+    pub search_options: SearchOptions,
+    /// Verdict of a similar earlier debate, shown to the judge for reference.
+    pub precedent: Option<String>,
+    /// Repetitions and self-contradictions found in the debate, for the judge.
+    pub consistency: Option<String>,
+    /// Format and style for the debaters and rubric for the judge.
+    pub preset: Option<&'static Preset>,
+    /// Weighted rubric requested for this debate, in place of the operator's.
+    pub rubric: Option<Rubric>,
+    /// Debaters are reminded their side is assigned; set when a round is
+    /// retried after a refusal.
+    pub softened: bool,
+    /// System prompt templates, fixed when the debate starts.
+    pub templates: Arc<PromptTemplates>,
+    /// Seed of a reproducible debate; its debater and judge rounds run at
+    /// temperature 0.
+    pub seed: Option<u64>,
+    /// Language the debaters and the judge write in, when not the prompts'.
+    pub language: Option<String>,
+    pub metrics: Arc<dyn Metrics>,
+    /// Results of tool calls already made in this debate, keyed by `cache_key`.
+    search_cache: Arc<Mutex<HashMap<String, SearchResult>>>,
+}
+
+impl ToolContext {
+    pub fn new(
+        db: Arc<dyn Store>,
+        user_id: String,
+        session_id: String,
+        fact_checker: ClientInfo,
+        metrics: Arc<dyn Metrics>,
+    ) -> Self {
+        Self {
+            db,
+            user_id,
+            session_id,
+            fact_checker,
+            searches_remaining: Arc::new(AtomicUsize::new(max_searches_per_debate())),
+            search_options: SearchOptions::from_env(),
+            precedent: None,
+            consistency: None,
+            preset: None,
+            rubric: None,
+            softened: false,
+            templates: Arc::new(PromptTemplates::default()),
+            seed: None,
+            language: None,
+            metrics,
+            search_cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Prior result for an identical tool call in this debate, marked as reused.
+    pub fn cached_result(&self, name: &str, args: &serde_json::Value) -> Option<SearchResult> {
+        let cache = self.search_cache.lock().unwrap();
+        cache.get(&cache_key(name, args)).map(|prior| SearchResult {
+            query: prior.query.clone(),
+            results: format!(
+                "(Already searched earlier in this debate; reusing the previous result. Do not repeat this search.)\n\n{}",
+                prior.results
+            ),
+            sources: prior.sources.clone(),
+        })
+    }
+
+    fn cache_result(&self, name: &str, args: &serde_json::Value, result: &SearchResult) {
+        self.search_cache
+            .lock()
+            .unwrap()
+            .insert(cache_key(name, args), result.clone());
+    }
+
+    /// Count a tool call that failed or timed out.
+    fn record_degradation(&self, tool: &str) {
+        self.metrics
+            .incr_labeled("tool_degraded_total", &[("tool", tool)], 1);
+    }
+
+    /// Override search settings for this debate.
+    pub fn with_search_options(mut self, search_options: SearchOptions) -> Self {
+        self.search_options = search_options;
+        self
+    }
+
+    /// Show the judge an earlier verdict on a similar topic.
+    pub fn with_precedent(mut self, precedent: Option<String>) -> Self {
+        self.precedent = precedent;
+        self
+    }
+
+    /// Show the judge the repetitions and self-contradictions found.
+    pub fn with_consistency(mut self, consistency: Option<String>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    /// Argue and judge the debate by a preset.
+    pub fn with_preset(mut self, preset: Option<&'static Preset>) -> Self {
+        self.preset = preset;
+        self
+    }
+
+    /// Judge the debate by a weighted rubric.
+    pub fn with_rubric(mut self, rubric: Option<Rubric>) -> Self {
+        self.rubric = rubric;
+        self
+    }
+
+    /// Weighted rubric the judge scores by: the requested one, else the
+    /// operator's (`JUDGE_RUBRIC`) unless a preset brings its own rubric.
+    pub fn judging_rubric(&self) -> Option<&Rubric> {
+        self.rubric
+            .as_ref()
+            .or_else(|| self.preset.is_none().then(rubric::configured).flatten())
+    }
+
+    /// Run the debate with these prompt templates instead of the built-in
+    /// defaults.
+    pub fn with_templates(mut self, templates: PromptTemplates) -> Self {
+        self.templates = Arc::new(templates);
+        self
+    }
+
+    /// Remind the debaters that their side is assigned, to retry a refused
+    /// round.
+    pub fn with_softened_framing(mut self, softened: bool) -> Self {
+        self.softened = softened;
+        self
+    }
+
+    /// Make the debate reproducible: rounds run at temperature 0.
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Whether the debate-wide search budget has any calls left.
+    pub fn has_search_budget(&self) -> bool {
+        self.searches_remaining.load(Ordering::Relaxed) > 0
+    }
+
+    /// Take one call from the debate-wide budget; false when exhausted.
+    pub fn consume_search(&self) -> bool {
+        self.searches_remaining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            .is_ok()
+    }
+}
+
+/// Tool definitions available in this debate (empty when no tool is enabled).
+/// The engine narrows this per role with `tools_for_role`.
+pub async fn available_tools(ctx: &ToolContext) -> Vec<ToolDefinition> {
+    let mut tools = Vec::new();
+    if is_search_enabled() {
+        tools.push(search_tool_definition());
+        tools.push(fact_check_tool_definition());
+    }
+    if is_news_search_enabled() {
+        tools.push(news_search_tool_definition());
+    }
+    if ctx
+        .db
+        .session_has_documents(&ctx.user_id, &ctx.session_id)
+        .await
+    {
+        tools.push(search_documents_tool_definition());
+    }
+    tools
+}
+
+/// Build the tool definition for web search (OpenAI-compatible function schema).
+pub fn search_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        tool_type: "function".to_string(),
+        function: FunctionDefinition {
+            name: "web_search".to_string(),
+            description: Some(
+                "Search the web for factual evidence, statistics, news, or data to support your argument. Use specific, factual queries."
+                    .to_string(),
+            ),
+            parameters: Some(json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "Search query - be specific and factual, e.g. 'AI job displacement statistics 2025'"
+                    }
+                },
+                "required": ["query"]
+            })),
+        },
+    }
+}
+
+/// Build the tool definition for news search with an optional date range.
+pub fn news_search_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        tool_type: "function".to_string(),
+        function: FunctionDefinition {
+            name: "news_search".to_string(),
+            description: Some(
+                "Search recent news coverage for current events. Optionally restrict to a date range; results include publication dates."
+                    .to_string(),
+            ),
+            parameters: Some(json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "News search query, e.g. 'EU AI Act enforcement'"
+                    },
+                    "days": {
+                        "type": "integer",
+                        "description": "Only include news from the last N days (1-365)"
+                    },
+                    "start_date": {
+                        "type": "string",
+                        "description": "Earliest publication date, YYYY-MM-DD"
+                    },
+                    "end_date": {
+                        "type": "string",
+                        "description": "Latest publication date, YYYY-MM-DD"
+                    }
+                },
+                "required": ["query"]
+            })),
+        },
+    }
+}
+
+/// Human-readable description of a tool call's arguments (query or claim).
+pub fn call_summary(args: &serde_json::Value) -> String {
+    ["query", "claim"]
+        .iter()
+        .find_map(|key| args.get(*key).and_then(|v| v.as_str()))
+        .map(String::from)
+        .unwrap_or_else(|| args.to_string())
+}
+
+/// Short summary of a tool result for progress events: source titles, or the
+/// start of the formatted results when there are no sources.
+pub fn result_summary(result: &SearchResult) -> String {
+    if result.sources.is_empty() {
+        return result.results.chars().take(160).collect();
+    }
+    let titles = result
+        .sources
+        .iter()
+        .map(|h| h.title.as_str())
+        .collect::<Vec<_>>()
+        .join("; ");
+    format!("{} source(s): {}", result.sources.len(), titles)
+}
+
+/// Tool names a role may use, or `None` for every available tool.
+///
+/// `PRO_TOOLS` / `CON_TOOLS` / `JUDGE_TOOLS` (comma-separated, `none` for no
+/// tools) override the defaults: debaters may use everything, the judge only
+/// `fact_check` and only when `JUDGE_FACT_CHECK=1`.
+pub fn allowed_tools(role: &str) -> Option<Vec<String>> {
+    let key = format!("{}_TOOLS", role.to_uppercase());
+    if let Some(list) = std::env::var(&key).ok().filter(|v| !v.trim().is_empty()) {
+        return Some(
+            list.split(',')
+                .map(|t| t.trim().to_lowercase())
+                .filter(|t| !t.is_empty() && t != "none")
+                .collect(),
+        );
+    }
+    match role {
+        "judge" if fact_check::judge_fact_check_enabled() => Some(vec!["fact_check".to_string()]),
+        "judge" => Some(vec![]),
+        _ => None,
+    }
+}
+
+/// Restrict tool definitions to those the role is allowed to use.
+pub fn tools_for_role(role: &str, defs: Vec<ToolDefinition>) -> Vec<ToolDefinition> {
+    match allowed_tools(role) {
+        Some(allowed) => defs
+            .into_iter()
+            .filter(|d| allowed.iter().any(|a| *a == d.function.name))
+            .collect(),
+        None => defs,
+    }
+}
+
+/// Build the tool definition for checking a specific factual claim.
+pub fn fact_check_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        tool_type: "function".to_string(),
+        function: FunctionDefinition {
+            name: "fact_check".to_string(),
+            description: Some(
+                "Check a specific factual claim against web evidence. Returns supported, contradicted, or unclear with sources."
+                    .to_string(),
+            ),
+            parameters: Some(json!({
+                "type": "object",
+                "properties": {
+                    "claim": {
+                        "type": "string",
+                        "description": "One specific, checkable claim, e.g. 'Global EV sales exceeded 14 million in 2023'"
+                    }
+                },
+                "required": ["claim"]
+            })),
+        },
+    }
+}
+
+/// Build the tool definition for searching the session's uploaded documents.
+pub fn search_documents_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        tool_type: "function".to_string(),
+        function: FunctionDefinition {
+            name: "search_documents".to_string(),
+            description: Some(
+                "Search the documents supplied for this debate (papers, proposals, notes). Prefer this over web search when the debate is about the supplied material."
+                    .to_string(),
+            ),
+            parameters: Some(json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "What to look for in the supplied documents"
+                    }
+                },
+                "required": ["query"]
+            })),
+        },
+    }
+}
+
+/// Search result from a web search tool call.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub query: String,
+    pub results: String,
+    /// Structured hits, used for citation tracking.
+    pub sources: Vec<SearchHit>,
+}
+
+impl SearchResult {
+    /// Result returned when a tool fails or times out.
+    pub fn unavailable(query: String, reason: &str) -> Self {
+        Self {
+            query,
+            results: format!(
+                "{} ({}). Continue without this evidence and do not retry the same search.",
+                UNAVAILABLE, reason
+            ),
+            sources: vec![],
+        }
+    }
+
+    pub fn is_unavailable(&self) -> bool {
+        self.results.starts_with(UNAVAILABLE)
+    }
+}
+
+/// An executed tool call, persisted with the message it informed.
+#[derive(Debug, Clone)]
+pub struct ToolCallRecord {
+    pub name: String,
+    pub args: serde_json::Value,
+    pub result: SearchResult,
+    pub latency_ms: u64,
+    /// Backend that served the call (search provider, or `documents`).
+    pub provider: String,
+}
+
+/// Backend name recorded for a tool call.
+pub fn tool_provider(name: &str) -> String {
+    match name {
+        "search_documents" => "documents".to_string(),
+        _ => search::provider_name().unwrap_or("none").to_string(),
+    }
+}
+
+/// Execute a tool call requested by the model, bounded by `tool_timeout()`.
+/// Returns `None` for unknown tools or calls without usable arguments.
+///
+/// Failures and timeouts come back as a "search unavailable" result so the
+/// round continues unassisted; each one is counted as `tool_degraded`.
+pub async fn execute_tool_call(
+    ctx: &ToolContext,
+    name: &str,
+    args: &serde_json::Value,
+) -> Option<SearchResult> {
+    let timeout = tool_timeout();
+    match tokio::time::timeout(timeout, run_tool_call(ctx, name, args)).await {
+        Ok(result) => {
+            if let Some(result) = result.as_ref().filter(|r| !r.is_unavailable()) {
+                ctx.cache_result(name, args, result);
+            }
+            result
+        }
+        Err(_) => {
+            info!("Tool call {} timed out after {:?}", name, timeout);
+            ctx.record_degradation(name);
+            Some(SearchResult::unavailable(call_summary(args), "timed out"))
+        }
+    }
+}
+
+async fn run_tool_call(
+    ctx: &ToolContext,
+    name: &str,
+    args: &serde_json::Value,
+) -> Option<SearchResult> {
+    match name {
+        "web_search" => {
+            let query = args
+                .get("query")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .trim();
+            if query.is_empty() {
+                return None;
+            }
+            match execute_web_search(query, &ctx.search_options).await {
+                Ok(result) => Some(result),
+                Err(e) => {
+                    info!("Search failed for '{}': {}", query, e);
+                    ctx.record_degradation(name);
+                    Some(SearchResult::unavailable(query.to_string(), &e.to_string()))
+                }
+            }
+        }
+        "news_search" => {
+            let query = args
+                .get("query")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .trim();
+            if query.is_empty() {
+                return None;
+            }
+            let range = DateRange::from_args(args);
+            match execute_news_search(query, &range, &ctx.search_options).await {
+                Ok(result) => Some(result),
+                Err(e) => {
+                    info!("News search failed for '{}': {}", query, e);
+                    ctx.record_degradation(name);
+                    Some(SearchResult::unavailable(
+                        format!("news: {} ({})", query, range.label()),
+                        &e.to_string(),
+                    ))
+                }
+            }
+        }
+        "fact_check" => {
+            let claim = args
+                .get("claim")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .trim();
+            if claim.is_empty() {
+                return None;
+            }
+            info!("Fact check ({}): {}", ctx.fact_checker.model_id, claim);
+            Some(fact_check::check_claim(&ctx.fact_checker, claim, &ctx.search_options).await)
+        }
+        "search_documents" => {
+            let query = args
+                .get("query")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .trim();
+            if query.is_empty() {
+                return None;
+            }
+            info!("Document search ({}): {}", ctx.session_id, query);
+            let label = format!("documents: {}", query);
+            match documents::search_documents(
+                &ctx.db,
+                &ctx.user_id,
+                &ctx.session_id,
+                query,
+                DOCUMENT_HITS,
+            )
+            .await
+            {
+                Ok(hits) => Some(SearchResult {
+                    query: label,
+                    results: documents::format_hits(&hits),
+                    sources: vec![],
+                }),
+                Err(e) => {
+                    ctx.record_degradation(name);
+                    Some(SearchResult::unavailable(label, &e.to_string()))
+                }
+            }
+        }
+        _ => {
+            info!("Ignoring unknown tool call: {}", name);
+            None
+        }
+    }
+}
+
+/// Execute a web search via the configured search backend.
+pub async fn execute_web_search(query: &str, opts: &SearchOptions) -> Result<SearchResult> {
+    let provider = search::provider_from_env()
+        .ok_or_else(|| anyhow::anyhow!("No search provider configured"))?;
+
+    info!("Web search ({}): {}", provider.name(), query);
+
+    let mut resp = provider.search(query, opts).await?;
+    resp.hits = DomainPolicy::from_env().apply(resp.hits);
+
+    Ok(SearchResult {
+        query: query.to_string(),
+        results: search::format_response(&resp),
+        sources: resp.hits,
+    })
+}
+
+/// Execute a news search within a date range via the configured search backend.
+pub async fn execute_news_search(
+    query: &str,
+    range: &DateRange,
+    opts: &SearchOptions,
+) -> Result<SearchResult> {
+    let provider = search::provider_from_env()
+        .ok_or_else(|| anyhow::anyhow!("No search provider configured"))?;
+
+    info!(
+        "News search ({}, {}): {}",
+        provider.name(),
+        range.label(),
+        query
+    );
+
+    let mut resp = provider.search_news(query, opts, range).await?;
+    resp.hits = DomainPolicy::from_env().apply(resp.hits);
+
+    Ok(SearchResult {
+        query: format!("news: {} ({})", query, range.label()),
+        results: search::format_response(&resp),
+        sources: resp.hits,
+    })
+}
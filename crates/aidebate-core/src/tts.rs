@@ -0,0 +1,170 @@
+//! Text-to-speech rendering of debate transcripts.
+//!
+//! Uses an OpenAI-compatible speech endpoint (`TTS_API_URL`, default OpenAI)
+//! when `TTS_API_KEY` is set. Each side speaks with its own voice
+//! (`TTS_VOICE_PRO`, `TTS_VOICE_CON`, `TTS_VOICE_JUDGE`); rounds are rendered
+//! separately and the MP3 segments concatenated.
+
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::config;
+use crate::debate::Turn;
+use crate::subtitles::speakable;
+use crate::types::Position;
+
+const DEFAULT_TTS_URL: &str = "https://api.openai.com/v1/audio/speech";
+const DEFAULT_TTS_MODEL: &str = "tts-1";
+
+/// Longest input accepted by a single speech request.
+const MAX_INPUT_CHARS: usize = 4000;
+
+/// Whether speech synthesis is configured.
+pub fn enabled() -> bool {
+    std::env::var("TTS_API_KEY").is_ok_and(|v| !v.trim().is_empty())
+}
+
+fn voice(side: Position) -> String {
+    let (var, default) = match side {
+        Position::Pro => ("TTS_VOICE_PRO", "alloy"),
+        Position::Con => ("TTS_VOICE_CON", "onyx"),
+        Position::Judge => ("TTS_VOICE_JUDGE", "nova"),
+    };
+    std::env::var(var).unwrap_or_else(|_| default.to_string())
+}
+
+/// Render a transcript to MP3 audio, announcing each round before it.
+pub async fn render_transcript(topic: &str, transcript: &[Turn]) -> anyhow::Result<Vec<u8>> {
+    let mut audio = synthesize(Position::Judge, &format!("Debate topic: {}", topic)).await?;
+    for (side, phase, content, _) in transcript {
+        let script = format!(
+            "{}, {}.\n\n{}",
+            side.label(),
+            phase.title(),
+            speakable(content)
+        );
+        for chunk in chunks(&script, MAX_INPUT_CHARS) {
+            audio.extend(synthesize(*side, &chunk).await?);
+        }
+    }
+    Ok(audio)
+}
+
+async fn synthesize(side: Position, text: &str) -> anyhow::Result<Vec<u8>> {
+    let api_key =
+        std::env::var("TTS_API_KEY").map_err(|_| anyhow::anyhow!("TTS_API_KEY is not set"))?;
+    let url = std::env::var("TTS_API_URL").unwrap_or_else(|_| DEFAULT_TTS_URL.to_string());
+    let model = std::env::var("TTS_MODEL").unwrap_or_else(|_| DEFAULT_TTS_MODEL.to_string());
+    let resp = config::http_client()
+        .post(url)
+        .bearer_auth(api_key)
+        .timeout(Duration::from_secs(120))
+        .json(&json!({
+            "model": model,
+            "voice": voice(side),
+            "input": text,
+            "response_format": "mp3",
+        }))
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Speech request failed: {}", e))?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        anyhow::bail!(
+            "Speech request failed ({}): {}",
+            status,
+            body.chars().take(300).collect::<String>()
+        );
+    }
+    Ok(resp
+        .bytes()
+        .await
+        .map_err(|e| anyhow::anyhow!("Speech response read failed: {}", e))?
+        .to_vec())
+}
+
+/// Split `text` into pieces of at most `max_chars` characters, at line ends
+/// where possible.
+fn chunks(text: &str, max_chars: usize) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        let mut line = line;
+        while line.chars().count() > max_chars {
+            let end = line
+                .char_indices()
+                .nth(max_chars)
+                .map_or(line.len(), |(i, _)| i);
+            out.push(line[..end].to_string());
+            line = &line[end..];
+        }
+        if current.chars().count() + line.chars().count() + 1 > max_chars {
+            out.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    out.push(current);
+    out.retain(|chunk| !chunk.trim().is_empty());
+    out
+}
+
+/// Playing time of MPEG Layer III audio, from its frame headers.
+pub fn mp3_duration_secs(data: &[u8]) -> f64 {
+    const BITRATES_V1: [u32; 15] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ];
+    const BITRATES_V2: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+    let mut pos = 0;
+    let mut seconds = 0.0;
+    while pos + 4 <= data.len() {
+        // ID3v2 tags may precede (or, with concatenated segments, sit between) frames
+        if data[pos..].starts_with(b"ID3") && pos + 10 <= data.len() {
+            let size = data[pos + 6..pos + 10]
+                .iter()
+                .fold(0usize, |acc, b| (acc << 7) | (*b as usize & 0x7f));
+            pos += 10 + size;
+            continue;
+        }
+        let (b1, b2) = (data[pos + 1], data[pos + 2]);
+        let version = (b1 >> 3) & 0x03;
+        let layer = (b1 >> 1) & 0x03;
+        let bitrate_index = (b2 >> 4) as usize;
+        let rate_index = ((b2 >> 2) & 0x03) as usize;
+        let is_frame = data[pos] == 0xff
+            && b1 & 0xe0 == 0xe0
+            && version != 1
+            && layer == 1
+            && (1..15).contains(&bitrate_index)
+            && rate_index < 3;
+        if !is_frame {
+            pos += 1;
+            continue;
+        }
+        let (bitrate, sample_rate, samples) = match version {
+            3 => (
+                BITRATES_V1[bitrate_index],
+                [44100, 48000, 32000][rate_index],
+                1152,
+            ),
+            2 => (
+                BITRATES_V2[bitrate_index],
+                [22050, 24000, 16000][rate_index],
+                576,
+            ),
+            _ => (
+                BITRATES_V2[bitrate_index],
+                [11025, 12000, 8000][rate_index],
+                576,
+            ),
+        };
+        let padding = ((b2 >> 1) & 0x01) as usize;
+        let frame_len = (samples / 8 * bitrate as usize * 1000) / sample_rate as usize + padding;
+        seconds += samples as f64 / sample_rate as f64;
+        pos += frame_len.max(1);
+    }
+    seconds
+}
@@ -0,0 +1,564 @@
+use std::sync::Arc;
+
+use ai_lib_rust::AiClient;
+use serde::{Deserialize, Serialize};
+
+use crate::rubric::Criterion;
+use crate::templates::PromptVariant;
+
+/// Backend serving a role: a real provider client or the built-in mock.
+#[derive(Clone)]
+pub enum ClientBackend {
+    Live(Arc<AiClient>),
+    Mock,
+}
+
+#[derive(Clone)]
+pub struct ClientInfo {
+    pub name: String,
+    pub model_id: String,
+    pub backend: ClientBackend,
+}
+
+impl ClientInfo {
+    /// Client info backed by the offline mock provider.
+    pub fn mock(role: &str) -> Self {
+        Self {
+            name: "mock".to_string(),
+            model_id: format!("mock/{}", role),
+            backend: ClientBackend::Mock,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DebateRequest {
+    /// Ignored in favor of the token subject when JWT auth is enabled.
+    #[serde(default)]
+    pub user_id: String,
+    pub session_id: String,
+    /// Taken from the collection item when `collection_item` is set.
+    #[serde(default)]
+    pub topic: String,
+    /// Optional model override for Pro side (e.g. "deepseek/deepseek-chat")
+    pub pro_model: Option<String>,
+    /// Optional model override for Con side (e.g. "zhipu/glm-4-plus")
+    pub con_model: Option<String>,
+    /// Optional model override for Judge (e.g. "groq/llama-3.3-70b-versatile")
+    pub judge_model: Option<String>,
+    /// Optional preset id (see `GET /presets`) setting the debate format,
+    /// style and judging rubric
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Optional search depth override ("basic" or "advanced")
+    pub search_depth: Option<String>,
+    /// Optional results-per-search override (1-10)
+    pub search_max_results: Option<usize>,
+    /// Skip the replay cache and always run a fresh debate.
+    #[serde(default)]
+    pub no_cache: bool,
+    /// Email the transcript to this address when the debate completes
+    /// (requires SMTP configuration).
+    #[serde(default)]
+    pub notify_email: Option<String>,
+    /// Render the finished debate to audio and publish it to the podcast
+    /// feed (requires TTS configuration).
+    #[serde(default)]
+    pub podcast: bool,
+    /// After the verdict, review each Pro and Con round for logical fallacies,
+    /// streamed as `analysis` events.
+    #[serde(default)]
+    pub analyze_fallacies: bool,
+    /// After the verdict, stream an analysis of the missing evidence or
+    /// arguments most likely to have changed it, as an `outcome` phase.
+    #[serde(default)]
+    pub explain_outcome: bool,
+    /// Check each round against the side's earlier rounds for repetition and
+    /// self-contradiction, streamed as `warning` events and shown to the judge.
+    #[serde(default)]
+    pub check_consistency: bool,
+    /// Make the debate reproducible for benchmarks: the debaters and the
+    /// judge run at temperature 0, the replay cache and prompt A/B tests are
+    /// skipped, and the debate's inputs are recorded (see
+    /// `GET /sessions/{id}/inputs`).
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Language the debaters and the judge write in (e.g. "English"); the
+    /// prompts' language when omitted.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Weighted criteria the judge scores both sides on; the winner is the
+    /// side with the higher weighted total. Takes the place of a preset's
+    /// rubric and of `JUDGE_RUBRIC`.
+    #[serde(default)]
+    pub rubric: Option<Vec<Criterion>>,
+    /// Id of an item of one of the user's topic collections (see
+    /// `/collections`) to debate; the debate counts towards the collection's
+    /// progress.
+    #[serde(default)]
+    pub collection_item: Option<i64>,
+}
+
+/// Query parameters for `POST /debate/stream`.
+#[derive(Deserialize)]
+pub struct StreamQuery {
+    /// Comma-separated event types to send (e.g. `phase_start,phase_done`);
+    /// all of them when omitted.
+    #[serde(default)]
+    pub events: Option<String>,
+}
+
+/// Defaults a user saved for their debates (`/preferences`), applied to the
+/// fields a debate request leaves out.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UserPreferences {
+    #[serde(default)]
+    pub pro_model: Option<String>,
+    #[serde(default)]
+    pub con_model: Option<String>,
+    #[serde(default)]
+    pub judge_model: Option<String>,
+    /// Preset id, setting the debate format and speaking style.
+    #[serde(default)]
+    pub preset: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+impl UserPreferences {
+    /// Fill in the fields `request` leaves out or leaves empty.
+    pub fn apply(&self, request: &mut DebateRequest) {
+        fn fill(field: &mut Option<String>, preference: &Option<String>) {
+            if field.as_deref().is_none_or(|v| v.trim().is_empty()) {
+                field.clone_from(preference);
+            }
+        }
+        fill(&mut request.pro_model, &self.pro_model);
+        fill(&mut request.con_model, &self.con_model);
+        fill(&mut request.judge_model, &self.judge_model);
+        fill(&mut request.preset, &self.preset);
+        fill(&mut request.language, &self.language);
+    }
+}
+
+/// Body of `PUT /preferences`; fields left out are cleared.
+#[derive(Deserialize)]
+pub struct PreferencesRequest {
+    #[serde(default)]
+    pub user_id: String,
+    #[serde(flatten)]
+    pub preferences: UserPreferences,
+}
+
+/// Body of `POST /collections` and `PUT /collections/{id}`.
+#[derive(Deserialize)]
+pub struct CollectionRequest {
+    #[serde(default)]
+    pub user_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The topics to debate, in order.
+    pub topics: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default)]
+    pub user_id: String,
+    pub session_id: String,
+}
+
+/// Query parameters for per-session resources (documents, etc.).
+#[derive(Deserialize)]
+pub struct SessionQuery {
+    #[serde(default)]
+    pub user_id: String,
+}
+
+/// Query parameters for `GET /sessions/{id}/subtitles`.
+#[derive(Deserialize)]
+pub struct SubtitleQuery {
+    #[serde(default)]
+    pub user_id: String,
+    /// `srt` (default) or `vtt`.
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Query parameters for `GET /sessions/{id}/export`.
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub user_id: String,
+    /// `json` (default), `markdown` or `html`.
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Query parameters for `GET /sessions/{id}/spectate`.
+#[derive(Deserialize)]
+pub struct SpectateQuery {
+    #[serde(default)]
+    pub user_id: String,
+    /// Comma-separated event types to send, as for `POST /debate/stream`.
+    #[serde(default)]
+    pub events: Option<String>,
+}
+
+/// Query parameters for `GET /sessions/{id}/highlights`.
+#[derive(Deserialize)]
+pub struct HighlightsQuery {
+    #[serde(default)]
+    pub user_id: String,
+    /// Pick the quotes again instead of returning the stored ones.
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// Query parameters for `GET /sessions/{id}/quiz`.
+#[derive(Deserialize)]
+pub struct QuizQuery {
+    #[serde(default)]
+    pub user_id: String,
+    /// `json` (default) or `markdown`.
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Write a new quiz instead of returning the stored one.
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// Body of `POST /sessions/{id}/rejudge`.
+#[derive(Deserialize)]
+pub struct RejudgeRequest {
+    #[serde(default)]
+    pub user_id: String,
+    /// Judge model for the new verdict; the default judge when omitted.
+    pub judge_model: Option<String>,
+}
+
+/// Body of `POST /sessions/{id}/edit`.
+#[derive(Deserialize)]
+pub struct EditRoundRequest {
+    #[serde(default)]
+    pub user_id: String,
+    /// Id of the Pro or Con round to replace, as in `/history`.
+    pub message_id: i64,
+    /// New text of the round.
+    pub content: String,
+}
+
+/// Query parameters for `POST /sessions/{id}/fork`.
+#[derive(Deserialize)]
+pub struct ForkQuery {
+    /// Last phase copied into the fork (`opening`, `rebuttal`, `defense` or
+    /// `closing`); the phases after it run again.
+    pub after_phase: String,
+}
+
+/// Body of `POST /sessions/{id}/fork`. Roles without a model keep the one
+/// they had in the original debate.
+#[derive(Deserialize)]
+pub struct ForkRequest {
+    #[serde(default)]
+    pub user_id: String,
+    pub pro_model: Option<String>,
+    pub con_model: Option<String>,
+    pub judge_model: Option<String>,
+}
+
+/// Body of `PATCH /sessions/{id}`; fields left out are unchanged, and an
+/// empty title or notes clears it.
+#[derive(Deserialize)]
+pub struct SessionMetadataRequest {
+    #[serde(default)]
+    pub user_id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// `private`, `shared` or `public`.
+    #[serde(default)]
+    pub visibility: Option<String>,
+}
+
+/// Query parameters for `GET /gallery` and `GET /admin/gallery`.
+#[derive(Deserialize)]
+pub struct GalleryQuery {
+    /// Number of debates to return (default 20, at most 100).
+    #[serde(default)]
+    pub limit: Option<i64>,
+    /// Only debates whose topic is tagged with this category.
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Curation status listed by `GET /admin/gallery` (default `nominated`).
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+/// Body of `PUT /admin/gallery/{id}`.
+#[derive(Deserialize)]
+pub struct GalleryReviewRequest {
+    /// `approved` or `rejected`.
+    pub status: String,
+}
+
+/// Body of `POST /gallery/{id}/vote`.
+#[derive(Deserialize)]
+pub struct GalleryVoteRequest {
+    #[serde(default)]
+    pub user_id: String,
+    /// `pro` or `con`.
+    pub side: String,
+}
+
+/// Body of `POST /sessions/{id}/ask-judge`.
+#[derive(Deserialize)]
+pub struct AskJudgeRequest {
+    #[serde(default)]
+    pub user_id: String,
+    pub question: String,
+}
+
+/// Query parameters for `GET /sessions`.
+#[derive(Deserialize)]
+pub struct SessionListQuery {
+    #[serde(default)]
+    pub user_id: String,
+    /// Number of sessions to return (default 20, at most 100).
+    #[serde(default)]
+    pub limit: Option<i64>,
+    /// Only sessions whose topic is tagged with this category.
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// Category filter for `/stats` and `/podcast.xml`.
+#[derive(Deserialize)]
+pub struct CategoryQuery {
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// Query parameters for `GET /analytics/arguments`.
+#[derive(Deserialize)]
+pub struct ArgumentAnalyticsQuery {
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Only debates on motions similar to this one.
+    #[serde(default)]
+    pub topic: Option<String>,
+    /// Lowest similarity to `topic` (default 0.6).
+    #[serde(default)]
+    pub min_score: Option<f32>,
+}
+
+/// Query parameters for `GET /leaderboard`.
+#[derive(Deserialize)]
+pub struct LeaderboardQuery {
+    #[serde(default)]
+    pub category: Option<String>,
+    /// First day counted (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Last day counted (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub to: Option<String>,
+    /// Only debates judged by this model.
+    #[serde(default)]
+    pub judge_model: Option<String>,
+}
+
+/// Query parameters for `GET /sessions/similar`.
+#[derive(Deserialize)]
+pub struct SimilarQuery {
+    #[serde(default)]
+    pub user_id: String,
+    #[serde(default)]
+    pub topic: String,
+    /// Number of debates to return (default 5, at most 20).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Query parameters for `GET /admin/audit`.
+#[derive(Deserialize)]
+pub struct AuditQuery {
+    pub user_id: Option<String>,
+    /// Return events with an id below this one (paging).
+    pub before: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// Query parameters for `GET /admin/judge-calibration`.
+#[derive(Deserialize)]
+pub struct JudgeCalibrationQuery {
+    /// Comma-separated judge models; the configured judge and
+    /// `CALIBRATION_JUDGE_MODELS` when omitted.
+    pub models: Option<String>,
+    pub category: Option<String>,
+    /// Most recent debates compared.
+    pub limit: Option<i64>,
+}
+
+/// Body of `PUT /admin/prompt-templates/{name}`.
+#[derive(Deserialize)]
+pub struct PromptTemplateRequest {
+    pub body: String,
+}
+
+/// Body of `PUT /admin/prompt-variants`.
+#[derive(Deserialize)]
+pub struct PromptVariantsRequest {
+    pub variants: Vec<PromptVariant>,
+}
+
+/// Query parameters for `POST /sessions/{id}/documents`; the body is the raw file.
+#[derive(Deserialize)]
+pub struct DocumentUploadQuery {
+    #[serde(default)]
+    pub user_id: String,
+    pub filename: String,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct HistoryMessage {
+    pub id: i64,
+    pub role: String,
+    pub phase: String,
+    pub provider: Option<String>,
+    pub content: String,
+    /// Written or changed by a person rather than generated.
+    pub human_edited: bool,
+    /// Tool calls executed while producing this message.
+    #[sqlx(skip)]
+    pub tool_calls: Vec<StoredToolCall>,
+}
+
+/// A persisted tool call, returned with its message in history.
+#[derive(Serialize, Default)]
+pub struct StoredToolCall {
+    pub name: String,
+    pub args: serde_json::Value,
+    pub query: String,
+    pub result: String,
+    pub sources: serde_json::Value,
+    pub latency_ms: i64,
+    pub provider: String,
+}
+
+/// Provider information returned by the /api/models endpoint.
+#[derive(Serialize, Clone)]
+pub struct AvailableProvider {
+    pub provider: String,
+    pub display_name: String,
+    pub env_var: String,
+    pub has_key: bool,
+    pub models: Vec<AvailableModel>,
+}
+
+/// Model information returned by the /api/models endpoint.
+#[derive(Serialize, Clone)]
+pub struct AvailableModel {
+    pub model_id: String,
+    pub display_name: String,
+}
+
+/// How a UI presents one debate role, returned by the /participants endpoint.
+#[derive(Serialize, Clone)]
+pub struct Participant {
+    /// `pro`, `con` or `judge`.
+    pub role: String,
+    pub model_id: String,
+    pub model_name: String,
+    pub provider: String,
+    pub provider_name: String,
+    /// Brand color as `#rrggbb`.
+    pub color: String,
+    /// URL of the provider's icon, if known.
+    pub logo: Option<String>,
+    pub capabilities: ModelCapabilities,
+}
+
+#[derive(Serialize, Clone, Copy)]
+pub struct ModelCapabilities {
+    /// Whether the model can call tools such as web search.
+    pub tools: bool,
+    /// Whether the model reasons before answering.
+    pub reasoning: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Position {
+    Pro,
+    Con,
+    Judge,
+}
+
+impl Position {
+    /// Parse a stored role string (`pro`, `con`, `judge`).
+    pub fn from_role(role: &str) -> Option<Self> {
+        match role {
+            "pro" => Some(Position::Pro),
+            "con" => Some(Position::Con),
+            "judge" => Some(Position::Judge),
+            _ => None,
+        }
+    }
+    pub fn role_str(&self) -> &'static str {
+        match self {
+            Position::Pro => "pro",
+            Position::Con => "con",
+            Position::Judge => "judge",
+        }
+    }
+    pub fn label(&self) -> &'static str {
+        match self {
+            Position::Pro => "Pro",
+            Position::Con => "Con",
+            Position::Judge => "Judge",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DebatePhase {
+    Opening,
+    Rebuttal,
+    Defense,
+    Closing,
+    Judgement,
+}
+
+impl DebatePhase {
+    /// Parse a stored phase string (see `as_str`).
+    pub fn from_name(phase: &str) -> Option<Self> {
+        match phase {
+            "opening" => Some(DebatePhase::Opening),
+            "rebuttal" => Some(DebatePhase::Rebuttal),
+            "defense" => Some(DebatePhase::Defense),
+            "closing" => Some(DebatePhase::Closing),
+            "judgement" => Some(DebatePhase::Judgement),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DebatePhase::Opening => "opening",
+            DebatePhase::Rebuttal => "rebuttal",
+            DebatePhase::Defense => "defense",
+            DebatePhase::Closing => "closing",
+            DebatePhase::Judgement => "judgement",
+        }
+    }
+    pub fn title(&self) -> &'static str {
+        match self {
+            DebatePhase::Opening => "一辩开篇",
+            DebatePhase::Rebuttal => "二辩反驳",
+            DebatePhase::Defense => "三辩防守",
+            DebatePhase::Closing => "总结陈词",
+            DebatePhase::Judgement => "裁判裁决",
+        }
+    }
+}
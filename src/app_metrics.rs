@@ -1,51 +0,0 @@
-use async_trait::async_trait;
-use std::sync::Arc;
-use std::time::Instant;
-
-/// Simple timer trait for measuring operation durations
-pub trait Timer: Send {
-    fn stop(self: Box<Self>);
-}
-
-/// Metrics trait for recording application metrics
-#[async_trait]
-pub trait Metrics: Send + Sync {
-    #[allow(dead_code)]
-    async fn incr_counter(&self, name: &str, value: u64);
-    async fn start_timer(&self, name: &str) -> Option<Box<dyn Timer + Send>>;
-}
-
-pub struct SimpleMetrics;
-
-pub struct SimpleTimer {
-    name: String,
-    start: Instant,
-}
-
-impl SimpleMetrics {
-    pub fn new() -> Arc<Self> {
-        Arc::new(Self)
-    }
-}
-
-impl Timer for SimpleTimer {
-    fn stop(self: Box<Self>) {
-        let elapsed = self.start.elapsed();
-        let ms = elapsed.as_secs_f64() * 1000.0;
-        println!("[metrics] timer {}: {:.2} ms", self.name, ms);
-    }
-}
-
-#[async_trait]
-impl Metrics for SimpleMetrics {
-    async fn incr_counter(&self, name: &str, value: u64) {
-        println!("[metrics] counter {} += {}", name, value);
-    }
-
-    async fn start_timer(&self, name: &str) -> Option<Box<dyn Timer + Send>> {
-        Some(Box::new(SimpleTimer {
-            name: name.to_string(),
-            start: Instant::now(),
-        }))
-    }
-}
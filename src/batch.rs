@@ -0,0 +1,306 @@
+//! `batch` subcommand: run debates for every topic in a file.
+//!
+//! The topics file is either plain text, one topic per line (blank lines and
+//! lines starting with `#` are skipped), or YAML (`.yaml` / `.yml`) with a
+//! list of topics, each a string or a mapping with per-topic model overrides:
+//!
+//! ```yaml
+//! - "Remote work is better than office work"
+//! - topic: "Nuclear power should replace coal"
+//!   pro: openai/gpt-4o
+//!   con: anthropic/claude-3-5-sonnet
+//!   judge: groq/llama-3.3-70b-versatile
+//!   name: nuclear-gpt4o-vs-claude
+//!   notify_email: team@example.com
+//! ```
+//!
+//! Debates run with bounded concurrency. Each finished debate is written to
+//! the output directory as `<name>.json`, `<name>.md` and `<name>.html`, and
+//! `index.json` lists every topic with its models, files and outcome. With
+//! SMTP configured,
+//! transcripts can also be emailed (`--notify-email`, or `notify_email` per
+//! topic), with TTS configured `--podcast` publishes each debate to the
+//! podcast feed, and with Notion configured each transcript is added to the
+//! Notion database.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use clap::Args;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::cli::run_topic;
+use crate::config;
+use crate::debate::Roles;
+#[cfg(feature = "email")]
+use crate::email;
+use crate::export::{to_html, to_json, to_markdown};
+#[cfg(feature = "notion")]
+use crate::notion;
+#[cfg(feature = "podcast")]
+use crate::podcast;
+use crate::storage::{init_db, SqliteStore, Store};
+#[cfg(feature = "podcast")]
+use crate::tts;
+use crate::types::ClientInfo;
+
+#[derive(Args)]
+pub struct BatchArgs {
+    /// Topics file: one topic per line, or YAML (`.yaml` / `.yml`).
+    pub topics: PathBuf,
+    /// Directory the results are written to.
+    #[arg(long, value_name = "DIR", default_value = "batch-results")]
+    pub out: PathBuf,
+    /// How many debates run at the same time.
+    #[arg(long, default_value_t = 2)]
+    pub concurrency: usize,
+    /// Email each finished transcript to this address (requires SMTP).
+    #[arg(long, value_name = "ADDRESS")]
+    pub notify_email: Option<String>,
+    /// Publish each finished debate to the podcast feed (requires TTS).
+    #[arg(long)]
+    pub podcast: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TopicEntry {
+    Plain(String),
+    Detailed(TopicSpec),
+}
+
+/// A topic with optional per-topic models and output file name.
+#[derive(Deserialize)]
+struct TopicSpec {
+    topic: String,
+    #[serde(default)]
+    pro: Option<String>,
+    #[serde(default)]
+    con: Option<String>,
+    #[serde(default)]
+    judge: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    notify_email: Option<String>,
+}
+
+impl From<TopicEntry> for TopicSpec {
+    fn from(entry: TopicEntry) -> Self {
+        match entry {
+            TopicEntry::Plain(topic) => TopicSpec {
+                topic,
+                pro: None,
+                con: None,
+                judge: None,
+                name: None,
+                notify_email: None,
+            },
+            TopicEntry::Detailed(spec) => spec,
+        }
+    }
+}
+
+fn load_topics(path: &Path) -> anyhow::Result<Vec<TopicSpec>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let is_yaml = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+    if is_yaml {
+        let entries: Vec<TopicEntry> = serde_yaml::from_str(&text)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+        return Ok(entries.into_iter().map(TopicSpec::from).collect());
+    }
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| TopicSpec::from(TopicEntry::Plain(line.to_string())))
+        .collect())
+}
+
+/// File name stem for a topic: its explicit name, or a numbered slug.
+fn file_stem(index: usize, spec: &TopicSpec) -> String {
+    if let Some(name) = spec
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+    {
+        return name.replace(['/', '\\'], "-");
+    }
+    let mut slug = String::new();
+    for c in spec.topic.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= 48 {
+            break;
+        }
+    }
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() {
+        format!("{:03}", index + 1)
+    } else {
+        format!("{:03}-{}", index + 1, slug)
+    }
+}
+
+/// Run the `batch` subcommand.
+pub async fn run(args: BatchArgs) -> anyhow::Result<()> {
+    let specs = load_topics(&args.topics)?;
+    if specs.is_empty() {
+        anyhow::bail!("no topics in {}", args.topics.display());
+    }
+    let wants_email = args.notify_email.is_some() || specs.iter().any(|s| s.notify_email.is_some());
+    #[cfg(feature = "email")]
+    if wants_email && email::mailer().is_none() {
+        anyhow::bail!("emailing transcripts requires SMTP_HOST and SMTP_FROM");
+    }
+    #[cfg(not(feature = "email"))]
+    if wants_email {
+        anyhow::bail!("emailing transcripts requires the `email` feature");
+    }
+    #[cfg(feature = "podcast")]
+    if args.podcast && !tts::enabled() {
+        anyhow::bail!("--podcast requires TTS_API_KEY");
+    }
+    #[cfg(not(feature = "podcast"))]
+    if args.podcast {
+        anyhow::bail!("--podcast requires the `podcast` feature");
+    }
+    std::fs::create_dir_all(&args.out)?;
+
+    let db_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://debate.db".to_string());
+    let db: Arc<dyn Store> = Arc::new(SqliteStore::new(init_db(&db_url).await?));
+    let defaults = config::init_clients().await?;
+    let total = specs.len();
+    eprintln!(
+        "Running {} debates, {} at a time, into {}",
+        total,
+        args.concurrency.max(1),
+        args.out.display()
+    );
+
+    let mut results: Vec<(usize, serde_json::Value)> =
+        futures::stream::iter(specs.into_iter().enumerate())
+            .map(|(index, mut spec)| {
+                if spec.notify_email.is_none() {
+                    spec.notify_email = args.notify_email.clone();
+                }
+                run_one(index, total, spec, &defaults, &db, &args.out, args.podcast)
+            })
+            .buffer_unordered(args.concurrency.max(1))
+            .collect()
+            .await;
+
+    results.sort_by_key(|(index, _)| *index);
+    let failed = results
+        .iter()
+        .filter(|(_, entry)| entry["status"] == "failed")
+        .count();
+    let index: Vec<serde_json::Value> = results.into_iter().map(|(_, entry)| entry).collect();
+    std::fs::write(
+        args.out.join("index.json"),
+        serde_json::to_string_pretty(&json!({ "debates": index }))?,
+    )?;
+
+    eprintln!("{} of {} debates completed", total - failed, total);
+    if failed > 0 {
+        anyhow::bail!("{} debates failed", failed);
+    }
+    Ok(())
+}
+
+/// Run one topic and write its files. Returns the topic's `index.json` entry.
+async fn run_one(
+    index: usize,
+    total: usize,
+    spec: TopicSpec,
+    defaults: &(ClientInfo, ClientInfo, ClientInfo),
+    db: &Arc<dyn Store>,
+    out_dir: &Path,
+    publish: bool,
+) -> (usize, serde_json::Value) {
+    let stem = file_stem(index, &spec);
+    let outcome = async {
+        let roles = Roles::resolve(defaults, &spec.pro, &spec.con, &spec.judge).await?;
+        let session_id = format!("batch-{}-{}", std::process::id(), index + 1);
+        let transcript = run_topic(
+            &spec.topic,
+            &roles,
+            db.clone(),
+            session_id.clone(),
+            &mut std::io::sink(),
+        )
+        .await?;
+        let json_file = format!("{}.json", stem);
+        let md_file = format!("{}.md", stem);
+        let html_file = format!("{}.html", stem);
+        std::fs::write(
+            out_dir.join(&json_file),
+            serde_json::to_string_pretty(&to_json(&spec.topic, &transcript))?,
+        )?;
+        std::fs::write(
+            out_dir.join(&md_file),
+            to_markdown(&spec.topic, &transcript),
+        )?;
+        std::fs::write(out_dir.join(&html_file), to_html(&spec.topic, &transcript))?;
+        #[cfg(feature = "email")]
+        if let (Some(to), Some(mailer)) = (&spec.notify_email, email::mailer()) {
+            if let Err(e) = mailer.send_transcript(to, &spec.topic, &transcript).await {
+                eprintln!("[{}/{}] could not email {}: {}", index + 1, total, to, e);
+            }
+        }
+        #[cfg(feature = "notion")]
+        if let Some(notion) = notion::notion() {
+            if let Err(e) = notion.export_transcript(&spec.topic, &transcript).await {
+                eprintln!(
+                    "[{}/{}] could not export to Notion: {}",
+                    index + 1,
+                    total,
+                    e
+                );
+            }
+        }
+        #[cfg(feature = "podcast")]
+        if publish {
+            if let Err(e) =
+                podcast::publish_episode(db, "cli", &session_id, &spec.topic, &transcript).await
+            {
+                eprintln!("[{}/{}] could not publish episode: {}", index + 1, total, e);
+            }
+        }
+        anyhow::Ok(json!({
+            "status": "completed",
+            "models": {
+                "pro": roles.pro.model_id,
+                "con": roles.con.model_id,
+                "judge": roles.judge.model_id,
+            },
+            "json": json_file,
+            "markdown": md_file,
+            "html": html_file,
+        }))
+    }
+    .await;
+
+    let mut entry = match outcome {
+        Ok(entry) => {
+            eprintln!("[{}/{}] done: {}", index + 1, total, spec.topic);
+            entry
+        }
+        Err(e) => {
+            eprintln!("[{}/{}] failed: {}: {}", index + 1, total, spec.topic, e);
+            json!({ "status": "failed", "error": e.to_string() })
+        }
+    };
+    entry["topic"] = json!(spec.topic);
+    (index, entry)
+}
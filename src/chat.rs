@@ -0,0 +1,156 @@
+//! Pieces shared by the chat integrations (`slack.rs`, `discord.rs`,
+//! `telegram.rs`).
+
+use tracing::warn;
+
+use crate::audit::AuditContext;
+use crate::budget;
+use crate::config;
+use crate::moderation::Verdict;
+use crate::quota;
+use crate::state::AppState;
+use crate::storage::SessionMetadata;
+
+/// Longest topic accepted from chat, as for `/debate/stream`.
+pub const MAX_TOPIC_LEN: usize = 2000;
+
+/// Moderation, budget and quota checks for a debate started from chat.
+/// Rejections are audited; the error is the message to show the user.
+pub async fn admit(state: &AppState, audit: &AuditContext, topic: &str) -> Result<(), String> {
+    if topic.len() > MAX_TOPIC_LEN {
+        audit
+            .record(&state.db, "rejected", Some("invalid_topic"))
+            .await;
+        return Err(format!(
+            "That topic is too long ({} characters at most).",
+            MAX_TOPIC_LEN
+        ));
+    }
+    if let Some(policy) = &state.moderation {
+        if let Verdict::Rejected(reason) = policy.check(topic).await {
+            audit
+                .record(
+                    &state.db,
+                    "rejected",
+                    Some(&format!("moderation: {}", reason)),
+                )
+                .await;
+            return Err(format!("Sorry, I can't debate that: {}", reason));
+        }
+    }
+    if budget::budget_status(&state.db).await.is_exceeded() {
+        audit
+            .record(&state.db, "rejected", Some("budget_exceeded"))
+            .await;
+        return Err("The debate budget for this period has been used up.".to_string());
+    }
+    let quota = quota::quota_status(&state.db, &audit.user_id).await;
+    if quota.is_exceeded() {
+        audit
+            .record(&state.db, "rejected", Some(&quota.exceeded_message()))
+            .await;
+        return Err(quota.exceeded_message());
+    }
+    Ok(())
+}
+
+/// Link to a finished chat debate's HTML export, when `PUBLIC_BASE_URL` is
+/// set. The session is made `shared` so the link opens without signing in;
+/// shared sessions stay out of the feed and the gallery.
+pub async fn transcript_link(
+    state: &AppState,
+    user_id: &str,
+    session_id: &str,
+    topic: &str,
+) -> Option<String> {
+    let base = config::public_base_url()?;
+    let shared = async {
+        state.db.save_session(user_id, session_id, topic).await?;
+        state
+            .db
+            .save_session_metadata(&SessionMetadata {
+                session_id: session_id.to_string(),
+                user_id: user_id.to_string(),
+                title: None,
+                notes: None,
+                visibility: Some("shared".to_string()),
+            })
+            .await
+    };
+    if let Err(e) = shared.await {
+        warn!("Failed to share chat session {}: {}", session_id, e);
+        return None;
+    }
+    Some(format!(
+        "{}/sessions/{}/export?format=html",
+        base,
+        url_encode(session_id)
+    ))
+}
+
+/// Percent-encode everything but unreserved URL characters.
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Cut `text` to at most `max_chars` characters, marking the cut.
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Split `text` into messages of at most `max_chars` characters, breaking at
+/// line ends where possible.
+pub fn split(text: &str, max_chars: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for line in text.split_inclusive('\n') {
+        let mut line = line;
+        loop {
+            let line_len = line.chars().count();
+            if current_len + line_len <= max_chars {
+                current.push_str(line);
+                current_len += line_len;
+                break;
+            }
+            if current_len > 0 {
+                parts.push(std::mem::take(&mut current));
+                current_len = 0;
+                continue;
+            }
+            // A single line longer than a whole message
+            let end = line
+                .char_indices()
+                .nth(max_chars)
+                .map_or(line.len(), |(i, _)| i);
+            parts.push(line[..end].to_string());
+            line = &line[end..];
+        }
+    }
+    parts.push(current);
+    parts.retain(|part| !part.trim().is_empty());
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_encode_escapes_reserved_characters() {
+        assert_eq!(url_encode("telegram-42-7"), "telegram-42-7");
+        assert_eq!(url_encode("slack:T1|a b/c"), "slack%3AT1%7Ca%20b%2Fc");
+        assert_eq!(url_encode("é"), "%C3%A9");
+    }
+}
@@ -0,0 +1,179 @@
+//! `check-config` subcommand: validate the environment without starting the
+//! server.
+//!
+//! Reports which providers have keys, whether each role's model can be
+//! reached, whether the database is reachable and has the current schema, and
+//! whether web search is enabled. Fatal problems (no usable provider, an
+//! unreachable database) make the command exit non-zero.
+
+use std::str::FromStr;
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+use crate::config;
+use crate::search;
+use crate::storage;
+
+/// Prints report lines and counts the problems found.
+#[derive(Default)]
+struct Report {
+    fatal: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn section(&self, title: &str) {
+        println!("\n{}", title);
+    }
+
+    fn ok(&self, line: impl AsRef<str>) {
+        println!("  ✓ {}", line.as_ref());
+    }
+
+    fn warn(&mut self, line: impl AsRef<str>) {
+        self.warnings += 1;
+        println!("  ! {}", line.as_ref());
+    }
+
+    fn fail(&mut self, line: impl AsRef<str>) {
+        self.fatal += 1;
+        println!("  ✗ {}", line.as_ref());
+    }
+}
+
+fn has_env(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|v| !v.trim().is_empty())
+}
+
+/// Run the check and print the report. Fails when a fatal problem was found.
+pub async fn run() -> anyhow::Result<()> {
+    config::load_secret_files();
+    let mut report = Report::default();
+
+    check_providers(&mut report);
+    check_database(&mut report).await;
+    check_search(&mut report);
+
+    println!(
+        "\n{} fatal problem(s), {} warning(s)",
+        report.fatal, report.warnings
+    );
+    if report.fatal > 0 {
+        anyhow::bail!("configuration check failed");
+    }
+    Ok(())
+}
+
+fn check_providers(report: &mut Report) {
+    report.section("Providers");
+    if config::mock_enabled() {
+        report.ok("DEBATE_MOCK enabled: all roles use the offline mock provider");
+        return;
+    }
+
+    let providers = config::detect_available_providers();
+    for provider in &providers {
+        if provider.has_key {
+            report.ok(format!("{} ({})", provider.display_name, provider.env_var));
+        } else {
+            println!(
+                "  - {} ({} not set)",
+                provider.display_name, provider.env_var
+            );
+        }
+    }
+    if !providers.iter().any(|p| p.has_key) {
+        report.fail("no provider API key is set; at least one is required");
+    }
+
+    report.section("Roles");
+    let fallback = config::fallback_model_id();
+    let fallback_ready = config::api_key_var(fallback).is_some_and(has_env);
+    let (pro, con, judge) = config::role_model_ids();
+    let mut roles = vec![("pro", pro), ("con", con), ("judge", judge)];
+    if let Some(model_id) = config::fact_check_model_id() {
+        roles.push(("fact-check", model_id));
+    }
+    for (role, model_id) in roles {
+        match config::api_key_var(&model_id) {
+            Some(var) if has_env(var) => report.ok(format!("{:<10} {}", role, model_id)),
+            Some(var) if fallback_ready => report.warn(format!(
+                "{:<10} {}: {} not set, will fall back to {}",
+                role, model_id, var, fallback
+            )),
+            Some(var) => report.fail(format!(
+                "{:<10} {}: {} not set and fallback {} is unavailable",
+                role, model_id, var, fallback
+            )),
+            None => report.warn(format!(
+                "{:<10} {}: unknown provider, key cannot be checked",
+                role, model_id
+            )),
+        }
+    }
+}
+
+async fn check_database(report: &mut Report) {
+    report.section("Database");
+    let db_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://debate.db".to_string());
+    let opts = match SqliteConnectOptions::from_str(&db_url) {
+        Ok(opts) => opts,
+        Err(e) => {
+            report.fail(format!("{}: invalid DATABASE_URL: {}", db_url, e));
+            return;
+        }
+    };
+
+    let path = opts.get_filename().to_path_buf();
+    if !db_url.contains(":memory:") && !path.exists() {
+        let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+        match dir {
+            Some(dir) if !dir.is_dir() => report.fail(format!(
+                "{}: directory {} does not exist",
+                db_url,
+                dir.display()
+            )),
+            _ => report.warn(format!(
+                "{}: not created yet; created on first start",
+                db_url
+            )),
+        }
+        return;
+    }
+
+    let db = match SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await
+    {
+        Ok(db) => db,
+        Err(e) => {
+            report.fail(format!("{}: cannot connect: {}", db_url, e));
+            return;
+        }
+    };
+    match storage::missing_tables(&db).await {
+        Ok(missing) if missing.is_empty() => {
+            report.ok(format!("{}: reachable, schema up to date", db_url))
+        }
+        Ok(missing) => report.warn(format!(
+            "{}: missing tables {}; created on next start",
+            db_url,
+            missing.join(", ")
+        )),
+        Err(e) => report.fail(format!("{}: schema query failed: {}", db_url, e)),
+    }
+    db.close().await;
+}
+
+fn check_search(report: &mut Report) {
+    report.section("Search");
+    match search::provider_name() {
+        Some(name) => report.ok(format!("web search enabled ({})", name)),
+        None if has_env("SEARCH_PROVIDER") => report.warn(format!(
+            "SEARCH_PROVIDER={} is set but its API key is missing or the backend is unknown",
+            std::env::var("SEARCH_PROVIDER").unwrap_or_default()
+        )),
+        None => println!("  - web search disabled (no search backend configured)"),
+    }
+}
@@ -0,0 +1,137 @@
+//! Command-line interface.
+//!
+//! `aidebate` (or `aidebate serve`) starts the HTTP server. `aidebate debate
+//! "<topic>"` runs one debate in the terminal: rounds are streamed to stdout
+//! as they are generated, and the transcript can also be written to a JSON,
+//! Markdown or standalone HTML file. Provider keys, search and mock settings come from the same
+//! environment variables as the server. `aidebate batch` runs a whole file of
+//! topics (see `batch.rs`) and `aidebate check-config` validates the
+//! environment (see `check.rs`).
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::{Args, Parser, Subcommand};
+use futures::StreamExt;
+
+use crate::batch::BatchArgs;
+use crate::config;
+use crate::debate::{run_debate, Roles, RunEvent, Turn};
+use crate::export::{to_html, to_json, to_markdown};
+use crate::storage::{init_db, SqliteStore, Store};
+use crate::templates;
+use crate::tools::ToolContext;
+
+#[derive(Parser)]
+#[command(name = "aidebate", version, about = "Multi-provider AI debate system")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Start the HTTP server (the default).
+    Serve,
+    /// Run a single debate and print it to stdout.
+    Debate(DebateArgs),
+    /// Run debates for every topic in a file and save the results.
+    Batch(BatchArgs),
+    /// Validate providers, database and search configuration.
+    CheckConfig,
+}
+
+#[derive(Args)]
+pub struct DebateArgs {
+    /// The motion to debate.
+    pub topic: String,
+    /// Model for the Pro side (e.g. "deepseek/deepseek-chat").
+    #[arg(long)]
+    pub pro: Option<String>,
+    /// Model for the Con side.
+    #[arg(long)]
+    pub con: Option<String>,
+    /// Model for the judge.
+    #[arg(long)]
+    pub judge: Option<String>,
+    /// Also write the transcript as JSON to this file.
+    #[arg(long, value_name = "FILE")]
+    pub json: Option<PathBuf>,
+    /// Also write the transcript as Markdown to this file.
+    #[arg(long, value_name = "FILE")]
+    pub markdown: Option<PathBuf>,
+    /// Also write the transcript as a standalone HTML page to this file.
+    #[arg(long, value_name = "FILE")]
+    pub html: Option<PathBuf>,
+}
+
+/// Run the `debate` subcommand.
+pub async fn run_debate(args: DebateArgs) -> anyhow::Result<()> {
+    let db_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://debate.db".to_string());
+    let db: Arc<dyn Store> = Arc::new(SqliteStore::new(init_db(&db_url).await?));
+
+    let defaults = config::init_clients().await?;
+    let roles = Roles::resolve(&defaults, &args.pro, &args.con, &args.judge).await?;
+    let session_id = format!("cli-{}", std::process::id());
+    let transcript = run_topic(&args.topic, &roles, db, session_id, &mut std::io::stdout()).await?;
+
+    if let Some(path) = &args.json {
+        std::fs::write(
+            path,
+            serde_json::to_string_pretty(&to_json(&args.topic, &transcript))?,
+        )?;
+        eprintln!("Wrote {}", path.display());
+    }
+    if let Some(path) = &args.markdown {
+        std::fs::write(path, to_markdown(&args.topic, &transcript))?;
+        eprintln!("Wrote {}", path.display());
+    }
+    if let Some(path) = &args.html {
+        std::fs::write(path, to_html(&args.topic, &transcript))?;
+        eprintln!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Run a full debate on `topic`, writing each round's text to `out` as it
+/// streams; tool activity goes to stderr. Returns the transcript.
+pub async fn run_topic(
+    topic: &str,
+    roles: &Roles,
+    db: Arc<dyn Store>,
+    session_id: String,
+    out: &mut dyn Write,
+) -> anyhow::Result<Vec<Turn>> {
+    let prompt_templates = templates::load(&db).await;
+    let tool_ctx = ToolContext::new(
+        db,
+        "cli".to_string(),
+        session_id,
+        roles.fact_checker.clone(),
+        crate::app_metrics::PrometheusMetrics::new(),
+    )
+    .with_templates(prompt_templates);
+
+    writeln!(out, "# {}\n", topic)?;
+    let mut transcript = Vec::new();
+    let events = run_debate(topic, roles, &tool_ctx);
+    futures::pin_mut!(events);
+    while let Some(event) = events.next().await {
+        match event? {
+            RunEvent::RoundStarted { side, phase, model } => {
+                writeln!(out, "## {} · {} ({})\n", side.label(), phase.title(), model)?;
+            }
+            RunEvent::Delta(delta) => {
+                write!(out, "{}", delta)?;
+                out.flush()?;
+            }
+            RunEvent::ToolCall { name, query } => eprintln!("[{}] {}", name, query),
+            RunEvent::RoundFinished(turn) => {
+                writeln!(out, "\n")?;
+                transcript.push(turn);
+            }
+        }
+    }
+    Ok(transcript)
+}
@@ -0,0 +1,287 @@
+//! Concurrency limits for upstream providers and for debates as a whole.
+//!
+//! Each provider gets its own semaphore so that at most N streams hit a given
+//! vendor at the same time. Rounds beyond the limit wait for a free slot.
+//!
+//! Separately, `MAX_CONCURRENT_DEBATES` caps how many debates run at once on
+//! this instance. Further debates wait in a FIFO queue (up to
+//! `DEBATE_QUEUE_MAX`) and are told their position while they wait.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+const DEFAULT_PROVIDER_CONCURRENCY: usize = 4;
+
+/// Get max simultaneous streams for a provider.
+/// Reads `<PROVIDER>_MAX_CONCURRENCY`, then `PROVIDER_MAX_CONCURRENCY`, defaulting to 4.
+pub fn max_concurrency_for_provider(provider: &str) -> usize {
+    let provider_key = format!("{}_MAX_CONCURRENCY", provider.to_uppercase());
+    std::env::var(&provider_key)
+        .or_else(|_| std::env::var("PROVIDER_MAX_CONCURRENCY"))
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_PROVIDER_CONCURRENCY)
+}
+
+/// Slot usage for one provider.
+#[derive(Serialize)]
+pub struct ProviderSlots {
+    pub provider: String,
+    pub limit: usize,
+    pub in_use: usize,
+}
+
+/// Lazily created per-provider semaphores.
+#[derive(Default)]
+pub struct ProviderLimiter {
+    semaphores: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl ProviderLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the semaphore for a provider, creating it on first use.
+    pub fn semaphore(&self, provider: &str) -> Arc<Semaphore> {
+        let mut guard = self.semaphores.lock().unwrap_or_else(|e| e.into_inner());
+        guard
+            .entry(provider.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(max_concurrency_for_provider(provider))))
+            .clone()
+    }
+
+    /// Current slot usage for every provider seen so far.
+    pub fn snapshot(&self) -> Vec<ProviderSlots> {
+        let guard = self.semaphores.lock().unwrap_or_else(|e| e.into_inner());
+        let mut slots: Vec<ProviderSlots> = guard
+            .iter()
+            .map(|(provider, semaphore)| {
+                let limit = max_concurrency_for_provider(provider);
+                ProviderSlots {
+                    provider: provider.clone(),
+                    limit,
+                    in_use: limit.saturating_sub(semaphore.available_permits()),
+                }
+            })
+            .collect();
+        slots.sort_by(|a, b| a.provider.cmp(&b.provider));
+        slots
+    }
+}
+
+/// Max debates running at once on this instance (`MAX_CONCURRENT_DEBATES`);
+/// unlimited when unset or 0.
+pub fn max_concurrent_debates() -> Option<usize> {
+    std::env::var("MAX_CONCURRENT_DEBATES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| *v > 0)
+}
+
+/// Max debates waiting for a slot (`DEBATE_QUEUE_MAX`, default 100).
+pub fn max_queued_debates() -> usize {
+    std::env::var("DEBATE_QUEUE_MAX")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(100)
+}
+
+/// Outcome of asking for a debate slot.
+pub enum Admission {
+    /// Runs now; the permit (if limited) is held for the whole debate.
+    Admitted(Option<OwnedSemaphorePermit>),
+    /// Waits in line for a slot.
+    Queued(QueueTicket),
+    /// The queue is full.
+    Rejected,
+}
+
+/// Debate usage for the admin dashboard.
+#[derive(Serialize)]
+pub struct DebateSlots {
+    pub limit: Option<usize>,
+    pub running: usize,
+    pub queued: usize,
+}
+
+/// Global limit on simultaneous debates, with a FIFO queue for the rest.
+pub struct DebateLimiter {
+    limit: Option<usize>,
+    semaphore: Arc<Semaphore>,
+    queue: Arc<Mutex<VecDeque<u64>>>,
+    next_ticket: AtomicU64,
+}
+
+impl DebateLimiter {
+    pub fn from_env() -> Self {
+        let limit = max_concurrent_debates();
+        Self {
+            limit,
+            semaphore: Arc::new(Semaphore::new(limit.unwrap_or(0))),
+            queue: Arc::new(Mutex::new(VecDeque::new())),
+            next_ticket: AtomicU64::new(0),
+        }
+    }
+
+    /// Take a free slot, or a place in the queue if none is free.
+    pub fn admit(&self) -> Admission {
+        if self.limit.is_none() {
+            return Admission::Admitted(None);
+        }
+        if let Ok(permit) = self.semaphore.clone().try_acquire_owned() {
+            return Admission::Admitted(Some(permit));
+        }
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        if queue.len() >= max_queued_debates() {
+            return Admission::Rejected;
+        }
+        let id = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        queue.push_back(id);
+        Admission::Queued(QueueTicket {
+            id,
+            queue: self.queue.clone(),
+            semaphore: self.semaphore.clone(),
+        })
+    }
+
+    pub fn snapshot(&self) -> DebateSlots {
+        let queued = self.queue.lock().unwrap_or_else(|e| e.into_inner()).len();
+        DebateSlots {
+            limit: self.limit,
+            running: self
+                .limit
+                .map(|l| l.saturating_sub(self.semaphore.available_permits()))
+                .unwrap_or(0),
+            queued,
+        }
+    }
+}
+
+/// A place in the debate queue; leaving it (admitted or not) frees the place.
+pub struct QueueTicket {
+    id: u64,
+    queue: Arc<Mutex<VecDeque<u64>>>,
+    semaphore: Arc<Semaphore>,
+}
+
+impl QueueTicket {
+    /// 1-based position in the queue.
+    pub fn position(&self) -> usize {
+        let queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        queue
+            .iter()
+            .position(|id| *id == self.id)
+            .map(|p| p + 1)
+            .unwrap_or(1)
+    }
+
+    /// Wait for a slot. Waiters are served in arrival order.
+    pub async fn wait(&self) -> Option<OwnedSemaphorePermit> {
+        self.semaphore.clone().acquire_owned().await.ok()
+    }
+}
+
+impl Drop for QueueTicket {
+    fn drop(&mut self) {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        queue.retain(|id| *id != self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::poll;
+
+    fn limiter(limit: usize) -> DebateLimiter {
+        DebateLimiter {
+            limit: Some(limit),
+            semaphore: Arc::new(Semaphore::new(limit)),
+            queue: Arc::new(Mutex::new(VecDeque::new())),
+            next_ticket: AtomicU64::new(0),
+        }
+    }
+
+    fn admitted(admission: Admission) -> OwnedSemaphorePermit {
+        match admission {
+            Admission::Admitted(Some(permit)) => permit,
+            _ => panic!("expected a slot"),
+        }
+    }
+
+    fn queued(admission: Admission) -> QueueTicket {
+        match admission {
+            Admission::Queued(ticket) => ticket,
+            _ => panic!("expected a place in the queue"),
+        }
+    }
+
+    #[test]
+    fn queue_positions_follow_arrival_order() {
+        let limiter = limiter(1);
+        let _running = admitted(limiter.admit());
+        let first = queued(limiter.admit());
+        let second = queued(limiter.admit());
+        let third = queued(limiter.admit());
+        assert_eq!(
+            (first.position(), second.position(), third.position()),
+            (1, 2, 3)
+        );
+        drop(second);
+        assert_eq!((first.position(), third.position()), (1, 2));
+        let snapshot = limiter.snapshot();
+        assert_eq!((snapshot.running, snapshot.queued), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn released_slot_goes_to_the_first_waiter() {
+        let limiter = limiter(1);
+        let running = admitted(limiter.admit());
+        let (first, second) = (queued(limiter.admit()), queued(limiter.admit()));
+        let mut first_wait = Box::pin(first.wait());
+        let mut second_wait = Box::pin(second.wait());
+        assert!(poll!(&mut first_wait).is_pending());
+        assert!(poll!(&mut second_wait).is_pending());
+        drop(running);
+        let permit = first_wait.await.unwrap();
+        assert!(poll!(&mut second_wait).is_pending());
+        drop(permit);
+        let _permit = second_wait.await.unwrap();
+        assert_eq!(limiter.snapshot().running, 1);
+    }
+
+    #[tokio::test]
+    async fn cancelled_waiter_leaves_the_queue() {
+        let limiter = limiter(1);
+        let running = admitted(limiter.admit());
+        let (first, second) = (queued(limiter.admit()), queued(limiter.admit()));
+        {
+            let mut first_wait = Box::pin(first.wait());
+            assert!(poll!(&mut first_wait).is_pending());
+        }
+        // The client went away while waiting
+        drop(first);
+        assert_eq!(second.position(), 1);
+        assert_eq!(limiter.snapshot().queued, 1);
+        drop(running);
+        assert!(second.wait().await.is_some());
+    }
+
+    #[test]
+    fn provider_slots_are_released_on_drop() {
+        let limiter = ProviderLimiter::new();
+        let semaphore = limiter.semaphore("testprovider");
+        assert!(Arc::ptr_eq(&semaphore, &limiter.semaphore("testprovider")));
+        let permit = semaphore.clone().try_acquire_owned().unwrap();
+        let in_use = |limiter: &ProviderLimiter| limiter.snapshot()[0].in_use;
+        assert_eq!(in_use(&limiter), 1);
+        drop(permit);
+        assert_eq!(in_use(&limiter), 0);
+    }
+}
@@ -1,5 +0,0 @@
-mod engine;
-
-pub use engine::{
-    execute_judge_round_stream, execute_one_round, execute_round_with_tools, DebateStreamChunk,
-};
@@ -0,0 +1,292 @@
+//! Discord bot: `!debate <topic>` in any channel the bot can read.
+//!
+//! Enabled when `DISCORD_BOT_TOKEN` is set; the bot needs the Message Content
+//! intent. It opens a thread on the command message and posts every round
+//! there as it finishes. After the AI judge's verdict it posts a ballot that
+//! members vote on by reacting 🟢 (Pro) or 🔴 (Con); when voting closes
+//! (`DISCORD_VOTE_SECS`, default 300) the audience tally is posted next to the
+//! judge's pick.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use serenity::all::{
+    ChannelId, Client, Context, CreateThread, EventHandler, GatewayIntents, Message, ReactionType,
+    Ready,
+};
+use serenity::async_trait;
+use tracing::{error, info, warn};
+
+use crate::audit::AuditContext;
+use crate::chat;
+use crate::debate::{run_debate, Roles, RunEvent};
+use crate::prompts;
+use crate::state::AppState;
+use crate::templates;
+use crate::tools::ToolContext;
+use crate::types::Position;
+
+/// Discord rejects messages longer than 2000 characters.
+const MAX_MESSAGE_CHARS: usize = 1900;
+
+const PRO_VOTE: char = '🟢';
+const CON_VOTE: char = '🔴';
+
+pub struct DiscordBot {
+    token: String,
+    vote_window: Duration,
+}
+
+impl DiscordBot {
+    /// `None` unless `DISCORD_BOT_TOKEN` is set.
+    pub fn from_env() -> Option<Self> {
+        let token = std::env::var("DISCORD_BOT_TOKEN")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())?;
+        let vote_secs = std::env::var("DISCORD_VOTE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300);
+        Some(Self {
+            token,
+            vote_window: Duration::from_secs(vote_secs),
+        })
+    }
+
+    /// Connect to the gateway and handle commands until the connection is
+    /// closed for good.
+    pub async fn run(self, state: Arc<AppState>) {
+        let intents = GatewayIntents::GUILD_MESSAGES
+            | GatewayIntents::DIRECT_MESSAGES
+            | GatewayIntents::MESSAGE_CONTENT;
+        let handler = Handler {
+            state,
+            vote_window: self.vote_window,
+        };
+        let client = Client::builder(&self.token, intents)
+            .event_handler(handler)
+            .await;
+        let result = match client {
+            Ok(mut client) => client.start().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!("Discord bot stopped: {}", e);
+        }
+    }
+}
+
+struct Handler {
+    state: Arc<AppState>,
+    vote_window: Duration,
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn ready(&self, _ctx: Context, ready: Ready) {
+        info!("Discord bot connected as {}", ready.user.name);
+    }
+
+    async fn message(&self, ctx: Context, msg: Message) {
+        if msg.author.bot {
+            return;
+        }
+        let Some(topic) = msg.content.strip_prefix("!debate") else {
+            return;
+        };
+        if !topic.is_empty() && !topic.starts_with(char::is_whitespace) {
+            return;
+        }
+        let topic = topic.trim();
+        if topic.is_empty() {
+            reply(&ctx, &msg, "Usage: `!debate <topic>`").await;
+            return;
+        }
+
+        let user_id = format!("discord:{}", msg.author.id);
+        let session_id = format!("discord-{}-{}", msg.channel_id, msg.id);
+        let mut audit = AuditContext {
+            user_id: user_id.clone(),
+            session_id: session_id.clone(),
+            ip: "discord".to_string(),
+            topic: topic.chars().take(500).collect(),
+            providers: String::new(),
+        };
+        if let Err(message) = chat::admit(&self.state, &audit, topic).await {
+            reply(&ctx, &msg, &message).await;
+            return;
+        }
+        audit.providers = format!(
+            "pro={},con={},judge={}",
+            self.state.pro.model_id, self.state.con.model_id, self.state.judge.model_id
+        );
+
+        // Threads only exist in guild channels; in DMs the rounds go inline
+        let thread = CreateThread::new(chat::truncate(topic, 90));
+        let channel = match msg
+            .channel_id
+            .create_thread_from_message(&ctx.http, msg.id, thread)
+            .await
+        {
+            Ok(thread) => thread.id,
+            Err(e) => {
+                warn!("Could not open a Discord thread, posting inline: {}", e);
+                msg.channel_id
+            }
+        };
+
+        audit.record(&self.state.db, "started", None).await;
+        match self
+            .run_in_channel(&ctx, channel, topic, &user_id, &session_id)
+            .await
+        {
+            Ok(verdict) => {
+                audit.record(&self.state.db, "completed", None).await;
+                if let Err(e) = self.audience_vote(&ctx, channel, &verdict).await {
+                    warn!("Discord audience vote failed: {}", e);
+                }
+            }
+            Err(e) => {
+                warn!("Discord debate failed: {}", e);
+                audit
+                    .record(&self.state.db, "failed", Some(&e.to_string()))
+                    .await;
+                say(&ctx, channel, &format!("The debate failed: {}", e)).await;
+            }
+        }
+    }
+}
+
+impl Handler {
+    /// Run the debate, saving each round and posting it to `channel`.
+    /// Returns the judge's verdict.
+    async fn run_in_channel(
+        &self,
+        ctx: &Context,
+        channel: ChannelId,
+        topic: &str,
+        user_id: &str,
+        session_id: &str,
+    ) -> anyhow::Result<String> {
+        let state = &self.state;
+        let defaults = (state.pro.clone(), state.con.clone(), state.judge.clone());
+        let roles = Roles::resolve(&defaults, &None, &None, &None).await?;
+        let tool_ctx = ToolContext::new(
+            state.db.clone(),
+            user_id.to_string(),
+            session_id.to_string(),
+            roles.fact_checker.clone(),
+            state.metrics.clone(),
+        )
+        .with_templates(templates::load(&state.db).await);
+
+        let mut verdict = String::new();
+        let events = run_debate(topic, &roles, &tool_ctx);
+        futures::pin_mut!(events);
+        while let Some(event) = events.next().await {
+            match event? {
+                RunEvent::RoundStarted { .. } => {
+                    let _ = channel.broadcast_typing(&ctx.http).await;
+                }
+                RunEvent::RoundFinished((side, phase, content, model)) => {
+                    if let Err(e) = state
+                        .db
+                        .save_message(user_id, session_id, side, phase, Some(&model), &content)
+                        .await
+                    {
+                        warn!("Failed to save Discord debate message: {}", e);
+                    }
+                    let text = format!(
+                        "**{} · {}** ({})\n{}",
+                        side.label(),
+                        phase.title(),
+                        model,
+                        content.trim()
+                    );
+                    for part in chat::split(&text, MAX_MESSAGE_CHARS) {
+                        channel.say(&ctx.http, part).await?;
+                    }
+                    if side == Position::Judge {
+                        verdict = content;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(verdict)
+    }
+
+    /// Post the ballot, wait for the vote window, then post the tally.
+    async fn audience_vote(
+        &self,
+        ctx: &Context,
+        channel: ChannelId,
+        verdict: &str,
+    ) -> serenity::Result<()> {
+        let minutes = self.vote_window.as_secs().div_ceil(60);
+        let ballot = channel
+            .say(
+                &ctx.http,
+                format!(
+                    "Who won? React {} for Pro or {} for Con. Voting closes in {} min.",
+                    PRO_VOTE, CON_VOTE, minutes
+                ),
+            )
+            .await?;
+        ballot.react(&ctx.http, PRO_VOTE).await?;
+        ballot.react(&ctx.http, CON_VOTE).await?;
+
+        tokio::time::sleep(self.vote_window).await;
+
+        let ballot = ctx.http.get_message(channel, ballot.id).await?;
+        // The bot's own reactions are not votes
+        let votes = |emoji: char| {
+            ballot
+                .reactions
+                .iter()
+                .find(|r| r.reaction_type == ReactionType::from(emoji))
+                .map_or(0, |r| r.count.saturating_sub(u64::from(r.me)))
+        };
+        let (pro, con) = (votes(PRO_VOTE), votes(CON_VOTE));
+        let audience = match pro.cmp(&con) {
+            std::cmp::Ordering::Greater => Some(Position::Pro),
+            std::cmp::Ordering::Less => Some(Position::Con),
+            std::cmp::Ordering::Equal => None,
+        };
+        let judge = prompts::parse_winner(verdict);
+        let agreement = match (audience, judge) {
+            (Some(a), Some(j)) if a == j => "The audience agrees with the judge.",
+            (Some(_), Some(_)) => "The audience disagrees with the judge.",
+            _ => "",
+        };
+        channel
+            .say(
+                &ctx.http,
+                format!(
+                    "Voting closed. Audience: Pro {} – Con {} ({}). Judge: {}. {}",
+                    pro,
+                    con,
+                    audience.map_or("a tie", |s| s.label()),
+                    judge.map_or("no clear winner", |s| s.label()),
+                    agreement
+                )
+                .trim_end(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+async fn reply(ctx: &Context, msg: &Message, text: &str) {
+    if let Err(e) = msg.reply(&ctx.http, text).await {
+        warn!("Failed to reply on Discord: {}", e);
+    }
+}
+
+async fn say(ctx: &Context, channel: ChannelId, text: &str) {
+    if let Err(e) = channel.say(&ctx.http, text).await {
+        warn!("Failed to post on Discord: {}", e);
+    }
+}
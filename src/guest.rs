@@ -0,0 +1,245 @@
+//! Signed guest tokens for anonymous users.
+//!
+//! Without JWT auth the server takes `user_id` from the client as given, so
+//! rate limits and quotas keyed on it are bypassed by changing the id. With
+//! `GUEST_TOKEN_SECRET` set, `POST /guest` issues a guest identity and a
+//! token for it that expires after `GUEST_TOKEN_TTL_HOURS` (default 24).
+//! User endpoints then require `Authorization: Bearer <token>` and take the
+//! user id from the token. Tokens are `guest.<id>.<expires>.<signature>`,
+//! the signature being the hex HMAC-SHA256 of `<id>.<expires>`. Issuing is
+//! rate limited per client IP, so new identities cannot be minted freely.
+//! When JWT auth is also enabled, either kind of token is accepted.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tracing::{info, warn};
+
+use crate::auth::AuthError;
+
+const PREFIX: &str = "guest";
+
+const MIN_SECRET_LEN: usize = 32;
+
+pub struct GuestTokens {
+    secret: String,
+    ttl_secs: u64,
+    seq: AtomicU64,
+}
+
+/// A newly issued guest identity.
+pub struct GuestToken {
+    pub user_id: String,
+    pub token: String,
+    /// Unix seconds.
+    pub expires_at: u64,
+}
+
+impl GuestTokens {
+    /// `None` unless `GUEST_TOKEN_SECRET` is set; a secret shorter than 32
+    /// characters is refused.
+    pub fn from_env() -> Option<Self> {
+        let secret = std::env::var("GUEST_TOKEN_SECRET")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())?;
+        if secret.len() < MIN_SECRET_LEN {
+            warn!(
+                "GUEST_TOKEN_SECRET is shorter than {} characters; guest tokens disabled",
+                MIN_SECRET_LEN
+            );
+            return None;
+        }
+        let ttl_hours: u64 = std::env::var("GUEST_TOKEN_TTL_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(24);
+        info!("Guest tokens enabled");
+        Some(Self {
+            secret,
+            ttl_secs: ttl_hours.max(1) * 3600,
+            seq: AtomicU64::new(0),
+        })
+    }
+
+    /// Issue a token for a new guest identity.
+    pub fn issue(&self) -> GuestToken {
+        let now = unix_secs();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        let id = self.sign(&format!("id:{}:{}", nanos, seq))[..16].to_string();
+        let expires_at = now + self.ttl_secs;
+        let payload = format!("{}.{}", id, expires_at);
+        GuestToken {
+            user_id: user_id(&id),
+            token: format!("{}.{}.{}", PREFIX, payload, self.sign(&payload)),
+            expires_at,
+        }
+    }
+
+    /// The user id of a valid, unexpired guest token. A client-supplied
+    /// `user_id` must be empty or match it.
+    pub fn resolve_user(&self, token: &str, claimed: &str) -> Result<String, AuthError> {
+        let invalid = || AuthError::Unauthorized("invalid guest token".to_string());
+        let mut parts = token.split('.');
+        let (Some(PREFIX), Some(id), Some(expires), Some(signature), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return Err(invalid());
+        };
+        let signature = hex::decode(signature).map_err(|_| invalid())?;
+        let mut mac = self.mac();
+        mac.update(format!("{}.{}", id, expires).as_bytes());
+        mac.verify_slice(&signature).map_err(|_| invalid())?;
+        let expires: u64 = expires.parse().map_err(|_| invalid())?;
+        if expires <= unix_secs() {
+            return Err(AuthError::Unauthorized("guest token expired".to_string()));
+        }
+        let subject = user_id(id);
+        if !claimed.is_empty() && claimed != subject {
+            return Err(AuthError::Forbidden);
+        }
+        Ok(subject)
+    }
+
+    fn mac(&self) -> Hmac<Sha256> {
+        Hmac::<Sha256>::new_from_slice(self.secret.as_bytes()).expect("HMAC accepts any key length")
+    }
+
+    fn sign(&self, payload: &str) -> String {
+        let mut mac = self.mac();
+        mac.update(payload.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+/// Whether a bearer token is a guest token rather than a JWT.
+pub fn is_guest_token(token: &str) -> bool {
+    token.starts_with("guest.")
+}
+
+fn user_id(id: &str) -> String {
+    format!("guest:{}", id)
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guest_tokens() -> GuestTokens {
+        GuestTokens {
+            secret: "a-test-secret-of-at-least-32-chars".to_string(),
+            ttl_secs: 3600,
+            seq: AtomicU64::new(0),
+        }
+    }
+
+    fn rejection(result: Result<String, AuthError>) -> String {
+        match result {
+            Ok(user_id) => panic!("accepted as {}", user_id),
+            Err(AuthError::Unauthorized(message)) => message,
+            Err(AuthError::Forbidden) => "forbidden".to_string(),
+        }
+    }
+
+    #[test]
+    fn issued_token_resolves_to_its_user() {
+        let tokens = guest_tokens();
+        let issued = tokens.issue();
+        assert!(is_guest_token(&issued.token));
+        assert!(issued.user_id.starts_with("guest:"));
+        assert_eq!(
+            tokens.resolve_user(&issued.token, "").ok(),
+            Some(issued.user_id.clone())
+        );
+        assert_eq!(
+            tokens.resolve_user(&issued.token, &issued.user_id).ok(),
+            Some(issued.user_id.clone())
+        );
+        assert_eq!(
+            rejection(tokens.resolve_user(&issued.token, "guest:someone-else")),
+            "forbidden"
+        );
+    }
+
+    #[test]
+    fn tampered_token_is_rejected() {
+        let tokens = guest_tokens();
+        let issued = tokens.issue();
+        let (payload, signature) = issued.token.rsplit_once('.').unwrap();
+        let flipped = if signature.starts_with('0') { "1" } else { "0" };
+        let tampered = format!("{}.{}{}", payload, flipped, &signature[1..]);
+        assert_eq!(
+            rejection(tokens.resolve_user(&tampered, "")),
+            "invalid guest token"
+        );
+        // A later expiry under the old signature
+        let mut parts = payload.split('.').skip(1);
+        let (id, expires) = (parts.next().unwrap(), parts.next().unwrap());
+        let extended = expires.parse::<u64>().unwrap() + 3600;
+        let extended = format!("guest.{}.{}.{}", id, extended, signature);
+        assert_eq!(
+            rejection(tokens.resolve_user(&extended, "")),
+            "invalid guest token"
+        );
+        // Signed with another secret
+        let other = GuestTokens {
+            secret: "another-secret-of-at-least-32-chars".to_string(),
+            ..guest_tokens()
+        };
+        assert_eq!(
+            rejection(other.resolve_user(&issued.token, "")),
+            "invalid guest token"
+        );
+    }
+
+    #[test]
+    fn expired_token_is_rejected() {
+        let tokens = guest_tokens();
+        let payload = format!("abc123.{}", unix_secs() - 1);
+        let token = format!("guest.{}.{}", payload, tokens.sign(&payload));
+        assert_eq!(
+            rejection(tokens.resolve_user(&token, "")),
+            "guest token expired"
+        );
+    }
+
+    #[test]
+    fn malformed_token_is_rejected() {
+        let tokens = guest_tokens();
+        let payload = "abc123.soon";
+        let unparsable = format!("guest.{}.{}", payload, tokens.sign(payload));
+        for token in [
+            "",
+            "guest",
+            "guest.abc123.9999999999",
+            "guest.abc123.9999999999.not-hex",
+            "guest.abc123.9999999999.00.extra",
+            "jwt.abc123.9999999999.00",
+            &unparsable,
+        ] {
+            assert_eq!(
+                rejection(tokens.resolve_user(token, "")),
+                "invalid guest token",
+                "{:?}",
+                token
+            );
+        }
+    }
+}
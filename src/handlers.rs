@@ -1,53 +1,235 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use axum::body::Body;
-use axum::extract::{Query, State};
-use axum::http::{HeaderValue, Method};
-use axum::response::{Html, Response};
-use axum::routing::{get, post};
-use axum::{Json, Router};
+use axum::body::{Body, Bytes};
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{ConnectInfo, DefaultBodyLimit, MatchedPath, Path, Query, Request, State};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{delete, get, patch, post, put};
+use axum::{Extension, Json, Router};
+use axum_server::tls_rustls::RustlsConfig;
 use futures::StreamExt;
 use serde_json::json;
 use tokio::net::TcpListener;
-use tower_http::{cors::CorsLayer, timeout::TimeoutLayer};
-use tracing::info;
+use tower_http::{cors::CorsLayer, services::ServeDir, timeout::TimeoutLayer};
+use tracing::{info, info_span, warn, Instrument};
 
-use crate::app_metrics::{SimpleMetrics, Timer};
-use crate::config;
+use crate::app_metrics::{self, GaugeGuard, PhaseTiming, RoundMetrics, Timer};
+use crate::arguments;
+use crate::audit::{self, AuditContext};
+use crate::auth::{self, AuthError, AuthenticatedUser};
+use crate::budget;
+use crate::cache;
+use crate::calibration;
+use crate::categories;
+use crate::citations;
+use crate::concurrency::Admission;
+use crate::config::{self, OpeningMode};
+use crate::consistency;
 use crate::debate::{
-    execute_judge_round_stream, execute_one_round, execute_round_with_tools, DebateStreamChunk,
+    continue_debate, execute_dissent_stream, execute_judge_round_stream, execute_one_round,
+    execute_outcome_analysis_stream, rounds_before, DebateStreamChunk, Roles, RunEvent, Turn,
 };
-use crate::storage::{fetch_history, save_message};
+use crate::diff;
+use crate::documents;
+#[cfg(feature = "email")]
+use crate::email;
+use crate::error::ApiError;
+use crate::export::{self, ExportFormat};
+use crate::fact_check;
+use crate::fallacies;
+use crate::guest;
+use crate::highlights;
+use crate::judge_qa;
+use crate::leaderboard;
+use crate::moderation::Verdict;
+#[cfg(feature = "notion")]
+use crate::notion;
+use crate::panel;
+#[cfg(feature = "podcast")]
+use crate::podcast;
+use crate::presets;
+use crate::prompts;
+use crate::quiz;
+use crate::quota;
+use crate::rate_limit::{self, ActiveDebate, RateLimit};
+use crate::refusals;
+use crate::rubric::{self, Rubric};
+use crate::search::{self, SearchOptions};
+use crate::sections::{Piece, SectionSplitter};
+use crate::similar;
+use crate::slack;
+use crate::state::AppState;
+use crate::storage::{GalleryEntry, SessionMetadata, GALLERY_STATUSES, VISIBILITIES};
+use crate::subtitles::{self, SubtitleFormat};
+use crate::summaries;
+use crate::templates;
+use crate::tone;
 use crate::tools;
+#[cfg(feature = "podcast")]
+use crate::tts;
 use crate::types::{
-    AppState, ClientInfo, DebatePhase, DebateRequest, HistoryMessage, HistoryQuery, Position,
+    ArgumentAnalyticsQuery, AskJudgeRequest, AuditQuery, CategoryQuery, ClientInfo,
+    CollectionRequest, DebatePhase, DebateRequest, DocumentUploadQuery, EditRoundRequest,
+    ExportQuery, ForkQuery, ForkRequest, GalleryQuery, GalleryReviewRequest, GalleryVoteRequest,
+    HighlightsQuery, HistoryMessage, HistoryQuery, JudgeCalibrationQuery, LeaderboardQuery,
+    Participant, Position, PreferencesRequest, PromptTemplateRequest, PromptVariantsRequest,
+    QuizQuery, RejudgeRequest, SessionListQuery, SessionMetadataRequest, SessionQuery,
+    SimilarQuery, SpectateQuery, StreamQuery, SubtitleQuery,
 };
+use crate::validation::{self, validate_id};
+use crate::webhooks;
 
-/// Build the Axum router and shared state.
-pub async fn build_app(
-    db: sqlx::SqlitePool,
-    clients: (ClientInfo, ClientInfo, ClientInfo),
-) -> Router {
-    let (pro, con, judge) = clients;
-    let state = Arc::new(AppState {
-        db,
-        pro,
-        con,
-        judge,
-        start_time: Instant::now(),
-        rate_limits: tokio::sync::RwLock::new(HashMap::new()),
-        metrics: SimpleMetrics::new(),
-    });
+/// How often a queued debate re-checks its queue position.
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Longest accepted session title and notes, in characters.
+const MAX_SESSION_TITLE_CHARS: usize = 200;
+const MAX_SESSION_NOTES_CHARS: usize = 4000;
+
+/// Limits of a topic collection.
+const MAX_COLLECTION_NAME_CHARS: usize = 100;
+const MAX_COLLECTION_DESCRIPTION_CHARS: usize = 2000;
+const MAX_COLLECTION_TOPICS: usize = 50;
+
+/// Longest accepted text of an edited round.
+const MAX_EDITED_ROUND_BYTES: usize = 16 * 1024;
 
-    Router::new()
-        .route("/", get(index))
-        .route("/favicon.svg", get(serve_favicon))
+/// Phase name and stored analysis kind of the "what would change the outcome"
+/// analysis.
+const OUTCOME_PHASE: &str = "outcome";
+
+/// The debate routes over `state`. `web_ui` adds the bundled pages;
+/// `default_layers` adds the body limit, timeout and CORS layers.
+pub(crate) fn routes(state: Arc<AppState>, web_ui: bool, default_layers: bool) -> Router {
+    // Read-only user endpoints share a more generous per-IP limit
+    let read_only = Router::new()
+        .route("/history", get(get_history).post(get_history_post))
+        .route("/sessions", get(list_sessions))
+        .route("/sessions/{id}/documents", get(list_documents))
+        .route("/sessions/{id}/subtitles", get(get_subtitles))
+        .route("/sessions/{id}", get(get_session))
+        .route("/sessions/{id}/export", get(get_export))
+        .route("/sessions/{id}/spectate", get(spectate_session))
+        .route("/sessions/{id}/analysis", get(get_analysis))
+        .route("/sessions/{id}/highlights", get(get_highlights))
+        .route("/sessions/{id}/quiz", get(get_quiz))
+        .route("/sessions/{id}/ask-judge", get(list_judge_questions))
+        .route("/sessions/{id}/inputs", get(get_debate_inputs))
+        .route("/sessions/{a}/diff/{b}", get(get_session_diff))
+        .route("/sessions/similar", get(get_similar_sessions))
+        .route("/quota", get(get_quota))
+        .route("/preferences", get(get_preferences))
+        .route("/collections", get(list_collections))
+        .route("/collections/{id}", get(get_collection))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            limit_read_only,
+        ));
+
+    // Admin API, behind its own auth: ADMIN_TOKEN or a JWT with the admin role
+    let admin = Router::new()
+        .route("/budget", get(get_budget))
+        .route("/audit", get(get_audit))
+        .route("/dashboard", get(get_dashboard))
+        .route("/judge-calibration", get(get_judge_calibration))
+        .route("/prompt-templates", get(list_prompt_templates))
+        .route(
+            "/prompt-templates/{name}",
+            get(list_prompt_template_versions).put(save_prompt_template_version),
+        )
+        .route(
+            "/sessions/{id}/prompt-templates",
+            get(get_session_prompt_templates),
+        )
+        .route(
+            "/prompt-variants",
+            get(list_prompt_variants)
+                .put(set_prompt_variants)
+                .delete(clear_prompt_variants),
+        )
+        .route("/gallery", get(list_gallery_nominations))
+        .route("/gallery/{id}", put(review_gallery_entry))
+        .route("/webhooks", get(webhooks::list).post(webhooks::create))
+        .route("/webhooks/{id}", delete(webhooks::delete))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_admin));
+
+    let mut router = Router::new();
+    #[cfg(feature = "podcast")]
+    if tts::enabled() {
+        router = router
+            .route("/podcast.xml", get(get_podcast_feed))
+            .route("/podcast/episodes/{file}", get(get_podcast_episode));
+    }
+    if state.slack.is_some() {
+        router = router.route("/integrations/slack/commands", post(slack::slash_command));
+    }
+    if web_ui {
+        let embedded = Router::new()
+            .route("/", get(index))
+            .route("/favicon.svg", get(serve_favicon))
+            .route("/js/marked.min.js", get(serve_marked))
+            .route("/admin", get(admin_page));
+        router = match config::static_dir() {
+            // Files in STATIC_DIR win; anything it lacks comes from the binary
+            Some(dir) => {
+                info!("Serving web UI from {}", dir.display());
+                let embedded: Router = embedded.with_state(state.clone());
+                router.fallback_service(ServeDir::new(dir).fallback(embedded))
+            }
+            None => router.merge(embedded),
+        };
+    }
+    let router = router
         .route("/api/models", get(get_models))
+        .route("/participants", get(get_participants))
+        .route("/presets", get(list_presets))
+        .route("/guest", post(create_guest))
         .route("/debate/stream", post(debate_stream))
-        .route("/history", get(get_history).post(get_history_post))
         .route("/health", get(health))
+        .route("/metrics", get(get_metrics))
+        .route("/stats", get(get_stats))
+        .route("/analytics/arguments", get(get_argument_analytics))
+        .route("/leaderboard", get(get_leaderboard))
+        .route("/gallery", get(get_gallery))
+        .route("/gallery/{id}/vote", post(vote_gallery))
+        .route(
+            "/preferences",
+            put(put_preferences).delete(delete_user_preferences),
+        )
+        .route("/collections", post(create_collection))
+        .route(
+            "/collections/{id}",
+            put(replace_collection).delete(remove_collection),
+        )
+        .route(
+            "/sessions/{id}/documents",
+            post(upload_document).layer(DefaultBodyLimit::max(documents::max_document_bytes())),
+        )
+        .route("/sessions/{id}", patch(update_session))
+        .route("/sessions/{id}/rejudge", post(rejudge))
+        .route("/sessions/{id}/edit", post(edit_round))
+        .route("/sessions/{id}/fork", post(fork_session))
+        .route("/sessions/{id}/ask-judge", post(ask_judge))
+        .route(
+            "/sessions/{id}/gallery",
+            post(nominate_session).delete(withdraw_nomination),
+        )
+        .merge(read_only)
+        .nest("/admin", admin)
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            track_requests,
+        ));
+    if !default_layers {
+        return router.with_state(state);
+    }
+    router
+        .layer(DefaultBodyLimit::max(validation::max_body_bytes()))
         .layer(TimeoutLayer::with_status_code(
             axum::http::StatusCode::REQUEST_TIMEOUT,
             Duration::from_secs(420),
@@ -55,15 +237,51 @@ pub async fn build_app(
         .layer(
             CorsLayer::new()
                 .allow_origin("*".parse::<HeaderValue>().unwrap())
-                .allow_methods([Method::GET, Method::POST])
-                .allow_headers([axum::http::header::CONTENT_TYPE]),
+                .allow_methods([
+                    Method::GET,
+                    Method::POST,
+                    Method::PUT,
+                    Method::PATCH,
+                    Method::DELETE,
+                ])
+                .allow_headers([
+                    axum::http::header::CONTENT_TYPE,
+                    axum::http::header::AUTHORIZATION,
+                ]),
         )
         .with_state(state)
 }
 
+/// Get TLS certificate and key paths (PEM) if HTTPS serving is configured.
+/// Both `TLS_CERT_PATH` and `TLS_KEY_PATH` must be set.
+fn tls_paths() -> Option<(String, String)> {
+    let cert = std::env::var("TLS_CERT_PATH")
+        .ok()
+        .filter(|v| !v.is_empty())?;
+    let key = std::env::var("TLS_KEY_PATH")
+        .ok()
+        .filter(|v| !v.is_empty())?;
+    Some((cert, key))
+}
+
 pub async fn serve(listener: TcpListener, app: Router) -> anyhow::Result<()> {
+    if let Some((cert_path, key_path)) = tls_paths() {
+        let tls_config = RustlsConfig::from_pem_file(&cert_path, &key_path)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load TLS cert/key: {}", e))?;
+        info!("ai-debate v0.2.0 running at https://127.0.0.1:3000");
+        axum_server::from_tcp_rustls(listener.into_std()?, tls_config)
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .await?;
+        return Ok(());
+    }
+
     info!("ai-debate v0.2.0 running at http://127.0.0.1:3000");
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
     Ok(())
 }
 
@@ -73,11 +291,27 @@ async fn index() -> Html<&'static str> {
     Html(include_str!("../static/index.html"))
 }
 
+async fn admin_page() -> Html<&'static str> {
+    Html(include_str!("../static/admin.html"))
+}
+
 async fn serve_favicon() -> Response {
     Response::builder()
         .header("Content-Type", "image/svg+xml")
         .header("Cache-Control", "public, max-age=86400")
-        .body(axum::body::Body::from(include_str!("../static/favicon.svg")))
+        .body(axum::body::Body::from(include_str!(
+            "../static/favicon.svg"
+        )))
+        .unwrap()
+}
+
+async fn serve_marked() -> Response {
+    Response::builder()
+        .header("Content-Type", "text/javascript; charset=utf-8")
+        .header("Cache-Control", "public, max-age=86400")
+        .body(axum::body::Body::from(include_str!(
+            "../static/js/marked.min.js"
+        )))
         .unwrap()
 }
 
@@ -101,10 +335,60 @@ async fn get_models(State(state): State<Arc<AppState>>) -> Json<serde_json::Valu
         },
         "features": {
             "web_search": tools::is_search_enabled(),
+            "news_search": tools::is_news_search_enabled(),
+            "judge_fact_check": fact_check::judge_fact_check_enabled(),
+            "search_provider": search::provider_name(),
+            "mock": config::mock_enabled(),
+            "guest_tokens": state.guests.is_some(),
         }
     }))
 }
 
+/// How UIs should present the configured Pro, Con and Judge: display
+/// names, provider colors and icons, and capability flags.
+async fn get_participants(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    let participants: Vec<Participant> = [
+        ("pro", &state.pro),
+        ("con", &state.con),
+        ("judge", &state.judge),
+    ]
+    .into_iter()
+    .map(|(role, client)| config::participant(role, &client.model_id))
+    .collect();
+    Json(json!({ "participants": participants }))
+}
+
+/// Issue a signed guest token for a new anonymous identity.
+async fn create_guest(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Some(guests) = &state.guests else {
+        return Err(ApiError::not_found("guest tokens are not enabled"));
+    };
+    let limit = RateLimit::guest();
+    let key = format!("guest:{}", audit::client_ip(&headers, &addr));
+    if state
+        .rate_limiter
+        .hit(&key, limit.window, limit.max_requests)
+        .await
+    {
+        return Err(ApiError::rate_limited());
+    }
+    let guest = guests.issue();
+    Ok(Json(json!({
+        "user_id": guest.user_id,
+        "token": guest.token,
+        "expires_at": guest.expires_at,
+    })))
+}
+
+/// Curated debate presets, usable as `preset` in the debate request.
+async fn list_presets() -> Json<serde_json::Value> {
+    Json(json!({ "presets": presets::PRESETS }))
+}
+
 async fn health(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
     Json(json!({
         "status": "ok",
@@ -119,65 +403,2407 @@ async fn health(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
     }))
 }
 
+/// Prometheus text exposition of request, stream, provider and token metrics.
+async fn get_metrics(State(state): State<Arc<AppState>>) -> Response {
+    Response::builder()
+        .status(200)
+        .header("Content-Type", "text/plain; version=0.0.4")
+        .body(Body::from(state.metrics.render()))
+        .unwrap()
+}
+
+/// RSS feed of the audio debates published as podcast episodes.
+#[cfg(feature = "podcast")]
+async fn get_podcast_feed(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<CategoryQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let category = match category_filter(&q.category) {
+        Ok(category) => category,
+        Err(e) => return e.into_response(),
+    };
+    let base_url = config::public_base_url().unwrap_or_else(|| {
+        let host = headers
+            .get(axum::http::header::HOST)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("localhost:3000");
+        format!("http://{}", host)
+    });
+    let episodes = state.db.fetch_podcast_episodes(category, 100).await;
+    Response::builder()
+        .header("Content-Type", "application/rss+xml; charset=utf-8")
+        .body(Body::from(podcast::render_feed(&base_url, &episodes)))
+        .unwrap()
+}
+
+/// Audio of one podcast episode (`{id}.mp3`).
+#[cfg(feature = "podcast")]
+async fn get_podcast_episode(
+    State(state): State<Arc<AppState>>,
+    Path(file): Path<String>,
+) -> Result<Response, ApiError> {
+    let not_found = || ApiError::not_found("episode not found");
+    let id = file
+        .strip_suffix(".mp3")
+        .and_then(|id| id.parse::<i64>().ok())
+        .ok_or_else(not_found)?;
+    let episode = state
+        .db
+        .fetch_podcast_episode(id)
+        .await
+        .ok_or_else(not_found)?;
+    let audio = tokio::fs::read(podcast::podcast_dir().join(&episode.file))
+        .await
+        .map_err(|_| not_found())?;
+    Ok(Response::builder()
+        .header("Content-Type", "audio/mpeg")
+        .header("Cache-Control", "public, max-age=86400")
+        .body(Body::from(audio))
+        .unwrap())
+}
+
+/// Per-provider, per-phase time-to-first-token, stream duration and error classes.
+async fn get_stats(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<CategoryQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let category = category_filter(&q.category)?;
+    Ok(Json(json!({
+        "uptime_secs": state.start_time.elapsed().as_secs(),
+        "providers": state.metrics.provider_stats(),
+        "tone": state.db.tone_by_provider(category).await,
+        "categories": state.db.sessions_by_category().await,
+        "prompt_variants": templates::variant_stats(&state.db, category).await,
+    })))
+}
+
+/// Which argument types most often appear in winning cases, per provider.
+async fn get_argument_analytics(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<ArgumentAnalyticsQuery>,
+) -> Result<Json<arguments::ArgumentAnalytics>, ApiError> {
+    let category = category_filter(&q.category)?;
+    let sessions = match q.topic.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        Some(topic) => {
+            let min_score = q.min_score.unwrap_or(0.6);
+            let sessions = similar::sessions_near(&state.db, topic, min_score)
+                .await
+                .map_err(|e| {
+                    warn!("Similar-topic lookup failed: {}", e);
+                    ApiError::provider_unavailable("embedding failed")
+                })?;
+            Some(sessions)
+        }
+        None => None,
+    };
+    Ok(Json(
+        arguments::analytics(&state.db, category, sessions.as_ref()).await,
+    ))
+}
+
+/// Model and provider standings with win rates and Elo ratings.
+async fn get_leaderboard(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<LeaderboardQuery>,
+) -> Result<Json<leaderboard::Leaderboard>, ApiError> {
+    let filter = leaderboard::Filter {
+        category: category_filter(&q.category)?,
+        from: date_filter("from", &q.from)?,
+        to: date_filter("to", &q.to)?,
+        judge_model: q
+            .judge_model
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty()),
+    };
+    Ok(Json(leaderboard::standings(&state.db, &filter).await))
+}
+
+/// A `YYYY-MM-DD` day from a query parameter.
+fn date_filter<'a>(
+    field: &'static str,
+    value: &'a Option<String>,
+) -> Result<Option<&'a str>, ApiError> {
+    let Some(day) = value.as_deref().map(str::trim).filter(|d| !d.is_empty()) else {
+        return Ok(None);
+    };
+    let valid = day.len() == 10
+        && day.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        });
+    if !valid {
+        return Err(ApiError::invalid(
+            field,
+            format!("{} must be a date (YYYY-MM-DD)", field),
+        ));
+    }
+    Ok(Some(day))
+}
+
+/// A known topic category from a `category` query parameter.
+fn category_filter(category: &Option<String>) -> Result<Option<&'static str>, ApiError> {
+    match category.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+        None => Ok(None),
+        Some(name) => categories::normalize(name)
+            .map(Some)
+            .ok_or_else(|| ApiError::invalid("category", "unknown category")),
+    }
+}
+
+/// Count requests by method, matched route and status.
+async fn track_requests(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_default();
+    let response = next.run(request).await;
+    state.metrics.incr_labeled(
+        "http_requests_total",
+        &[
+            ("method", &method),
+            ("path", &path),
+            ("status", response.status().as_str()),
+        ],
+        1,
+    );
+    response
+}
+
+/// Per-IP rate limit for read-only endpoints.
+async fn limit_read_only(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limit = RateLimit::read_only();
+    let key = format!("read:{}", audit::client_ip(request.headers(), &addr));
+    if state
+        .rate_limiter
+        .hit(&key, limit.window, limit.max_requests)
+        .await
+    {
+        return ApiError::rate_limited().into_response();
+    }
+    next.run(request).await
+}
+
+/// Return current spend, configured budgets, and the per-provider breakdown for this month.
+async fn get_budget(State(state): State<Arc<AppState>>) -> Response {
+    let status = budget::budget_status(&state.db).await;
+    let exceeded = status.is_exceeded();
+    let providers = state.db.spend_by_provider("start of month").await;
+    Json(json!({
+        "budget": status,
+        "exceeded": exceeded,
+        "providers_this_month": providers,
+    }))
+    .into_response()
+}
+
+/// Aggregated operational snapshot polled by the built-in `/admin` page.
+async fn get_dashboard(State(state): State<Arc<AppState>>) -> Response {
+    let (prompt_tokens, completion_tokens) = state.db.tokens_since("start of day").await;
+    let budget = budget::budget_status(&state.db).await;
+    let stats = state.metrics.provider_stats();
+    Json(json!({
+        "uptime_secs": state.start_time.elapsed().as_secs(),
+        "active_debates": state.metrics.gauge("active_debates"),
+        "cluster_active_debates": state.rate_limiter.add_active(rate_limit::ALL_DEBATES, 0).await,
+        "rate_limit_backend": state.rate_limiter.name(),
+        "queue_depth": state.metrics.gauge("queued_rounds"),
+        "provider_slots": state.provider_limits.snapshot(),
+        "debate_slots": state.debate_limits.snapshot(),
+        "provider_health": app_metrics::provider_health(&stats),
+        "today": {
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": completion_tokens,
+            "spend_usd": budget.daily_spent_usd,
+        },
+        "budget": budget,
+    }))
+    .into_response()
+}
+
+/// Audit trail of debate activity, newest first (`?user_id=&before=&limit=`).
+async fn get_audit(State(state): State<Arc<AppState>>, Query(q): Query<AuditQuery>) -> Response {
+    let limit = q.limit.unwrap_or(100).clamp(1, 1000);
+    let events = state
+        .db
+        .fetch_audit_events(q.user_id.as_deref(), q.before, limit)
+        .await;
+    Json(json!({ "events": events })).into_response()
+}
+
+/// The prompt templates in use, with their variables and built-in defaults.
+async fn list_prompt_templates(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    let active = templates::load(&state.db).await;
+    let entries: Vec<serde_json::Value> = [active.side, active.judge]
+        .into_iter()
+        .map(|template| {
+            json!({
+                "name": template.name,
+                "version": template.version,
+                "body": template.body,
+                "created_at": template.created_at,
+                "variables": templates::variables(&template.name),
+                "default": templates::builtin(&template.name).map(|t| t.body),
+            })
+        })
+        .collect();
+    Json(json!({ "templates": entries }))
+}
+
+/// Every version of a prompt template, newest first; version 0 is the
+/// built-in default.
+async fn list_prompt_template_versions(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let builtin = templates::builtin(&name).ok_or_else(|| unknown_template(&name))?;
+    let mut versions = state.db.fetch_prompt_template_versions(&name).await;
+    versions.push(builtin);
+    Ok(Json(json!({ "name": name, "versions": versions })))
+}
+
+/// Store a new version of a prompt template; debates started from now on
+/// use it.
+async fn save_prompt_template_version(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    payload: Result<Json<PromptTemplateRequest>, JsonRejection>,
+) -> Result<Response, ApiError> {
+    if templates::variables(&name).is_none() {
+        return Err(unknown_template(&name));
+    }
+    let Json(request) = payload.map_err(validation::json_rejection)?;
+    templates::validate(&name, &request.body).map_err(|error| ApiError::invalid("body", error))?;
+    let version = state
+        .db
+        .save_prompt_template(&name, &request.body)
+        .await
+        .map_err(ApiError::internal)?;
+    info!("Prompt template {} updated to version {}", name, version);
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({ "name": name, "version": version })),
+    )
+        .into_response())
+}
+
+/// The prompt templates a session ran with, to reproduce it.
+async fn get_session_prompt_templates(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    validate_id("session_id", &session_id)?;
+    if state
+        .db
+        .fetch_session_templates(&session_id)
+        .await
+        .is_empty()
+    {
+        return Err(ApiError::not_found(
+            "no prompt templates recorded for this session",
+        ));
+    }
+    let used = templates::for_session(&state.db, &session_id).await;
+    Ok(Json(
+        json!({ "session_id": session_id, "templates": [used.side, used.judge] }),
+    ))
+}
+
+/// The prompt variants of the running A/B test, if any.
+async fn list_prompt_variants(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    Json(json!({ "variants": state.db.fetch_prompt_variants().await }))
+}
+
+/// Start an A/B test: new debates are assigned to one of the variants at
+/// random. Replaces any running test.
+async fn set_prompt_variants(
+    State(state): State<Arc<AppState>>,
+    payload: Result<Json<PromptVariantsRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(request) = payload.map_err(validation::json_rejection)?;
+    templates::validate_variants(&state.db, &request.variants)
+        .await
+        .map_err(|error| ApiError::invalid("variants", error))?;
+    state
+        .db
+        .save_prompt_variants(&request.variants)
+        .await
+        .map_err(ApiError::internal)?;
+    info!(
+        "Prompt A/B test set: {}",
+        request
+            .variants
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    Ok(Json(json!({ "variants": request.variants })))
+}
+
+/// End the A/B test; new debates use the latest templates again.
+async fn clear_prompt_variants(State(state): State<Arc<AppState>>) -> Result<StatusCode, ApiError> {
+    state
+        .db
+        .save_prompt_variants(&[])
+        .await
+        .map_err(ApiError::internal)?;
+    info!("Prompt A/B test ended");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn unknown_template(name: &str) -> ApiError {
+    ApiError::not_found(format!(
+        "unknown template {:?}; expected one of {}",
+        name,
+        templates::NAMES.join(", ")
+    ))
+}
+
+/// Agreement between judge models over recent finished debates
+/// (`?models=&category=&limit=`). Verdicts missing for a judge are produced
+/// and stored, so later runs reuse them.
+async fn get_judge_calibration(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<JudgeCalibrationQuery>,
+) -> Result<Json<calibration::CalibrationReport>, ApiError> {
+    let category = category_filter(&q.category)?;
+    let bad_request = |error: String| ApiError::invalid("models", error);
+    let mut model_ids: Vec<String> = match q.models.as_deref().filter(|m| !m.trim().is_empty()) {
+        Some(models) => models.split(',').map(|m| m.trim().to_string()).collect(),
+        None => std::iter::once(state.judge.model_id.clone())
+            .chain(config::calibration_judge_model_ids())
+            .collect(),
+    };
+    model_ids.retain(|m| !m.is_empty());
+    let mut seen = HashSet::new();
+    model_ids.retain(|m| seen.insert(m.clone()));
+    if model_ids.len() < 2 {
+        return Err(bad_request(
+            "at least two judge models are needed (models= or CALIBRATION_JUDGE_MODELS)"
+                .to_string(),
+        ));
+    }
+    if budget::budget_status(&state.db).await.is_exceeded() {
+        return Err(ApiError::budget_exceeded());
+    }
+    let mut judges = Vec::new();
+    for model_id in &model_ids {
+        let judge = resolve_client(&state, &Some(model_id.clone()), "judge")
+            .await
+            .map_err(|e| bad_request(format!("Judge model init failed: {}", e)))?;
+        judges.push(judge);
+    }
+    let limit = q.limit.unwrap_or(10).clamp(1, 50);
+    let sessions = state.db.fetch_judged_sessions(category, limit).await;
+    let report = calibration::calibrate(&state.db, &judges, &sessions, state.metrics.clone()).await;
+    info!(
+        "Judge calibration over {} debates: {}",
+        report.debates,
+        model_ids.join(", ")
+    );
+    Ok(Json(report))
+}
+
+/// List documents uploaded for a session.
+async fn list_documents(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let docs = state.db.fetch_documents(&user_id, &session_id).await;
+    Ok(Json(json!({ "documents": docs })))
+}
+
+/// Captions for a session's debate, as an SRT or WebVTT download.
+async fn get_subtitles(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<SubtitleQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Response, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let format = match q.format.as_deref() {
+        None => SubtitleFormat::Srt,
+        Some(name) => SubtitleFormat::from_name(name)
+            .ok_or_else(|| ApiError::invalid("format", "format must be srt or vtt"))?,
+    };
+    let messages = state.db.fetch_history(&user_id, &session_id).await;
+    if messages.is_empty() {
+        return Err(ApiError::not_found("session not found"));
+    }
+    let timings = state.db.fetch_timings(&user_id, &session_id).await;
+    let cues = subtitles::cues(&messages, timings.as_ref());
+    Ok(Response::builder()
+        .header("Content-Type", format.content_type())
+        .header(
+            "Content-Disposition",
+            format!(
+                "attachment; filename=\"{}.{}\"",
+                session_id,
+                format.extension()
+            ),
+        )
+        .body(Body::from(subtitles::render(format, &cues)))
+        .unwrap())
+}
+
+/// A session's transcript as a JSON, Markdown or standalone HTML download.
+/// Sessions their owner made shared or public can be exported by anyone,
+/// signed in or not.
+async fn get_export(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<ExportQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Response, ApiError> {
+    validate_id("session_id", &session_id)?;
+    let metadata = state.db.fetch_session_metadata(&session_id).await;
+    let user = resolve_user(&state, host_user, &headers, &q.user_id).await;
+    let owner = readable_session_owner(&state, &session_id, metadata.as_ref(), user).await?;
+    let format = match q.format.as_deref() {
+        None => ExportFormat::Json,
+        Some(name) => ExportFormat::from_name(name)
+            .ok_or_else(|| ApiError::invalid("format", "format must be json, markdown or html"))?,
+    };
+    let messages = state.db.fetch_history(&owner, &session_id).await;
+    if messages.is_empty() {
+        return Err(ApiError::not_found("session not found"));
+    }
+    let topic = state
+        .db
+        .fetch_session_topic(&session_id)
+        .await
+        .unwrap_or_else(|| session_id.clone());
+    let transcript = export::transcript_from_history(&messages);
+    let (title, notes) = metadata
+        .as_ref()
+        .map(|m| (m.title.as_deref(), m.notes.as_deref()))
+        .unwrap_or_default();
+    Ok(Response::builder()
+        .header("Content-Type", format.content_type())
+        .header(
+            "Content-Disposition",
+            format!(
+                "attachment; filename=\"{}.{}\"",
+                session_id,
+                format.extension()
+            ),
+        )
+        .body(Body::from(format.render_session(
+            &topic,
+            title,
+            notes,
+            &transcript,
+        )))
+        .unwrap())
+}
+
+/// A session's metadata and how many clients are watching it live. Like its
+/// export, a shared or public session can be read by anyone.
+async fn get_session(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    validate_id("session_id", &session_id)?;
+    let metadata = state.db.fetch_session_metadata(&session_id).await;
+    let user = resolve_user(&state, host_user, &headers, &q.user_id).await;
+    let owner = readable_session_owner(&state, &session_id, metadata.as_ref(), user).await?;
+    let metadata = metadata
+        .filter(|metadata| metadata.user_id == owner)
+        .ok_or_else(|| ApiError::not_found("session not found"))?;
+    Ok(Json(json!({
+        "session": metadata,
+        "viewers": state.spectators.viewers(&session_id),
+    })))
+}
+
+/// Watch a running debate as SSE, from the events sent after joining until
+/// it ends. Anyone who can read the session can watch it.
+async fn spectate_session(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<SpectateQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Response, ApiError> {
+    validate_id("session_id", &session_id)?;
+    let metadata = state.db.fetch_session_metadata(&session_id).await;
+    let user = resolve_user(&state, host_user, &headers, &q.user_id).await;
+    readable_session_owner(&state, &session_id, metadata.as_ref(), user).await?;
+    let events = state
+        .spectators
+        .watch(&session_id)
+        .ok_or_else(|| ApiError::not_found("session is not live"))?;
+    let event_filter = EventFilter::parse(q.events.as_deref());
+    let body_stream = events.filter_map(move |event| {
+        let wanted = event_filter.wants(event["type"].as_str().unwrap_or_default());
+        std::future::ready(wanted.then(|| Ok::<_, std::io::Error>(sse_json(&event))))
+    });
+    Ok(Response::builder()
+        .status(200)
+        .header("Content-Type", "text/event-stream")
+        .header("Cache-Control", "no-cache")
+        .body(Body::from_stream(body_stream))
+        .unwrap())
+}
+
+/// A user's sessions, newest first, with their stored summaries.
+async fn list_sessions(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<SessionListQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    let limit = q.limit.unwrap_or(20).clamp(1, 100);
+    let category = category_filter(&q.category)?;
+    let sessions = state.db.fetch_sessions(&user_id, category, limit).await;
+    Ok(Json(json!({ "sessions": sessions })))
+}
+
+/// Set the title, notes or visibility of a session.
+async fn update_session(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    payload: Result<Json<SessionMetadataRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(request) = payload.map_err(validation::json_rejection)?;
+    let user_id = resolve_user(&state, host_user, &headers, &request.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let mut metadata = state
+        .db
+        .fetch_session_metadata(&session_id)
+        .await
+        .filter(|metadata| metadata.user_id == user_id)
+        .ok_or_else(|| ApiError::not_found("session not found"))?;
+    for (field, value, target, max_chars) in [
+        (
+            "title",
+            request.title,
+            &mut metadata.title,
+            MAX_SESSION_TITLE_CHARS,
+        ),
+        (
+            "notes",
+            request.notes,
+            &mut metadata.notes,
+            MAX_SESSION_NOTES_CHARS,
+        ),
+    ] {
+        let Some(value) = value else {
+            continue;
+        };
+        let value = value.trim();
+        if value.chars().count() > max_chars {
+            let error = format!("{} exceeds {} characters", field, max_chars);
+            return Err(ApiError::invalid(field, error));
+        }
+        *target = (!value.is_empty()).then(|| value.to_string());
+    }
+    if let Some(visibility) = request.visibility {
+        if !VISIBILITIES.contains(&visibility.as_str()) {
+            return Err(ApiError::invalid(
+                "visibility",
+                format!("visibility must be one of {}", VISIBILITIES.join(", ")),
+            ));
+        }
+        metadata.visibility = Some(visibility);
+    }
+    state
+        .db
+        .save_session_metadata(&metadata)
+        .await
+        .map_err(ApiError::internal)?;
+    // Title and notes as stored, after redaction
+    let metadata = state
+        .db
+        .fetch_session_metadata(&session_id)
+        .await
+        .unwrap_or(metadata);
+    Ok(Json(json!({ "session": metadata })))
+}
+
+/// Nominate a judged session for the public gallery, making it public. An
+/// admin approves it before it is listed.
+async fn nominate_session(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let mut metadata = state
+        .db
+        .fetch_session_metadata(&session_id)
+        .await
+        .filter(|metadata| metadata.user_id == user_id)
+        .ok_or_else(|| ApiError::not_found("session not found"))?;
+    let judged = state
+        .db
+        .fetch_history(&user_id, &session_id)
+        .await
+        .iter()
+        .any(|m| m.role == Position::Judge.role_str());
+    if !judged {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "not_judged",
+            "only judged debates can be nominated",
+        ));
+    }
+    let status = match state.db.fetch_gallery_status(&session_id).await.as_deref() {
+        Some("rejected") => {
+            return Err(ApiError::new(
+                StatusCode::CONFLICT,
+                "gallery_rejected",
+                "this debate was not accepted into the gallery",
+            ));
+        }
+        Some("approved") => "approved",
+        _ => "nominated",
+    };
+    if metadata.visibility.as_deref() != Some("public") {
+        metadata.visibility = Some("public".to_string());
+        state
+            .db
+            .save_session_metadata(&metadata)
+            .await
+            .map_err(ApiError::internal)?;
+    }
+    state
+        .db
+        .save_gallery_status(&session_id, Some(status))
+        .await
+        .map_err(ApiError::internal)?;
+    info!("Session {} nominated for the gallery", session_id);
+    Ok(Json(json!({ "session_id": session_id, "gallery": status })))
+}
+
+/// Withdraw a session from the gallery, or its nomination. The session stays
+/// public.
+async fn withdraw_nomination(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<StatusCode, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    match state.db.fetch_gallery_status(&session_id).await.as_deref() {
+        Some("nominated" | "approved") => {}
+        _ => return Err(ApiError::not_found("session is not in the gallery")),
+    }
+    state
+        .db
+        .save_gallery_status(&session_id, None)
+        .await
+        .map_err(ApiError::internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Approved debates of the public gallery with their summaries, winners and
+/// audience votes, most recently approved first.
+async fn get_gallery(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<GalleryQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let limit = q.limit.unwrap_or(20).clamp(1, 100);
+    let category = category_filter(&q.category)?;
+    let debates: Vec<serde_json::Value> = state
+        .db
+        .fetch_gallery("approved", category, limit)
+        .await
+        .into_iter()
+        .map(gallery_entry)
+        .collect();
+    Ok(Json(json!({ "debates": debates })))
+}
+
+/// Vote for the side that convinced the user in a gallery debate; a later
+/// vote replaces the user's earlier one.
+async fn vote_gallery(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    payload: Result<Json<GalleryVoteRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(request) = payload.map_err(validation::json_rejection)?;
+    let user_id = resolve_user(&state, host_user, &headers, &request.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    let side = request.side.trim().to_lowercase();
+    if side != "pro" && side != "con" {
+        return Err(ApiError::invalid("side", "side must be pro or con"));
+    }
+    let approved = state
+        .db
+        .fetch_gallery_entry(&session_id)
+        .await
+        .is_some_and(|entry| entry.status == "approved");
+    if !approved {
+        return Err(ApiError::not_found("debate is not in the gallery"));
+    }
+    state
+        .db
+        .save_gallery_vote(&session_id, &user_id, &side)
+        .await
+        .map_err(ApiError::internal)?;
+    let entry = state
+        .db
+        .fetch_gallery_entry(&session_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("debate is not in the gallery"))?;
+    Ok(Json(gallery_entry(entry)))
+}
+
+/// Gallery debates awaiting review, or with another curation status.
+async fn list_gallery_nominations(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<GalleryQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let status = q.status.as_deref().unwrap_or("nominated");
+    if !GALLERY_STATUSES.contains(&status) {
+        return Err(ApiError::invalid(
+            "status",
+            format!("status must be one of {}", GALLERY_STATUSES.join(", ")),
+        ));
+    }
+    let limit = q.limit.unwrap_or(20).clamp(1, 100);
+    let category = category_filter(&q.category)?;
+    let debates: Vec<serde_json::Value> = state
+        .db
+        .fetch_gallery(status, category, limit)
+        .await
+        .into_iter()
+        .map(gallery_entry)
+        .collect();
+    Ok(Json(json!({ "debates": debates })))
+}
+
+/// Approve a nominated debate into the gallery, or reject it (also taking
+/// an approved one out).
+async fn review_gallery_entry(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    payload: Result<Json<GalleryReviewRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(request) = payload.map_err(validation::json_rejection)?;
+    validate_id("session_id", &session_id)?;
+    let status = request.status.trim();
+    if status != "approved" && status != "rejected" {
+        return Err(ApiError::invalid(
+            "status",
+            "status must be approved or rejected",
+        ));
+    }
+    if state.db.fetch_gallery_entry(&session_id).await.is_none() {
+        return Err(ApiError::not_found("session was not nominated"));
+    }
+    state
+        .db
+        .save_gallery_status(&session_id, Some(status))
+        .await
+        .map_err(ApiError::internal)?;
+    info!("Gallery nomination {} {}", session_id, status);
+    let entry = state
+        .db
+        .fetch_gallery_entry(&session_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("session was not nominated"))?;
+    Ok(Json(gallery_entry(entry)))
+}
+
+/// A gallery debate as returned by the gallery endpoints.
+fn gallery_entry(entry: GalleryEntry) -> serde_json::Value {
+    let winner = entry
+        .verdict
+        .as_deref()
+        .and_then(prompts::parse_winner)
+        .map(|side| side.role_str());
+    let tags: Vec<&str> = entry
+        .tags
+        .as_deref()
+        .map(|t| t.split(',').collect())
+        .unwrap_or_default();
+    json!({
+        "session_id": entry.session_id,
+        "topic": entry.topic,
+        "summary": entry.summary,
+        "blurb": entry.blurb,
+        "tags": tags,
+        "winner": winner,
+        "votes": { "pro": entry.pro_votes, "con": entry.con_votes },
+        "status": entry.status,
+        "updated_at": entry.updated_at,
+    })
+}
+
+/// Stored post-debate analyses (such as fallacy findings) of a session.
+async fn get_analysis(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let analysis = state.db.fetch_analysis(&user_id, &session_id).await;
+    Ok(Json(json!({ "analysis": analysis })))
+}
+
+/// What a debate depended on besides the model outputs (seed, models,
+/// settings, template versions, precedent), recorded when it started.
+async fn get_debate_inputs(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let inputs = state
+        .db
+        .fetch_debate_inputs(&user_id, &session_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("session not found"))?;
+    Ok(Json(json!({ "inputs": inputs })))
+}
+
+/// Phase-aligned comparison of two of the user's debates, with a line diff
+/// of each pair of rounds and the difference in verdicts.
+async fn get_session_diff(
+    State(state): State<Arc<AppState>>,
+    Path((session_a, session_b)): Path<(String, String)>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    let mut sessions = Vec::new();
+    for session_id in [&session_a, &session_b] {
+        validate_id("session_id", session_id)?;
+        require_session_owner(&state, session_id, &user_id).await?;
+        let messages = state.db.fetch_history(&user_id, session_id).await;
+        if messages.is_empty() {
+            return Err(ApiError::not_found(format!(
+                "session {} not found",
+                session_id
+            )));
+        }
+        let topic = state
+            .db
+            .fetch_session_topic(session_id)
+            .await
+            .unwrap_or_else(|| session_id.clone());
+        sessions.push((topic, export::transcript_from_history(&messages)));
+    }
+    let (topic_b, transcript_b) = sessions.pop().unwrap_or_default();
+    let (topic_a, transcript_a) = sessions.pop().unwrap_or_default();
+    let normalized = |topic: &str| {
+        topic
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    let same_topic = normalized(&topic_a) == normalized(&topic_b);
+    Ok(Json(json!({
+        "a": { "session_id": session_a, "topic": topic_a },
+        "b": { "session_id": session_b, "topic": topic_b },
+        "same_topic": same_topic,
+        "diff": diff::diff_transcripts(&transcript_a, &transcript_b),
+    })))
+}
+
+/// The most impactful quotes of each side, picked by the judge's model on
+/// first request and stored.
+async fn get_highlights(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<HighlightsQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    if !q.refresh {
+        let stored = state
+            .db
+            .fetch_analysis(&user_id, &session_id)
+            .await
+            .into_iter()
+            .find(|a| a.kind == highlights::ANALYSIS_KIND);
+        if let Some(stored) = stored {
+            return Ok(Json(json!({ "highlights": stored.result })));
+        }
+    }
+    let messages = state.db.fetch_history(&user_id, &session_id).await;
+    if messages.is_empty() {
+        return Err(ApiError::not_found("session not found"));
+    }
+    let topic = state
+        .db
+        .fetch_session_topic(&session_id)
+        .await
+        .unwrap_or_else(|| session_id.clone());
+    let transcript = export::transcript_from_history(&messages);
+    let picked = highlights::extract(&state.judge, &topic, &transcript)
+        .await
+        .map_err(|e| {
+            warn!("Highlight extraction for {} failed: {}", session_id, e);
+            ApiError::provider_unavailable("analysis failed")
+        })?;
+    let result = json!(picked);
+    let stored = async {
+        state
+            .db
+            .delete_analysis(&session_id, highlights::ANALYSIS_KIND)
+            .await?;
+        state
+            .db
+            .save_analysis(
+                &user_id,
+                &session_id,
+                "all",
+                "all",
+                highlights::ANALYSIS_KIND,
+                &result,
+            )
+            .await
+    };
+    if let Err(e) = stored.await {
+        warn!("Failed to save highlights for {}: {}", session_id, e);
+    }
+    Ok(Json(json!({ "highlights": result })))
+}
+
+/// Comprehension and critical-thinking questions with answer keys, written
+/// by the judge's model on first request and stored.
+async fn get_quiz(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<QuizQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Response, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let format = match q.format.as_deref().map(ExportFormat::from_name) {
+        None => ExportFormat::Json,
+        Some(Some(format)) if format != ExportFormat::Html => format,
+        Some(_) => {
+            return Err(ApiError::invalid(
+                "format",
+                "format must be json or markdown",
+            ))
+        }
+    };
+    let messages = state.db.fetch_history(&user_id, &session_id).await;
+    if messages.is_empty() {
+        return Err(ApiError::not_found("session not found"));
+    }
+    let topic = state
+        .db
+        .fetch_session_topic(&session_id)
+        .await
+        .unwrap_or_else(|| session_id.clone());
+    let stored = if q.refresh {
+        None
+    } else {
+        state
+            .db
+            .fetch_analysis(&user_id, &session_id)
+            .await
+            .into_iter()
+            .find(|a| a.kind == quiz::ANALYSIS_KIND)
+            .and_then(|a| serde_json::from_value::<Vec<quiz::Question>>(a.result).ok())
+    };
+    let questions = match stored {
+        Some(questions) => questions,
+        None => {
+            let transcript = export::transcript_from_history(&messages);
+            let questions = quiz::generate(&state.judge, &topic, &transcript)
+                .await
+                .map_err(|e| {
+                    warn!("Quiz generation for {} failed: {}", session_id, e);
+                    ApiError::provider_unavailable("analysis failed")
+                })?;
+            let result = json!(questions);
+            let stored = async {
+                state
+                    .db
+                    .delete_analysis(&session_id, quiz::ANALYSIS_KIND)
+                    .await?;
+                state
+                    .db
+                    .save_analysis(
+                        &user_id,
+                        &session_id,
+                        "all",
+                        "all",
+                        quiz::ANALYSIS_KIND,
+                        &result,
+                    )
+                    .await
+            };
+            if let Err(e) = stored.await {
+                warn!("Failed to save quiz for {}: {}", session_id, e);
+            }
+            questions
+        }
+    };
+    let body = match format {
+        ExportFormat::Markdown => quiz::to_markdown(&topic, &questions),
+        _ => serde_json::to_string_pretty(&json!({ "topic": topic, "questions": questions }))
+            .unwrap_or_default(),
+    };
+    Ok(Response::builder()
+        .header("Content-Type", format.content_type())
+        .header(
+            "Content-Disposition",
+            format!(
+                "inline; filename=\"{}-quiz.{}\"",
+                session_id,
+                format.extension()
+            ),
+        )
+        .body(Body::from(body))
+        .unwrap())
+}
+
+/// Earlier debates of the user on motions related to `topic`, most similar first.
+async fn get_similar_sessions(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<SimilarQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    let topic = q.topic.trim();
+    if topic.is_empty() || topic.len() > 2000 {
+        return Err(ApiError::invalid(
+            "topic",
+            "topic is required (at most 2000 bytes)",
+        ));
+    }
+    let limit = q.limit.unwrap_or(5).clamp(1, 20);
+    let debates = similar::find_similar(&state.db, &user_id, topic, None, limit)
+        .await
+        .map_err(|e| {
+            warn!("Similar debate search failed: {}", e);
+            ApiError::provider_unavailable("embedding failed")
+        })?;
+    Ok(Json(json!({ "topic": topic, "debates": debates })))
+}
+
+/// Upload a document (PDF, text, Markdown) as evidence for a session.
+/// The raw file is the request body; the filename is passed as a query parameter.
+async fn upload_document(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<DocumentUploadQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    body: Bytes,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let bad_request = |msg: String| ApiError::new(StatusCode::BAD_REQUEST, "invalid_document", msg);
+    if body.is_empty() {
+        return Err(bad_request("empty document".to_string()));
+    }
+    let content_type = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("text/plain")
+        .to_string();
+
+    let text = documents::extract_text(&q.filename, &content_type, body.to_vec())
+        .await
+        .map_err(|e| bad_request(e.to_string()))?;
+    let (document_id, chunks) = documents::ingest_document(
+        &state.db,
+        &user_id,
+        &session_id,
+        &q.filename,
+        &content_type,
+        &text,
+    )
+    .await
+    .map_err(|e| bad_request(e.to_string()))?;
+
+    info!(
+        "Document '{}' ingested for session {} ({} chunks)",
+        q.filename, session_id, chunks
+    );
+    Ok(Json(json!({
+        "document_id": document_id,
+        "filename": q.filename,
+        "chunks": chunks,
+    })))
+}
+
+/// Judge a finished debate again, optionally with another judge model. Only
+/// the judgement phase runs, against the stored Pro and Con rounds; the new
+/// verdict is stored as a `rejudge` analysis next to the original one.
+async fn rejudge(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    payload: Result<Json<RejudgeRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(payload) = payload.map_err(validation::json_rejection)?;
+    let user_id = resolve_user(&state, host_user, &headers, &payload.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    if is_rate_limited(&state, &user_id).await {
+        return Err(ApiError::rate_limited());
+    }
+    if budget::budget_status(&state.db).await.is_exceeded() {
+        return Err(ApiError::budget_exceeded());
+    }
+    let messages = state.db.fetch_history(&user_id, &session_id).await;
+    let (judged, transcript) =
+        calibration::split_transcript(export::transcript_from_history(&messages));
+    let Some(original) = calibration::original_verdict(&judged) else {
+        return Err(ApiError::not_found(
+            "session has no verdict to compare with",
+        ));
+    };
+    let topic = state
+        .db
+        .fetch_session_topic(&session_id)
+        .await
+        .unwrap_or_else(|| session_id.clone());
+    let judge_client = resolve_client(&state, &payload.judge_model, "judge")
+        .await
+        .map_err(|e| ApiError::invalid("judge_model", format!("Judge model init failed: {}", e)))?;
+    let alternative = calibration::judge_again(
+        &state.db,
+        &judge_client,
+        &user_id,
+        &session_id,
+        &topic,
+        &transcript,
+        state.metrics.clone(),
+    )
+    .await
+    .map_err(|e| {
+        warn!("Re-judging {} failed: {}", session_id, e);
+        ApiError::provider_unavailable("analysis failed")
+    })?;
+    info!("Session {} re-judged by {}", session_id, alternative.model);
+    let agrees = original.winner == alternative.winner;
+    Ok(Json(json!({
+        "original": original,
+        "alternative": alternative,
+        "agrees": agrees,
+    })))
+}
+
+/// Replace the text of a stored Pro or Con round and continue the debate from
+/// it. The earlier rounds and the edited one, marked as human-edited, are
+/// copied into a new session linked to this one; the later rounds and the
+/// verdict then run again in the background with the original models.
+async fn edit_round(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    payload: Result<Json<EditRoundRequest>, JsonRejection>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let Json(payload) = payload.map_err(validation::json_rejection)?;
+    let user_id = resolve_user(&state, host_user, &headers, &payload.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let content = payload.content.trim();
+    if content.is_empty() || content.len() > MAX_EDITED_ROUND_BYTES {
+        let error = format!(
+            "content is required (at most {} bytes)",
+            MAX_EDITED_ROUND_BYTES
+        );
+        return Err(ApiError::invalid("content", error));
+    }
+    if is_rate_limited(&state, &user_id).await {
+        return Err(ApiError::rate_limited());
+    }
+    if budget::budget_status(&state.db).await.is_exceeded() {
+        return Err(ApiError::budget_exceeded());
+    }
+    let messages = state.db.fetch_history(&user_id, &session_id).await;
+    let Some(edited) = messages.iter().find(|m| m.id == payload.message_id) else {
+        return Err(ApiError::not_found("round not found").with_field("message_id"));
+    };
+    let (Some(side @ (Position::Pro | Position::Con)), Some(phase)) = (
+        Position::from_role(&edited.role),
+        DebatePhase::from_name(&edited.phase),
+    ) else {
+        return Err(ApiError::invalid(
+            "message_id",
+            "only Pro and Con rounds can be edited",
+        ));
+    };
+    let transcript = export::transcript_from_history(&messages);
+    let mut rounds = rounds_before(&transcript, side, phase);
+    rounds.push((
+        side,
+        phase,
+        content.to_string(),
+        edited.provider.clone().unwrap_or_default(),
+    ));
+
+    // The later rounds run with the models of the original debate
+    let roles = continuation_roles(&state, &transcript, [&None, &None, &None]).await?;
+    let fork_id = format!("edit-{}-{}", payload.message_id, unix_millis());
+    start_fork(&state, &user_id, &session_id, &fork_id, roles, rounds, true).await?;
+    info!(
+        "Session {} edited at message {} into {}",
+        session_id, payload.message_id, fork_id
+    );
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "session_id": fork_id, "forked_from": session_id })),
+    ))
+}
+
+/// Copy a finished debate up to the end of `after_phase` into a new session
+/// linked to this one, and run the later phases there in the background,
+/// with the original models unless others are given.
+async fn fork_session(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<ForkQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    payload: Result<Json<ForkRequest>, JsonRejection>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let Json(payload) = payload.map_err(validation::json_rejection)?;
+    let user_id = resolve_user(&state, host_user, &headers, &payload.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let after_phase = DebatePhase::from_name(q.after_phase.trim());
+    let next_phase = match after_phase {
+        Some(DebatePhase::Opening) => DebatePhase::Rebuttal,
+        Some(DebatePhase::Rebuttal) => DebatePhase::Defense,
+        Some(DebatePhase::Defense) => DebatePhase::Closing,
+        Some(DebatePhase::Closing) => DebatePhase::Judgement,
+        _ => {
+            return Err(ApiError::invalid(
+                "after_phase",
+                "after_phase must be opening, rebuttal, defense or closing",
+            ))
+        }
+    };
+    if is_rate_limited(&state, &user_id).await {
+        return Err(ApiError::rate_limited());
+    }
+    if budget::budget_status(&state.db).await.is_exceeded() {
+        return Err(ApiError::budget_exceeded());
+    }
+    let messages = state.db.fetch_history(&user_id, &session_id).await;
+    if messages.is_empty() {
+        return Err(ApiError::not_found("session not found"));
+    }
+    let transcript = export::transcript_from_history(&messages);
+    let rounds = rounds_before(&transcript, Position::Pro, next_phase);
+    let reached = |side: Position| {
+        rounds
+            .iter()
+            .any(|(s, p, ..)| *s == side && Some(*p) == after_phase)
+    };
+    if !(reached(Position::Pro) && reached(Position::Con)) {
+        return Err(ApiError::invalid(
+            "after_phase",
+            "the debate did not finish that phase",
+        ));
+    }
+
+    let models = [&payload.pro_model, &payload.con_model, &payload.judge_model];
+    let roles = continuation_roles(&state, &transcript, models).await?;
+    let after_phase = after_phase.map_or("", |p| p.as_str());
+    let fork_id = format!("fork-{}-{}", after_phase, unix_millis());
+    start_fork(
+        &state,
+        &user_id,
+        &session_id,
+        &fork_id,
+        roles,
+        rounds,
+        false,
+    )
+    .await?;
+    info!(
+        "Session {} forked after {} into {}",
+        session_id, after_phase, fork_id
+    );
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "session_id": fork_id, "forked_from": session_id })),
+    ))
+}
+
+/// Clients for continuing a debate: each role uses the model in `models`
+/// (Pro, Con, judge) if given, or else the one it had in `transcript`.
+async fn continuation_roles(
+    state: &AppState,
+    transcript: &[Turn],
+    models: [&Option<String>; 3],
+) -> Result<Roles, ApiError> {
+    let defaults = (state.pro.clone(), state.con.clone(), state.judge.clone());
+    let model_of = |side: Position, default: &ClientInfo, chosen: &Option<String>| {
+        let chosen = chosen.as_deref().map(str::trim).filter(|m| !m.is_empty());
+        match chosen {
+            Some(model) => Some(model.to_string()),
+            None => transcript
+                .iter()
+                .rev()
+                .find(|(s, ..)| *s == side)
+                .map(|(.., model)| model.clone())
+                .filter(|model| !model.is_empty() && *model != default.model_id),
+        }
+    };
+    Roles::resolve(
+        &defaults,
+        &model_of(Position::Pro, &defaults.0, models[0]),
+        &model_of(Position::Con, &defaults.1, models[1]),
+        &model_of(Position::Judge, &defaults.2, models[2]),
+    )
+    .await
+    .map_err(|e| ApiError::provider_unavailable(format!("Model init failed: {}", e)))
+}
+
+/// Copy `rounds` of `session_id` into the new session `fork_id`, linked to
+/// it, and run the rest of the debate there in the background with the
+/// original prompt templates, preset and language. With `edited`, the last
+/// copied round is marked as human-edited.
+async fn start_fork(
+    state: &Arc<AppState>,
+    user_id: &str,
+    session_id: &str,
+    fork_id: &str,
+    roles: Roles,
+    rounds: Vec<Turn>,
+    edited: bool,
+) -> Result<(), ApiError> {
+    let topic = state
+        .db
+        .fetch_session_topic(session_id)
+        .await
+        .unwrap_or_else(|| session_id.to_string());
+    let prompt_templates = templates::for_session(&state.db, session_id).await;
+    let inputs = state.db.fetch_debate_inputs(user_id, session_id).await;
+    let preset = inputs
+        .as_ref()
+        .and_then(|inputs| inputs["preset"].as_str().and_then(presets::find));
+    let language = inputs
+        .as_ref()
+        .and_then(|inputs| inputs["language"].as_str().map(str::to_string));
+    let judging_rubric = inputs
+        .as_ref()
+        .and_then(|inputs| serde_json::from_value::<Rubric>(inputs["rubric"].clone()).ok());
+    let stored = async {
+        state.db.save_session(user_id, fork_id, &topic).await?;
+        state.db.save_session_fork(fork_id, session_id).await?;
+        state
+            .db
+            .save_session_templates(fork_id, &prompt_templates.versions())
+            .await?;
+        for (i, (side, phase, content, model)) in rounds.iter().enumerate() {
+            let model = Some(model.as_str()).filter(|m| !m.is_empty());
+            let id = state
+                .db
+                .save_message(user_id, fork_id, *side, *phase, model, content)
+                .await?;
+            if edited && i + 1 == rounds.len() {
+                state.db.mark_message_edited(id).await?;
+            }
+        }
+        anyhow::Ok(())
+    };
+    stored
+        .await
+        .map_err(|e| ApiError::internal(format!("storing the copy of {}: {}", session_id, e)))?;
+
+    let tool_ctx = tools::ToolContext::new(
+        state.db.clone(),
+        user_id.to_string(),
+        fork_id.to_string(),
+        roles.fact_checker.clone(),
+        state.metrics.clone(),
+    )
+    .with_preset(preset)
+    .with_rubric(judging_rubric)
+    .with_templates(prompt_templates)
+    .with_language(language);
+    continue_in_background(
+        state.clone(),
+        user_id.to_string(),
+        fork_id.to_string(),
+        topic,
+        roles,
+        tool_ctx,
+        rounds,
+    );
+    Ok(())
+}
+
+fn unix_millis() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+/// Run the rounds of `session_id` after those in `transcript`, saving each
+/// one as it finishes.
+fn continue_in_background(
+    state: Arc<AppState>,
+    user_id: String,
+    session_id: String,
+    topic: String,
+    roles: Roles,
+    tool_ctx: tools::ToolContext,
+    transcript: Vec<Turn>,
+) {
+    tokio::spawn(async move {
+        let events = continue_debate(&topic, &roles, &tool_ctx, transcript);
+        tokio::pin!(events);
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(RunEvent::RoundFinished((side, phase, content, model))) => {
+                    let saved = state
+                        .db
+                        .save_message(&user_id, &session_id, side, phase, Some(&model), &content)
+                        .await;
+                    if let Err(e) = saved {
+                        warn!("Failed to save a round of {}: {}", session_id, e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("Continuing {} failed: {}", session_id, e);
+                    return;
+                }
+            }
+        }
+        info!("Session {} finished", session_id);
+    });
+}
+
+/// Answer a follow-up question about a session's verdict, as its judge, and
+/// store the exchange.
+async fn ask_judge(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    payload: Result<Json<AskJudgeRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(payload) = payload.map_err(validation::json_rejection)?;
+    let user_id = resolve_user(&state, host_user, &headers, &payload.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let question = payload.question.trim();
+    if question.is_empty() || question.len() > judge_qa::MAX_QUESTION_BYTES {
+        let error = format!(
+            "question is required (at most {} bytes)",
+            judge_qa::MAX_QUESTION_BYTES
+        );
+        return Err(ApiError::invalid("question", error));
+    }
+    if is_rate_limited(&state, &user_id).await {
+        return Err(ApiError::rate_limited());
+    }
+    if budget::budget_status(&state.db).await.is_exceeded() {
+        return Err(ApiError::budget_exceeded());
+    }
+    let messages = state.db.fetch_history(&user_id, &session_id).await;
+    let transcript = export::transcript_from_history(&messages);
+    // The judge that gave the verdict answers for it
+    let verdict = transcript
+        .iter()
+        .rev()
+        .find(|(side, ..)| *side == Position::Judge);
+    let Some((_, _, _, judge_model)) = verdict else {
+        return Err(ApiError::not_found("session has no verdict to ask about"));
+    };
+    let judge = match resolve_client(&state, &Some(judge_model.clone()), "judge").await {
+        Ok(judge) => judge,
+        Err(e) => {
+            warn!(
+                "Judge {} unavailable, answering with the default judge: {}",
+                judge_model, e
+            );
+            state.judge.clone()
+        }
+    };
+    let topic = state
+        .db
+        .fetch_session_topic(&session_id)
+        .await
+        .unwrap_or_else(|| session_id.clone());
+    let earlier = state.db.fetch_judge_questions(&user_id, &session_id).await;
+    let answer = judge_qa::answer(&judge, &topic, &transcript, &earlier, question)
+        .await
+        .map_err(|e| {
+            warn!("Judge answer for {} failed: {}", session_id, e);
+            ApiError::provider_unavailable("analysis failed")
+        })?;
+    let id = state
+        .db
+        .save_judge_question(&user_id, &session_id, question, &answer, &judge.model_id)
+        .await
+        .map_err(|e| {
+            ApiError::internal(format!("saving the judge answer for {}: {}", session_id, e))
+        })?;
+    Ok(Json(json!({
+        "id": id,
+        "question": question,
+        "answer": answer,
+        "model": judge.model_id,
+    })))
+}
+
+/// Questions asked to the judge about a session, with the answers, oldest first.
+async fn list_judge_questions(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &session_id)?;
+    require_session_owner(&state, &session_id, &user_id).await?;
+    let questions = state.db.fetch_judge_questions(&user_id, &session_id).await;
+    Ok(Json(json!({ "questions": questions })))
+}
+
+/// Today's debate quota usage for a user.
+async fn get_quota(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<quota::QuotaStatus>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    Ok(Json(quota::quota_status(&state.db, &user_id).await))
+}
+
+/// The defaults a user saved for their debates; all empty if none.
+async fn get_preferences(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    let preferences = state
+        .db
+        .fetch_preferences(&user_id)
+        .await
+        .unwrap_or_default();
+    Ok(Json(json!({ "preferences": preferences })))
+}
+
+/// Save a user's debate defaults, replacing any saved before. Debates apply
+/// them to the fields their request leaves out.
+async fn put_preferences(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    payload: Result<Json<PreferencesRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(request) = payload.map_err(validation::json_rejection)?;
+    let user_id = resolve_user(&state, host_user, &headers, &request.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    let mut preferences = request.preferences;
+    for field in [
+        &mut preferences.pro_model,
+        &mut preferences.con_model,
+        &mut preferences.judge_model,
+        &mut preferences.preset,
+        &mut preferences.language,
+    ] {
+        *field = field
+            .take()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+    }
+    if let Some(id) = preferences
+        .preset
+        .as_deref()
+        .filter(|id| presets::find(id).is_none())
+    {
+        return Err(ApiError::invalid(
+            "preset",
+            format!("unknown preset {:?}", id),
+        ));
+    }
+    if let Some(language) = &preferences.language {
+        validation::validate_language(language)?;
+    }
+    state
+        .db
+        .save_preferences(&user_id, &preferences)
+        .await
+        .map_err(ApiError::internal)?;
+    Ok(Json(json!({ "preferences": preferences })))
+}
+
+/// Forget a user's debate defaults.
+async fn delete_user_preferences(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<StatusCode, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    match state.db.delete_preferences(&user_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(ApiError::not_found("no preferences saved")),
+        Err(e) => Err(ApiError::internal(e)),
+    }
+}
+
+/// Create a topic collection: an ordered list of topics, such as the units of
+/// a course, that debates are run against.
+async fn create_collection(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    payload: Result<Json<CollectionRequest>, JsonRejection>,
+) -> Result<Response, ApiError> {
+    let Json(request) = payload.map_err(validation::json_rejection)?;
+    let user_id = resolve_user(&state, host_user, &headers, &request.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    let (name, description, topics) = collection_fields(request)?;
+    let id = state
+        .db
+        .save_collection(&user_id, &name, description.as_deref(), &topics)
+        .await
+        .map_err(ApiError::internal)?;
+    let collection = state
+        .db
+        .fetch_collection(&user_id, id)
+        .await
+        .ok_or_else(|| ApiError::internal("collection not saved"))?;
+    let items = state.db.fetch_collection_items(id).await;
+    let body = json!({ "collection": collection, "items": items });
+    Ok((StatusCode::CREATED, Json(body)).into_response())
+}
+
+/// The user's topic collections, newest first.
+async fn list_collections(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    Ok(Json(
+        json!({ "collections": state.db.fetch_collections(&user_id).await }),
+    ))
+}
+
+/// A topic collection with the debates run against each of its topics and
+/// their winners, and the collection's progress.
+async fn get_collection(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    let collection = state
+        .db
+        .fetch_collection(&user_id, id)
+        .await
+        .ok_or_else(|| ApiError::not_found("collection not found"))?;
+    let debates = state.db.fetch_collection_debates(id).await;
+    let (mut debated, mut judged, mut pro_wins, mut con_wins) = (0, 0, 0, 0);
+    let items: Vec<serde_json::Value> = state
+        .db
+        .fetch_collection_items(id)
+        .await
+        .into_iter()
+        .map(|item| {
+            let results: Vec<_> = debates
+                .iter()
+                .filter(|debate| debate.item_id == item.id)
+                .map(|debate| {
+                    let winner = debate.verdict.as_deref().and_then(prompts::parse_winner);
+                    (debate, winner.map(|side| side.role_str()))
+                })
+                .collect();
+            // An item's result is that of its latest judged debate
+            let latest = results
+                .iter()
+                .rev()
+                .find(|(debate, _)| debate.verdict.is_some());
+            let winner = latest.and_then(|(_, winner)| *winner);
+            debated += usize::from(!results.is_empty());
+            judged += usize::from(latest.is_some());
+            match winner {
+                Some("pro") => pro_wins += 1,
+                Some("con") => con_wins += 1,
+                _ => {}
+            }
+            let debates: Vec<serde_json::Value> = results
+                .iter()
+                .map(|(debate, winner)| {
+                    json!({
+                        "session_id": debate.session_id,
+                        "created_at": debate.created_at,
+                        "judged": debate.verdict.is_some(),
+                        "winner": winner,
+                    })
+                })
+                .collect();
+            json!({
+                "id": item.id,
+                "position": item.position,
+                "topic": item.topic,
+                "debates": debates,
+                "winner": winner,
+            })
+        })
+        .collect();
+    let progress = json!({
+        "items": items.len(),
+        "debated": debated,
+        "judged": judged,
+        "pro_wins": pro_wins,
+        "con_wins": con_wins,
+    });
+    Ok(Json(
+        json!({ "collection": collection, "items": items, "progress": progress }),
+    ))
+}
+
+/// Replace the name, description and topics of a topic collection. Topics
+/// kept from before keep their debates.
+async fn replace_collection(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    payload: Result<Json<CollectionRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(request) = payload.map_err(validation::json_rejection)?;
+    let user_id = resolve_user(&state, host_user, &headers, &request.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    if state.db.fetch_collection(&user_id, id).await.is_none() {
+        return Err(ApiError::not_found("collection not found"));
+    }
+    let (name, description, topics) = collection_fields(request)?;
+    state
+        .db
+        .update_collection(id, &name, description.as_deref(), &topics)
+        .await
+        .map_err(ApiError::internal)?;
+    let collection = state
+        .db
+        .fetch_collection(&user_id, id)
+        .await
+        .ok_or_else(|| ApiError::not_found("collection not found"))?;
+    let items = state.db.fetch_collection_items(id).await;
+    Ok(Json(json!({ "collection": collection, "items": items })))
+}
+
+/// Delete a topic collection; the debates run against it are kept.
+async fn remove_collection(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(q): Query<SessionQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<StatusCode, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    match state.db.delete_collection(&user_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(ApiError::not_found("collection not found")),
+        Err(e) => Err(ApiError::internal(e)),
+    }
+}
+
+/// The trimmed name, description and topics of a collection request.
+fn collection_fields(
+    request: CollectionRequest,
+) -> Result<(String, Option<String>, Vec<String>), ApiError> {
+    let name = request.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_COLLECTION_NAME_CHARS {
+        let error = format!(
+            "name is required (at most {} characters)",
+            MAX_COLLECTION_NAME_CHARS
+        );
+        return Err(ApiError::invalid("name", error));
+    }
+    let description = request
+        .description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty());
+    if description
+        .as_ref()
+        .is_some_and(|d| d.chars().count() > MAX_COLLECTION_DESCRIPTION_CHARS)
+    {
+        let error = format!(
+            "description exceeds {} characters",
+            MAX_COLLECTION_DESCRIPTION_CHARS
+        );
+        return Err(ApiError::invalid("description", error));
+    }
+    let topics: Vec<String> = request
+        .topics
+        .iter()
+        .map(|t| t.trim().to_string())
+        .collect();
+    if topics.is_empty() || topics.len() > MAX_COLLECTION_TOPICS {
+        let error = format!("a collection has 1-{} topics", MAX_COLLECTION_TOPICS);
+        return Err(ApiError::invalid("topics", error));
+    }
+    for (i, topic) in topics.iter().enumerate() {
+        if topic.is_empty() || topic.len() > 2000 {
+            let error = format!("topic {} is empty or exceeds 2000 bytes", i + 1);
+            return Err(ApiError::invalid("topics", error));
+        }
+        if topics[..i].contains(topic) {
+            return Err(ApiError::invalid(
+                "topics",
+                format!("topic {} is listed twice", i + 1),
+            ));
+        }
+    }
+    Ok((name, description, topics))
+}
+
 async fn get_history(
     State(state): State<Arc<AppState>>,
     Query(q): Query<HistoryQuery>,
-) -> Json<serde_json::Value> {
-    let rows: Vec<HistoryMessage> = fetch_history(&state.db, &q.user_id, &q.session_id).await;
-    Json(json!({ "history": rows }))
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = resolve_user(&state, host_user, &headers, &q.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &q.session_id)?;
+    require_session_owner(&state, &q.session_id, &user_id).await?;
+    let rows: Vec<HistoryMessage> = state.db.fetch_history(&user_id, &q.session_id).await;
+    let partial = state.db.fetch_checkpoints(&user_id, &q.session_id).await;
+    let tone = round_tone(&state, &user_id, &q.session_id).await;
+    Ok(Json(
+        json!({ "history": rows, "partial": partial, "tone": tone }),
+    ))
 }
 
 async fn get_history_post(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<DebateRequest>,
-) -> Json<serde_json::Value> {
-    let rows: Vec<HistoryMessage> =
-        fetch_history(&state.db, &payload.user_id, &payload.session_id).await;
-    Json(json!({ "history": rows }))
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    payload: Result<Json<DebateRequest>, JsonRejection>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Json(payload) = payload.map_err(validation::json_rejection)?;
+    let user_id = resolve_user(&state, host_user, &headers, &payload.user_id).await?;
+    validate_id("user_id", &user_id)?;
+    validate_id("session_id", &payload.session_id)?;
+    require_session_owner(&state, &payload.session_id, &user_id).await?;
+    let rows: Vec<HistoryMessage> = state.db.fetch_history(&user_id, &payload.session_id).await;
+    let partial = state
+        .db
+        .fetch_checkpoints(&user_id, &payload.session_id)
+        .await;
+    let tone = round_tone(&state, &user_id, &payload.session_id).await;
+    Ok(Json(
+        json!({ "history": rows, "partial": partial, "tone": tone }),
+    ))
+}
+
+/// Stored tone scores of a session's rounds, for the history view.
+async fn round_tone(state: &AppState, user_id: &str, session_id: &str) -> Vec<serde_json::Value> {
+    state
+        .db
+        .fetch_analysis(user_id, session_id)
+        .await
+        .into_iter()
+        .filter(|a| a.kind == tone::ANALYSIS_KIND)
+        .map(|a| json!({ "role": a.role, "phase": a.phase, "tone": a.result }))
+        .collect()
 }
 
 async fn debate_stream(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<DebateRequest>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<StreamQuery>,
+    headers: HeaderMap,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    payload: Result<Json<DebateRequest>, JsonRejection>,
 ) -> Response {
+    let mut payload = match payload {
+        Ok(Json(payload)) => payload,
+        Err(rejection) => return validation::json_rejection(rejection).into_response(),
+    };
     let timer = state.metrics.start_timer("debate_stream").await;
+    let auth_result = resolve_user(&state, host_user, &headers, &payload.user_id).await;
+    if let Ok(user_id) = &auth_result {
+        payload.user_id = user_id.clone();
+    }
+    let mut audit = AuditContext {
+        user_id: payload.user_id.clone(),
+        session_id: payload.session_id.clone(),
+        ip: audit::client_ip(&headers, &addr),
+        topic: payload.topic.chars().take(500).collect(),
+        providers: String::new(),
+    };
+
+    if let Err(rejection) = auth_result {
+        audit
+            .record(&state.db, "rejected", Some("unauthorized"))
+            .await;
+        if let Some(t) = timer {
+            t.stop();
+        }
+        return rejection.into_response();
+    }
+
+    if let Err(invalid) = validate_id("user_id", &payload.user_id)
+        .and_then(|_| validate_id("session_id", &payload.session_id))
+        .and_then(|_| match &payload.notify_email {
+            Some(address) => validation::validate_notify_email(address),
+            None => Ok(()),
+        })
+        .and_then(|_| validation::validate_podcast(payload.podcast))
+    {
+        if let Some(t) = timer {
+            t.stop();
+        }
+        return invalid.into_response();
+    }
+    let event_filter = EventFilter::parse(query.events.as_deref());
+
+    if let Err(forbidden) =
+        require_session_owner(&state, &payload.session_id, &payload.user_id).await
+    {
+        audit
+            .record(&state.db, "rejected", Some("session_forbidden"))
+            .await;
+        if let Some(t) = timer {
+            t.stop();
+        }
+        return forbidden.into_response();
+    }
 
     if is_rate_limited(&state, &payload.user_id).await {
-        return sse_error("rate_limited", timer);
+        return reject(&state, &audit, ApiError::rate_limited(), timer).await;
+    }
+
+    // A debate of a collection item takes the item's topic
+    let collection_item = match payload.collection_item {
+        Some(item_id) => match state
+            .db
+            .fetch_collection_item(&payload.user_id, item_id)
+            .await
+        {
+            Some(item) => Some(item),
+            None => {
+                let error = format!("unknown collection item {}", item_id);
+                let error = ApiError::invalid("collection_item", error);
+                return reject(&state, &audit, error, timer).await;
+            }
+        },
+        None => None,
+    };
+    if let Some(item) = &collection_item {
+        if payload.topic.trim().is_empty() {
+            payload.topic = item.topic.clone();
+            audit.topic = payload.topic.chars().take(500).collect();
+        } else if payload.topic.trim() != item.topic {
+            let error = ApiError::invalid("topic", "topic differs from the collection item's");
+            return reject(&state, &audit, error, timer).await;
+        }
     }
 
     if payload.topic.trim().is_empty() || payload.topic.len() > 2000 {
-        return sse_error("invalid_topic", timer);
+        let error = ApiError::invalid("topic", "topic is required (at most 2000 bytes)");
+        return reject(&state, &audit, error, timer).await;
+    }
+
+    // Saved preferences fill in what the request leaves out
+    if let Some(preferences) = state.db.fetch_preferences(&payload.user_id).await {
+        preferences.apply(&mut payload);
+    }
+    let language = payload
+        .language
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string);
+    if let Some(Err(error)) = language.as_deref().map(validation::validate_language) {
+        return reject(&state, &audit, error, timer).await;
+    }
+
+    let preset = match payload.preset.as_deref().filter(|id| !id.is_empty()) {
+        Some(id) => match presets::find(id) {
+            Some(preset) => Some(preset),
+            None => {
+                let error = ApiError::invalid("preset", format!("unknown preset {:?}", id));
+                return reject(&state, &audit, error, timer).await;
+            }
+        },
+        None => None,
+    };
+
+    // Without a rubric of its own, a debate without a preset is judged by the
+    // operator's
+    let judging_rubric = match payload.rubric.clone().map(Rubric::new) {
+        Some(Ok(rubric)) => Some(rubric),
+        Some(Err(e)) => {
+            return reject(&state, &audit, ApiError::invalid("rubric", e), timer).await;
+        }
+        None => preset.is_none().then(rubric::configured).flatten().cloned(),
+    };
+
+    if let Some(policy) = &state.moderation {
+        match policy.check(&payload.topic).await {
+            Verdict::Rejected(reason) => {
+                audit
+                    .record(
+                        &state.db,
+                        "rejected",
+                        Some(&format!("moderation: {}", reason)),
+                    )
+                    .await;
+                if let Some(t) = timer {
+                    t.stop();
+                }
+                return sse_once(&json!({ "type": "rejected", "reason": reason }));
+            }
+            Verdict::Restricted(category) => {
+                audit.record(&state.db, "restricted", Some(&category)).await;
+            }
+            Verdict::Allowed => {}
+        }
+    }
+
+    if budget::budget_status(&state.db).await.is_exceeded() {
+        return reject(&state, &audit, ApiError::budget_exceeded(), timer).await;
+    }
+
+    let quota = quota::quota_status(&state.db, &payload.user_id).await;
+    if quota.is_exceeded() {
+        let error = ApiError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "quota_exceeded",
+            quota.exceeded_message(),
+        );
+        return reject(&state, &audit, error, timer).await;
+    }
+
+    // The user's slot is held for the lifetime of the stream
+    let (user_slot, running) = ActiveDebate::acquire(
+        state.rate_limiter.clone(),
+        &format!("user:{}", payload.user_id),
+    )
+    .await;
+    let max_running = rate_limit::max_concurrent_debates_per_user();
+    if max_running > 0 && running > max_running as i64 {
+        drop(user_slot);
+        let error = ApiError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "too_many_debates",
+            format!("at most {} debates may run at once per user", max_running),
+        );
+        return reject(&state, &audit, error, timer).await;
+    }
+
+    let admission = state.debate_limits.admit();
+    if matches!(admission, Admission::Rejected) {
+        let error = ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "server_busy",
+            "the server is running as many debates as it can; try again later",
+        );
+        return reject(&state, &audit, error, timer).await;
     }
 
     // Resolve clients: use custom models if specified, otherwise use defaults.
+    let model_init_failed = |role: &str, e: anyhow::Error| {
+        ApiError::provider_unavailable(format!("{} model init failed: {}", role, e))
+    };
     let pro_client = match resolve_client(&state, &payload.pro_model, "pro").await {
         Ok(c) => c,
-        Err(e) => {
-            return sse_error(&format!("Pro model init failed: {}", e), timer);
-        }
+        Err(e) => return reject(&state, &audit, model_init_failed("Pro", e), timer).await,
     };
     let con_client = match resolve_client(&state, &payload.con_model, "con").await {
         Ok(c) => c,
-        Err(e) => {
-            return sse_error(&format!("Con model init failed: {}", e), timer);
-        }
+        Err(e) => return reject(&state, &audit, model_init_failed("Con", e), timer).await,
     };
     let judge_client = match resolve_client(&state, &payload.judge_model, "judge").await {
         Ok(c) => c,
-        Err(e) => {
-            return sse_error(&format!("Judge model init failed: {}", e), timer);
+        Err(e) => return reject(&state, &audit, model_init_failed("Judge", e), timer).await,
+    };
+
+    // The fact-checker role defaults to the judge's model
+    let fact_checker = match config::fact_check_model_id() {
+        Some(model_id) => match config::build_client_for_model(&model_id).await {
+            Ok(c) => c,
+            Err(e) => {
+                return reject(&state, &audit, model_init_failed("Fact-check", e), timer).await;
+            }
+        },
+        None => judge_client.clone(),
+    };
+
+    let mut judge_panel = Vec::new();
+    for model_id in config::judge_panel_model_ids() {
+        match config::build_client_for_model(&model_id).await {
+            Ok(c) => judge_panel.push(c),
+            Err(e) => {
+                return reject(&state, &audit, model_init_failed("Panel judge", e), timer).await;
+            }
         }
+    }
+
+    audit.providers = format!(
+        "pro={},con={},judge={}",
+        pro_client.model_id, con_client.model_id, judge_client.model_id
+    );
+
+    // Templates are fixed for the whole debate, even if edited meanwhile.
+    // Seeded debates stay out of A/B tests and the replay cache, so that
+    // reruns use the same templates and really call the providers again
+    let seed = payload.seed;
+    let prompt_templates = match seed {
+        Some(_) => templates::load(&state.db).await,
+        None => templates::assign(&state.db).await,
     };
+    let rubric_tag = judging_rubric.as_ref().map(Rubric::tag);
+    let precedent = similar::precedent(
+        &state.db,
+        &payload.user_id,
+        &payload.topic,
+        &payload.session_id,
+    )
+    .await;
+    // The cache is shared by all users, so debates grounded in the user's own
+    // documents or past verdicts are neither replayed nor stored
+    let personal = precedent.is_some()
+        || state
+            .db
+            .session_has_documents(&payload.user_id, &payload.session_id)
+            .await;
+    let cache_key = (cache::cache_enabled() && seed.is_none() && !personal).then(|| {
+        cache::cache_key(
+            &payload.topic,
+            [
+                &pro_client.model_id,
+                &con_client.model_id,
+                &judge_client.model_id,
+            ],
+            payload.search_depth.as_deref(),
+            payload.search_max_results,
+            preset.map(|p| p.id),
+            prompt_templates.cache_tag().as_deref(),
+            language.as_deref(),
+            rubric_tag.as_deref(),
+        )
+    });
+    // Debates of a collection item are always run, so that they count
+    // towards its progress
+    let skip_cache = payload.no_cache || collection_item.is_some();
+    if let Err(e) = state
+        .db
+        .save_session(&payload.user_id, &payload.session_id, &payload.topic)
+        .await
+    {
+        warn!("Failed to record session {}: {}", payload.session_id, e);
+    }
+    if let (Some(key), false) = (&cache_key, skip_cache) {
+        if let Some(cached) = cache::lookup(&state.db, key).await {
+            audit.record(&state.db, "cached", None).await;
+            if let Some(t) = timer {
+                t.stop();
+            }
+            let (user_id, session_id) = (&payload.user_id, &payload.session_id);
+            return replay_cached(&state, user_id, session_id, cached, &event_filter).await;
+        }
+    }
+
+    audit.record(&state.db, "started", None).await;
+    if let Some(item) = &collection_item {
+        if let Err(e) = state
+            .db
+            .save_session_collection_item(&payload.session_id, item.id)
+            .await
+        {
+            warn!(
+                "Failed to link {} to collection item {}: {}",
+                payload.session_id, item.id, e
+            );
+        }
+    }
+    let template_versions = prompt_templates.versions();
+    if let Err(e) = state
+        .db
+        .save_session_templates(&payload.session_id, &template_versions)
+        .await
+    {
+        warn!(
+            "Failed to record prompt templates of {}: {}",
+            payload.session_id, e
+        );
+    }
+    let prompt_variant = prompt_templates.variant.clone();
+    if let Err(e) = state
+        .db
+        .save_session_variant(&payload.session_id, prompt_variant.as_deref())
+        .await
+    {
+        warn!(
+            "Failed to record prompt variant of {}: {}",
+            payload.session_id, e
+        );
+    }
+    let template_versions: serde_json::Map<String, serde_json::Value> = template_versions
+        .iter()
+        .map(|(name, version)| (name.to_string(), json!(version)))
+        .collect();
+    {
+        let db = state.db.clone();
+        let client = judge_client.clone();
+        let (session_id, topic) = (payload.session_id.clone(), payload.topic.clone());
+        tokio::spawn(async move {
+            if let Err(e) = categories::tag_session(&db, &client, &session_id, &topic).await {
+                warn!("Failed to categorize session {}: {}", session_id, e);
+            }
+        });
+    }
+    webhooks::emit(
+        &state.db,
+        &audit,
+        "debate_started",
+        json!({
+            "models": {
+                "pro": pro_client.model_id,
+                "con": con_client.model_id,
+                "judge": judge_client.model_id,
+            }
+        }),
+    );
+
+    let search_options = SearchOptions::from_env()
+        .with_overrides(payload.search_depth.as_deref(), payload.search_max_results);
 
     let topic = payload.topic.clone();
     let user_id = payload.user_id.clone();
     let session_id = payload.session_id.clone();
+    let notify_email = payload.notify_email.clone();
+    let podcast = payload.podcast;
+    let analyze_fallacies = payload.analyze_fallacies;
+    let explain_outcome = payload.explain_outcome;
+    let check_consistency = payload.check_consistency;
+    let spectators = state.spectators.clone();
     let state = state.clone();
     let mut timer = timer;
+    let debate_span = info_span!("debate", session_id = %session_id, user_id = %user_id);
 
     let stream = async_stream::stream! {
-        yield sse_json(&json!({
+        // Wait for a debate slot, reporting the queue position as it changes
+        let _debate_permit = match admission {
+            Admission::Queued(ticket) => {
+                let wait = ticket.wait();
+                tokio::pin!(wait);
+                let mut reported = 0;
+                loop {
+                    let position = ticket.position();
+                    if position != reported {
+                        reported = position;
+                        yield json!({
+                            "type": "queued",
+                            "position": position,
+                            "message": "Waiting for a free debate slot",
+                        });
+                    }
+                    if let Ok(permit) = tokio::time::timeout(QUEUE_POLL_INTERVAL, &mut wait).await {
+                        break permit;
+                    }
+                }
+            }
+            Admission::Admitted(permit) => permit,
+            Admission::Rejected => None,
+        };
+
+        let _active = GaugeGuard::new(state.metrics.clone(), "active_debates");
+        let _user_slot = user_slot;
+        let (_cluster_active, _) =
+            ActiveDebate::acquire(state.rate_limiter.clone(), rate_limit::ALL_DEBATES).await;
+
+        yield json!({
             "type": "phase",
             "phase": "init",
             "message": "Debate started",
@@ -185,210 +2811,1002 @@ async fn debate_stream(
                 "pro": pro_client.model_id,
                 "con": con_client.model_id,
                 "judge": judge_client.model_id,
-            }
-        }));
+            },
+            "preset": preset.map(|p| p.id),
+            "rubric": rubric_tag,
+            "prompt_templates": template_versions,
+            "prompt_variant": prompt_variant,
+            "seed": seed,
+            "language": language,
+        });
 
         let mut transcript = Vec::new();
+        let mut consistency_findings = Vec::new();
+        let mut timings: Vec<PhaseTiming> = Vec::new();
+        let debate_started = Instant::now();
+        let mut tool_ctx = tools::ToolContext::new(
+            state.db.clone(),
+            user_id.clone(),
+            session_id.clone(),
+            fact_checker,
+            state.metrics.clone(),
+        )
+        .with_search_options(search_options)
+        .with_precedent(precedent)
+        .with_preset(preset)
+        .with_rubric(judging_rubric)
+        .with_templates(prompt_templates)
+        .with_seed(seed)
+        .with_language(language);
 
-        // Four debate phases: pro then con each phase
+        // Four debate phases, pro then con each phase, then the judgement
         let debate_phases = [
             DebatePhase::Opening,
             DebatePhase::Rebuttal,
             DebatePhase::Defense,
             DebatePhase::Closing,
+            DebatePhase::Judgement,
         ];
 
+        let opening_mode = config::opening_mode();
+        let inputs = json!({
+            "seed": seed,
+            "topic": topic,
+            "models": {
+                "pro": pro_client.model_id,
+                "con": con_client.model_id,
+                "judge": judge_client.model_id,
+                "fact_checker": tool_ctx.fact_checker.model_id,
+                "judge_panel": judge_panel.iter().map(|c| &c.model_id).collect::<Vec<_>>(),
+            },
+            "temperature_pinned": seed.is_some(),
+            "max_tokens": {
+                "pro": config::max_tokens_for_role("pro"),
+                "con": config::max_tokens_for_role("con"),
+                "judge": config::max_tokens_for_role("judge"),
+            },
+            "preset": preset.map(|p| p.id),
+            "rubric": tool_ctx.rubric,
+            "language": tool_ctx.language,
+            "prompt_templates": template_versions,
+            "prompt_variant": prompt_variant,
+            "opening_mode": match opening_mode {
+                OpeningMode::Sequential => "sequential",
+                OpeningMode::Parallel => "parallel",
+                OpeningMode::Buffered => "buffered",
+            },
+            "search": {
+                "enabled": tools::is_search_enabled(),
+                "depth": tool_ctx.search_options.depth,
+                "max_results": tool_ctx.search_options.max_results,
+                "max_calls_per_round": tools::max_tool_calls_per_round(),
+                "max_calls_per_debate": tools::max_searches_per_debate(),
+            },
+            "check_consistency": check_consistency,
+            "precedent": tool_ctx.precedent,
+        });
+        if let Err(e) = state.db.save_debate_inputs(&user_id, &session_id, &inputs).await {
+            warn!("Failed to record inputs of {}: {}", session_id, e);
+        }
         for phase in debate_phases {
-            for (side, client) in [
-                (Position::Pro, &pro_client),
-                (Position::Con, &con_client),
-            ] {
-                yield sse_json(&json!({
-                    "type": "phase_start",
-                    "phase": phase.as_str(),
-                    "side": side.role_str(),
-                    "title": phase.title(),
-                    "provider": client.name,
-                    "model": client.model_id,
-                }));
+            // Each step runs its rounds together; a step only sees the
+            // transcript of earlier steps
+            let steps: Vec<Vec<(Position, &ClientInfo)>> = match phase {
+                DebatePhase::Judgement => {
+                    let notes = consistency::judge_notes(&consistency_findings);
+                    tool_ctx = tool_ctx.with_consistency(notes);
+                    vec![vec![(Position::Judge, &judge_client)]]
+                }
+                DebatePhase::Opening if opening_mode != OpeningMode::Sequential => {
+                    vec![vec![(Position::Pro, &pro_client), (Position::Con, &con_client)]]
+                }
+                _ => vec![vec![(Position::Pro, &pro_client)], vec![(Position::Con, &con_client)]],
+            };
 
-                // Choose between tool-enabled and regular execution
-                let search_enabled = tools::is_search_enabled();
-                let round_result = if search_enabled {
-                    execute_round_with_tools(client, side, phase, &topic, &transcript).await
-                } else {
-                    execute_one_round(client, side, phase, &topic, &transcript).await
-                };
+            for step in steps {
+                let rounds = step
+                    .into_iter()
+                    .map(|(side, client)| {
+                        debate_round(
+                            state.clone(),
+                            client.clone(),
+                            side,
+                            phase,
+                            topic.clone(),
+                            transcript.clone(),
+                            tool_ctx.clone(),
+                            debate_span.clone(),
+                        )
+                    })
+                    .collect();
+                let mut events = merge_rounds(rounds, opening_mode == OpeningMode::Buffered);
 
-                match round_result {
+                let mut finished = Vec::new();
+                while let Some(event) = events.next().await {
+                    match event {
+                        RoundEvent::Event(event) => yield event,
+                        RoundEvent::Finished(turn) => finished.push(turn),
+                        RoundEvent::Failed(message) => {
+                            if let Some(t) = timer.take() { t.stop(); }
+                            audit.record(&state.db, "failed", Some(&message)).await;
+                            webhooks::emit(&state.db, &audit, "error", json!({ "message": message }));
+                            yield json!({"type":"error","message": message});
+                            return;
+                        }
+                        RoundEvent::Refused(message) => {
+                            if let Some(t) = timer.take() { t.stop(); }
+                            audit.record(&state.db, "refused", Some(&message)).await;
+                            webhooks::emit(&state.db, &audit, "error", json!({ "message": message }));
+                            yield json!({"type":"refused","message": message});
+                            return;
+                        }
+                    }
+                }
+
+                // Record Pro before Con regardless of which finished first
+                finished.sort_by_key(|turn| matches!(turn.side, Position::Con));
+                for turn in finished {
+                    timings.push(turn.timing);
+                    transcript.push((turn.side, phase, turn.content, turn.model_id.clone()));
+                    if let Ok(message_id) = state.db.save_message(&user_id, &session_id,
+                        turn.side, phase, Some(&turn.model_id), &turn.saved_content,
+                    ).await {
+                        for record in &turn.tool_records {
+                            let _ = state.db.save_tool_call(message_id, &session_id, record).await;
+                        }
+                    }
+                    let _ = state.db.clear_checkpoint(&session_id, turn.side, phase).await;
+                    webhooks::emit(&state.db, &audit, "phase_done", json!({
+                        "phase": phase.as_str(),
+                        "side": turn.side.role_str(),
+                        "model": turn.model_id,
+                        "content": turn.saved_content,
+                    }));
+                    let mut phase_done = json!({
+                        "type": "phase_done",
+                        "phase": phase.as_str(),
+                        "side": turn.side.role_str(),
+                        "model": turn.model_id,
+                    });
+                    if matches!(turn.side, Position::Judge) {
+                        let confidence = prompts::parse_confidence(&turn.saved_content);
+                        if !confidence.is_empty() {
+                            if let Err(e) = state.db.save_analysis(&user_id, &session_id, "judge", "judgement",
+                                prompts::CONFIDENCE_ANALYSIS_KIND, &json!(confidence),
+                            ).await {
+                                warn!("Failed to save verdict confidence: {}", e);
+                            }
+                        }
+                        let scores = tool_ctx.judging_rubric().and_then(|r| r.score(&turn.saved_content));
+                        if let Some(scores) = &scores {
+                            if let Err(e) = state.db.save_analysis(&user_id, &session_id, "judge", "judgement",
+                                rubric::SCORES_ANALYSIS_KIND, &json!(scores),
+                            ).await {
+                                warn!("Failed to save verdict scores: {}", e);
+                            }
+                        }
+                        phase_done["confidence"] = json!(confidence);
+                        phase_done["scores"] = json!(scores);
+                    }
+                    yield phase_done;
+                    if check_consistency && !matches!(turn.side, Position::Judge) {
+                        let (earlier, current) = transcript.split_at(transcript.len() - 1);
+                        let findings = match consistency::check_round(
+                            &judge_client, &topic, turn.side, phase, &current[0].2, earlier,
+                        )
+                        .await
+                        {
+                            Ok(findings) => findings,
+                            Err(e) => {
+                                warn!("Consistency check of {} {} failed: {}", turn.side.role_str(), phase.as_str(), e);
+                                continue;
+                            }
+                        };
+                        if let Err(e) = state.db.save_analysis(&user_id, &session_id, turn.side.role_str(), phase.as_str(),
+                            consistency::ANALYSIS_KIND, &json!(findings),
+                        )
+                        .await
+                        {
+                            warn!("Failed to save consistency findings: {}", e);
+                        }
+                        for finding in findings {
+                            let mut event = json!(finding);
+                            event["type"] = json!("warning");
+                            yield event;
+                            consistency_findings.push(finding);
+                        }
+                    }
+                }
+            }
+        }
+        if !judge_panel.is_empty() {
+            let (judged, rounds) = calibration::split_transcript(transcript.clone());
+            let votes = match calibration::original_verdict(&judged) {
+                Some(verdict) => panel::vote(&state.db, &judge_panel, &topic, &rounds, verdict, &tool_ctx).await,
+                None => Vec::new(),
+            };
+            let dissenter = panel::dissent(&votes).and_then(|(majority, dissent)| {
+                std::iter::once(&judge_client)
+                    .chain(&judge_panel)
+                    .find(|client| client.model_id == dissent.model)
+                    .map(|client| (majority, dissent, client))
+            });
+            if let Some((majority, dissent, client)) = dissenter {
+                let language = tool_ctx.language.as_deref();
+                match execute_dissent_stream(client, &topic, &rounds, majority, &dissent.content, language).await {
                     Ok((mut stream, model_id)) => {
+                        let tally: Vec<_> = votes
+                            .iter()
+                            .map(|vote| json!({ "model": vote.model, "winner": vote.winner }))
+                            .collect();
+                        yield json!({
+                            "type": "phase_start",
+                            "phase": panel::DISSENT_PHASE,
+                            "side": "judge",
+                            "title": "Dissenting opinion",
+                            "provider": client.name,
+                            "model": model_id,
+                            "majority": majority.role_str(),
+                            "winner": dissent.winner,
+                            "votes": tally,
+                        });
                         let mut full_content = String::new();
-
+                        let mut usage_reported = false;
                         while let Some(chunk_res) = stream.next().await {
                             match chunk_res {
                                 Ok(DebateStreamChunk::Delta(delta)) => {
                                     if !delta.is_empty() {
-                                        yield sse_json(&json!({
+                                        yield json!({
                                             "type": "delta",
-                                            "side": side.role_str(),
-                                            "phase": phase.as_str(),
+                                            "side": "judge",
+                                            "phase": panel::DISSENT_PHASE,
                                             "model": model_id,
                                             "content": delta,
-                                        }));
+                                        });
                                         full_content.push_str(&delta);
                                     }
                                 }
-                                Ok(DebateStreamChunk::Thinking(thinking)) => {
-                                    if !thinking.is_empty() {
-                                        yield sse_json(&json!({
-                                            "type": "thinking",
-                                            "side": side.role_str(),
-                                            "phase": phase.as_str(),
-                                            "model": model_id,
-                                            "content": thinking,
-                                        }));
-                                    }
-                                }
                                 Ok(DebateStreamChunk::Usage(usage)) => {
-                                    yield sse_json(&json!({
+                                    usage_reported = true;
+                                    let _ = budget::record_usage(&state.db, &model_id, &usage).await;
+                                    yield json!({
                                         "type": "usage",
-                                        "side": side.role_str(),
-                                        "phase": phase.as_str(),
+                                        "side": "judge",
+                                        "phase": panel::DISSENT_PHASE,
                                         "model": model_id,
                                         "usage": usage,
-                                    }));
-                                }
-                                Ok(DebateStreamChunk::SearchPerformed(search_result)) => {
-                                    yield sse_json(&json!({
-                                        "type": "search",
-                                        "side": side.role_str(),
-                                        "phase": phase.as_str(),
-                                        "model": model_id,
-                                        "query": search_result.query,
-                                        "results": search_result.results,
-                                    }));
+                                    });
                                 }
+                                Ok(_) => {}
                                 Err(e) => {
-                                    if let Some(t) = timer.take() { t.stop(); }
-                                    yield sse_json(&json!({"type":"error","message": format!("Stream error: {}", e)}));
-                                    return;
+                                    warn!("Dissenting opinion for {} failed: {}", session_id, e);
+                                    break;
                                 }
                             }
                         }
-
-                        transcript.push((side, phase, full_content.clone(), model_id.clone()));
-                        let _ = save_message(
-                            &state.db, &user_id, &session_id,
-                            side, phase, Some(&model_id), &full_content,
-                        ).await;
-                        yield sse_json(&json!({
+                        if !usage_reported && !full_content.is_empty() {
+                            let usage = budget::estimated_usage(&model_id, &full_content);
+                            let _ = budget::record_usage(&state.db, &model_id, &usage).await;
+                        }
+                        if !full_content.is_empty() {
+                            if let Err(e) = state.db.save_analysis(&user_id, &session_id, "judge", panel::DISSENT_PHASE, panel::DISSENT_PHASE,
+                                &json!({
+                                    "model": model_id,
+                                    "winner": dissent.winner,
+                                    "majority": majority.role_str(),
+                                    "votes": tally,
+                                    "content": full_content,
+                                }),
+                            ).await {
+                                warn!("Failed to save dissenting opinion: {}", e);
+                            }
+                        }
+                        yield json!({
                             "type": "phase_done",
-                            "phase": phase.as_str(),
-                            "side": side.role_str(),
+                            "phase": panel::DISSENT_PHASE,
+                            "side": "judge",
                             "model": model_id,
-                        }));
+                        });
                     }
+                    Err(e) => warn!("Dissenting opinion for {} failed: {}", session_id, e),
+                }
+            }
+        }
+        if let Some(t) = timer.take() {
+            t.stop();
+        }
+        audit.record(&state.db, "completed", None).await;
+        if let Some((_, _, verdict, model)) = transcript.last() {
+            webhooks::emit(&state.db, &audit, "verdict", json!({
+                "winner": prompts::parse_winner(verdict).map(|side| side.role_str()),
+                "model": model,
+                "content": verdict,
+            }));
+        }
+        #[cfg(feature = "email")]
+        if let (Some(to), Some(mailer)) = (notify_email, email::mailer()) {
+            let (topic, transcript) = (topic.clone(), transcript.clone());
+            tokio::spawn(async move {
+                if let Err(e) = mailer.send_transcript(&to, &topic, &transcript).await {
+                    warn!("Failed to email transcript: {}", e);
+                }
+            });
+        }
+        {
+            let db = state.db.clone();
+            let (user_id, session_id) = (user_id.clone(), session_id.clone());
+            let (topic, transcript) = (topic.clone(), transcript.clone());
+            tokio::spawn(async move {
+                if let Err(e) =
+                    similar::index_debate(&db, &user_id, &session_id, &topic, &transcript).await
+                {
+                    warn!("Failed to index debate for similar search: {}", e);
+                }
+            });
+        }
+        {
+            let db = state.db.clone();
+            let (client, session_id) = (judge_client.clone(), session_id.clone());
+            let (topic, transcript) = (topic.clone(), transcript.clone());
+            tokio::spawn(async move {
+                if let Err(e) =
+                    summaries::summarize_session(&db, &client, &session_id, &topic, &transcript).await
+                {
+                    warn!("Failed to summarize session {}: {}", session_id, e);
+                }
+            });
+        }
+        if arguments::enabled() {
+            let db = state.db.clone();
+            let (client, user_id, session_id) =
+                (judge_client.clone(), user_id.clone(), session_id.clone());
+            let (topic, transcript) = (topic.clone(), transcript.clone());
+            tokio::spawn(async move {
+                arguments::extract_debate(&db, &client, &user_id, &session_id, &topic, &transcript)
+                    .await;
+            });
+        }
+        if tone::enabled() {
+            let db = state.db.clone();
+            let (client, user_id, session_id) =
+                (judge_client.clone(), user_id.clone(), session_id.clone());
+            let transcript = transcript.clone();
+            tokio::spawn(async move {
+                tone::analyze_debate(&db, &client, &user_id, &session_id, &transcript).await;
+            });
+        }
+        #[cfg(feature = "notion")]
+        if let Some(notion) = notion::notion() {
+            let (topic, transcript) = (topic.clone(), transcript.clone());
+            tokio::spawn(async move {
+                if let Err(e) = notion.export_transcript(&topic, &transcript).await {
+                    warn!("Failed to export transcript to Notion: {}", e);
+                }
+            });
+        }
+        #[cfg(feature = "podcast")]
+        if podcast {
+            let db = state.db.clone();
+            let (user_id, session_id) = (user_id.clone(), session_id.clone());
+            let (topic, transcript) = (topic.clone(), transcript.clone());
+            tokio::spawn(async move {
+                if let Err(e) =
+                    podcast::publish_episode(&db, &user_id, &session_id, &topic, &transcript).await
+                {
+                    warn!("Failed to publish podcast episode: {}", e);
+                }
+            });
+        }
+        if analyze_fallacies {
+            // Rounds are reviewed concurrently; findings are streamed in debate order
+            let rounds: Vec<_> = transcript
+                .iter()
+                .filter(|(side, ..)| *side != Position::Judge)
+                .collect();
+            let reviews = futures::future::join_all(rounds.iter().map(|(side, phase, content, _)| {
+                fallacies::detect(&judge_client, &topic, *side, *phase, content)
+            }))
+            .await;
+            for ((side, phase, ..), review) in rounds.into_iter().zip(reviews) {
+                let findings = match review {
+                    Ok(findings) => findings,
                     Err(e) => {
-                        if let Some(t) = timer.take() { t.stop(); }
-                        yield sse_json(&json!({"type":"error","message": format!("Round failed: {}", e)}));
-                        return;
+                        warn!("Fallacy analysis of {} {} failed: {}", side.role_str(), phase.as_str(), e);
+                        continue;
                     }
+                };
+                let result = json!(findings);
+                if let Err(e) = state.db.save_analysis(&user_id, &session_id, side.role_str(), phase.as_str(),
+                    fallacies::ANALYSIS_KIND, &result,
+                )
+                .await
+                {
+                    warn!("Failed to save fallacy analysis: {}", e);
                 }
+                yield json!({
+                    "type": "analysis",
+                    "kind": fallacies::ANALYSIS_KIND,
+                    "side": side.role_str(),
+                    "phase": phase.as_str(),
+                    "findings": result,
+                });
             }
         }
-
-        // Judge round - now with real streaming
-        {
-            yield sse_json(&json!({
-                "type": "phase_start",
-                "phase": "judgement",
-                "side": "judge",
-                "title": DebatePhase::Judgement.title(),
-                "provider": judge_client.name,
-                "model": judge_client.model_id,
-            }));
-
-            match execute_judge_round_stream(&judge_client, &topic, &transcript).await {
+        if explain_outcome {
+            match execute_outcome_analysis_stream(&judge_client, &topic, &transcript).await {
                 Ok((mut stream, model_id)) => {
+                    yield json!({
+                        "type": "phase_start",
+                        "phase": OUTCOME_PHASE,
+                        "side": "judge",
+                        "title": "What would change the outcome",
+                        "provider": judge_client.name,
+                        "model": model_id,
+                    });
                     let mut full_content = String::new();
-
+                    let mut usage_reported = false;
                     while let Some(chunk_res) = stream.next().await {
                         match chunk_res {
                             Ok(DebateStreamChunk::Delta(delta)) => {
                                 if !delta.is_empty() {
-                                    yield sse_json(&json!({
+                                    yield json!({
                                         "type": "delta",
                                         "side": "judge",
-                                        "phase": "judgement",
+                                        "phase": OUTCOME_PHASE,
                                         "model": model_id,
                                         "content": delta,
-                                    }));
+                                    });
                                     full_content.push_str(&delta);
                                 }
                             }
-                            Ok(DebateStreamChunk::Thinking(thinking)) => {
-                                if !thinking.is_empty() {
-                                    yield sse_json(&json!({
-                                        "type": "thinking",
-                                        "side": "judge",
-                                        "phase": "judgement",
-                                        "model": model_id,
-                                        "content": thinking,
-                                    }));
-                                }
-                            }
                             Ok(DebateStreamChunk::Usage(usage)) => {
-                                yield sse_json(&json!({
+                                usage_reported = true;
+                                let _ = budget::record_usage(&state.db, &model_id, &usage).await;
+                                yield json!({
                                     "type": "usage",
                                     "side": "judge",
-                                    "phase": "judgement",
+                                    "phase": OUTCOME_PHASE,
                                     "model": model_id,
                                     "usage": usage,
-                                }));
-                            }
-                            Ok(DebateStreamChunk::SearchPerformed(_)) => {
-                                // Judge doesn't use tools - ignore
+                                });
                             }
+                            Ok(_) => {}
                             Err(e) => {
-                                if let Some(t) = timer.take() { t.stop(); }
-                                yield sse_json(&json!({"type":"error","message": format!("Judge stream error: {}", e)}));
-                                return;
+                                warn!("Outcome analysis stream for {} failed: {}", session_id, e);
+                                break;
                             }
                         }
                     }
-
-                    transcript.push((Position::Judge, DebatePhase::Judgement, full_content.clone(), model_id.clone()));
-                    let _ = save_message(
-                        &state.db, &user_id, &session_id,
-                        Position::Judge, DebatePhase::Judgement, Some(&model_id), &full_content,
-                    ).await;
-                    yield sse_json(&json!({
+                    if !usage_reported && !full_content.is_empty() {
+                        let usage = budget::estimated_usage(&model_id, &full_content);
+                        let _ = budget::record_usage(&state.db, &model_id, &usage).await;
+                    }
+                    if !full_content.is_empty() {
+                        if let Err(e) = state.db.save_analysis(&user_id, &session_id, "judge", OUTCOME_PHASE, OUTCOME_PHASE,
+                            &json!({ "model": model_id, "content": full_content }),
+                        ).await {
+                            warn!("Failed to save outcome analysis: {}", e);
+                        }
+                    }
+                    yield json!({
                         "type": "phase_done",
-                        "phase": "judgement",
+                        "phase": OUTCOME_PHASE,
                         "side": "judge",
                         "model": model_id,
-                    }));
+                    });
+                }
+                Err(e) => warn!("Outcome analysis for {} failed: {}", session_id, e),
+            }
+        }
+        let timings = json!({
+            "total_ms": debate_started.elapsed().as_millis() as u64,
+            "prompt_tokens": timings.iter().map(|t| t.prompt_tokens).sum::<u64>(),
+            "completion_tokens": timings.iter().map(|t| t.completion_tokens).sum::<u64>(),
+            "phases": timings,
+        });
+        let _ = state.db.save_timings(&user_id, &session_id, &timings).await;
+        // Documents may have been uploaded while the debate ran
+        if let Some(key) = &cache_key {
+            if !state.db.session_has_documents(&user_id, &session_id).await {
+                let _ = cache::store(&state.db, key, &transcript).await;
+            }
+        }
+        yield json!({"type": "done", "timings": timings});
+    };
+
+    // Spectators get every event; the client only those it subscribed to
+    let stream = spectators.relay(&payload.session_id, stream);
+    let body_stream = stream.filter_map(move |event| {
+        let wanted = event_filter.wants(event["type"].as_str().unwrap_or_default());
+        std::future::ready(wanted.then(|| Ok::<_, std::io::Error>(sse_json(&event))))
+    });
+    Response::builder()
+        .status(200)
+        .header("Content-Type", "text/event-stream")
+        .header("Cache-Control", "no-cache")
+        .body(Body::from_stream(body_stream))
+        .unwrap()
+}
+
+// --- Debate rounds ----------------------------------------------------------
+
+/// Output of one round: stream events as they are produced, then the
+/// finished turn or the error that ended it.
+enum RoundEvent {
+    Event(serde_json::Value),
+    Finished(Turn),
+    Failed(String),
+    /// The model refused the round even after a softened retry.
+    Refused(String),
+}
+
+/// A completed Pro, Con or Judge turn, not yet recorded in the transcript.
+struct Turn {
+    side: Position,
+    model_id: String,
+    content: String,
+    /// Content with the cited sources appended, as stored.
+    saved_content: String,
+    tool_records: Vec<tools::ToolCallRecord>,
+    timing: PhaseTiming,
+}
+
+/// Decides when a round's partial content is next checkpointed.
+struct Checkpointer {
+    interval: Option<Duration>,
+    last: Instant,
+}
+
+impl Checkpointer {
+    fn new() -> Self {
+        Self {
+            interval: config::checkpoint_interval(),
+            last: Instant::now(),
+        }
+    }
+
+    fn due(&mut self) -> bool {
+        match self.interval {
+            Some(interval) if self.last.elapsed() >= interval => {
+                self.last = Instant::now();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+type RoundEvents = std::pin::Pin<Box<dyn futures::Stream<Item = RoundEvent> + Send>>;
+
+/// Run one round: wait for a provider slot, stream the response and, for
+/// Pro and Con, attach citations. A refused or filter-blocked Pro or Con
+/// round is retried once with softened framing; the judge is not retried.
+#[allow(clippy::too_many_arguments)]
+fn debate_round(
+    state: Arc<AppState>,
+    client: ClientInfo,
+    side: Position,
+    phase: DebatePhase,
+    topic: String,
+    transcript: Vec<(Position, DebatePhase, String, String)>,
+    tool_ctx: tools::ToolContext,
+    debate_span: tracing::Span,
+) -> RoundEvents {
+    Box::pin(async_stream::stream! {
+        let semaphore = state.provider_limits.semaphore(&client.name);
+        let _permit = match semaphore.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                yield RoundEvent::Event(json!({
+                    "type": "queued",
+                    "phase": phase.as_str(),
+                    "side": side.role_str(),
+                    "provider": client.name,
+                    "message": "Waiting for a free provider slot",
+                }));
+                let _waiting = GaugeGuard::new(state.metrics.clone(), "queued_rounds");
+                match semaphore.acquire_owned().await {
+                    Ok(permit) => permit,
+                    Err(e) => {
+                        yield RoundEvent::Failed(format!("Provider limiter closed: {}", e));
+                        return;
+                    }
+                }
+            }
+        };
+
+        yield RoundEvent::Event(json!({
+            "type": "phase_start",
+            "phase": phase.as_str(),
+            "side": side.role_str(),
+            "title": phase.title(),
+            "provider": client.name,
+            "model": client.model_id,
+        }));
+
+        let judge = matches!(side, Position::Judge);
+        let (failed, stream_error) = if judge {
+            ("Judge failed", "Judge stream error")
+        } else {
+            ("Round failed", "Stream error")
+        };
+        let mut round_metrics = RoundMetrics::start(state.metrics.clone(), &client.name, phase.as_str());
+        let phase_span = info_span!(
+            parent: &debate_span,
+            "phase",
+            phase = phase.as_str(),
+            side = side.role_str(),
+            provider = %client.name,
+        );
+        let mut model_id = client.model_id.clone();
+        let mut full_content = String::new();
+        let mut search_results: Vec<tools::SearchResult> = Vec::new();
+        let mut tool_records: Vec<tools::ToolCallRecord> = Vec::new();
+        let mut usage_reported = false;
+        let mut checkpoint = Checkpointer::new();
+        // A refused or filter-blocked round is retried once with softened framing
+        let mut softened = false;
+        let stream_reasoning = config::stream_reasoning();
+
+        loop {
+            let round_ctx = tool_ctx.clone().with_softened_framing(softened);
+            // Tools are attached only when enabled; otherwise this is a plain stream
+            let tool_defs = tools::available_tools(&round_ctx).await;
+            let round_result = if judge {
+                execute_judge_round_stream(&client, &topic, &transcript, tool_defs, &round_ctx)
+                    .instrument(phase_span.clone())
+                    .await
+            } else {
+                execute_one_round(&client, side, phase, &topic, &transcript, tool_defs, &round_ctx)
+                    .instrument(phase_span.clone())
+                    .await
+            };
+
+            let mut stream = match round_result {
+                Ok((stream, round_model)) => {
+                    model_id = round_model;
+                    stream
                 }
                 Err(e) => {
-                    if let Some(t) = timer.take() { t.stop(); }
-                    yield sse_json(&json!({"type":"error","message": format!("Judge failed: {}", e)}));
+                    round_metrics.error(&e.to_string());
+                    yield RoundEvent::Failed(format!("{}: {}", failed, e));
                     return;
                 }
+            };
+
+            let mut filtered = false;
+            let mut sections = SectionSplitter::default();
+            while let Some(chunk_res) = stream.next().instrument(phase_span.clone()).await {
+                match chunk_res {
+                    Ok(DebateStreamChunk::Delta(delta)) => {
+                        if !delta.is_empty() {
+                            round_metrics.token();
+                            // The verdict has no argument sections
+                            let pieces = if judge {
+                                vec![Piece::Text(delta.clone())]
+                            } else {
+                                sections.push(&delta)
+                            };
+                            for piece in pieces {
+                                let event = piece_event(side, phase, &model_id, piece);
+                                yield RoundEvent::Event(event);
+                            }
+                            full_content.push_str(&delta);
+                            if checkpoint.due() {
+                                let _ = state.db.save_checkpoint(&tool_ctx.user_id, &tool_ctx.session_id,
+                                    side, phase, Some(&model_id), &full_content,
+                                ).await;
+                            }
+                        }
+                    }
+                    Ok(DebateStreamChunk::Thinking(thinking)) => {
+                        if !thinking.is_empty() {
+                            round_metrics.token();
+                        }
+                        if !thinking.is_empty() && stream_reasoning {
+                            yield RoundEvent::Event(json!({
+                                "type": "reasoning_delta",
+                                "side": side.role_str(),
+                                "phase": phase.as_str(),
+                                "model": model_id,
+                                "content": thinking,
+                            }));
+                        }
+                    }
+                    Ok(DebateStreamChunk::Usage(usage)) => {
+                        usage_reported = true;
+                        let _ = budget::record_usage(&state.db, &model_id, &usage).await;
+                        let (prompt, completion) = budget::usage_tokens(&usage);
+                        round_metrics.usage(prompt, completion);
+                        state.metrics.incr_labeled("tokens_generated_total", &[("provider", &client.name)], completion);
+                        yield RoundEvent::Event(json!({
+                            "type": "usage",
+                            "side": side.role_str(),
+                            "phase": phase.as_str(),
+                            "model": model_id,
+                            "usage": usage,
+                        }));
+                    }
+                    Ok(DebateStreamChunk::ToolCall { name, query }) => {
+                        yield RoundEvent::Event(json!({
+                            "type": "tool_call",
+                            "side": side.role_str(),
+                            "phase": phase.as_str(),
+                            "model": model_id,
+                            "tool": name,
+                            "query": query,
+                        }));
+                    }
+                    Ok(DebateStreamChunk::SearchPerformed(search_result)) => {
+                        yield RoundEvent::Event(json!({
+                            "type": "tool_result",
+                            "side": side.role_str(),
+                            "phase": phase.as_str(),
+                            "model": model_id,
+                            "query": search_result.query,
+                            "summary": tools::result_summary(&search_result),
+                            "results": search_result.results,
+                        }));
+                        search_results.push(search_result);
+                    }
+                    Ok(DebateStreamChunk::ToolExecuted(record)) => {
+                        tool_records.push(record);
+                    }
+                    Err(e) if !judge && refusals::is_filter_error(&e.to_string()) => {
+                        warn!("{} round blocked by content filter: {}", client.name, e);
+                        filtered = true;
+                        break;
+                    }
+                    Err(e) => {
+                        round_metrics.error(&e.to_string());
+                        if !full_content.is_empty() {
+                            let _ = state.db.save_checkpoint(&tool_ctx.user_id, &tool_ctx.session_id,
+                                side, phase, Some(&model_id), &full_content,
+                            ).await;
+                        }
+                        yield RoundEvent::Failed(format!("{}: {}", stream_error, e));
+                        return;
+                    }
+                }
+            }
+
+            for piece in sections.finish() {
+                yield RoundEvent::Event(piece_event(side, phase, &model_id, piece));
             }
+            if judge || (!filtered && !refusals::is_refusal(&full_content)) {
+                break;
+            }
+            if softened {
+                round_metrics.error("refused");
+                yield RoundEvent::Refused(format!(
+                    "{} declined to argue the {} side of this topic",
+                    client.name,
+                    side.role_str(),
+                ));
+                return;
+            }
+            yield RoundEvent::Event(json!({
+                "type": "retry",
+                "side": side.role_str(),
+                "phase": phase.as_str(),
+                "model": model_id,
+                "reason": if filtered { "content_filter" } else { "refusal" },
+            }));
+            softened = true;
+            full_content.clear();
+            search_results.clear();
+            tool_records.clear();
         }
 
-        if let Some(t) = timer.take() {
-            t.stop();
+        // Providers that don't report usage are billed from a local count
+        if !usage_reported && !full_content.is_empty() {
+            let usage = budget::estimated_usage(&model_id, &full_content);
+            let _ = budget::record_usage(&state.db, &model_id, &usage).await;
+            let (prompt, completion) = budget::usage_tokens(&usage);
+            round_metrics.usage(prompt, completion);
+            state.metrics.incr_labeled("tokens_generated_total", &[("provider", &client.name)], completion);
+            yield RoundEvent::Event(json!({
+                "type": "usage",
+                "side": side.role_str(),
+                "phase": phase.as_str(),
+                "model": model_id,
+                "usage": usage,
+            }));
         }
-        yield "data: {\"type\":\"done\"}\n\n".to_string();
-    };
 
-    let body_stream = stream.map(|chunk| Ok::<_, std::io::Error>(chunk));
+        let timing = round_metrics.finish(side.role_str(), &model_id);
+
+        // Attach the sources the argument actually cited
+        let sources = citations::number_sources(&search_results);
+        let cited = if judge {
+            Vec::new()
+        } else {
+            citations::referenced(&full_content, &sources)
+        };
+        for citation in &cited {
+            yield RoundEvent::Event(json!({
+                "type": "citation",
+                "side": side.role_str(),
+                "phase": phase.as_str(),
+                "model": model_id,
+                "index": citation.index,
+                "title": citation.title,
+                "url": citation.url,
+            }));
+        }
+        let saved_content = if cited.is_empty() {
+            full_content.clone()
+        } else {
+            format!("{}{}", full_content, citations::sources_footnote(&cited))
+        };
+
+        yield RoundEvent::Finished(Turn {
+            side,
+            model_id,
+            content: full_content,
+            saved_content,
+            tool_records,
+            timing,
+        });
+    })
+}
+
+/// Run rounds concurrently. Events are interleaved as they arrive or, when
+/// `in_order`, each round's events are held back until the rounds before it
+/// have finished. Failures are passed through immediately.
+fn merge_rounds(rounds: Vec<RoundEvents>, in_order: bool) -> RoundEvents {
+    let count = rounds.len();
+    let mut lanes =
+        futures::stream::select_all(rounds.into_iter().enumerate().map(|(lane, round)| {
+            round
+                .map(move |event| (lane, Some(event)))
+                .chain(futures::stream::once(async move { (lane, None) }))
+                .boxed()
+        }));
+    Box::pin(async_stream::stream! {
+        let mut held: Vec<Vec<RoundEvent>> = (0..count).map(|_| Vec::new()).collect();
+        let mut done = vec![false; count];
+        let mut current = 0;
+        while let Some((lane, event)) = lanes.next().await {
+            match event {
+                Some(event @ (RoundEvent::Failed(_) | RoundEvent::Refused(_))) => yield event,
+                Some(event) if !in_order || lane == current => yield event,
+                Some(event) => held[lane].push(event),
+                None => {
+                    done[lane] = true;
+                    while current < count && done[current] {
+                        current += 1;
+                        if current < count {
+                            for event in std::mem::take(&mut held[current]) {
+                                yield event;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// A `delta` event for text of a round, or a `section` event where one of
+/// its sections starts.
+fn piece_event(
+    side: Position,
+    phase: DebatePhase,
+    model_id: &str,
+    piece: Piece,
+) -> serde_json::Value {
+    match piece {
+        Piece::Text(content) => json!({
+            "type": "delta",
+            "side": side.role_str(),
+            "phase": phase.as_str(),
+            "model": model_id,
+            "content": content,
+        }),
+        Piece::Section(section) => json!({
+            "type": "section",
+            "side": side.role_str(),
+            "phase": phase.as_str(),
+            "model": model_id,
+            "section": section.as_str(),
+        }),
+    }
+}
+
+/// Answer a debate by replaying a cached transcript. The turns are saved to
+/// the session like a live debate, and every event is marked `cached`.
+async fn replay_cached(
+    state: &AppState,
+    user_id: &str,
+    session_id: &str,
+    cached: cache::CachedDebate,
+    filter: &EventFilter,
+) -> Response {
+    let model_of = |role: &str| {
+        cached
+            .turns
+            .iter()
+            .find(|t| t.role == role)
+            .map(|t| t.model.clone())
+            .unwrap_or_default()
+    };
+    let mut body = String::new();
+    let mut send = |event: &serde_json::Value| {
+        if filter.wants(event["type"].as_str().unwrap_or_default()) {
+            body.push_str(&sse_json(event));
+        }
+    };
+    send(&json!({
+        "type": "phase",
+        "phase": "init",
+        "message": "Replaying cached debate",
+        "cached": true,
+        "cached_at": cached.created_at,
+        "models": {
+            "pro": model_of("pro"),
+            "con": model_of("con"),
+            "judge": model_of("judge"),
+        }
+    }));
+    for turn in &cached.turns {
+        let (Some(side), Some(phase)) = (turn.position(), turn.debate_phase()) else {
+            continue;
+        };
+        let _ = state
+            .db
+            .save_message(
+                user_id,
+                session_id,
+                side,
+                phase,
+                Some(&turn.model),
+                &turn.content,
+            )
+            .await;
+        let mut events = vec![json!({
+            "type": "phase_start",
+            "phase": turn.phase,
+            "side": turn.role,
+            "title": phase.title(),
+            "model": turn.model,
+        })];
+        // Debater turns are split into sections as they are live
+        let pieces = match side {
+            Position::Judge => vec![Piece::Text(turn.content.clone())],
+            _ => {
+                let mut sections = SectionSplitter::default();
+                let mut pieces = sections.push(&turn.content);
+                pieces.extend(sections.finish());
+                pieces
+            }
+        };
+        events.extend(
+            pieces
+                .into_iter()
+                .map(|piece| piece_event(side, phase, &turn.model, piece)),
+        );
+        events.push(json!({
+            "type": "phase_done",
+            "phase": turn.phase,
+            "side": turn.role,
+            "model": turn.model,
+        }));
+        for mut event in events {
+            event["cached"] = json!(true);
+            send(&event);
+        }
+    }
+    send(&json!({
+        "type": "done",
+        "cached": true,
+        "cached_at": cached.created_at,
+    }));
     Response::builder()
         .status(200)
         .header("Content-Type", "text/event-stream")
         .header("Cache-Control", "no-cache")
-        .body(Body::from_stream(body_stream))
+        .body(Body::from(body))
         .unwrap()
 }
 
@@ -417,33 +3835,443 @@ async fn resolve_client(
 }
 
 async fn is_rate_limited(state: &Arc<AppState>, user_id: &str) -> bool {
-    let now = Instant::now();
-    let (window, max_requests) = crate::types::rate_limit_window();
-    let mut guard = state.rate_limits.write().await;
-    let entry = guard.entry(user_id.to_string()).or_insert_with(Vec::new);
-    entry.retain(|t| now.duration_since(*t) < window);
-    if entry.len() >= max_requests {
-        true
+    let limit = RateLimit::streaming();
+    state
+        .rate_limiter
+        .hit(
+            &format!("stream:{}", user_id),
+            limit.window,
+            limit.max_requests,
+        )
+        .await
+}
+
+/// Effective user id for a request. A user set by the embedding app's own
+/// auth middleware takes precedence, then the subject of a guest token or,
+/// when JWT auth is enabled, of the JWT; in all cases a client-supplied
+/// `user_id` must be empty or match. With guest tokens enabled and JWT auth
+/// disabled, a request without a guest token is unauthorized.
+async fn resolve_user(
+    state: &AppState,
+    host_user: Option<Extension<AuthenticatedUser>>,
+    headers: &HeaderMap,
+    claimed: &str,
+) -> Result<String, ApiError> {
+    if let Some(Extension(AuthenticatedUser(user_id))) = host_user {
+        if !claimed.is_empty() && claimed != user_id {
+            return Err(auth_error(AuthError::Forbidden));
+        }
+        return Ok(user_id);
+    }
+    if let Some(guests) = &state.guests {
+        if let Some(token) = auth::bearer_token(headers).filter(|t| guest::is_guest_token(t)) {
+            return guests.resolve_user(token, claimed).map_err(auth_error);
+        }
+        if state.auth.is_none() {
+            return Err(auth_error(AuthError::Unauthorized(
+                "guest token required; get one from POST /guest".to_string(),
+            )));
+        }
+    }
+    let Some(auth) = &state.auth else {
+        return Ok(claimed.to_string());
+    };
+    auth.resolve_user(headers, claimed)
+        .await
+        .map_err(auth_error)
+}
+
+fn auth_error(e: AuthError) -> ApiError {
+    match e {
+        AuthError::Unauthorized(detail) => ApiError::unauthorized(detail),
+        AuthError::Forbidden => {
+            ApiError::forbidden("user_id does not match the authenticated user")
+        }
+    }
+}
+
+/// Reject access to a session that already belongs to another user. Fails
+/// closed: access is refused when ownership cannot be checked.
+/// The owner of a session `user` may read: their own, or one its owner made
+/// shared or public, which anyone can read, signed in or not.
+async fn readable_session_owner(
+    state: &AppState,
+    session_id: &str,
+    metadata: Option<&SessionMetadata>,
+    user: Result<String, ApiError>,
+) -> Result<String, ApiError> {
+    let shared_owner = metadata
+        .filter(|m| matches!(m.visibility.as_deref(), Some("shared" | "public")))
+        .map(|m| m.user_id.clone());
+    match (user, shared_owner) {
+        (Err(_), Some(owner)) => Ok(owner),
+        (Ok(user_id), Some(owner)) if user_id.is_empty() => Ok(owner),
+        (user, shared_owner) => {
+            let user_id = user?;
+            validate_id("user_id", &user_id)?;
+            match require_session_owner(state, session_id, &user_id).await {
+                Ok(()) => Ok(user_id),
+                Err(forbidden) => shared_owner.ok_or(forbidden),
+            }
+        }
+    }
+}
+
+async fn require_session_owner(
+    state: &AppState,
+    session_id: &str,
+    user_id: &str,
+) -> Result<(), ApiError> {
+    match state.db.session_owned_by_other(session_id, user_id).await {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(ApiError::forbidden("session belongs to another user")),
+        Err(e) => Err(ApiError::internal(format!(
+            "session owner check for {} failed: {}",
+            session_id, e
+        ))),
+    }
+}
+
+/// Guard for the `/admin` API: `Authorization: Bearer <ADMIN_TOKEN>`, or a JWT
+/// carrying the admin role when JWT auth is enabled. Admin endpoints are
+/// disabled when neither is configured.
+async fn require_admin(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let admin_token = auth::admin_token();
+    if admin_token.is_none() && state.auth.is_none() {
+        return ApiError::forbidden("admin endpoints are disabled; set ADMIN_TOKEN")
+            .into_response();
+    }
+
+    let headers = request.headers();
+    let token_matches = match (auth::bearer_token(headers), &admin_token) {
+        (Some(given), Some(expected)) => auth::token_matches(given, expected),
+        _ => false,
+    };
+    let allowed = if token_matches {
+        Ok(())
+    } else if let Some(auth) = &state.auth {
+        auth.authorize_admin(headers).await
     } else {
-        entry.push(now);
-        false
+        Err(AuthError::Unauthorized("invalid admin token".to_string()))
+    };
+    match allowed {
+        Ok(()) => next.run(request).await,
+        Err(AuthError::Unauthorized(_)) => {
+            ApiError::unauthorized("invalid admin credentials").into_response()
+        }
+        Err(AuthError::Forbidden) => ApiError::forbidden("admin role required").into_response(),
     }
 }
 
-fn sse_error(msg: &str, timer: Option<Box<dyn Timer + Send>>) -> Response {
+/// Record a debate request refused before its stream started in the audit
+/// log, by error code, and return the error.
+async fn reject(
+    state: &AppState,
+    audit: &AuditContext,
+    error: ApiError,
+    timer: Option<Box<dyn Timer + Send>>,
+) -> Response {
+    audit
+        .record(&state.db, "rejected", Some(error.code()))
+        .await;
     if let Some(t) = timer {
         t.stop();
     }
+    error.into_response()
+}
+
+/// A complete SSE response consisting of a single event.
+fn sse_once(v: &serde_json::Value) -> Response {
     Response::builder()
         .status(200)
         .header("Content-Type", "text/event-stream")
-        .body(Body::from(format!(
-            "data: {{\"type\":\"error\",\"message\":\"{}\"}}\n\n",
-            msg
-        )))
+        .body(Body::from(sse_json(v)))
         .unwrap()
 }
 
 fn sse_json(v: &serde_json::Value) -> String {
     format!("data: {}\n\n", v.to_string())
 }
+
+/// The event types a client subscribed to with `?events=`; all of them
+/// unless it named some.
+#[derive(Default)]
+struct EventFilter {
+    types: Option<HashSet<String>>,
+}
+
+impl EventFilter {
+    /// Parse a comma-separated list of event types.
+    fn parse(events: Option<&str>) -> Self {
+        let types: HashSet<String> = events
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        Self {
+            types: (!types.is_empty()).then_some(types),
+        }
+    }
+
+    fn wants(&self, kind: &str) -> bool {
+        self.types.as_ref().is_none_or(|types| types.contains(kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::DebateRouter;
+    use crate::storage::{init_db, SqliteStore, Store};
+    use axum::extract::connect_info::MockConnectInfo;
+    use tower::ServiceExt;
+
+    /// A fresh database file under the system temp directory. The replay
+    /// cache is on and the mock provider streams without delay.
+    async fn test_db() -> Arc<SqliteStore> {
+        static ENV: std::sync::Once = std::sync::Once::new();
+        ENV.call_once(|| {
+            std::env::set_var("DEBATE_CACHE", "1");
+            std::env::set_var("DEBATE_MOCK_DELAY_MS", "0");
+        });
+        static NEXT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let n = NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let path =
+            std::env::temp_dir().join(format!("aidebate-test-{}-{}.db", std::process::id(), n));
+        let _ = std::fs::remove_file(&path);
+        let pool = init_db(&format!("sqlite://{}", path.display()))
+            .await
+            .unwrap();
+        Arc::new(SqliteStore::new(pool))
+    }
+
+    /// The API over `db` with mock clients, as seen from a fixed client address.
+    async fn test_app(db: Arc<dyn Store>) -> Router {
+        let clients = (
+            ClientInfo::mock("pro"),
+            ClientInfo::mock("con"),
+            ClientInfo::mock("judge"),
+        );
+        DebateRouter::new(db, clients)
+            .without_web_ui()
+            .without_jwt_auth()
+            .build()
+            .await
+            .layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))))
+    }
+
+    async fn get_status(app: &Router, uri: &str) -> StatusCode {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    /// Run a debate and return its SSE body.
+    async fn stream_debate(app: &Router, user_id: &str, session_id: &str, topic: &str) -> String {
+        let body = json!({ "user_id": user_id, "session_id": session_id, "topic": topic });
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/debate/stream")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn stream_sends_only_subscribed_events() {
+        let app = test_app(test_db().await).await;
+        let body = json!({ "user_id": "erin", "session_id": "e1", "topic": "Homework should be abolished" });
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/debate/stream?events=phase_done,done")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let types: Vec<String> = String::from_utf8(body.to_vec())
+            .unwrap()
+            .split("\n\n")
+            .filter_map(|chunk| chunk.strip_prefix("data: "))
+            .map(|data| {
+                let event: serde_json::Value = serde_json::from_str(data).unwrap();
+                event["type"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(types.last().map(String::as_str), Some("done"));
+        assert!(types.iter().any(|kind| kind == "phase_done"));
+        assert!(types
+            .iter()
+            .all(|kind| kind == "phase_done" || kind == "done"));
+    }
+
+    #[tokio::test]
+    async fn repeated_topic_is_replayed_from_the_cache() {
+        let app = test_app(test_db().await).await;
+        let topic = "Cities should ban cars from their centers";
+        let first = stream_debate(&app, "carol", "c1", topic).await;
+        assert!(first.contains("\"type\":\"done\""));
+        assert!(!first.contains("\"cached\":true"));
+        let second = stream_debate(&app, "dave", "d1", topic).await;
+        assert!(second.contains("\"cached\":true"));
+    }
+
+    #[tokio::test]
+    async fn replayed_session_is_listed_and_exported() {
+        let app = test_app(test_db().await).await;
+        let topic = "Libraries should lend tools as well as books";
+        stream_debate(&app, "frank", "f1", topic).await;
+        let replay = stream_debate(&app, "grace", "g1", topic).await;
+        assert!(replay.contains("\"cached\":true"));
+        let get_body = |uri: &str| {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                String::from_utf8(body.to_vec()).unwrap()
+            }
+        };
+        let sessions = get_body("/sessions?user_id=grace").await;
+        assert!(sessions.contains("\"g1\""), "{}", sessions);
+        let export = get_body("/sessions/g1/export?user_id=grace&format=markdown").await;
+        assert!(export.contains(topic), "{}", export);
+    }
+
+    #[tokio::test]
+    async fn finished_session_has_no_viewers_and_cannot_be_watched() {
+        let app = test_app(test_db().await).await;
+        stream_debate(&app, "heidi", "h1", "Cities should ban cars downtown").await;
+        let request = Request::builder()
+            .uri("/sessions/h1?user_id=heidi")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["session"]["session_id"], "h1");
+        assert_eq!(body["viewers"], 0);
+        let status = get_status(&app, "/sessions/h1/spectate?user_id=heidi").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let status = get_status(&app, "/sessions/h1/spectate?user_id=ivan").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn document_grounded_debate_is_never_replayed_to_another_user() {
+        let db = test_db().await;
+        db.save_document("alice", "a1", "notes.txt", "text/plain", 42)
+            .await
+            .unwrap();
+        let app = test_app(db).await;
+        let topic = "Remote work improves productivity";
+        let grounded = stream_debate(&app, "alice", "a1", topic).await;
+        assert!(grounded.contains("\"type\":\"done\""));
+        let other = stream_debate(&app, "bob", "b1", topic).await;
+        assert!(other.contains("\"type\":\"done\""));
+        assert!(!other.contains("\"cached\":true"));
+    }
+
+    #[tokio::test]
+    async fn session_of_another_user_is_forbidden() {
+        let db = test_db().await;
+        db.save_message(
+            "alice",
+            "s1",
+            Position::Pro,
+            DebatePhase::Opening,
+            None,
+            "...",
+        )
+        .await
+        .unwrap();
+        let app = test_app(db).await;
+        assert_eq!(
+            get_status(&app, "/sessions/s1/documents?user_id=bob").await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            get_status(&app, "/sessions/s1/documents?user_id=alice").await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn shared_session_exports_without_a_user() {
+        let db = test_db().await;
+        db.save_session("alice", "s2", "Tea is better than coffee")
+            .await
+            .unwrap();
+        db.save_message(
+            "alice",
+            "s2",
+            Position::Pro,
+            DebatePhase::Opening,
+            None,
+            "Tea.",
+        )
+        .await
+        .unwrap();
+        let app = test_app(db.clone()).await;
+        assert_eq!(
+            get_status(&app, "/sessions/s2/export?format=html").await,
+            StatusCode::BAD_REQUEST
+        );
+        let mut metadata = db.fetch_session_metadata("s2").await.unwrap();
+        metadata.visibility = Some("shared".to_string());
+        db.save_session_metadata(&metadata).await.unwrap();
+        assert_eq!(
+            get_status(&app, "/sessions/s2/export?format=html").await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_owner_check_refuses_access() {
+        let db = test_db().await;
+        let app = test_app(db.clone()).await;
+        db.pool().close().await;
+        assert_eq!(
+            get_status(&app, "/sessions/s1/documents?user_id=bob").await,
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn session_without_messages_stays_with_its_owner() {
+        let db = test_db().await;
+        db.save_session("alice", "s3", "Cats make better pets")
+            .await
+            .unwrap();
+        let app = test_app(db.clone()).await;
+        assert_eq!(
+            get_status(&app, "/sessions/s3/documents?user_id=mallory").await,
+            StatusCode::FORBIDDEN
+        );
+        db.save_session("mallory", "s3", "Something else")
+            .await
+            .unwrap();
+        let sessions = db.fetch_sessions("alice", None, 10).await;
+        assert_eq!(sessions[0].topic, "Cats make better pets");
+    }
+}
@@ -0,0 +1,47 @@
+//! HTTP server for aidebate: the axum routes, authentication, rate limiting,
+//! quotas and moderation on top of the `aidebate-core` engine.
+//!
+//! [`DebateRouter`] builds the debate routes over a caller-supplied store and
+//! clients, to serve on their own (as the `aidebate` binary does) or mount
+//! inside another axum application.
+
+mod auth;
+mod chat;
+mod concurrency;
+mod discord;
+mod error;
+mod guest;
+mod handlers;
+mod moderation;
+mod quota;
+mod rate_limit;
+mod router;
+mod scheduler;
+mod slack;
+mod spectate;
+mod state;
+mod telegram;
+mod telemetry;
+mod validation;
+mod webhooks;
+
+// Engine modules live in `aidebate-core`; re-export them at the crate root so
+// server modules can keep referring to `crate::storage`, `crate::tools`, etc.
+#[cfg(feature = "email")]
+use aidebate_core::email;
+#[cfg(feature = "notion")]
+use aidebate_core::notion;
+use aidebate_core::{
+    app_metrics, arguments, audit, budget, cache, calibration, categories, citations, config,
+    consistency, debate, diff, documents, export, fact_check, fallacies, highlights, judge_qa,
+    leaderboard, panel, presets, prompts, quiz, refusals, rubric, search, sections, similar,
+    storage, subtitles, suggestions, summaries, templates, tone, tools, types,
+};
+#[cfg(feature = "podcast")]
+use aidebate_core::{podcast, tts};
+
+pub use auth::AuthenticatedUser;
+pub use handlers::serve;
+pub use rate_limit::RateLimitStore;
+pub use router::DebateRouter;
+pub use telemetry::init_tracing;
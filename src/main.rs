@@ -1,31 +1,56 @@
-mod app_metrics;
-mod config;
-mod debate;
-mod handlers;
-mod prompts;
-mod storage;
-mod tools;
-mod types;
+mod batch;
+mod check;
+mod cli;
 
+// The subcommands use the engine directly; re-export its modules at the crate
+// root so they can refer to `crate::config`, `crate::tools`, etc.
+#[cfg(feature = "email")]
+use aidebate_core::email;
+#[cfg(feature = "notion")]
+use aidebate_core::notion;
+use aidebate_core::{app_metrics, config, debate, export, search, storage, tools, types};
+#[cfg(feature = "podcast")]
+use aidebate_core::{podcast, tts};
+
+use aidebate_server::{init_tracing, serve, DebateRouter};
 use axum::Router;
+use clap::Parser;
+use std::sync::Arc;
 use tokio::net::TcpListener;
 
-use crate::config::{init_clients, init_tracing};
-use crate::handlers::{build_app, serve};
-use crate::storage::init_db;
+use crate::cli::{Cli, Command};
+use crate::config::init_clients;
+use crate::storage::{init_db, SqliteStore};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
-    init_tracing();
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Debate(args)) => return cli::run_debate(args).await,
+        Some(Command::Batch(args)) => return batch::run(args).await,
+        Some(Command::CheckConfig) => return check::run().await,
+        Some(Command::Serve) | None => {}
+    }
+
+    let tracer_provider = init_tracing();
 
     let db_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://debate.db".to_string());
-    let db = init_db(&db_url).await?;
+    let db = Arc::new(SqliteStore::new(init_db(&db_url).await?));
 
     // init_clients is now async
     let clients = init_clients().await?;
-    let app: Router = build_app(db, clients).await;
+    let app: Router = DebateRouter::new(db, clients)
+        .with_bots()
+        .with_scheduler()
+        .build()
+        .await;
 
     let listener = TcpListener::bind("0.0.0.0:3000").await?;
-    serve(listener, app).await
+    let result = serve(listener, app).await;
+
+    if let Some(provider) = tracer_provider {
+        let _ = provider.shutdown();
+    }
+    result
 }
@@ -0,0 +1,262 @@
+//! Topic moderation pre-check.
+//!
+//! Optional checks run before a debate starts, cheapest first:
+//! - a policy file from `MODERATION_POLICY_FILE` (see below),
+//! - a local policy from `MODERATION_BLOCKLIST` (comma-separated keywords or
+//!   regular expressions, matched case-insensitively), and
+//! - the OpenAI moderation API when `MODERATION_PROVIDER=openai` (uses
+//!   `OPENAI_API_KEY`).
+//!
+//! The policy file is JSON with a list of rules, each a case-insensitive
+//! regular expression with a category label and an action:
+//!
+//! ```json
+//! { "rules": [
+//!     { "pattern": "\\bbomb[- ]making\\b", "category": "weapons", "action": "ban" },
+//!     { "pattern": "\\bdiet pills?\\b", "category": "medical", "action": "restrict" }
+//! ] }
+//! ```
+//!
+//! `ban` (the default) refuses the topic without calling the moderation API.
+//! `restrict` lets the debate proceed but records the category in the audit
+//! log. The file is re-read when it changes, so rules can be edited without a
+//! restart; a file that fails to parse leaves the previous rules in place.
+//!
+//! Disallowed topics are refused with a `rejected` SSE event.
+
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::config;
+
+/// How often the policy file is checked for changes.
+const POLICY_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize)]
+struct PolicyFile {
+    rules: Vec<RuleSpec>,
+}
+
+#[derive(Deserialize)]
+struct RuleSpec {
+    pattern: String,
+    category: String,
+    #[serde(default)]
+    action: PolicyAction,
+}
+
+#[derive(Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum PolicyAction {
+    #[default]
+    Ban,
+    Restrict,
+}
+
+struct PolicyRule {
+    pattern: Regex,
+    category: String,
+    action: PolicyAction,
+}
+
+/// Outcome of the moderation pre-check.
+pub enum Verdict {
+    Allowed,
+    /// Allowed, but matched a restricted category.
+    Restricted(String),
+    /// Refused, with the reason shown to the user.
+    Rejected(String),
+}
+
+pub struct ModerationPolicy {
+    rules: Arc<RwLock<Vec<PolicyRule>>>,
+    blocklist: Vec<Regex>,
+    openai_key: Option<String>,
+}
+
+impl ModerationPolicy {
+    /// `None` when no moderation is configured.
+    pub fn from_env() -> Option<Self> {
+        let blocklist: Vec<Regex> = std::env::var("MODERATION_BLOCKLIST")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .filter_map(
+                |p| match RegexBuilder::new(p).case_insensitive(true).build() {
+                    Ok(re) => Some(re),
+                    Err(e) => {
+                        warn!(
+                            "Ignoring invalid MODERATION_BLOCKLIST pattern '{}': {}",
+                            p, e
+                        );
+                        None
+                    }
+                },
+            )
+            .collect();
+        let openai_key = std::env::var("MODERATION_PROVIDER")
+            .is_ok_and(|v| v.eq_ignore_ascii_case("openai"))
+            .then(|| std::env::var("OPENAI_API_KEY").ok())
+            .flatten();
+        let policy_file = std::env::var("MODERATION_POLICY_FILE")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from);
+        if policy_file.is_none() && blocklist.is_empty() && openai_key.is_none() {
+            return None;
+        }
+
+        let rules = Arc::new(RwLock::new(Vec::new()));
+        if let Some(path) = policy_file {
+            let modified = reload_rules(&path, &rules);
+            watch_policy_file(path, modified, Arc::downgrade(&rules));
+        }
+        Some(Self {
+            rules,
+            blocklist,
+            openai_key,
+        })
+    }
+
+    /// Whether the topic may be debated. A failing moderation API does not
+    /// block the debate.
+    pub async fn check(&self, topic: &str) -> Verdict {
+        let mut restricted = None;
+        {
+            let rules = self.rules.read().unwrap_or_else(|e| e.into_inner());
+            for rule in rules.iter().filter(|r| r.pattern.is_match(topic)) {
+                match rule.action {
+                    PolicyAction::Ban => {
+                        return Verdict::Rejected(format!(
+                            "topic is not allowed ({})",
+                            rule.category
+                        ));
+                    }
+                    PolicyAction::Restrict => {
+                        restricted.get_or_insert_with(|| rule.category.clone());
+                    }
+                }
+            }
+        }
+        if let Some(re) = self.blocklist.iter().find(|re| re.is_match(topic)) {
+            return Verdict::Rejected(format!("topic matches blocked pattern '{}'", re.as_str()));
+        }
+        let allowed = match restricted {
+            Some(category) => Verdict::Restricted(category),
+            None => Verdict::Allowed,
+        };
+        let Some(key) = self.openai_key.as_ref() else {
+            return allowed;
+        };
+        match openai_moderation(key, topic).await {
+            Ok(categories) if !categories.is_empty() => Verdict::Rejected(format!(
+                "topic flagged by moderation: {}",
+                categories.join(", ")
+            )),
+            Ok(_) => allowed,
+            Err(e) => {
+                warn!("Moderation check failed, allowing topic: {}", e);
+                allowed
+            }
+        }
+    }
+}
+
+fn load_rules(path: &PathBuf) -> anyhow::Result<Vec<PolicyRule>> {
+    let text = std::fs::read_to_string(path)?;
+    let file: PolicyFile = serde_json::from_str(&text)?;
+    file.rules
+        .into_iter()
+        .map(|spec| {
+            let pattern = RegexBuilder::new(&spec.pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| anyhow::anyhow!("invalid pattern '{}': {}", spec.pattern, e))?;
+            Ok(PolicyRule {
+                pattern,
+                category: spec.category,
+                action: spec.action,
+            })
+        })
+        .collect()
+}
+
+/// Load the policy file into `rules`, keeping the old rules on error.
+/// Returns the file's modification time.
+fn reload_rules(path: &PathBuf, rules: &RwLock<Vec<PolicyRule>>) -> Option<SystemTime> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    match load_rules(path) {
+        Ok(loaded) => {
+            info!(
+                "Loaded {} moderation policy rules from {}",
+                loaded.len(),
+                path.display()
+            );
+            *rules.write().unwrap_or_else(|e| e.into_inner()) = loaded;
+        }
+        Err(e) => warn!("Failed to load moderation policy {}: {}", path.display(), e),
+    }
+    modified
+}
+
+/// Reload the policy file whenever its modification time changes.
+fn watch_policy_file(
+    path: PathBuf,
+    mut last_modified: Option<SystemTime>,
+    rules: std::sync::Weak<RwLock<Vec<PolicyRule>>>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLICY_RELOAD_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(rules) = rules.upgrade() else {
+                break;
+            };
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+            if modified.is_some() && modified != last_modified {
+                last_modified = reload_rules(&path, &rules);
+            }
+        }
+    });
+}
+
+/// Flagged categories for `input`; empty if not flagged.
+async fn openai_moderation(api_key: &str, input: &str) -> anyhow::Result<Vec<String>> {
+    let resp = config::http_client()
+        .post("https://api.openai.com/v1/moderations")
+        .bearer_auth(api_key)
+        .json(&json!({ "model": "omni-moderation-latest", "input": input }))
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Moderation request failed: {}", e))?
+        .error_for_status()
+        .map_err(|e| anyhow::anyhow!("Moderation request failed: {}", e))?
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|e| anyhow::anyhow!("Moderation response parse failed: {}", e))?;
+
+    let result = &resp["results"][0];
+    if !result["flagged"].as_bool().unwrap_or(false) {
+        return Ok(vec![]);
+    }
+    let mut categories: Vec<String> = result["categories"]
+        .as_object()
+        .map(|c| {
+            c.iter()
+                .filter(|(_, v)| v.as_bool() == Some(true))
+                .map(|(k, _)| k.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    if categories.is_empty() {
+        categories.push("flagged".to_string());
+    }
+    Ok(categories)
+}
@@ -0,0 +1,69 @@
+//! Per-user daily debate quotas.
+//!
+//! Debates started today (UTC) are counted from the `started` and `cached`
+//! events in the audit log, so replays from the debate cache count too. When `DAILY_DEBATE_QUOTA` is set, a user who has reached it is
+//! refused until midnight UTC.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::storage::Store;
+
+/// Quota usage for one user today.
+#[derive(Serialize)]
+pub struct QuotaStatus {
+    pub user_id: String,
+    pub daily_limit: Option<i64>,
+    pub used_today: i64,
+    pub remaining: Option<i64>,
+    /// Seconds until the quota resets at midnight UTC.
+    pub resets_in_secs: u64,
+}
+
+impl QuotaStatus {
+    pub fn is_exceeded(&self) -> bool {
+        matches!(self.remaining, Some(r) if r <= 0)
+    }
+
+    /// Error message shown when a debate is refused.
+    pub fn exceeded_message(&self) -> String {
+        let hours = self.resets_in_secs / 3600;
+        let minutes = (self.resets_in_secs % 3600) / 60;
+        format!(
+            "Daily debate quota reached ({} of {} used); resets in {}h {}m",
+            self.used_today,
+            self.daily_limit.unwrap_or(0),
+            hours,
+            minutes
+        )
+    }
+}
+
+/// Debates per user per day (`DAILY_DEBATE_QUOTA`); unlimited when unset or 0.
+pub fn daily_quota() -> Option<i64> {
+    std::env::var("DAILY_DEBATE_QUOTA")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+}
+
+fn secs_until_utc_midnight() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    86_400 - now % 86_400
+}
+
+pub async fn quota_status(db: &dyn Store, user_id: &str) -> QuotaStatus {
+    let used = db.debates_started_since(user_id, "start of day").await;
+    let limit = daily_quota();
+    QuotaStatus {
+        user_id: user_id.to_string(),
+        daily_limit: limit,
+        used_today: used,
+        remaining: limit.map(|l| (l - used).max(0)),
+        resets_in_secs: secs_until_utc_midnight(),
+    }
+}
@@ -0,0 +1,359 @@
+//! Rate limiting and debate concurrency counters.
+//!
+//! The default store keeps sliding windows in process memory, which only works
+//! for a single replica. Set `REDIS_URL` to share limits and counters across
+//! replicas behind a load balancer. Redis errors fail open: a request is never
+//! rejected because the limiter is unavailable.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+/// Shared key for the cluster-wide active debate counter.
+pub const ALL_DEBATES: &str = "all";
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// A sliding-window request limit.
+#[derive(Clone, Copy)]
+pub struct RateLimit {
+    pub window: Duration,
+    pub max_requests: usize,
+}
+
+impl RateLimit {
+    /// Limit for starting debates, per user
+    /// (`RATE_LIMIT_STREAM_REQUESTS` per `RATE_LIMIT_STREAM_WINDOW_SECS`, default 8 / 10s).
+    pub fn streaming() -> Self {
+        Self {
+            window: Duration::from_secs(env_or("RATE_LIMIT_STREAM_WINDOW_SECS", 10u64).max(1)),
+            max_requests: env_or("RATE_LIMIT_STREAM_REQUESTS", 8usize).max(1),
+        }
+    }
+
+    /// Limit for read-only endpoints, per client IP
+    /// (`RATE_LIMIT_READ_REQUESTS` per `RATE_LIMIT_READ_WINDOW_SECS`, default 120 / 60s).
+    pub fn read_only() -> Self {
+        Self {
+            window: Duration::from_secs(env_or("RATE_LIMIT_READ_WINDOW_SECS", 60u64).max(1)),
+            max_requests: env_or("RATE_LIMIT_READ_REQUESTS", 120usize).max(1),
+        }
+    }
+
+    /// Limit for issuing guest tokens, per client IP
+    /// (`RATE_LIMIT_GUEST_REQUESTS` per `RATE_LIMIT_GUEST_WINDOW_SECS`, default 5 / 3600s).
+    pub fn guest() -> Self {
+        Self {
+            window: Duration::from_secs(env_or("RATE_LIMIT_GUEST_WINDOW_SECS", 3600u64).max(1)),
+            max_requests: env_or("RATE_LIMIT_GUEST_REQUESTS", 5usize).max(1),
+        }
+    }
+
+    /// Limit for debates started from a chat integration, per chat
+    /// (`RATE_LIMIT_CHAT_REQUESTS` per `RATE_LIMIT_CHAT_WINDOW_SECS`, default 3 / 600s).
+    pub fn chat() -> Self {
+        Self {
+            window: Duration::from_secs(env_or("RATE_LIMIT_CHAT_WINDOW_SECS", 600u64).max(1)),
+            max_requests: env_or("RATE_LIMIT_CHAT_REQUESTS", 3usize).max(1),
+        }
+    }
+}
+
+/// Maximum debates a single user may run at once (`MAX_CONCURRENT_DEBATES_PER_USER`,
+/// default 2; 0 disables the cap).
+pub fn max_concurrent_debates_per_user() -> usize {
+    env_or("MAX_CONCURRENT_DEBATES_PER_USER", 2)
+}
+
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Record a request for `key`; returns `true` if it exceeds `max` requests
+    /// within `window` (the rejected request is not counted).
+    async fn hit(&self, key: &str, window: Duration, max: usize) -> bool;
+
+    /// Adjust the in-flight debate counter for `key` and return the new value.
+    async fn add_active(&self, key: &str, delta: i64) -> i64;
+}
+
+/// Single-process store.
+#[derive(Default)]
+pub struct MemoryStore {
+    /// Each key's window, as of its latest request, and its request times.
+    hits: RwLock<HashMap<String, (Duration, Vec<Instant>)>>,
+    active: Mutex<HashMap<String, i64>>,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn hit(&self, key: &str, window: Duration, max: usize) -> bool {
+        let now = Instant::now();
+        let mut guard = self.hits.write().await;
+        let (key_window, entry) = guard
+            .entry(key.to_string())
+            .or_insert_with(|| (window, Vec::new()));
+        *key_window = window;
+        entry.retain(|t| now.duration_since(*t) < window);
+        if entry.len() >= max {
+            true
+        } else {
+            entry.push(now);
+            false
+        }
+    }
+
+    async fn add_active(&self, key: &str, delta: i64) -> i64 {
+        let mut guard = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        let count = (guard.get(key).copied().unwrap_or(0) + delta).max(0);
+        if count == 0 {
+            guard.remove(key);
+        } else {
+            guard.insert(key.to_string(), count);
+        }
+        count
+    }
+}
+
+/// How often the in-memory store drops idle keys.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+impl MemoryStore {
+    /// Remove keys with no request inside their own window, so the map holds
+    /// only recently active users instead of every user ever seen.
+    async fn sweep(&self) -> usize {
+        let now = Instant::now();
+        let mut guard = self.hits.write().await;
+        let before = guard.len();
+        guard.retain(|_, (window, hits)| {
+            hits.last()
+                .is_some_and(|t| now.duration_since(*t) < *window)
+        });
+        before - guard.len()
+    }
+}
+
+/// Sliding window over a sorted set, timed by the Redis server clock so that
+/// replicas with skewed clocks agree.
+const SLIDING_WINDOW_SCRIPT: &str = r#"
+local t = redis.call('TIME')
+local now = t[1] * 1000 + math.floor(t[2] / 1000)
+local window = tonumber(ARGV[1])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], 0, now - window)
+if redis.call('ZCARD', KEYS[1]) >= tonumber(ARGV[2]) then
+  return 1
+end
+redis.call('ZADD', KEYS[1], now, ARGV[3])
+redis.call('PEXPIRE', KEYS[1], window)
+return 0
+"#;
+
+/// Counters expire if a replica dies without decrementing them.
+const ACTIVE_TTL_SECS: i64 = 3600;
+
+/// Store shared across replicas via Redis.
+pub struct RedisStore {
+    conn: ConnectionManager,
+    script: redis::Script,
+    seq: AtomicU64,
+}
+
+impl RedisStore {
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        let client = redis::Client::open(url)?;
+        let conn = ConnectionManager::new(client).await?;
+        Ok(Self {
+            conn,
+            script: redis::Script::new(SLIDING_WINDOW_SCRIPT),
+            seq: AtomicU64::new(0),
+        })
+    }
+
+    /// Unique sorted-set member for one request.
+    fn member(&self) -> String {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        format!("{}-{}", nanos, self.seq.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisStore {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn hit(&self, key: &str, window: Duration, max: usize) -> bool {
+        let mut conn = self.conn.clone();
+        let result: redis::RedisResult<i64> = self
+            .script
+            .key(format!("aidebate:rl:{}", key))
+            .arg(window.as_millis() as u64)
+            .arg(max)
+            .arg(self.member())
+            .invoke_async(&mut conn)
+            .await;
+        match result {
+            Ok(limited) => limited == 1,
+            Err(e) => {
+                warn!("Redis rate limit check failed, allowing request: {}", e);
+                false
+            }
+        }
+    }
+
+    async fn add_active(&self, key: &str, delta: i64) -> i64 {
+        let mut conn = self.conn.clone();
+        let key = format!("aidebate:active:{}", key);
+        let result: redis::RedisResult<(i64, i64)> = redis::pipe()
+            .atomic()
+            .incr(&key, delta)
+            .expire(&key, ACTIVE_TTL_SECS)
+            .query_async(&mut conn)
+            .await;
+        match result {
+            Ok((count, _)) => count.max(0),
+            Err(e) => {
+                warn!("Redis active counter update failed: {}", e);
+                0
+            }
+        }
+    }
+}
+
+/// Redis store when `REDIS_URL` is set and reachable, otherwise in-memory.
+pub async fn store_from_env() -> Arc<dyn RateLimitStore> {
+    if let Some(url) = std::env::var("REDIS_URL")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+    {
+        match RedisStore::connect(&url).await {
+            Ok(store) => {
+                info!("Rate limiting backed by Redis");
+                return Arc::new(store);
+            }
+            Err(e) => warn!(
+                "Failed to connect to REDIS_URL, using in-memory rate limits: {}",
+                e
+            ),
+        }
+    }
+    let store = Arc::new(MemoryStore::default());
+    let sweeper = Arc::downgrade(&store);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(store) = sweeper.upgrade() else {
+                break;
+            };
+            let evicted = store.sweep().await;
+            if evicted > 0 {
+                debug!("Evicted {} idle rate-limit entries", evicted);
+            }
+        }
+    });
+    store
+}
+
+/// Holds one slot of an active debate counter and releases it on drop, so a
+/// client disconnect mid-stream still decrements it.
+pub struct ActiveDebate {
+    store: Arc<dyn RateLimitStore>,
+    key: String,
+}
+
+impl ActiveDebate {
+    pub async fn acquire(store: Arc<dyn RateLimitStore>, key: &str) -> (Self, i64) {
+        let count = store.add_active(key, 1).await;
+        (
+            Self {
+                store,
+                key: key.to_string(),
+            },
+            count,
+        )
+    }
+}
+
+impl Drop for ActiveDebate {
+    fn drop(&mut self) {
+        let store = self.store.clone();
+        let key = std::mem::take(&mut self.key);
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                store.add_active(&key, -1).await;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sweep_keeps_keys_inside_their_own_window() {
+        let store = MemoryStore::default();
+        let guest = RateLimit {
+            window: Duration::from_secs(3600),
+            max_requests: 1,
+        };
+        assert!(
+            !store
+                .hit("guest:1.2.3.4", guest.window, guest.max_requests)
+                .await
+        );
+        assert!(
+            !store
+                .hit("read:1.2.3.4", Duration::from_millis(1), 10)
+                .await
+        );
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(store.sweep().await, 1);
+        // The guest limit still holds after the sweep
+        assert!(
+            store
+                .hit("guest:1.2.3.4", guest.window, guest.max_requests)
+                .await
+        );
+    }
+    #[tokio::test]
+    async fn chat_limit_holds_across_sweeps() {
+        let store = MemoryStore::default();
+        let chat = RateLimit {
+            window: Duration::from_secs(600),
+            max_requests: 2,
+        };
+        for _ in 0..chat.max_requests {
+            assert!(
+                !store
+                    .hit("chat:telegram:42", chat.window, chat.max_requests)
+                    .await
+            );
+        }
+        assert_eq!(store.sweep().await, 0);
+        assert!(
+            store
+                .hit("chat:telegram:42", chat.window, chat.max_requests)
+                .await
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Builder for embedding the debate routes in another axum application.
+//!
+//! `DebateRouter` takes the store and clients from the caller, optionally
+//! with a rate limit store and metrics, and can leave out the bundled web UI
+//! or replace the default middleware stack with the host app's own:
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use aidebate_core::{config, storage};
+//! use aidebate_server::{AuthenticatedUser, DebateRouter};
+//! use axum::{extract::Request, middleware::Next, Router};
+//! use std::sync::Arc;
+//!
+//! async fn host_auth(mut request: Request, next: Next) -> axum::response::Response {
+//!     // ...authenticate with the host app's own scheme...
+//!     request.extensions_mut().insert(AuthenticatedUser("alice".to_string()));
+//!     next.run(request).await
+//! }
+//!
+//! let db = storage::init_db("sqlite://debate.db").await?;
+//! let store = Arc::new(storage::SqliteStore::new(db));
+//! let clients = config::init_clients().await?;
+//! let debate = DebateRouter::new(store, clients)
+//!     .without_web_ui()
+//!     .without_default_layers()
+//!     .build()
+//!     .await;
+//! let app: Router = Router::new()
+//!     .nest("/debate-api", debate)
+//!     .layer(axum::middleware::from_fn(host_auth));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The routes read the client address, so serve the app with
+//! `into_make_service_with_connect_info::<SocketAddr>()`. The `/admin` API
+//! keeps its own guard (`ADMIN_TOKEN` or a JWT admin role). Background tasks
+//! only run when asked for: [`DebateRouter::with_bots`] starts the Discord
+//! and Telegram bots and [`DebateRouter::with_scheduler`] the recurring
+//! debates, as the `aidebate` binary does.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::Router;
+
+use crate::app_metrics::{Metrics, PrometheusMetrics};
+use crate::auth::JwtVerifier;
+use crate::concurrency::{DebateLimiter, ProviderLimiter};
+use crate::discord::DiscordBot;
+use crate::guest::GuestTokens;
+use crate::handlers;
+use crate::moderation::ModerationPolicy;
+use crate::rate_limit::{self, RateLimitStore};
+use crate::scheduler::Scheduler;
+use crate::slack::SlackConfig;
+use crate::spectate::Spectators;
+use crate::state::AppState;
+use crate::storage::Store;
+use crate::telegram::TelegramBot;
+use crate::types::ClientInfo;
+
+pub struct DebateRouter {
+    db: Arc<dyn Store>,
+    clients: (ClientInfo, ClientInfo, ClientInfo),
+    rate_limiter: Option<Arc<dyn RateLimitStore>>,
+    metrics: Option<Arc<dyn Metrics>>,
+    jwt_auth: bool,
+    web_ui: bool,
+    default_layers: bool,
+    bots: bool,
+    scheduler: bool,
+}
+
+impl DebateRouter {
+    /// Routes over `db` with the given Pro, Con and Judge clients.
+    pub fn new(db: Arc<dyn Store>, clients: (ClientInfo, ClientInfo, ClientInfo)) -> Self {
+        Self {
+            db,
+            clients,
+            rate_limiter: None,
+            metrics: None,
+            jwt_auth: true,
+            web_ui: true,
+            default_layers: true,
+            bots: false,
+            scheduler: false,
+        }
+    }
+
+    /// Use this rate limit store instead of the one from `REDIS_URL`.
+    pub fn rate_limiter(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        self.rate_limiter = Some(store);
+        self
+    }
+
+    /// Record metrics here instead of a new Prometheus registry.
+    pub fn metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Skip JWT validation even if `JWT_ISSUER` is set; the host app
+    /// identifies users with [`AuthenticatedUser`](crate::AuthenticatedUser).
+    pub fn without_jwt_auth(mut self) -> Self {
+        self.jwt_auth = false;
+        self
+    }
+
+    /// Leave out `/`, `/admin` and `/favicon.svg`. The bundled pages call the
+    /// API at absolute paths, so they only work when mounted at the root.
+    pub fn without_web_ui(mut self) -> Self {
+        self.web_ui = false;
+        self
+    }
+
+    /// Leave out the body limit, timeout and CORS layers so the host app can
+    /// apply its own.
+    pub fn without_default_layers(mut self) -> Self {
+        self.default_layers = false;
+        self
+    }
+
+    /// Start the Discord and Telegram bots configured by `DISCORD_BOT_TOKEN`
+    /// and `TELEGRAM_BOT_TOKEN` when the router is built.
+    pub fn with_bots(mut self) -> Self {
+        self.bots = true;
+        self
+    }
+
+    /// Start the recurring debates configured by `DEBATE_SCHEDULE` when the
+    /// router is built.
+    pub fn with_scheduler(mut self) -> Self {
+        self.scheduler = true;
+        self
+    }
+
+    pub async fn build(self) -> Router {
+        let (pro, con, judge) = self.clients;
+        let rate_limiter = match self.rate_limiter {
+            Some(store) => store,
+            None => rate_limit::store_from_env().await,
+        };
+        let metrics: Arc<dyn Metrics> = match self.metrics {
+            Some(metrics) => metrics,
+            None => PrometheusMetrics::new(),
+        };
+        let state = Arc::new(AppState {
+            db: self.db,
+            pro,
+            con,
+            judge,
+            start_time: Instant::now(),
+            rate_limiter,
+            metrics,
+            provider_limits: ProviderLimiter::new(),
+            debate_limits: DebateLimiter::from_env(),
+            auth: self.jwt_auth.then(JwtVerifier::from_env).flatten(),
+            guests: GuestTokens::from_env(),
+            moderation: ModerationPolicy::from_env(),
+            slack: SlackConfig::from_env(),
+            spectators: Spectators::new(),
+        });
+        if self.bots {
+            if let Some(bot) = DiscordBot::from_env() {
+                tokio::spawn(bot.run(state.clone()));
+            }
+            if let Some(bot) = TelegramBot::from_env() {
+                tokio::spawn(bot.run(state.clone()));
+            }
+        }
+        if self.scheduler {
+            if let Some(scheduler) = Scheduler::from_env() {
+                tokio::spawn(scheduler.run(state.clone()));
+            }
+        }
+        handlers::routes(state, self.web_ui, self.default_layers)
+    }
+}
@@ -0,0 +1,520 @@
+//! Scheduled recurring debates, for a "daily debate" instance.
+//!
+//! Enabled when `DEBATE_SCHEDULE` holds one or more cron expressions
+//! (`minute hour day-of-month month day-of-week`, in UTC, separated by `;`),
+//! e.g. `0 9 * * *` for every day at 09:00. At each matching minute the
+//! server runs a debate on its own: the next topic of `SCHEDULE_TOPICS`
+//! (separated by `|`, used in rotation), or a topic suggested by the judge's
+//! model when none are configured. `SCHEDULE_PRESET` optionally names a
+//! preset. The debate is saved as a session of `SCHEDULE_USER_ID` (default
+//! `scheduler`), summarized, tagged and indexed like any other, announced to
+//! webhooks, and published to the podcast feed when TTS is configured.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::StreamExt;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::audit::AuditContext;
+use crate::categories;
+use crate::chat;
+use crate::debate::{run_debate, Roles, RunEvent, Turn};
+#[cfg(feature = "podcast")]
+use crate::podcast;
+use crate::presets::{self, Preset};
+use crate::prompts;
+use crate::similar;
+use crate::state::AppState;
+use crate::suggestions;
+use crate::summaries;
+use crate::templates;
+use crate::tools::ToolContext;
+#[cfg(feature = "podcast")]
+use crate::tts;
+use crate::webhooks;
+
+/// Recent scheduled topics a suggested topic must differ from.
+const RECENT_TOPICS: i64 = 30;
+
+pub struct Scheduler {
+    schedules: Vec<CronSchedule>,
+    topics: Vec<String>,
+    user_id: String,
+    preset: Option<&'static Preset>,
+    /// Set while a scheduled debate runs; a tick during it is skipped.
+    running: Arc<AtomicBool>,
+}
+
+impl Scheduler {
+    /// `None` unless `DEBATE_SCHEDULE` holds a valid cron expression. Invalid
+    /// expressions are logged and ignored.
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var("DEBATE_SCHEDULE").ok()?;
+        let schedules: Vec<CronSchedule> = spec
+            .split(';')
+            .map(str::trim)
+            .filter(|expr| !expr.is_empty())
+            .filter_map(|expr| match CronSchedule::parse(expr) {
+                Ok(schedule) => Some(schedule),
+                Err(e) => {
+                    warn!("Ignoring DEBATE_SCHEDULE entry {:?}: {}", expr, e);
+                    None
+                }
+            })
+            .collect();
+        if schedules.is_empty() {
+            return None;
+        }
+        let topics = std::env::var("SCHEDULE_TOPICS")
+            .unwrap_or_default()
+            .split('|')
+            .map(str::trim)
+            .filter(|topic| !topic.is_empty() && topic.len() <= chat::MAX_TOPIC_LEN)
+            .map(str::to_string)
+            .collect();
+        let user_id = std::env::var("SCHEDULE_USER_ID")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "scheduler".to_string());
+        let preset = std::env::var("SCHEDULE_PRESET")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .and_then(|id| {
+                let preset = presets::find(&id);
+                if preset.is_none() {
+                    warn!("Ignoring unknown SCHEDULE_PRESET {:?}", id);
+                }
+                preset
+            });
+        Some(Self {
+            schedules,
+            topics,
+            user_id,
+            preset,
+            running: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Wake at the start of every minute and start a debate when a schedule
+    /// matches. Runs for the life of the server.
+    pub async fn run(self, state: Arc<AppState>) {
+        let this = Arc::new(self);
+        info!(
+            "Debate scheduler started with {} schedule(s)",
+            this.schedules.len()
+        );
+        loop {
+            let now = unix_secs();
+            tokio::time::sleep(Duration::from_secs(60 - now % 60)).await;
+            let time = UtcMinute::from_unix(unix_secs());
+            if !this.schedules.iter().any(|s| s.matches(&time)) {
+                continue;
+            }
+            if this.running.swap(true, Ordering::SeqCst) {
+                warn!("Skipping scheduled debate: the previous one is still running");
+                continue;
+            }
+            let (this, state) = (this.clone(), state.clone());
+            tokio::spawn(async move {
+                this.run_scheduled(&state, &time).await;
+                this.running.store(false, Ordering::SeqCst);
+            });
+        }
+    }
+
+    /// Pick a topic, run the debate and publish it.
+    async fn run_scheduled(&self, state: &AppState, time: &UtcMinute) {
+        let recent: Vec<String> = state
+            .db
+            .fetch_sessions(&self.user_id, None, RECENT_TOPICS)
+            .await
+            .into_iter()
+            .map(|session| session.topic)
+            .collect();
+        let topic = match self.next_topic(recent.first()) {
+            Some(topic) => topic,
+            None => match suggestions::suggest_topic(&state.judge, &recent).await {
+                Ok(topic) => topic,
+                Err(e) => {
+                    warn!("Scheduled debate skipped: {}", e);
+                    return;
+                }
+            },
+        };
+        let session_id = format!("scheduled-{}", time.stamp());
+        let mut audit = AuditContext {
+            user_id: self.user_id.clone(),
+            session_id: session_id.clone(),
+            ip: "scheduler".to_string(),
+            topic: topic.chars().take(500).collect(),
+            providers: String::new(),
+        };
+        if let Err(reason) = chat::admit(state, &audit, &topic).await {
+            warn!("Scheduled debate on {:?} rejected: {}", topic, reason);
+            return;
+        }
+        audit.providers = format!(
+            "pro={},con={},judge={}",
+            state.pro.model_id, state.con.model_id, state.judge.model_id
+        );
+        info!("Running scheduled debate {}: {}", session_id, topic);
+        audit.record(&state.db, "started", None).await;
+        webhooks::emit(&state.db, &audit, "debate_started", json!({}));
+        match self.debate(state, &topic, &session_id).await {
+            Ok(transcript) => {
+                audit.record(&state.db, "completed", None).await;
+                self.publish(state, &audit, &topic, &transcript).await;
+            }
+            Err(e) => {
+                warn!("Scheduled debate {} failed: {}", session_id, e);
+                audit
+                    .record(&state.db, "failed", Some(&e.to_string()))
+                    .await;
+                webhooks::emit(
+                    &state.db,
+                    &audit,
+                    "error",
+                    json!({ "message": e.to_string() }),
+                );
+            }
+        }
+    }
+
+    /// The configured topic after `last`, or the first one; `None` when no
+    /// topics are configured.
+    fn next_topic(&self, last: Option<&String>) -> Option<String> {
+        let next = last
+            .and_then(|last| self.topics.iter().position(|topic| topic == last))
+            .map_or(0, |i| (i + 1) % self.topics.len());
+        self.topics.get(next).cloned()
+    }
+
+    /// Run the debate, saving the session and each round.
+    async fn debate(
+        &self,
+        state: &AppState,
+        topic: &str,
+        session_id: &str,
+    ) -> anyhow::Result<Vec<Turn>> {
+        state
+            .db
+            .save_session(&self.user_id, session_id, topic)
+            .await?;
+        let prompt_templates = templates::assign(&state.db).await;
+        state
+            .db
+            .save_session_templates(session_id, &prompt_templates.versions())
+            .await?;
+        state
+            .db
+            .save_session_variant(session_id, prompt_templates.variant.as_deref())
+            .await?;
+        let defaults = (state.pro.clone(), state.con.clone(), state.judge.clone());
+        let roles = Roles::resolve(&defaults, &None, &None, &None).await?;
+        let tool_ctx = ToolContext::new(
+            state.db.clone(),
+            self.user_id.clone(),
+            session_id.to_string(),
+            roles.fact_checker.clone(),
+            state.metrics.clone(),
+        )
+        .with_preset(self.preset)
+        .with_templates(prompt_templates);
+
+        let mut transcript = Vec::new();
+        let events = run_debate(topic, &roles, &tool_ctx);
+        futures::pin_mut!(events);
+        while let Some(event) = events.next().await {
+            if let RunEvent::RoundFinished(turn) = event? {
+                let (side, phase, content, model) = &turn;
+                if let Err(e) = state
+                    .db
+                    .save_message(
+                        &self.user_id,
+                        session_id,
+                        *side,
+                        *phase,
+                        Some(model),
+                        content,
+                    )
+                    .await
+                {
+                    warn!("Failed to save scheduled debate message: {}", e);
+                }
+                transcript.push(turn);
+            }
+        }
+        Ok(transcript)
+    }
+
+    /// Summarize, tag and index the finished debate, announce the verdict
+    /// and add it to the podcast feed.
+    async fn publish(
+        &self,
+        state: &AppState,
+        audit: &AuditContext,
+        topic: &str,
+        transcript: &[Turn],
+    ) {
+        let (db, session_id) = (&state.db, &audit.session_id);
+        if let Some((_, _, verdict, model)) = transcript.last() {
+            webhooks::emit(
+                db,
+                audit,
+                "verdict",
+                json!({
+                    "winner": prompts::parse_winner(verdict).map(|side| side.role_str()),
+                    "model": model,
+                    "content": verdict,
+                }),
+            );
+        }
+        if let Err(e) = categories::tag_session(db, &state.judge, session_id, topic).await {
+            warn!("Failed to tag session {}: {}", session_id, e);
+        }
+        if let Err(e) =
+            summaries::summarize_session(db, &state.judge, session_id, topic, transcript).await
+        {
+            warn!("Failed to summarize session {}: {}", session_id, e);
+        }
+        if let Err(e) =
+            similar::index_debate(db, &self.user_id, session_id, topic, transcript).await
+        {
+            warn!("Failed to index debate for similar search: {}", e);
+        }
+        #[cfg(feature = "podcast")]
+        if tts::enabled() {
+            if let Err(e) =
+                podcast::publish_episode(db, &self.user_id, session_id, topic, transcript).await
+            {
+                warn!("Failed to publish podcast episode: {}", e);
+            }
+        }
+    }
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// A calendar minute in UTC.
+struct UtcMinute {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    /// 0 is Sunday.
+    weekday: u32,
+}
+
+impl UtcMinute {
+    fn from_unix(secs: u64) -> Self {
+        let days = (secs / 86_400) as i64;
+        let of_day = secs % 86_400;
+        // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Self {
+            year,
+            month,
+            day,
+            hour: (of_day / 3600) as u32,
+            minute: (of_day % 3600 / 60) as u32,
+            // 1970-01-01 was a Thursday
+            weekday: ((days + 4).rem_euclid(7)) as u32,
+        }
+    }
+
+    /// `2026-10-16-0900`, for session ids.
+    fn stamp(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}-{:02}{:02}",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+
+/// A five-field cron expression. Each field accepts `*`, numbers, ranges
+/// (`1-5`), lists (`1,15`) and steps (`*/15`, `0-30/10`); day-of-week 7 is
+/// Sunday, like 0. As in cron, when both day fields are restricted a day
+/// matching either one matches.
+struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("expected 5 fields, found {}", fields.len()));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        // Fold 7 (Sunday) onto 0
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    fn matches(&self, time: &UtcMinute) -> bool {
+        let has = |set: u64, value: u32| set & (1 << value) != 0;
+        let day = has(self.days, time.day);
+        let weekday = has(self.weekdays, time.weekday);
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        };
+        has(self.minutes, time.minute)
+            && has(self.hours, time.hour)
+            && has(self.months, time.month)
+            && day_matches
+    }
+}
+
+/// Bit set of the values `field` selects within `min..=max`.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid step in {:?}", part))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let value = |v: &str| {
+            v.parse::<u32>()
+                .ok()
+                .filter(|v| (min..=max).contains(v))
+                .ok_or_else(|| format!("{:?} is not a number from {} to {}", v, min, max))
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // `5/10` runs from 5 to the end of the field
+                None if step > 1 => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+        if start > end {
+            return Err(format!("empty range {:?}", range));
+        }
+        for v in (start..=end).step_by(step as usize) {
+            set |= 1 << v;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> UtcMinute {
+        UtcMinute::from_unix(secs)
+    }
+
+    #[test]
+    fn civil_time_from_unix_seconds() {
+        let time = at(1_792_141_200);
+        assert_eq!(time.stamp(), "2026-10-16-0900");
+        assert_eq!(time.weekday, 5);
+        let leap = at(1_709_251_140);
+        assert_eq!(leap.stamp(), "2024-02-29-2359");
+        assert_eq!(leap.weekday, 4);
+        assert_eq!(at(0).weekday, 4);
+    }
+
+    #[test]
+    fn fields_accept_lists_ranges_and_steps() {
+        assert_eq!(parse_field("*", 0, 3), Ok(0b1111));
+        assert_eq!(parse_field("1,3", 0, 7), Ok(0b1010));
+        assert_eq!(parse_field("2-4", 0, 7), Ok(0b11100));
+        assert_eq!(
+            parse_field("*/15", 0, 59),
+            Ok(1 | 1 << 15 | 1 << 30 | 1 << 45)
+        );
+        assert_eq!(
+            parse_field("0-30/10", 0, 59),
+            Ok(1 | 1 << 10 | 1 << 20 | 1 << 30)
+        );
+        assert_eq!(parse_field("50/5", 0, 59), Ok(1 << 50 | 1 << 55));
+    }
+
+    #[test]
+    fn invalid_fields_are_rejected() {
+        for (field, min, max) in [
+            ("60", 0, 59),
+            ("0", 1, 31),
+            ("5-1", 0, 59),
+            ("*/0", 0, 59),
+            ("*/x", 0, 59),
+            ("mon", 0, 7),
+            ("", 0, 59),
+            ("1,", 0, 59),
+        ] {
+            assert!(parse_field(field, min, max).is_err(), "{:?}", field);
+        }
+        assert!(CronSchedule::parse("0 9 * *").is_err());
+        assert!(CronSchedule::parse("0 9 * * * *").is_err());
+        assert!(CronSchedule::parse("0 24 * * *").is_err());
+    }
+
+    #[test]
+    fn schedule_matches_minutes_and_weekdays() {
+        let weekdays = CronSchedule::parse("0 9 * * 1-5").unwrap();
+        assert!(weekdays.matches(&at(1_792_141_200)));
+        assert!(!weekdays.matches(&at(1_792_141_200 + 60)));
+        let sunday = CronSchedule::parse("30 12 * * 7").unwrap();
+        assert!(sunday.matches(&at(1_792_326_600)));
+        assert!(!sunday.matches(&at(1_792_067_400)));
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either_one() {
+        // The 1st of the month or any Thursday
+        let schedule = CronSchedule::parse("30 12 1 * 4").unwrap();
+        assert!(schedule.matches(&at(1_792_067_400)));
+        assert!(schedule.matches(&at(1_793_536_200)));
+        assert!(!schedule.matches(&at(1_792_326_600)));
+        // With one day field left as `*`, both must match
+        let first = CronSchedule::parse("30 12 1 * *").unwrap();
+        assert!(first.matches(&at(1_793_536_200)));
+        assert!(!first.matches(&at(1_792_067_400)));
+    }
+}
@@ -0,0 +1,392 @@
+//! Slack app integration: the `/debate <topic>` slash command.
+//!
+//! Enabled when both `SLACK_SIGNING_SECRET` and `SLACK_BOT_TOKEN` are set.
+//! Point the slash command's request URL at `/integrations/slack/commands`.
+//! Requests are verified with the app's signing secret (and rejected when
+//! older than five minutes), then acknowledged right away; the debate runs in
+//! the background and is posted to the channel with `chat.postMessage`.
+//!
+//! `SLACK_POST_MODE=phases` (the default) posts every round as a reply in a
+//! thread under the announcement. `SLACK_POST_MODE=summary` posts only the
+//! judge's verdict, with a link to the full transcript when
+//! `PUBLIC_BASE_URL` is set. The bot must be invited to the channel.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use futures::StreamExt;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Sha256;
+use tracing::{info, warn};
+
+use crate::audit::AuditContext;
+use crate::chat;
+use crate::config;
+use crate::debate::{run_debate, Roles, RunEvent};
+use crate::error::ApiError;
+use crate::state::AppState;
+use crate::templates;
+use crate::tools::ToolContext;
+use crate::types::Position;
+
+/// Requests whose timestamp is further off than this are rejected as replays.
+const MAX_REQUEST_AGE_SECS: i64 = 300;
+
+/// Slack rejects longer messages; rounds are cut to fit.
+const MAX_MESSAGE_CHARS: usize = 3900;
+
+#[derive(Clone, Copy, PartialEq)]
+enum PostMode {
+    /// Every round as a thread reply.
+    Phases,
+    /// Only the verdict, plus a transcript link.
+    Summary,
+}
+
+pub struct SlackConfig {
+    signing_secret: String,
+    bot_token: String,
+    post_mode: PostMode,
+}
+
+impl SlackConfig {
+    /// `None` unless both the signing secret and the bot token are set.
+    pub fn from_env() -> Option<Self> {
+        let signing_secret = std::env::var("SLACK_SIGNING_SECRET")
+            .ok()
+            .filter(|v| !v.trim().is_empty())?;
+        let bot_token = std::env::var("SLACK_BOT_TOKEN")
+            .ok()
+            .filter(|v| !v.trim().is_empty())?;
+        let post_mode = match std::env::var("SLACK_POST_MODE").as_deref() {
+            Ok(mode) if mode.eq_ignore_ascii_case("summary") => PostMode::Summary,
+            _ => PostMode::Phases,
+        };
+        info!("Slack integration enabled");
+        Some(Self {
+            signing_secret: signing_secret.trim().to_string(),
+            bot_token: bot_token.trim().to_string(),
+            post_mode,
+        })
+    }
+
+    /// Check `X-Slack-Signature` against the raw request body.
+    fn verify(&self, headers: &HeaderMap, body: &[u8]) -> bool {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let Some(timestamp) = header("x-slack-request-timestamp") else {
+            return false;
+        };
+        let Ok(sent_at) = timestamp.parse::<i64>() else {
+            return false;
+        };
+        if (unix_now().as_secs() as i64 - sent_at).abs() > MAX_REQUEST_AGE_SECS {
+            return false;
+        }
+        let Some(signature) = header("x-slack-signature")
+            .and_then(|s| s.strip_prefix("v0="))
+            .and_then(|s| hex::decode(s).ok())
+        else {
+            return false;
+        };
+        let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(self.signing_secret.as_bytes()) else {
+            return false;
+        };
+        mac.update(b"v0:");
+        mac.update(timestamp.as_bytes());
+        mac.update(b":");
+        mac.update(body);
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    /// Post a message, optionally as a reply in a thread. Returns the
+    /// message's `ts`, which identifies it as a thread parent.
+    async fn post(
+        &self,
+        channel: &str,
+        text: &str,
+        thread_ts: Option<&str>,
+    ) -> anyhow::Result<String> {
+        let mut body = json!({ "channel": channel, "text": text });
+        if let Some(ts) = thread_ts {
+            body["thread_ts"] = json!(ts);
+        }
+        let resp: serde_json::Value = config::http_client()
+            .post("https://slack.com/api/chat.postMessage")
+            .bearer_auth(&self.bot_token)
+            .timeout(Duration::from_secs(15))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("chat.postMessage failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("chat.postMessage parse failed: {}", e))?;
+        if resp["ok"].as_bool() != Some(true) {
+            anyhow::bail!(
+                "chat.postMessage failed: {}",
+                resp["error"].as_str().unwrap_or("unknown error")
+            );
+        }
+        Ok(resp["ts"].as_str().unwrap_or_default().to_string())
+    }
+}
+
+/// The fields of a slash command payload that are used.
+#[derive(Deserialize)]
+struct SlashCommand {
+    #[serde(default)]
+    text: String,
+    channel_id: String,
+    user_id: String,
+    #[serde(default)]
+    team_id: String,
+}
+
+/// Reply visible only to the user who ran the command.
+fn ephemeral(text: &str) -> Response {
+    Json(json!({ "response_type": "ephemeral", "text": text })).into_response()
+}
+
+/// `POST /integrations/slack/commands`
+pub async fn slash_command(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Some(slack) = &state.slack else {
+        return ApiError::not_found("Slack integration is not enabled").into_response();
+    };
+    if !slack.verify(&headers, &body) {
+        return ApiError::unauthorized("invalid signature").into_response();
+    }
+    let command: SlashCommand = match serde_urlencoded::from_bytes(&body) {
+        Ok(command) => command,
+        Err(e) => {
+            return ApiError::new(StatusCode::BAD_REQUEST, "invalid_command", e.to_string())
+                .into_response()
+        }
+    };
+
+    let topic = command.text.trim().to_string();
+    if topic.is_empty() {
+        return ephemeral("Usage: `/debate <topic>`");
+    }
+
+    let user_id = format!("slack:{}:{}", command.team_id, command.user_id);
+    let session_id = format!("slack-{}-{}", command.channel_id, unix_now().as_millis());
+    let mut audit = AuditContext {
+        user_id: user_id.clone(),
+        session_id: session_id.clone(),
+        ip: "slack".to_string(),
+        topic: topic.chars().take(500).collect(),
+        providers: String::new(),
+    };
+
+    if let Err(message) = chat::admit(&state, &audit, &topic).await {
+        return ephemeral(&message);
+    }
+
+    audit.providers = format!(
+        "pro={},con={},judge={}",
+        state.pro.model_id, state.con.model_id, state.judge.model_id
+    );
+    let channel = command.channel_id.clone();
+    let announcement = format!("<@{}> started a debate: *{}*", command.user_id, topic);
+    let task_state = state.clone();
+    tokio::spawn(async move {
+        let Some(slack) = &task_state.slack else {
+            return;
+        };
+        let parent = match slack.post(&channel, &announcement, None).await {
+            Ok(ts) => ts,
+            Err(e) => {
+                warn!("Slack debate not started: {}", e);
+                audit
+                    .record(&task_state.db, "failed", Some(&e.to_string()))
+                    .await;
+                return;
+            }
+        };
+        audit.record(&task_state.db, "started", None).await;
+        match run_in_thread(
+            &task_state,
+            slack,
+            &channel,
+            &parent,
+            &topic,
+            &user_id,
+            &session_id,
+        )
+        .await
+        {
+            Ok(()) => audit.record(&task_state.db, "completed", None).await,
+            Err(e) => {
+                warn!("Slack debate failed: {}", e);
+                audit
+                    .record(&task_state.db, "failed", Some(&e.to_string()))
+                    .await;
+                if let Err(e) = slack
+                    .post(
+                        &channel,
+                        &format!("The debate failed: {}", e),
+                        Some(&parent),
+                    )
+                    .await
+                {
+                    warn!("Failed to report Slack debate failure: {}", e);
+                }
+            }
+        }
+    });
+
+    Json(json!({ "response_type": "ephemeral", "text": "Starting the debate…" })).into_response()
+}
+
+/// Run the debate, saving each round and posting it under `parent`.
+async fn run_in_thread(
+    state: &AppState,
+    slack: &SlackConfig,
+    channel: &str,
+    parent: &str,
+    topic: &str,
+    user_id: &str,
+    session_id: &str,
+) -> anyhow::Result<()> {
+    let defaults = (state.pro.clone(), state.con.clone(), state.judge.clone());
+    let roles = Roles::resolve(&defaults, &None, &None, &None).await?;
+    let tool_ctx = ToolContext::new(
+        state.db.clone(),
+        user_id.to_string(),
+        session_id.to_string(),
+        roles.fact_checker.clone(),
+        state.metrics.clone(),
+    )
+    .with_templates(templates::load(&state.db).await);
+
+    let events = run_debate(topic, &roles, &tool_ctx);
+    futures::pin_mut!(events);
+    while let Some(event) = events.next().await {
+        let RunEvent::RoundFinished((side, phase, content, model)) = event? else {
+            continue;
+        };
+        if let Err(e) = state
+            .db
+            .save_message(user_id, session_id, side, phase, Some(&model), &content)
+            .await
+        {
+            warn!("Failed to save Slack debate message: {}", e);
+        }
+        let text = match (slack.post_mode, side) {
+            (PostMode::Phases, _) => format!(
+                "*{} · {}* ({})\n{}",
+                side.label(),
+                phase.title(),
+                model,
+                chat::truncate(content.trim(), MAX_MESSAGE_CHARS)
+            ),
+            (PostMode::Summary, Position::Judge) => {
+                let mut text = format!(
+                    "*Verdict* ({})\n{}",
+                    model,
+                    chat::truncate(content.trim(), MAX_MESSAGE_CHARS)
+                );
+                if let Some(link) = chat::transcript_link(state, user_id, session_id, topic).await {
+                    text.push_str(&format!("\n<{}|Full transcript>", link));
+                }
+                text
+            }
+            (PostMode::Summary, _) => continue,
+        };
+        slack.post(channel, &text, Some(parent)).await?;
+    }
+    Ok(())
+}
+
+fn unix_now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SlackConfig {
+        SlackConfig {
+            signing_secret: "8f742231b10e8888abcd99yyyzzz85a5".to_string(),
+            bot_token: "xoxb-test".to_string(),
+            post_mode: PostMode::Phases,
+        }
+    }
+
+    /// Headers of a request sent at `sent_at` and signed with `secret`.
+    fn signed(secret: &str, sent_at: i64, body: &[u8]) -> HeaderMap {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("v0:{}:", sent_at).as_bytes());
+        mac.update(body);
+        let signature = format!("v0={}", hex::encode(mac.finalize().into_bytes()));
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-slack-request-timestamp",
+            sent_at.to_string().parse().unwrap(),
+        );
+        headers.insert("x-slack-signature", signature.parse().unwrap());
+        headers
+    }
+
+    fn now() -> i64 {
+        unix_now().as_secs() as i64
+    }
+
+    const BODY: &[u8] = b"command=%2Fdebate&text=Remote+work+is+here+to+stay";
+
+    #[test]
+    fn good_signature_is_accepted() {
+        let config = config();
+        let headers = signed(&config.signing_secret, now(), BODY);
+        assert!(config.verify(&headers, BODY));
+    }
+
+    #[test]
+    fn bad_signature_is_rejected() {
+        let config = config();
+        let headers = signed("some-other-signing-secret", now(), BODY);
+        assert!(!config.verify(&headers, BODY));
+        let headers = signed(&config.signing_secret, now(), BODY);
+        assert!(!config.verify(&headers, b"command=%2Fdebate&text=tampered"));
+        let mut headers = signed(&config.signing_secret, now(), BODY);
+        headers.insert("x-slack-signature", "v0=not-hex".parse().unwrap());
+        assert!(!config.verify(&headers, BODY));
+    }
+
+    #[test]
+    fn stale_timestamp_is_rejected() {
+        let config = config();
+        let sent_at = now() - MAX_REQUEST_AGE_SECS - 60;
+        let headers = signed(&config.signing_secret, sent_at, BODY);
+        assert!(!config.verify(&headers, BODY));
+        let sent_at = now() + MAX_REQUEST_AGE_SECS + 60;
+        let headers = signed(&config.signing_secret, sent_at, BODY);
+        assert!(!config.verify(&headers, BODY));
+    }
+
+    #[test]
+    fn missing_headers_are_rejected() {
+        let config = config();
+        let headers = signed(&config.signing_secret, now(), BODY);
+        for name in ["x-slack-request-timestamp", "x-slack-signature"] {
+            let mut headers = headers.clone();
+            headers.remove(name);
+            assert!(!config.verify(&headers, BODY), "without {}", name);
+        }
+        assert!(!config.verify(&HeaderMap::new(), BODY));
+    }
+}
@@ -0,0 +1,193 @@
+//! Live spectating of running debates.
+//!
+//! While a debate streams, every event it sends is also broadcast to the
+//! clients watching its session through `GET /sessions/{id}/spectate`.
+//! Every `VIEWER_COUNT_INTERVAL` the debate and its spectators get a
+//! `{"type":"viewers","count":n}` event with how many clients are watching.
+//! Spectators join mid-stream: they see the events sent after they joined.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+use serde_json::json;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::warn;
+
+/// How often a live debate reports its viewer count.
+const VIEWER_COUNT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Events buffered per session for spectators that fall behind; slower ones
+/// skip ahead.
+const SPECTATOR_BUFFER: usize = 256;
+
+type Sessions = Arc<Mutex<HashMap<String, broadcast::Sender<Arc<serde_json::Value>>>>>;
+
+/// The debates running on this instance that can be watched.
+#[derive(Clone)]
+pub struct Spectators {
+    sessions: Sessions,
+    interval: Duration,
+}
+
+impl Default for Spectators {
+    fn default() -> Self {
+        Self {
+            sessions: Sessions::default(),
+            interval: VIEWER_COUNT_INTERVAL,
+        }
+    }
+}
+
+impl Spectators {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clients watching a session; 0 when it is not live.
+    pub fn viewers(&self, session_id: &str) -> usize {
+        let guard = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        guard
+            .get(session_id)
+            .map_or(0, broadcast::Sender::receiver_count)
+    }
+
+    /// Watch a live session until its debate ends; `None` when it is not
+    /// live.
+    pub fn watch(&self, session_id: &str) -> Option<impl Stream<Item = Arc<serde_json::Value>>> {
+        let mut events = {
+            let guard = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+            guard.get(session_id)?.subscribe()
+        };
+        let session_id = session_id.to_string();
+        Some(async_stream::stream! {
+            loop {
+                match events.recv().await {
+                    Ok(event) => yield event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Spectator of {} skipped {} events", session_id, skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Pass a debate's events through, broadcasting them to the spectators
+    /// of `session_id` and adding the viewer count periodically. The session
+    /// is live until the returned stream ends or is dropped.
+    pub fn relay<S>(
+        &self,
+        session_id: &str,
+        events: S,
+    ) -> impl Stream<Item = serde_json::Value> + Send
+    where
+        S: Stream<Item = serde_json::Value> + Send,
+    {
+        let (sender, _) = broadcast::channel(SPECTATOR_BUFFER);
+        let live = Live {
+            sessions: self.sessions.clone(),
+            session_id: session_id.to_string(),
+            sender,
+        };
+        {
+            let mut guard = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+            // A rerun of the session takes over its spectators' next joins
+            guard.insert(live.session_id.clone(), live.sender.clone());
+        }
+        let interval = self.interval;
+        async_stream::stream! {
+            let mut events = std::pin::pin!(events);
+            let mut ticks = tokio::time::interval_at(Instant::now() + interval, interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                let event = tokio::select! {
+                    event = events.next() => match event {
+                        Some(event) => event,
+                        None => break,
+                    },
+                    _ = ticks.tick() => {
+                        json!({"type": "viewers", "count": live.sender.receiver_count()})
+                    }
+                };
+                // No spectators is not an error
+                let _ = live.sender.send(Arc::new(event.clone()));
+                yield event;
+            }
+        }
+    }
+}
+
+/// A session being relayed; unregistered when dropped, which ends its
+/// spectators' streams.
+struct Live {
+    sessions: Sessions,
+    session_id: String,
+    sender: broadcast::Sender<Arc<serde_json::Value>>,
+}
+
+impl Drop for Live {
+    fn drop(&mut self) {
+        let mut guard = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        if guard
+            .get(&self.session_id)
+            .is_some_and(|sender| sender.same_channel(&self.sender))
+        {
+            guard.remove(&self.session_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn spectators_see_events_until_the_debate_ends() {
+        let spectators = Spectators::new();
+        assert!(spectators.watch("s1").is_none());
+
+        let (debate, events) = futures::channel::mpsc::unbounded();
+        let mut relayed = Box::pin(spectators.relay("s1", events));
+        let mut watching = Box::pin(spectators.watch("s1").unwrap());
+        assert_eq!(spectators.viewers("s1"), 1);
+
+        debate
+            .unbounded_send(json!({"type": "phase_start"}))
+            .unwrap();
+        assert_eq!(relayed.next().await.unwrap()["type"], "phase_start");
+        assert_eq!(watching.next().await.unwrap()["type"], "phase_start");
+
+        drop(debate);
+        assert!(relayed.next().await.is_none());
+        drop(relayed);
+        assert!(watching.next().await.is_none());
+        assert_eq!(spectators.viewers("s1"), 0);
+        assert!(spectators.watch("s1").is_none());
+    }
+
+    #[tokio::test]
+    async fn viewer_count_is_reported_periodically() {
+        let spectators = Spectators {
+            interval: Duration::from_millis(10),
+            ..Spectators::new()
+        };
+        let (_debate, events) = futures::channel::mpsc::unbounded();
+        let mut relayed = Box::pin(spectators.relay("s1", events));
+        let _watching = [spectators.watch("s1"), spectators.watch("s1")];
+
+        let event = relayed.next().await.unwrap();
+        assert_eq!(event, json!({"type": "viewers", "count": 2}));
+    }
+
+    #[tokio::test]
+    async fn rerun_keeps_the_new_debate_live() {
+        let spectators = Spectators::new();
+        let first = spectators.relay("s1", futures::stream::pending());
+        let _second = spectators.relay("s1", futures::stream::pending());
+        drop(first);
+        assert!(spectators.watch("s1").is_some());
+    }
+}
@@ -0,0 +1,38 @@
+//! Shared state for the HTTP server.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::app_metrics::Metrics;
+use crate::auth::JwtVerifier;
+use crate::concurrency::{DebateLimiter, ProviderLimiter};
+use crate::guest::GuestTokens;
+use crate::moderation::ModerationPolicy;
+use crate::rate_limit::RateLimitStore;
+use crate::slack::SlackConfig;
+use crate::spectate::Spectators;
+use crate::storage::Store;
+use crate::types::ClientInfo;
+
+pub struct AppState {
+    pub db: Arc<dyn Store>,
+    pub pro: ClientInfo,
+    pub con: ClientInfo,
+    pub judge: ClientInfo,
+    pub start_time: Instant,
+    pub rate_limiter: Arc<dyn RateLimitStore>,
+    pub metrics: Arc<dyn Metrics>,
+    pub provider_limits: ProviderLimiter,
+    /// Instance-wide cap on simultaneous debates.
+    pub debate_limits: DebateLimiter,
+    /// JWT verifier; `None` when authentication is disabled.
+    pub auth: Option<JwtVerifier>,
+    /// Guest token issuer; `None` when guest tokens are disabled.
+    pub guests: Option<GuestTokens>,
+    /// Topic moderation; `None` when not configured.
+    pub moderation: Option<ModerationPolicy>,
+    /// Slack slash command integration; `None` when not configured.
+    pub slack: Option<SlackConfig>,
+    /// Running debates that can be watched live.
+    pub spectators: Spectators,
+}
@@ -0,0 +1,326 @@
+//! Telegram bot: debates started from a chat.
+//!
+//! Enabled when `TELEGRAM_BOT_TOKEN` is set. The bot long-polls `getUpdates`
+//! (so no public URL is needed; the bot must not have a webhook set). In
+//! groups it answers `/debate <topic>`; in a private chat any message is
+//! taken as the topic. Each round is relayed as it completes, followed by the
+//! judge's verdict and, when `PUBLIC_BASE_URL` is set, a link to the
+//! transcript.
+//!
+//! Debates are rate limited per chat (`RateLimit::chat`) and only one runs in
+//! a chat at a time.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::audit::AuditContext;
+use crate::chat;
+use crate::config;
+use crate::debate::{run_debate, Roles, RunEvent};
+use crate::rate_limit::{ActiveDebate, RateLimit};
+use crate::state::AppState;
+use crate::templates;
+use crate::tools::ToolContext;
+
+/// Telegram rejects messages longer than 4096 characters.
+const MAX_MESSAGE_CHARS: usize = 4000;
+
+/// Long-poll timeout for `getUpdates`, in seconds.
+const POLL_TIMEOUT_SECS: u64 = 50;
+
+const USAGE: &str = "Send /debate <topic> to start a debate.";
+
+#[derive(Deserialize)]
+struct Update {
+    update_id: i64,
+    #[serde(default)]
+    message: Option<IncomingMessage>,
+}
+
+#[derive(Deserialize)]
+struct IncomingMessage {
+    message_id: i64,
+    chat: Chat,
+    #[serde(default)]
+    from: Option<Sender>,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Chat {
+    id: i64,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct Sender {
+    id: i64,
+    #[serde(default)]
+    is_bot: bool,
+}
+
+pub struct TelegramBot {
+    api_base: String,
+}
+
+impl TelegramBot {
+    /// `None` unless `TELEGRAM_BOT_TOKEN` is set.
+    pub fn from_env() -> Option<Self> {
+        let token = std::env::var("TELEGRAM_BOT_TOKEN")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())?;
+        Some(Self {
+            api_base: format!("https://api.telegram.org/bot{}", token),
+        })
+    }
+
+    /// Poll for messages forever, handling each one in its own task.
+    pub async fn run(self, state: Arc<AppState>) {
+        info!("Telegram bot polling for updates");
+        let bot = Arc::new(self);
+        let mut offset = 0;
+        loop {
+            let updates = match bot.updates(offset).await {
+                Ok(updates) => updates,
+                Err(e) => {
+                    warn!("Telegram getUpdates failed: {}", e);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            for update in updates {
+                offset = update.update_id + 1;
+                if let Some(message) = update.message {
+                    tokio::spawn(bot.clone().handle(state.clone(), message));
+                }
+            }
+        }
+    }
+
+    async fn updates(&self, offset: i64) -> anyhow::Result<Vec<Update>> {
+        let result = self
+            .call(
+                "getUpdates",
+                json!({
+                    "offset": offset,
+                    "timeout": POLL_TIMEOUT_SECS,
+                    "allowed_updates": ["message"],
+                }),
+                Duration::from_secs(POLL_TIMEOUT_SECS + 10),
+            )
+            .await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Call a Bot API method and return its `result`.
+    async fn call(
+        &self,
+        method: &str,
+        body: serde_json::Value,
+        timeout: Duration,
+    ) -> anyhow::Result<serde_json::Value> {
+        let resp: serde_json::Value = config::http_client()
+            .post(format!("{}/{}", self.api_base, method))
+            .timeout(timeout)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("{} failed: {}", method, e.without_url()))?
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("{} parse failed: {}", method, e.without_url()))?;
+        if resp["ok"].as_bool() != Some(true) {
+            anyhow::bail!(
+                "{} failed: {}",
+                method,
+                resp["description"].as_str().unwrap_or("unknown error")
+            );
+        }
+        Ok(resp["result"].clone())
+    }
+
+    /// Send `text` to a chat, split into as many messages as needed.
+    async fn send(&self, chat_id: i64, text: &str) -> anyhow::Result<()> {
+        for part in chat::split(text, MAX_MESSAGE_CHARS) {
+            self.call(
+                "sendMessage",
+                json!({ "chat_id": chat_id, "text": part, "disable_web_page_preview": true }),
+                Duration::from_secs(15),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Like `send`, for replies whose failure only needs logging.
+    async fn notify(&self, chat_id: i64, text: &str) {
+        if let Err(e) = self.send(chat_id, text).await {
+            warn!("Failed to send Telegram message: {}", e);
+        }
+    }
+
+    async fn handle(self: Arc<Self>, state: Arc<AppState>, message: IncomingMessage) {
+        if message.from.as_ref().is_some_and(|from| from.is_bot) {
+            return;
+        }
+        let Some(text) = message.text.as_deref() else {
+            return;
+        };
+        let chat_id = message.chat.id;
+        let Some(topic) = parse_topic(text, message.chat.kind == "private") else {
+            return;
+        };
+        if topic.is_empty() {
+            self.notify(chat_id, USAGE).await;
+            return;
+        }
+
+        let sender = message.from.as_ref().map_or(chat_id, |from| from.id);
+        let user_id = format!("telegram:{}", sender);
+        let session_id = format!("telegram-{}-{}", chat_id, message.message_id);
+        let mut audit = AuditContext {
+            user_id: user_id.clone(),
+            session_id: session_id.clone(),
+            ip: "telegram".to_string(),
+            topic: topic.chars().take(500).collect(),
+            providers: String::new(),
+        };
+
+        let chat_key = format!("chat:telegram:{}", chat_id);
+        let limit = RateLimit::chat();
+        if state
+            .rate_limiter
+            .hit(&chat_key, limit.window, limit.max_requests)
+            .await
+        {
+            audit
+                .record(&state.db, "rejected", Some("rate_limited"))
+                .await;
+            self.notify(
+                chat_id,
+                "Too many debates in this chat; please try again later.",
+            )
+            .await;
+            return;
+        }
+        // The chat's slot is held until the debate ends
+        let (_chat_slot, running) =
+            ActiveDebate::acquire(state.rate_limiter.clone(), &chat_key).await;
+        if running > 1 {
+            audit.record(&state.db, "rejected", Some("chat_busy")).await;
+            self.notify(chat_id, "A debate is already running in this chat.")
+                .await;
+            return;
+        }
+        if let Err(reason) = chat::admit(&state, &audit, topic).await {
+            self.notify(chat_id, &reason).await;
+            return;
+        }
+        audit.providers = format!(
+            "pro={},con={},judge={}",
+            state.pro.model_id, state.con.model_id, state.judge.model_id
+        );
+
+        self.notify(chat_id, &format!("Debating: {}", topic)).await;
+        audit.record(&state.db, "started", None).await;
+        match self
+            .run_in_chat(&state, chat_id, topic, &user_id, &session_id)
+            .await
+        {
+            Ok(()) => {
+                audit.record(&state.db, "completed", None).await;
+                if let Some(link) =
+                    chat::transcript_link(&state, &user_id, &session_id, topic).await
+                {
+                    self.notify(chat_id, &format!("Full transcript: {}", link))
+                        .await;
+                }
+            }
+            Err(e) => {
+                warn!("Telegram debate failed: {}", e);
+                audit
+                    .record(&state.db, "failed", Some(&e.to_string()))
+                    .await;
+                self.notify(chat_id, &format!("The debate failed: {}", e))
+                    .await;
+            }
+        }
+    }
+
+    /// Run the debate, saving each round and relaying it to the chat.
+    async fn run_in_chat(
+        &self,
+        state: &AppState,
+        chat_id: i64,
+        topic: &str,
+        user_id: &str,
+        session_id: &str,
+    ) -> anyhow::Result<()> {
+        let defaults = (state.pro.clone(), state.con.clone(), state.judge.clone());
+        let roles = Roles::resolve(&defaults, &None, &None, &None).await?;
+        let tool_ctx = ToolContext::new(
+            state.db.clone(),
+            user_id.to_string(),
+            session_id.to_string(),
+            roles.fact_checker.clone(),
+            state.metrics.clone(),
+        )
+        .with_templates(templates::load(&state.db).await);
+
+        let events = run_debate(topic, &roles, &tool_ctx);
+        futures::pin_mut!(events);
+        while let Some(event) = events.next().await {
+            match event? {
+                RunEvent::RoundStarted { .. } => {
+                    let typing = json!({ "chat_id": chat_id, "action": "typing" });
+                    let _ = self
+                        .call("sendChatAction", typing, Duration::from_secs(10))
+                        .await;
+                }
+                RunEvent::RoundFinished((side, phase, content, model)) => {
+                    if let Err(e) = state
+                        .db
+                        .save_message(user_id, session_id, side, phase, Some(&model), &content)
+                        .await
+                    {
+                        warn!("Failed to save Telegram debate message: {}", e);
+                    }
+                    let text = format!(
+                        "{} · {} ({})\n\n{}",
+                        side.label(),
+                        phase.title(),
+                        model,
+                        content.trim()
+                    );
+                    self.send(chat_id, &text).await?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The topic in a message: the argument of `/debate` (also `/debate@BotName`),
+/// or the whole text in a private chat. `Some("")` asks for usage help;
+/// `None` means the message is not for the bot.
+fn parse_topic(text: &str, private: bool) -> Option<&str> {
+    let text = text.trim();
+    if !text.starts_with('/') {
+        return private.then_some(text);
+    }
+    let (command, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    match command.split('@').next() {
+        Some("/debate") => Some(rest.trim()),
+        Some("/start") | Some("/help") => Some(""),
+        _ => None,
+    }
+}
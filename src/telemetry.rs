@@ -0,0 +1,57 @@
+//! Logging and OpenTelemetry trace export.
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Initialize logging, plus OTLP trace export when `OTEL_EXPORTER_OTLP_ENDPOINT`
+/// is set. Returns the tracer provider so pending spans can be flushed on exit.
+pub fn init_tracing() -> Option<SdkTracerProvider> {
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+
+    let provider = otlp_tracer_provider();
+    let otel_layer = provider
+        .as_ref()
+        .map(|p| tracing_opentelemetry::layer().with_tracer(p.tracer("aidebate")));
+
+    let _ = tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_target(false)
+                .with_level(true),
+        )
+        .with(otel_layer)
+        .try_init();
+    provider
+}
+
+/// OTLP/HTTP span exporter; endpoint and headers come from the standard
+/// `OTEL_EXPORTER_OTLP_*` variables, service name from `OTEL_SERVICE_NAME`.
+fn otlp_tracer_provider() -> Option<SdkTracerProvider> {
+    std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .ok()
+        .filter(|v| !v.trim().is_empty())?;
+
+    let exporter = match opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()
+    {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            eprintln!("OTLP trace exporter init failed: {}", e);
+            return None;
+        }
+    };
+    let service_name =
+        std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "aidebate".to_string());
+    Some(
+        SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name(service_name).build())
+            .build(),
+    )
+}
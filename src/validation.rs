@@ -0,0 +1,180 @@
+//! Request size limits and input validation.
+//!
+//! Identifiers are checked before they reach the database, and malformed or
+//! oversized bodies are rejected with a JSON error instead of axum's plain-text
+//! rejection.
+
+use axum::extract::rejection::JsonRejection;
+use axum::http::StatusCode;
+
+#[cfg(feature = "email")]
+use crate::email;
+use crate::error::ApiError;
+#[cfg(feature = "podcast")]
+use crate::tts;
+
+/// Longest accepted `user_id` / `session_id`.
+const MAX_ID_LEN: usize = 128;
+
+/// Body size limit for JSON endpoints (`MAX_BODY_BYTES`, default 64 KiB).
+/// Document uploads have their own limit.
+pub fn max_body_bytes() -> usize {
+    std::env::var("MAX_BODY_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(64 * 1024)
+}
+
+/// `user_id` / `session_id`: 1-128 characters of `[A-Za-z0-9_.:@|-]`.
+/// `|` and `@` allow common OIDC subjects (`auth0|123`, e-mail addresses).
+pub fn validate_id(field: &'static str, value: &str) -> Result<(), ApiError> {
+    let valid_char = |c: char| c.is_ascii_alphanumeric() || "_.:@|-".contains(c);
+    if value.is_empty() || value.len() > MAX_ID_LEN || !value.chars().all(valid_char) {
+        return Err(ApiError::invalid(
+            field,
+            format!(
+                "invalid {}: expected 1-{} characters of A-Z a-z 0-9 _ . : @ | -",
+                field, MAX_ID_LEN
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// `notify_email`: a valid address, accepted only when SMTP delivery is
+/// configured.
+#[cfg(feature = "email")]
+pub fn validate_notify_email(value: &str) -> Result<(), ApiError> {
+    let error = if email::mailer().is_none() {
+        "email delivery is not configured"
+    } else if !email::is_valid_address(value) {
+        "invalid notify_email"
+    } else {
+        return Ok(());
+    };
+    Err(ApiError::invalid("notify_email", error))
+}
+
+#[cfg(not(feature = "email"))]
+pub fn validate_notify_email(_value: &str) -> Result<(), ApiError> {
+    Err(ApiError::invalid(
+        "notify_email",
+        "email delivery is not configured",
+    ))
+}
+
+/// `podcast`: accepted only when speech synthesis is configured.
+pub fn validate_podcast(podcast: bool) -> Result<(), ApiError> {
+    #[cfg(feature = "podcast")]
+    let configured = tts::enabled();
+    #[cfg(not(feature = "podcast"))]
+    let configured = false;
+    if podcast && !configured {
+        return Err(ApiError::invalid(
+            "podcast",
+            "podcast audio is not configured",
+        ));
+    }
+    Ok(())
+}
+
+/// `language`: a language name such as "English" or "简体中文", at most 40
+/// characters of letters, spaces, `-` and parentheses.
+pub fn validate_language(language: &str) -> Result<(), ApiError> {
+    let valid_char = |c: char| c.is_alphabetic() || " -()".contains(c);
+    let language = language.trim();
+    if language.is_empty() || language.chars().count() > 40 || !language.chars().all(valid_char) {
+        return Err(ApiError::invalid("language", "invalid language"));
+    }
+    Ok(())
+}
+
+/// Map a JSON extractor rejection (bad syntax, wrong shape, body too large)
+/// to a JSON error with the same status.
+pub fn json_rejection(rejection: JsonRejection) -> ApiError {
+    let code = match rejection.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        _ => "invalid_json",
+    };
+    ApiError::new(rejection.status(), code, rejection.body_text())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::{FromRequest, Request};
+    use axum::response::IntoResponse;
+    use axum::Json;
+
+    #[test]
+    fn ids_allow_oidc_subjects() {
+        for id in ["user_1", "auth0|123", "alice@example.com", "a.b:c-d", "x"] {
+            assert!(validate_id("user_id", id).is_ok(), "{}", id);
+        }
+        assert!(validate_id("user_id", &"a".repeat(MAX_ID_LEN)).is_ok());
+    }
+
+    #[test]
+    fn ids_reject_empty_long_and_unsafe_values() {
+        let long = "a".repeat(MAX_ID_LEN + 1);
+        for id in ["", long.as_str(), "a b", "../etc", "id;drop", "用户", "a/b"] {
+            let err = validate_id("session_id", id).unwrap_err();
+            assert_eq!(err.code(), "invalid_session_id", "{}", id);
+        }
+    }
+
+    #[test]
+    fn languages_are_names_of_bounded_length() {
+        for language in [
+            "English",
+            "简体中文",
+            "Português (Brasil)",
+            " Serbo-Croatian ",
+        ] {
+            assert!(validate_language(language).is_ok(), "{}", language);
+        }
+        let long = "a".repeat(41);
+        for language in [
+            "",
+            "   ",
+            "en<script>",
+            "English; ignore the topic",
+            long.as_str(),
+        ] {
+            let err = validate_language(language).unwrap_err();
+            assert_eq!(err.code(), "invalid_language", "{}", language);
+        }
+    }
+
+    #[test]
+    fn podcast_off_is_always_accepted() {
+        assert!(validate_podcast(false).is_ok());
+    }
+
+    async fn rejection(body: &'static str) -> ApiError {
+        let request = Request::builder()
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let rejection =
+            Json::<serde_json::Map<String, serde_json::Value>>::from_request(request, &())
+                .await
+                .unwrap_err();
+        json_rejection(rejection)
+    }
+
+    #[tokio::test]
+    async fn malformed_json_keeps_the_rejection_status() {
+        let err = rejection("{\"topic\":").await;
+        assert_eq!(err.code(), "invalid_json");
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+
+        let err = rejection("[1, 2]").await;
+        assert_eq!(err.code(), "invalid_json");
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Outbound event webhooks for no-code automations (Zapier, Make, n8n, ...).
+//!
+//! Subscriptions are managed through the admin API (`/admin/webhooks`). Each
+//! one lists the events it wants (`debate_started`, `phase_done`, `verdict`,
+//! `error`; none means all) and may be narrowed to one user or to topics
+//! containing some text. Events are POSTed as JSON, signed with the
+//! subscription's secret:
+//!
+//! - `X-Aidebate-Event`: the event name
+//! - `X-Aidebate-Timestamp`: Unix seconds
+//! - `X-Aidebate-Signature`: `sha256=` + hex HMAC-SHA256 of `{timestamp}.{body}`
+//!
+//! Failed deliveries (network errors and non-2xx answers) are retried with
+//! backoff. A `410 Gone` answer removes the subscription, as REST hooks
+//! expect.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Sha256;
+use tracing::{info, warn};
+
+use crate::audit::AuditContext;
+use crate::config;
+use crate::error::ApiError;
+use crate::state::AppState;
+use crate::storage::{Store, WebhookSubscription};
+use crate::validation;
+
+pub const EVENTS: &[&str] = &["debate_started", "phase_done", "verdict", "error"];
+
+/// Wait before each retry of a failed delivery.
+const RETRY_DELAYS_SECS: [u64; 3] = [5, 30, 120];
+
+const MIN_SECRET_LEN: usize = 16;
+
+/// Deliver `event` for the debate described by `audit` to every matching
+/// subscription, in the background.
+pub fn emit(
+    db: &Arc<dyn Store>,
+    audit: &AuditContext,
+    event: &'static str,
+    data: serde_json::Value,
+) {
+    let db = db.clone();
+    let audit = audit.clone();
+    tokio::spawn(async move {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let body = json!({
+            "event": event,
+            "timestamp": timestamp,
+            "user_id": audit.user_id,
+            "session_id": audit.session_id,
+            "topic": audit.topic,
+            "data": data,
+        })
+        .to_string();
+        for subscription in db.fetch_webhook_subscriptions().await {
+            if matches(&subscription, &audit, event) {
+                tokio::spawn(deliver(
+                    db.clone(),
+                    subscription,
+                    event,
+                    timestamp,
+                    body.clone(),
+                ));
+            }
+        }
+    });
+}
+
+fn matches(subscription: &WebhookSubscription, audit: &AuditContext, event: &str) -> bool {
+    let wants_event =
+        subscription.events.is_empty() || subscription.events.split(',').any(|name| name == event);
+    let wants_user = subscription
+        .user_id
+        .as_deref()
+        .is_none_or(|user_id| user_id == audit.user_id);
+    let wants_topic = subscription
+        .topic_contains
+        .as_deref()
+        .is_none_or(|text| audit.topic.to_lowercase().contains(&text.to_lowercase()));
+    wants_event && wants_user && wants_topic
+}
+
+fn signature(secret: &str, timestamp: u64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn deliver(
+    db: Arc<dyn Store>,
+    subscription: WebhookSubscription,
+    event: &'static str,
+    timestamp: u64,
+    body: String,
+) {
+    let signature = signature(&subscription.secret, timestamp, &body);
+    let mut attempt = 0;
+    loop {
+        let result = config::http_client()
+            .post(&subscription.url)
+            .header("Content-Type", "application/json")
+            .header("X-Aidebate-Event", event)
+            .header("X-Aidebate-Timestamp", timestamp.to_string())
+            .header("X-Aidebate-Signature", &signature)
+            .body(body.clone())
+            .send()
+            .await;
+        let failure = match result {
+            Ok(resp) if resp.status().is_success() => return,
+            Ok(resp) if resp.status().as_u16() == 410 => {
+                info!(
+                    "Webhook {} answered 410 Gone; unsubscribing",
+                    subscription.id
+                );
+                if let Err(e) = db.delete_webhook_subscription(subscription.id).await {
+                    warn!("Failed to remove webhook {}: {}", subscription.id, e);
+                }
+                return;
+            }
+            Ok(resp) => format!("HTTP {}", resp.status()),
+            Err(e) => e.without_url().to_string(),
+        };
+        let Some(delay) = RETRY_DELAYS_SECS.get(attempt) else {
+            warn!(
+                "Webhook {} gave up on {} after {} attempts: {}",
+                subscription.id,
+                event,
+                attempt + 1,
+                failure
+            );
+            return;
+        };
+        attempt += 1;
+        tokio::time::sleep(Duration::from_secs(*delay)).await;
+    }
+}
+
+// --- Admin API ----------------------------------------------------------------
+
+#[derive(Deserialize)]
+pub struct NewSubscription {
+    url: String,
+    /// Used to sign deliveries; at least 16 characters.
+    secret: String,
+    #[serde(default)]
+    events: Vec<String>,
+    #[serde(default)]
+    user_id: Option<String>,
+    #[serde(default)]
+    topic_contains: Option<String>,
+}
+
+/// `GET /admin/webhooks`: every subscription (secrets omitted).
+pub async fn list(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    let subscriptions = state.db.fetch_webhook_subscriptions().await;
+    Json(json!({ "webhooks": subscriptions, "events": EVENTS }))
+}
+
+/// `POST /admin/webhooks`: add a subscription.
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    payload: Result<Json<NewSubscription>, JsonRejection>,
+) -> Result<Response, ApiError> {
+    let Json(new) = payload.map_err(validation::json_rejection)?;
+    let url = new.url.trim();
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(ApiError::invalid("url", "url must be an http(s) URL"));
+    }
+    if new.secret.chars().count() < MIN_SECRET_LEN {
+        return Err(ApiError::invalid(
+            "secret",
+            "secret must be at least 16 characters",
+        ));
+    }
+    if let Some(unknown) = new.events.iter().find(|e| !EVENTS.contains(&e.as_str())) {
+        return Err(ApiError::invalid(
+            "events",
+            format!(
+                "unknown event {:?}; expected one of {}",
+                unknown,
+                EVENTS.join(", ")
+            ),
+        ));
+    }
+    let non_empty = |v: Option<String>| v.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let mut subscription = WebhookSubscription {
+        id: 0,
+        url: url.to_string(),
+        secret: new.secret,
+        events: new.events.join(","),
+        user_id: non_empty(new.user_id),
+        topic_contains: non_empty(new.topic_contains),
+    };
+    subscription.id = state
+        .db
+        .save_webhook_subscription(&subscription)
+        .await
+        .map_err(ApiError::internal)?;
+    info!(
+        "Webhook {} subscribed: {}",
+        subscription.id, subscription.url
+    );
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({ "webhook": subscription })),
+    )
+        .into_response())
+}
+
+/// `DELETE /admin/webhooks/{id}`.
+pub async fn delete(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    match state.db.delete_webhook_subscription(id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(ApiError::not_found("webhook not found")),
+        Err(e) => Err(ApiError::internal(format!(
+            "failed to remove webhook {}: {}",
+            id, e
+        ))),
+    }
+}